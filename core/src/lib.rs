@@ -0,0 +1,5 @@
+pub mod number;
+pub mod statistics;
+pub mod summary;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,125 @@
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Schema, SchemaObject},
+};
+use serde::{de, Deserializer, Serializer};
+use std::fmt;
+
+/// Serialize with `#[serde(with = "number")]`: finite values as the shortest number that parses
+/// back to the same bits, and NaN and the infinities, which JSON numbers cannot express, as the
+/// strings "NaN", "Infinity" and "-Infinity" instead of `null`.
+///
+/// ```
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Value(#[serde(with = "benchmark_core::number")] f64);
+///
+/// for x in [0.1, -0.0, 1e-310, f64::MAX, f64::INFINITY, f64::NEG_INFINITY] {
+///     let json = serde_json::to_string(&Value(x)).unwrap();
+///     let Value(y) = serde_json::from_str(&json).unwrap();
+///     assert_eq!(x.to_bits(), y.to_bits(), "{}", json);
+/// }
+/// assert_eq!(serde_json::to_string(&Value(f64::NAN)).unwrap(), "\"NaN\"");
+/// assert!(serde_json::from_str::<Value>("\"NaN\"").unwrap().0.is_nan());
+/// // reports written before non-finite values were spelled out have null.
+/// assert!(serde_json::from_str::<Value>("null").unwrap().0.is_nan());
+/// ```
+pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    match *value {
+        x if x.is_finite() => serializer.serialize_f64(x),
+        x if x.is_nan() => serializer.serialize_str("NaN"),
+        x if x > 0.0 => serializer.serialize_str("Infinity"),
+        _ => serializer.serialize_str("-Infinity"),
+    }
+}
+
+struct Visitor;
+
+impl de::Visitor<'_> for Visitor {
+    type Value = f64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a number, \"NaN\", \"Infinity\" or \"-Infinity\"")
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<f64, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<f64, E> {
+        Ok(value as f64)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<f64, E> {
+        Ok(value as f64)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<f64, E> {
+        match value {
+            "NaN" => Ok(f64::NAN),
+            "Infinity" => Ok(f64::INFINITY),
+            "-Infinity" => Ok(f64::NEG_INFINITY),
+            x => Err(E::invalid_value(de::Unexpected::Str(x), &self)),
+        }
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<f64, E> {
+        Ok(f64::NAN)
+    }
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    deserializer.deserialize_any(Visitor)
+}
+
+/// JSON Schema of a value serialized by `number`, with `#[schemars(schema_with = "number::schema")]`.
+pub fn schema(generator: &mut SchemaGenerator) -> Schema {
+    let mut schema = SchemaObject::default();
+    schema.subschemas().any_of = Some(vec![
+        generator.subschema_for::<f64>(),
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(vec!["NaN".into(), "Infinity".into(), "-Infinity".into()]),
+            ..Default::default()
+        }
+        .into(),
+    ]);
+    schema.into()
+}
+
+/// `number` for `Option<f64>`, whose `null` is None.
+pub mod option {
+    use schemars::{
+        gen::SchemaGenerator,
+        schema::{InstanceType, Schema, SchemaObject},
+    };
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(x) => super::serialize(x, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<f64>, D::Error> {
+        #[derive(Deserialize)]
+        struct Number(#[serde(with = "super")] f64);
+
+        Ok(Option::<Number>::deserialize(deserializer)?.map(|x| x.0))
+    }
+
+    pub fn schema(generator: &mut SchemaGenerator) -> Schema {
+        let mut schema = SchemaObject::default();
+        schema.subschemas().any_of = Some(vec![
+            super::schema(generator),
+            SchemaObject {
+                instance_type: Some(InstanceType::Null.into()),
+                ..Default::default()
+            }
+            .into(),
+        ]);
+        schema.into()
+    }
+}
@@ -0,0 +1,189 @@
+use serde::Serialize;
+
+/// Result of a two-sided Wilcoxon signed-rank test.
+#[derive(Clone, Copy, Serialize)]
+pub struct Wilcoxon {
+    /// pairs with a nonzero difference
+    pub n: usize,
+    /// sum of the ranks of positive differences
+    pub statistic: f64,
+    pub p: f64,
+}
+
+/// Complementary error function, accurate to 1.2e-7 (Numerical Recipes' erfcc).
+pub fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+/// Ranks of `values`, starting at 1, with ties getting their average rank.
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order = (0..values.len()).collect::<Vec<usize>>();
+    order.sort_by(|&x, &y| values[x].total_cmp(&values[y]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &index in &order[start..end] {
+            ranks[index] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// Test whether `differences` are symmetric around 0. Zero differences are dropped.
+/// Small samples without ties use the exact distribution, others the normal approximation
+/// with tie and continuity corrections.
+pub fn wilcoxon(differences: &[f64]) -> Option<Wilcoxon> {
+    let differences = differences
+        .iter()
+        .copied()
+        .filter(|x| *x != 0.0 && x.is_finite())
+        .collect::<Vec<f64>>();
+    let n = differences.len();
+    if n == 0 {
+        return None;
+    }
+    let mut magnitudes = differences.iter().map(|x| x.abs()).collect::<Vec<f64>>();
+    let ranks = ranks(&magnitudes);
+    magnitudes.sort_by(f64::total_cmp);
+    let ties = magnitudes.windows(2).any(|x| x[0] == x[1]);
+    let statistic = differences
+        .iter()
+        .zip(&ranks)
+        .filter(|(x, _)| **x > 0.0)
+        .map(|(_, rank)| rank)
+        .sum::<f64>();
+
+    let p = if n <= 30 && !ties {
+        // counts[s]: subsets of the ranks 1..=n summing to s.
+        let total = n * (n + 1) / 2;
+        let mut counts = vec![0u64; total + 1];
+        counts[0] = 1;
+        for rank in 1..=n {
+            for sum in (rank..=total).rev() {
+                counts[sum] += counts[sum - rank];
+            }
+        }
+        let all = 2f64.powi(n as i32);
+        let w = statistic as usize;
+        let lower = counts[..=w].iter().sum::<u64>() as f64 / all;
+        let upper = counts[w..].iter().sum::<u64>() as f64 / all;
+        (2.0 * lower.min(upper)).min(1.0)
+    } else {
+        let n = n as f64;
+        let mean = n * (n + 1.0) / 4.0;
+        let mut sorted = ranks.clone();
+        sorted.sort_by(f64::total_cmp);
+        let mut correction = 0.0;
+        let mut start = 0;
+        while start < sorted.len() {
+            let end = start
+                + sorted[start..]
+                    .iter()
+                    .take_while(|x| **x == sorted[start])
+                    .count();
+            let t = (end - start) as f64;
+            correction += t * t * t - t;
+            start = end;
+        }
+        let variance = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - correction / 48.0;
+        if variance <= 0.0 {
+            return None;
+        }
+        let deviation = statistic - mean;
+        let z = (deviation.abs() - 0.5).max(0.0) / variance.sqrt();
+        erfc(z / std::f64::consts::SQRT_2).min(1.0)
+    };
+    Some(Wilcoxon { n, statistic, p })
+}
+
+/// Spearman rank correlation of two samples of the same length.
+#[derive(Clone, Copy, Serialize)]
+pub struct Correlation {
+    pub n: usize,
+    pub rho: f64,
+    /// two-sided, from the normal approximation of rho·√(n-1)
+    pub p: f64,
+}
+
+/// Rank correlation of `x` and `y`; `None` for fewer than three pairs or a constant sample.
+pub fn spearman(x: &[f64], y: &[f64]) -> Option<Correlation> {
+    let n = x.len().min(y.len());
+    if n < 3 {
+        return None;
+    }
+    let (x, y) = (ranks(&x[..n]), ranks(&y[..n]));
+    let mean = (n as f64 + 1.0) / 2.0;
+    let (mut covariance, mut x_variance, mut y_variance) = (0.0, 0.0, 0.0);
+    for (x, y) in x.iter().zip(&y) {
+        covariance += (x - mean) * (y - mean);
+        x_variance += (x - mean) * (x - mean);
+        y_variance += (y - mean) * (y - mean);
+    }
+    if x_variance == 0.0 || y_variance == 0.0 {
+        return None;
+    }
+    let rho = covariance / (x_variance * y_variance).sqrt();
+    let z = rho.abs() * (n as f64 - 1.0).sqrt();
+    let p = erfc(z / std::f64::consts::SQRT_2).min(1.0);
+    Some(Correlation { n, rho, p })
+}
+
+/// Floating-point operations of an `m`×`n`×`k` product: 2mnk, and 2mn more to scale C by `beta`
+/// and add it unless beta is 0, when C is not read. With an `alpha` of 0, BLAS skips the product
+/// and only scales C, m·n.
+pub fn flops((m, n, k): (usize, usize, usize), alpha: f64, beta: f64) -> f64 {
+    let (m, n, k) = (m as f64, n as f64, k as f64);
+    if alpha == 0.0 {
+        m * n
+    } else if beta == 0.0 {
+        2.0 * m * n * k
+    } else {
+        2.0 * m * n * (k + 1.0)
+    }
+}
+
+/// Throughput in GFLOPS of `flops` per iteration: from the medium time in nanoseconds if
+/// there is one, from the average in milliseconds otherwise.
+pub fn gflops(flops: f64, medium: Option<f64>, average: f64) -> f64 {
+    match medium {
+        Some(medium) => flops / medium,
+        None => flops / average / 1000.0 / 1000.0,
+    }
+}
+
+/// Verdict of a paired test of a `change` in throughput at `significance`.
+pub fn verdict(test: Option<&Wilcoxon>, change: f64, significance: f64) -> &'static str {
+    match test {
+        Some(x) if x.p < significance && change > 0.0 => "faster",
+        Some(x) if x.p < significance && change < 0.0 => "slower",
+        Some(_) => "no difference",
+        None => "untested",
+    }
+}
+
+/// Change in percent of the geometric mean of ratios, given their logarithms.
+pub fn geomean_change(log_ratios: &[f64]) -> f64 {
+    ((log_ratios.iter().sum::<f64>() / log_ratios.len() as f64).exp() - 1.0) * 100.0
+}
@@ -0,0 +1,161 @@
+use crate::statistics::{self, Wilcoxon};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Metadata key under which suites store the configuration hash of an entry in its report.
+pub const META_CONFIGURATION: &str = "configuration";
+
+fn one() -> f64 {
+    1.0
+}
+
+/// A group of a batched report, as far as its throughput needs it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Group {
+    pub m: usize,
+    pub n: usize,
+    pub k: usize,
+    pub count: usize,
+    #[serde(default = "one", with = "crate::number")]
+    pub alpha: f64,
+    #[serde(default = "one", with = "crate::number")]
+    pub beta: f64,
+}
+
+/// The count of `--flops-formula` of a report, as far as its throughput needs it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Formula {
+    #[serde(with = "crate::number")]
+    pub value: f64,
+}
+
+/// Counters of a report, of which only the FLOPs change its throughput.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Counters {
+    #[serde(default)]
+    pub flops: Option<Formula>,
+}
+
+/// Statistics of a report; durations in nanoseconds, average and deviation in milliseconds.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Statistics {
+    pub medium: Option<f64>,
+    pub maximum: f64,
+    pub minimum: f64,
+    #[serde(with = "crate::number")]
+    pub average: f64,
+    #[serde(with = "crate::number")]
+    pub deviation: f64,
+}
+
+/// The parts of a report a viewer compares, read from report JSON whatever else it holds.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Summary {
+    pub name: String,
+    pub dimensions: (usize, usize, usize),
+    pub repeats: usize,
+    #[serde(default = "one", with = "crate::number")]
+    pub alpha: f64,
+    #[serde(default = "one", with = "crate::number")]
+    pub beta: f64,
+    pub statistics: Statistics,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub meta: BTreeMap<String, String>,
+    #[serde(default)]
+    pub batch: Vec<Group>,
+    #[serde(default)]
+    pub reuse_b: Option<usize>,
+    #[serde(default)]
+    pub counters: Option<Counters>,
+    /// measured durations in order, in nanoseconds
+    #[serde(default)]
+    pub samples: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+struct Suite {
+    reports: Vec<Summary>,
+}
+
+/// Summaries of a report or suite report file.
+pub fn parse(json: &str) -> Result<Vec<Summary>, String> {
+    match serde_json::from_str::<Summary>(json) {
+        Ok(summary) => Ok(vec![summary]),
+        Err(_) => serde_json::from_str::<Suite>(json)
+            .map(|x| x.reports)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+impl Summary {
+    /// Floating-point operations of one measured iteration.
+    pub fn flops(&self) -> f64 {
+        if let Some(flops) = self.counters.as_ref().and_then(|x| x.flops.as_ref()) {
+            flops.value
+        } else if self.batch.is_empty() {
+            statistics::flops(self.dimensions, self.alpha, self.beta)
+                * self.reuse_b.unwrap_or(1) as f64
+        } else {
+            self.batch
+                .iter()
+                .map(|x| statistics::flops((x.m, x.n, x.k), x.alpha, x.beta) * x.count as f64)
+                .sum()
+        }
+    }
+
+    pub fn gflops(&self) -> f64 {
+        statistics::gflops(
+            self.flops(),
+            self.statistics.medium,
+            self.statistics.average,
+        )
+    }
+}
+
+/// Throughput of one configuration in two runs.
+#[derive(Serialize)]
+pub struct Comparison {
+    pub name: String,
+    pub configuration: String,
+    pub before: f64,
+    pub after: f64,
+    /// in percent
+    pub change: f64,
+    /// on the durations paired by iteration
+    pub test: Option<Wilcoxon>,
+}
+
+/// Compare the reports of `before` and `after` that share a configuration, as
+/// `benchmark-viewer --pair-with` does, and test the changes of all of them.
+pub fn compare(before: &[Summary], after: &[Summary]) -> (Vec<Comparison>, Option<Wilcoxon>) {
+    let comparisons = before
+        .iter()
+        .filter_map(|x| {
+            let configuration = x.meta.get(META_CONFIGURATION)?;
+            let y = after
+                .iter()
+                .find(|y| y.meta.get(META_CONFIGURATION) == Some(configuration))?;
+            let differences = x
+                .samples
+                .iter()
+                .zip(&y.samples)
+                .map(|(x, y)| y / 1000.0 / 1000.0 - x / 1000.0 / 1000.0)
+                .collect::<Vec<f64>>();
+            Some(Comparison {
+                name: x.name.clone(),
+                configuration: configuration.clone(),
+                before: x.gflops(),
+                after: y.gflops(),
+                change: (y.gflops() / x.gflops() - 1.0) * 100.0,
+                test: statistics::wilcoxon(&differences),
+            })
+        })
+        .collect::<Vec<Comparison>>();
+    let ratios = comparisons
+        .iter()
+        .map(|x| (x.after / x.before).ln())
+        .collect::<Vec<f64>>();
+    (comparisons, statistics::wilcoxon(&ratios))
+}
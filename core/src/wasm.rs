@@ -0,0 +1,74 @@
+use crate::{statistics, summary};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, JsError> {
+    serde_json::to_string(value).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Summaries of a report or suite report file, as a JSON array, each with its GFLOPS.
+#[wasm_bindgen]
+pub fn summaries(json: &str) -> Result<String, JsError> {
+    #[derive(Serialize)]
+    struct Entry {
+        #[serde(flatten)]
+        summary: summary::Summary,
+        gflops: f64,
+    }
+
+    let summaries = summary::parse(json).map_err(|e| JsError::new(&e))?;
+    to_json(
+        &summaries
+            .into_iter()
+            .map(|summary| Entry {
+                gflops: summary.gflops(),
+                summary,
+            })
+            .collect::<Vec<Entry>>(),
+    )
+}
+
+/// Paired comparison of two report or suite report files as JSON: every configuration in both
+/// with its verdict at `significance`, and the overall change and verdict.
+#[wasm_bindgen]
+pub fn compare(before: &str, after: &str, significance: f64) -> Result<String, JsError> {
+    #[derive(Serialize)]
+    struct Row<'a> {
+        #[serde(flatten)]
+        comparison: &'a summary::Comparison,
+        verdict: &'static str,
+    }
+
+    #[derive(Serialize)]
+    struct Comparison<'a> {
+        configurations: Vec<Row<'a>>,
+        change: f64,
+        test: Option<statistics::Wilcoxon>,
+        verdict: &'static str,
+    }
+
+    let before = summary::parse(before).map_err(|e| JsError::new(&e))?;
+    let after = summary::parse(after).map_err(|e| JsError::new(&e))?;
+    let (comparisons, test) = summary::compare(&before, &after);
+    if comparisons.is_empty() {
+        return Err(JsError::new("no configuration is in both runs"));
+    }
+    let change = statistics::geomean_change(
+        &comparisons
+            .iter()
+            .map(|x| (x.after / x.before).ln())
+            .collect::<Vec<f64>>(),
+    );
+    to_json(&Comparison {
+        configurations: comparisons
+            .iter()
+            .map(|x| Row {
+                comparison: x,
+                verdict: statistics::verdict(x.test.as_ref(), x.change, significance),
+            })
+            .collect(),
+        change,
+        test,
+        verdict: statistics::verdict(test.as_ref(), change, significance),
+    })
+}
@@ -0,0 +1,53 @@
+/* C API of the benchmark library, built with `cargo build --release --features capi`
+ * into libbenchmark.so (benchmark.dll, libbenchmark.dylib). */
+#ifndef BENCH_DGEMM_H
+#define BENCH_DGEMM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define BENCH_DGEMM_OK 0
+#define BENCH_DGEMM_ERROR (-1)
+
+typedef struct bench_dgemm_config {
+    /* kernel source, or an object built beforehand if it has the platform's library extension */
+    const char *kernel_path;
+    /* compiler of the kernel source, NULL for the platform default */
+    const char *compiler;
+    size_t m, n, k;
+    size_t repeats;
+    size_t warm_up;
+    double alpha, beta;
+    /* A is generated from seed + 100 and B from seed + 200 */
+    uint64_t seed;
+} bench_dgemm_config;
+
+typedef struct bench_dgemm_report bench_dgemm_report;
+
+/* Fill config with the defaults of the command line: 10 repeats, alpha and beta 1. */
+void bench_dgemm_config_init(bench_dgemm_config *config);
+
+/* Compile or load the kernel, benchmark it and store its report in *report.
+ * Returns BENCH_DGEMM_OK, or BENCH_DGEMM_ERROR with the reason in bench_dgemm_last_error(). */
+int bench_dgemm_run(const bench_dgemm_config *config, bench_dgemm_report **report);
+
+/* Why the last failed call of this thread failed, valid until the next one fails. */
+const char *bench_dgemm_last_error(void);
+
+double bench_dgemm_report_gflops(const bench_dgemm_report *report);
+double bench_dgemm_report_medium_ns(const bench_dgemm_report *report);
+/* The report as JSON, as --save-as writes it; free with bench_dgemm_string_free. */
+char *bench_dgemm_report_json(const bench_dgemm_report *report);
+
+void bench_dgemm_report_free(bench_dgemm_report *report);
+void bench_dgemm_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,30 @@
+/* Naive triple loop. Handles every layout and transpose combination. */
+#include <stddef.h>
+
+#define ROW_MAJOR 101
+#define NO_TRANS 111
+
+/* index of op(X)(i, j) */
+static inline size_t at(int row_major, int trans, size_t i, size_t j, size_t ld) {
+    return (row_major != trans) ? i * ld + j : i + j * ld;
+}
+
+void call_dgemm(unsigned int layout, unsigned int trans_a, unsigned int trans_b,
+                size_t m, size_t n, size_t k, double alpha,
+                const double *A, size_t lda, const double *B, size_t ldb,
+                double beta, double *C, size_t ldc) {
+    int row_major = layout == ROW_MAJOR;
+    int ta = trans_a != NO_TRANS;
+    int tb = trans_b != NO_TRANS;
+
+    for (size_t i = 0; i < m; i++) {
+        for (size_t j = 0; j < n; j++) {
+            double sum = 0.0;
+            for (size_t p = 0; p < k; p++) {
+                sum += A[at(row_major, ta, i, p, lda)] * B[at(row_major, tb, p, j, ldb)];
+            }
+            double *c = &C[at(row_major, 0, i, j, ldc)];
+            *c = alpha * sum + (beta == 0.0 ? 0.0 : beta * *c);
+        }
+    }
+}
@@ -0,0 +1,23 @@
+/* Passthrough to the vendor BLAS the harness verifies against. */
+#include <stddef.h>
+
+#if defined(__APPLE__)
+#include <Accelerate/Accelerate.h>
+#elif defined(__aarch64__)
+#include <armpl.h>
+#else
+#include <mkl_cblas.h>
+#endif
+
+void call_dgemm(unsigned int layout, unsigned int trans_a, unsigned int trans_b,
+                size_t m, size_t n, size_t k, double alpha,
+                const double *A, size_t lda, const double *B, size_t ldb,
+                double beta, double *C, size_t ldc) {
+#if defined(__APPLE__)
+    cblas_dgemm((enum CBLAS_ORDER)layout, (enum CBLAS_TRANSPOSE)trans_a, (enum CBLAS_TRANSPOSE)trans_b,
+                (int)m, (int)n, (int)k, alpha, A, (int)lda, B, (int)ldb, beta, C, (int)ldc);
+#else
+    cblas_dgemm((CBLAS_LAYOUT)layout, (CBLAS_TRANSPOSE)trans_a, (CBLAS_TRANSPOSE)trans_b,
+                (int)m, (int)n, (int)k, alpha, A, (int)lda, B, (int)ldb, beta, C, (int)ldc);
+#endif
+}
@@ -1,6 +1,11 @@
 use std::{env, path};
 
 fn main() {
+    // BLAS is loaded at runtime, nothing to link.
+    if env::var_os("CARGO_FEATURE_RUNTIME").is_some() {
+        return;
+    }
+
     dotenv::from_path(path::absolute(env::current_dir().unwrap().join("../.env")).unwrap())
         .unwrap();
 
@@ -10,7 +15,12 @@ fn main() {
     );
 }
 
-#[cfg(target_arch = "aarch64")]
+#[cfg(target_os = "macos")]
+fn link(_: String, _: String) {
+    println!("cargo::rustc-link-lib=framework=Accelerate");
+}
+
+#[cfg(all(not(target_os = "macos"), target_arch = "aarch64"))]
 fn link(path_compiler: String, path_library: String) {
     println!("cargo::rustc-link-lib=dylib=omp");
     println!("cargo::rustc-link-lib=dylib=flang");
@@ -19,10 +29,23 @@ fn link(path_compiler: String, path_library: String) {
     println!("cargo::rustc-link-search=native={}/lib/", path_library);
 }
 
-#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[cfg(all(
+    not(windows),
+    not(target_os = "macos"),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
 fn link(path_compiler: String, path_library: String) {
     println!("cargo::rustc-link-lib=dylib=gomp");
     println!("cargo::rustc-link-lib=dylib=mkl_rt");
     println!("cargo::rustc-link-search=native={}/", path_compiler);
     println!("cargo::rustc-link-search=native={}/", path_library);
 }
+
+#[cfg(all(windows, any(target_arch = "x86", target_arch = "x86_64")))]
+fn link(path_compiler: String, path_library: String) {
+    // oneAPI layout: compiler/latest/lib and mkl/latest/lib
+    println!("cargo::rustc-link-lib=dylib=libiomp5md");
+    println!("cargo::rustc-link-lib=dylib=mkl_rt");
+    println!("cargo::rustc-link-search=native={}/", path_compiler);
+    println!("cargo::rustc-link-search=native={}/", path_library);
+}
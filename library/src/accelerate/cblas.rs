@@ -0,0 +1,59 @@
+/* written by hand after vecLib's cblas.h; Accelerate ships no standalone header to run bindgen on. */
+
+pub type __LAPACK_int = ::std::os::raw::c_int;
+impl CBLAS_LAYOUT {
+    pub const CblasRowMajor: CBLAS_LAYOUT = CBLAS_LAYOUT(101);
+}
+impl CBLAS_LAYOUT {
+    pub const CblasColMajor: CBLAS_LAYOUT = CBLAS_LAYOUT(102);
+}
+#[repr(transparent)]
+#[derive(
+    Copy, Clone, Hash, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+pub struct CBLAS_LAYOUT(pub ::std::os::raw::c_uint);
+impl CBLAS_TRANSPOSE {
+    pub const CblasNoTrans: CBLAS_TRANSPOSE = CBLAS_TRANSPOSE(111);
+}
+impl CBLAS_TRANSPOSE {
+    pub const CblasTrans: CBLAS_TRANSPOSE = CBLAS_TRANSPOSE(112);
+}
+impl CBLAS_TRANSPOSE {
+    pub const CblasConjTrans: CBLAS_TRANSPOSE = CBLAS_TRANSPOSE(113);
+}
+#[repr(transparent)]
+#[derive(
+    Copy, Clone, Hash, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+pub struct CBLAS_TRANSPOSE(pub ::std::os::raw::c_uint);
+unsafe extern "C" {
+    pub fn cblas_daxpy(
+        N: __LAPACK_int,
+        alpha: f64,
+        X: *const f64,
+        incX: __LAPACK_int,
+        Y: *mut f64,
+        incY: __LAPACK_int,
+    );
+}
+unsafe extern "C" {
+    pub fn cblas_dnrm2(N: __LAPACK_int, X: *const f64, incX: __LAPACK_int) -> f64;
+}
+unsafe extern "C" {
+    pub fn cblas_dgemm(
+        ORDER: CBLAS_LAYOUT,
+        TRANSA: CBLAS_TRANSPOSE,
+        TRANSB: CBLAS_TRANSPOSE,
+        M: __LAPACK_int,
+        N: __LAPACK_int,
+        K: __LAPACK_int,
+        ALPHA: f64,
+        A: *const f64,
+        LDA: __LAPACK_int,
+        B: *const f64,
+        LDB: __LAPACK_int,
+        BETA: f64,
+        C: *mut f64,
+        LDC: __LAPACK_int,
+    );
+}
@@ -0,0 +1,3 @@
+#[allow(warnings)]
+pub mod cblas;
+pub use cblas::*;
@@ -1,14 +1,44 @@
-#[cfg(target_arch = "aarch64")]
+#[cfg(target_os = "macos")]
+mod accelerate;
+#[cfg(all(target_os = "macos", not(feature = "runtime")))]
+pub use accelerate::{cblas_daxpy, cblas_dgemm, cblas_dnrm2};
+#[cfg(target_os = "macos")]
+pub use accelerate::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+
+#[cfg(all(not(target_os = "macos"), target_arch = "aarch64"))]
 mod armpl;
-#[cfg(target_arch = "aarch64")]
-pub use armpl::{cblas_daxpy, cblas_dgemm, cblas_dnrm2, CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+#[cfg(all(
+    not(target_os = "macos"),
+    target_arch = "aarch64",
+    not(feature = "runtime")
+))]
+pub use armpl::{cblas_daxpy, cblas_dgemm, cblas_dnrm2};
+#[cfg(all(not(target_os = "macos"), target_arch = "aarch64"))]
+pub use armpl::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
 
-#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[cfg(all(
+    not(target_os = "macos"),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
 mod mkl;
-#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-pub use mkl::{cblas_daxpy, cblas_dgemm, cblas_dnrm2, CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+#[cfg(all(
+    not(target_os = "macos"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(feature = "runtime")
+))]
+pub use mkl::{cblas_daxpy, cblas_dgemm, cblas_dnrm2};
+#[cfg(all(
+    not(target_os = "macos"),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+pub use mkl::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+
+#[cfg(feature = "runtime")]
+mod runtime;
+#[cfg(feature = "runtime")]
+pub use runtime::{cblas_daxpy, cblas_dgemm, cblas_dnrm2, ENV_BLAS};
 
-use std::fmt;
+use std::{fmt, str};
 
 impl fmt::Display for CBLAS_LAYOUT {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -48,15 +78,85 @@ impl<'a> TryFrom<&'a str> for CBLAS_LAYOUT {
     }
 }
 
-impl<'a> TryFrom<&'a str> for CBLAS_TRANSPOSE {
+/// Parse a transpose operation from its CBLAS value.
+///
+/// ```
+/// use library::CBLAS_TRANSPOSE;
+///
+/// assert!(CBLAS_TRANSPOSE::try_from(111) == Ok(CBLAS_TRANSPOSE::CblasNoTrans));
+/// assert!(CBLAS_TRANSPOSE::try_from(112) == Ok(CBLAS_TRANSPOSE::CblasTrans));
+/// assert!(CBLAS_TRANSPOSE::try_from(113) == Ok(CBLAS_TRANSPOSE::CblasConjTrans));
+/// for value in (0..=1024).chain([u32::MAX]).filter(|x| !(111..=113).contains(x)) {
+///     assert_eq!(
+///         CBLAS_TRANSPOSE::try_from(value).err().unwrap(),
+///         format!("expected one of [111, 112, 113], but got {}", value)
+///     );
+/// }
+/// ```
+impl TryFrom<u32> for CBLAS_TRANSPOSE {
     type Error = String;
 
-    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match CBLAS_TRANSPOSE(value) {
+            x @ (CBLAS_TRANSPOSE::CblasNoTrans
+            | CBLAS_TRANSPOSE::CblasTrans
+            | CBLAS_TRANSPOSE::CblasConjTrans) => Ok(x),
+            _ => Err([
+                "expected one of [111, 112, 113], but got ",
+                &value.to_string(),
+            ]
+            .concat()),
+        }
+    }
+}
+
+/// Parse a transpose operation from `N`/`T`/`C`, `FALSE`/`TRUE`/`CONJ` (case-insensitive)
+/// or its CBLAS value.
+///
+/// ```
+/// use library::CBLAS_TRANSPOSE;
+///
+/// for (names, expected) in [
+///     (["N", "n", "FALSE", "false", "111"], CBLAS_TRANSPOSE::CblasNoTrans),
+///     (["T", "t", "TRUE", "True", "112"], CBLAS_TRANSPOSE::CblasTrans),
+///     (["C", "c", "CONJ", "conj", "113"], CBLAS_TRANSPOSE::CblasConjTrans),
+/// ] {
+///     for name in names {
+///         assert!(name.parse::<CBLAS_TRANSPOSE>() == Ok(expected));
+///         assert!(CBLAS_TRANSPOSE::try_from(name) == Ok(expected));
+///     }
+/// }
+/// for name in ["", "X", "NT", "yes", "110", "114", "-111", " N"] {
+///     assert!(name.parse::<CBLAS_TRANSPOSE>().is_err());
+/// }
+/// assert_eq!(
+///     "X".parse::<CBLAS_TRANSPOSE>().err().unwrap(),
+///     "expected one of [N, T, C, FALSE, TRUE, CONJ], but got X"
+/// );
+/// ```
+impl str::FromStr for CBLAS_TRANSPOSE {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Ok(value) = value.parse::<u32>() {
+            return CBLAS_TRANSPOSE::try_from(value);
+        }
         match_table!(
             value.to_uppercase().as_str(),
+            "N" => CBLAS_TRANSPOSE::CblasNoTrans,
+            "T" => CBLAS_TRANSPOSE::CblasTrans,
+            "C" => CBLAS_TRANSPOSE::CblasConjTrans,
             "FALSE" => CBLAS_TRANSPOSE::CblasNoTrans,
             "TRUE" => CBLAS_TRANSPOSE::CblasTrans,
             "CONJ" => CBLAS_TRANSPOSE::CblasConjTrans
         )
     }
 }
+
+impl<'a> TryFrom<&'a str> for CBLAS_TRANSPOSE {
+    type Error = String;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+use std::{env, ffi::c_int, sync::LazyLock};
+
+/// Path to the BLAS shared object to load instead of the default.
+pub const ENV_BLAS: &str = "BENCHMARK_DGEMM_BLAS";
+
+#[cfg(target_os = "macos")]
+const DEFAULT_BLAS: &str = "/System/Library/Frameworks/Accelerate.framework/Accelerate";
+#[cfg(windows)]
+const DEFAULT_BLAS: &str = "mkl_rt.2.dll";
+#[cfg(all(not(target_os = "macos"), not(windows), target_arch = "aarch64"))]
+const DEFAULT_BLAS: &str = "libarmpl_mp.so";
+#[cfg(all(
+    not(target_os = "macos"),
+    not(windows),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+const DEFAULT_BLAS: &str = "libmkl_rt.so";
+
+static BLAS: LazyLock<libloading::Library> = LazyLock::new(|| {
+    let path = env::var(ENV_BLAS).unwrap_or_else(|_| DEFAULT_BLAS.to_string());
+    unsafe { libloading::Library::new(&path) }
+        .unwrap_or_else(|e| panic!("failed to load BLAS from {}: {}", path, e))
+});
+
+macro_rules! symbol {
+    ($name:literal, $type:ty) => {
+        BLAS.get::<$type>(concat!($name, "\0").as_bytes())
+            .unwrap_or_else(|e| panic!("BLAS does not provide {}: {}", $name, e))
+    };
+}
+
+pub unsafe fn cblas_daxpy(
+    n: c_int,
+    alpha: f64,
+    x: *const f64,
+    incx: c_int,
+    y: *mut f64,
+    incy: c_int,
+) {
+    symbol!(
+        "cblas_daxpy",
+        unsafe extern "C" fn(c_int, f64, *const f64, c_int, *mut f64, c_int)
+    )(n, alpha, x, incx, y, incy)
+}
+
+pub unsafe fn cblas_dnrm2(n: c_int, x: *const f64, incx: c_int) -> f64 {
+    symbol!(
+        "cblas_dnrm2",
+        unsafe extern "C" fn(c_int, *const f64, c_int) -> f64
+    )(n, x, incx)
+}
+
+pub unsafe fn cblas_dgemm(
+    layout: CBLAS_LAYOUT,
+    trans_a: CBLAS_TRANSPOSE,
+    trans_b: CBLAS_TRANSPOSE,
+    m: c_int,
+    n: c_int,
+    k: c_int,
+    alpha: f64,
+    a: *const f64,
+    lda: c_int,
+    b: *const f64,
+    ldb: c_int,
+    beta: f64,
+    c: *mut f64,
+    ldc: c_int,
+) {
+    symbol!(
+        "cblas_dgemm",
+        unsafe extern "C" fn(
+            CBLAS_LAYOUT,
+            CBLAS_TRANSPOSE,
+            CBLAS_TRANSPOSE,
+            c_int,
+            c_int,
+            c_int,
+            f64,
+            *const f64,
+            c_int,
+            *const f64,
+            c_int,
+            f64,
+            *mut f64,
+            c_int,
+        )
+    )(
+        layout, trans_a, trans_b, m, n, k, alpha, a, lda, b, ldb, beta, c, ldc,
+    )
+}
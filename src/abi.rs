@@ -0,0 +1,59 @@
+use std::ffi::c_uint;
+
+/// Optional data symbol of a kernel, `const unsigned int kernel_abi_version = 2;`.
+/// Kernels without it are treated as version 1.
+pub const SYMBOL_VERSION: &[u8] = b"kernel_abi_version";
+
+/// `call_dgemm(layout, trans_a, trans_b, m, n, k, alpha, A, lda, B, ldb, beta, C, ldc)`.
+pub const V1: c_uint = 1;
+/// `call_dgemm(context, layout, trans_a, ...)`, with a `Context` as the first argument.
+pub const V2: c_uint = 2;
+pub const SUPPORTED: &[c_uint] = &[V1, V2];
+
+/// Why the kernel is being called.
+#[repr(u32)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Prewarm = 0,
+    Verification = 1,
+    WarmUp = 2,
+    Measurement = 3,
+}
+
+/// First argument of a version 2 kernel.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Context {
+    pub abi_version: c_uint,
+    pub phase: Phase,
+    pub session: c_uint,
+    /// index of the call within its phase.
+    pub iteration: c_uint,
+}
+
+impl Context {
+    pub fn new(phase: Phase, session: usize, iteration: usize) -> Self {
+        Context {
+            abi_version: V2,
+            phase,
+            session: session as c_uint,
+            iteration: iteration as c_uint,
+        }
+    }
+}
+
+/// ABI version exported by `library`.
+pub fn version(library: &libloading::Library) -> Result<c_uint, String> {
+    let version = match unsafe { library.get::<*const c_uint>(SYMBOL_VERSION) } {
+        Ok(symbol) => unsafe { **symbol },
+        Err(_) => V1,
+    };
+    if SUPPORTED.contains(&version) {
+        Ok(version)
+    } else {
+        Err(format!(
+            "kernel exports kernel_abi_version = {}, but this harness supports {:?}",
+            version, SUPPORTED
+        ))
+    }
+}
@@ -0,0 +1,225 @@
+use std::{fs, thread};
+
+/// Parse a Linux-style CPU list, e.g. "0-3,8,10-11", or "all" for every CPU this process may run on.
+pub fn parse_cpu_list(value: &str) -> Result<Vec<usize>, String> {
+    if value.eq_ignore_ascii_case("all") {
+        return Ok(available());
+    }
+    let error = || ["expected a CPU list like '0-3,8', but got '", value, "'"].concat();
+
+    let mut cpus = Vec::new();
+    for part in value.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let first = first.trim().parse::<usize>().map_err(|_| error())?;
+                let last = last.trim().parse::<usize>().map_err(|_| error())?;
+                if first > last {
+                    return Err(error());
+                }
+                cpus.extend(first..=last);
+            }
+            None => cpus.push(part.parse::<usize>().map_err(|_| error())?),
+        }
+    }
+    if cpus.is_empty() {
+        return Err(error());
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    Ok(cpus)
+}
+
+/// Inverse of `parse_cpu_list`, collapsing consecutive CPUs into ranges.
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut parts = Vec::new();
+    let mut index = 0;
+    while index < cpus.len() {
+        let first = cpus[index];
+        while index + 1 < cpus.len() && cpus[index + 1] == cpus[index] + 1 {
+            index += 1;
+        }
+        parts.push(if cpus[index] == first {
+            first.to_string()
+        } else {
+            format!("{}-{}", first, cpus[index])
+        });
+        index += 1;
+    }
+    parts.join(",")
+}
+
+/// CPUs this process may run on.
+#[cfg(target_os = "linux")]
+pub fn available() -> Vec<usize> {
+    unsafe {
+        let mut set = std::mem::zeroed::<libc::cpu_set_t>();
+        if libc::sched_getaffinity(0, size_of::<libc::cpu_set_t>(), &mut set) == 0 {
+            return (0..libc::CPU_SETSIZE as usize)
+                .filter(|&x| libc::CPU_ISSET(x, &set))
+                .collect();
+        }
+    }
+    (0..thread::available_parallelism().map_or(1, |x| x.get())).collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn available() -> Vec<usize> {
+    (0..thread::available_parallelism().map_or(1, |x| x.get())).collect()
+}
+
+/// Restrict this process, and every thread and child it creates afterwards, to `cpus`.
+#[cfg(target_os = "linux")]
+pub fn pin(cpus: &[usize]) -> Result<(), String> {
+    unsafe {
+        let mut set = std::mem::zeroed::<libc::cpu_set_t>();
+        for &cpu in cpus {
+            if cpu >= libc::CPU_SETSIZE as usize {
+                return Err(format!("CPU {} is out of range", cpu));
+            }
+            libc::CPU_SET(cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin(_: &[usize]) -> Result<(), String> {
+    Err("pinning to CPUs is not supported on this platform".to_string())
+}
+
+fn read_cpu_list(path: &str) -> Option<Vec<usize>> {
+    parse_cpu_list(fs::read_to_string(path).ok()?.trim()).ok()
+}
+
+/// Available CPUs grouped by socket (physical package), in package order.
+pub fn sockets() -> Vec<Vec<usize>> {
+    let mut sockets = std::collections::BTreeMap::<usize, Vec<usize>>::new();
+    for cpu in available() {
+        let package = fs::read_to_string(format!(
+            "/sys/devices/system/cpu/cpu{}/topology/physical_package_id",
+            cpu
+        ))
+        .ok()
+        .and_then(|x| x.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+        sockets.entry(package).or_default().push(cpu);
+    }
+    sockets.into_values().collect()
+}
+
+/// NUMA nodes with their available CPUs; nodes without CPUs are skipped.
+pub fn numa_nodes() -> Vec<(usize, Vec<usize>)> {
+    let available = available();
+    let mut nodes = Vec::new();
+    for node in 0.. {
+        let path = format!("/sys/devices/system/node/node{}", node);
+        if !std::path::Path::new(&path).exists() {
+            break;
+        }
+        let cpus = read_cpu_list(&[path.as_str(), "/cpulist"].concat())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|x| available.contains(x))
+            .collect::<Vec<usize>>();
+        if !cpus.is_empty() {
+            nodes.push((node, cpus));
+        }
+    }
+    nodes
+}
+
+/// Parse a list of NUMA nodes, e.g. "0-1", or "all" for every node with CPUs.
+pub fn parse_node_list(value: &str) -> Result<Vec<usize>, String> {
+    if value.eq_ignore_ascii_case("all") {
+        return Ok(numa_nodes().into_iter().map(|(node, _)| node).collect());
+    }
+    parse_cpu_list(value)
+}
+
+/// Unit of a scaling study.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Domain {
+    Socket,
+    Numa,
+}
+
+impl<'a> TryFrom<&'a str> for Domain {
+    type Error = String;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_uppercase().as_str() {
+            "SOCKET" => Ok(Domain::Socket),
+            "NUMA" => Ok(Domain::Numa),
+            x => Err(["expected one of [SOCKET, NUMA], but got ", x].concat()),
+        }
+    }
+}
+
+impl Domain {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Domain::Socket => "socket",
+            Domain::Numa => "numa",
+        }
+    }
+
+    /// CPUs and local NUMA nodes of each unit, in order.
+    pub fn units(&self) -> Vec<(Vec<usize>, Vec<usize>)> {
+        match self {
+            Domain::Socket => sockets()
+                .into_iter()
+                .map(|cpus| {
+                    let nodes = nodes_of(&cpus);
+                    (cpus, nodes)
+                })
+                .collect(),
+            Domain::Numa => numa_nodes()
+                .into_iter()
+                .map(|(node, cpus)| (cpus, vec![node]))
+                .collect(),
+        }
+    }
+}
+
+/// NUMA nodes local to any of `cpus`.
+pub fn nodes_of(cpus: &[usize]) -> Vec<usize> {
+    numa_nodes()
+        .into_iter()
+        .filter(|(_, x)| x.iter().any(|cpu| cpus.contains(cpu)))
+        .map(|(node, _)| node)
+        .collect()
+}
+
+/// Allocate all further memory of this process on `nodes` only (MPOL_BIND).
+#[cfg(target_os = "linux")]
+pub fn bind_memory(nodes: &[usize]) -> Result<(), String> {
+    const MPOL_BIND: libc::c_int = 2;
+    const BITS: usize = libc::c_ulong::BITS as usize;
+
+    let mut mask = vec![0 as libc::c_ulong; nodes.iter().max().map_or(1, |x| x / BITS + 1)];
+    for &node in nodes {
+        mask[node / BITS] |= 1 << (node % BITS);
+    }
+    // the kernel reads one bit less than maxnode.
+    let maxnode = mask.len() * BITS + 1;
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_set_mempolicy,
+            MPOL_BIND,
+            mask.as_ptr(),
+            maxnode as libc::c_ulong,
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn bind_memory(_: &[usize]) -> Result<(), String> {
+    Err("memory policies are not supported on this platform".to_string())
+}
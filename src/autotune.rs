@@ -0,0 +1,491 @@
+use crate::{
+    cache::Cache,
+    config,
+    constraint::{self, Constraint},
+    suite,
+    table::csv_field,
+    units::FlopsUnit,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path, time};
+
+/// Values of every tunable of a candidate, by name.
+pub type Candidate = BTreeMap<String, String>;
+
+/// Tunables of a kernel and the benchmark options every candidate runs with.
+#[derive(Deserialize)]
+pub struct Space {
+    pub kernel: String,
+    /// Values of every tunable, substituted for `{name}` in option values like suite parameters.
+    pub params: toml::Table,
+    /// Options of every candidate, passed as `--key value` like suite entries.
+    #[serde(default)]
+    pub options: toml::Table,
+    /// Conditions every candidate has to meet, e.g. `MC % MR == 0`; others are skipped before
+    /// they are compiled. `L1_BYTES`, `L2_BYTES`, ... are the cache sizes of this machine.
+    #[serde(default)]
+    pub constraints: Vec<String>,
+    #[serde(skip)]
+    parsed: Vec<Constraint>,
+}
+
+impl Space {
+    pub fn load(file: &path::Path) -> Result<Space, String> {
+        let mut space: Space =
+            toml::from_str(&fs::read_to_string(file).map_err(|e| e.to_string())?)
+                .map_err(|e| e.to_string())?;
+        // the scheduler decides how long and where each candidate runs.
+        for key in ["repeats", "save-as", "save_as"] {
+            if space.options.contains_key(key) {
+                return Err(format!("'{}' is set by the scheduler", key));
+            }
+        }
+
+        space.parsed = space
+            .constraints
+            .iter()
+            .map(|x| constraint::parse(x))
+            .collect::<Result<_, _>>()?;
+
+        // kernel path is relative to the space file.
+        if let Some(dir) = file.parent() {
+            space.kernel = dir.join(&space.kernel).to_string_lossy().to_string();
+        }
+        Ok(space)
+    }
+
+    /// Every combination of tunable values that meets the constraints on a machine with `caches`.
+    pub fn candidates(&self, caches: &[Cache]) -> Result<Vec<Candidate>, String> {
+        let mut constants = BTreeMap::new();
+        for cache in caches {
+            constants.insert(format!("L{}_BYTES", cache.level), cache.size as f64);
+        }
+        let mut candidates = Vec::new();
+        for candidate in suite::instances(&self.params) {
+            let mut variables = constants.clone();
+            for (name, value) in &candidate {
+                if let Ok(value) = value.parse::<f64>() {
+                    variables.insert(name.clone(), value);
+                }
+            }
+            let mut holds = true;
+            for constraint in &self.parsed {
+                holds = holds && constraint.holds(&variables)?;
+            }
+            if holds {
+                candidates.push(candidate);
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// Benchmark arguments of `candidate`, kernel path first.
+    pub fn arguments(&self, candidate: &Candidate) -> Vec<String> {
+        let mut arguments = vec![self.kernel.clone()];
+        for (key, value) in &self.options {
+            config::push_value(
+                &mut arguments,
+                &["--", key.replace('_', "-").as_str()].concat(),
+                &suite::substitute(value, candidate),
+            );
+        }
+        arguments
+    }
+}
+
+/// A candidate to run next, as an index into the candidates of the space.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Proposal {
+    pub candidate: usize,
+    pub repeats: usize,
+}
+
+/// How a search picks the candidates of its next round from the evaluations so far.
+/// Implement it to drive `search` with a strategy of your own.
+pub trait SearchStrategy {
+    /// Candidates of the next round; empty when the search is done.
+    fn propose(&mut self, candidates: &[Candidate], evaluations: &[Evaluation]) -> Vec<Proposal>;
+}
+
+/// Built-in strategies, selectable with `--strategy`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Halving,
+    Bayesian,
+}
+
+impl<'a> TryFrom<&'a str> for Strategy {
+    type Error = String;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_uppercase().as_str() {
+            "HALVING" => Ok(Strategy::Halving),
+            "BAYESIAN" => Ok(Strategy::Bayesian),
+            x => Err(["expected one of [HALVING, BAYESIAN], but got ", x].concat()),
+        }
+    }
+}
+
+/// Successive halving: every rung measures the remaining candidates with `eta` times the repeats
+/// of the previous one and promotes the best `1/eta` of them, until one is left.
+pub struct Halving {
+    pub eta: usize,
+    /// repeats of the first rung
+    pub min_repeats: usize,
+}
+
+impl Halving {
+    /// Candidates and repeats of every rung when starting with `candidates`.
+    pub fn rungs(&self, candidates: usize) -> Vec<(usize, usize)> {
+        let (mut count, mut repeats) = (candidates, self.min_repeats);
+        let mut rungs = Vec::new();
+        loop {
+            rungs.push((count, repeats));
+            if count <= self.eta {
+                return rungs;
+            }
+            count = count.div_ceil(self.eta);
+            repeats *= self.eta;
+        }
+    }
+}
+
+impl SearchStrategy for Halving {
+    fn propose(&mut self, candidates: &[Candidate], evaluations: &[Evaluation]) -> Vec<Proposal> {
+        let Some(last) = evaluations.iter().rfind(|x| x.status != Status::Seeded) else {
+            return (0..candidates.len())
+                .map(|candidate| Proposal {
+                    candidate,
+                    repeats: self.min_repeats,
+                })
+                .collect();
+        };
+        let rung = evaluations
+            .iter()
+            .filter(|x| x.round == last.round && x.status != Status::Seeded)
+            .collect::<Vec<&Evaluation>>();
+        // a rung of at most `eta` promotes only its winner.
+        if rung.len() <= self.eta {
+            return Vec::new();
+        }
+        let gflops = rung.iter().map(|x| x.gflops).collect::<Vec<Option<f64>>>();
+        promote(&gflops, rung.len().div_ceil(self.eta))
+            .into_iter()
+            .filter_map(|x| {
+                Some(Proposal {
+                    candidate: candidates.iter().position(|y| *y == rung[x].candidate)?,
+                    repeats: rung[x].repeats * self.eta,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Indices of the `keep` highest `gflops`; failed candidates are never promoted.
+pub fn promote(gflops: &[Option<f64>], keep: usize) -> Vec<usize> {
+    let mut ranked = (0..gflops.len())
+        .filter(|&x| gflops[x].is_some())
+        .collect::<Vec<usize>>();
+    ranked.sort_by(|&x, &y| gflops[y].unwrap().total_cmp(&gflops[x].unwrap()));
+    ranked.truncate(keep);
+    ranked
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Completed,
+    Failed,
+    /// measured again with more repeats in a later round
+    Promoted,
+    Winner,
+    /// taken from the log of a previous search given to `--warm-start`
+    Seeded,
+}
+
+impl Status {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Completed => "completed",
+            Status::Failed => "failed",
+            Status::Promoted => "promoted",
+            Status::Winner => "winner",
+            Status::Seeded => "seeded",
+        }
+    }
+}
+
+/// One run of a candidate.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Evaluation {
+    pub candidate: Candidate,
+    /// round of the search that ran it, e.g. the rung of successive halving
+    pub round: usize,
+    pub repeats: usize,
+    /// medium GFLOPS; None if the run failed
+    #[serde(with = "crate::number::option")]
+    pub gflops: Option<f64>,
+    pub status: Status,
+    /// seconds since the Unix epoch when its round finished
+    pub timestamp: u64,
+    /// copied from a seeded evaluation with at least as many repeats instead of measured
+    #[serde(default)]
+    pub reused: bool,
+}
+
+/// Every evaluation of a search, in order.
+#[derive(Serialize, Deserialize)]
+pub struct SearchLog {
+    /// Path of the space file.
+    pub space: String,
+    pub evaluations: Vec<Evaluation>,
+    #[serde(default)]
+    pub winner: Option<Candidate>,
+}
+
+impl SearchLog {
+    pub fn new(space: &str) -> Self {
+        SearchLog {
+            space: space.to_string(),
+            evaluations: Vec::new(),
+            winner: None,
+        }
+    }
+
+    pub fn load(file: &path::Path) -> Result<SearchLog, String> {
+        let file = fs::File::open(file).map_err(|e| e.to_string())?;
+        serde_json::from_reader(file).map_err(|e| e.to_string())
+    }
+
+    /// Write through a temporary file, so an interrupted search never leaves a truncated log.
+    pub fn save(&self, file: &path::Path) -> Result<(), String> {
+        let temp = file.with_extension("tmp");
+        let out = fs::File::create(&temp).map_err(|e| e.to_string())?;
+        serde_json::to_writer_pretty(out, self).map_err(|e| e.to_string())?;
+        fs::rename(&temp, file).map_err(|e| e.to_string())
+    }
+}
+
+/// How much the GFLOPS of a search depend on one tunable.
+pub struct Sensitivity {
+    pub name: String,
+    /// value, mean GFLOPS and number of candidates of every value of the tunable
+    pub levels: Vec<(String, f64, usize)>,
+    /// difference between the best and worst mean, relative to the mean of all candidates
+    pub spread: f64,
+}
+
+impl SearchLog {
+    /// One row per evaluation with a column per tunable, for analysis tools, with throughput in
+    /// `flops`, as the header says.
+    pub fn to_csv(&self, flops: FlopsUnit) -> String {
+        let mut names = self
+            .evaluations
+            .iter()
+            .flat_map(|x| x.candidate.keys().cloned())
+            .collect::<Vec<String>>();
+        names.sort();
+        names.dedup();
+
+        let mut out = ["round".to_string()]
+            .into_iter()
+            .chain(names.iter().map(|x| csv_field(x)))
+            .chain(["repeats", flops.name(), "status", "timestamp", "reused"].map(String::from))
+            .collect::<Vec<String>>()
+            .join(",");
+        out.push('\n');
+        for evaluation in &self.evaluations {
+            let row = [evaluation.round.to_string()]
+                .into_iter()
+                .chain(
+                    names
+                        .iter()
+                        .map(|x| csv_field(evaluation.candidate.get(x).map_or("", |x| x.as_str()))),
+                )
+                .chain([
+                    evaluation.repeats.to_string(),
+                    evaluation
+                        .gflops
+                        .map_or(String::new(), |x| flops.of(x).to_string()),
+                    evaluation.status.name().to_string(),
+                    evaluation.timestamp.to_string(),
+                    evaluation.reused.to_string(),
+                ])
+                .collect::<Vec<String>>();
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Sensitivity of every tunable, most influential first. Every candidate counts once, with
+    /// its last completed evaluation, which had the most repeats.
+    pub fn sensitivities(&self) -> Vec<Sensitivity> {
+        let mut latest = Vec::<(&Candidate, f64)>::new();
+        for evaluation in &self.evaluations {
+            let Some(gflops) = evaluation.gflops else {
+                continue;
+            };
+            match latest.iter_mut().find(|x| *x.0 == evaluation.candidate) {
+                Some(x) => x.1 = gflops,
+                None => latest.push((&evaluation.candidate, gflops)),
+            }
+        }
+        if latest.is_empty() {
+            return Vec::new();
+        }
+        let mean = latest.iter().map(|x| x.1).sum::<f64>() / latest.len() as f64;
+
+        let mut sensitivities = latest[0]
+            .0
+            .keys()
+            .map(|name| {
+                let mut levels = BTreeMap::<&str, (f64, usize)>::new();
+                for (candidate, gflops) in &latest {
+                    let value = candidate.get(name).map_or("", |x| x.as_str());
+                    let level = levels.entry(value).or_default();
+                    level.0 += gflops;
+                    level.1 += 1;
+                }
+                let levels = levels
+                    .into_iter()
+                    .map(|(value, (sum, count))| (value.to_string(), sum / count as f64, count))
+                    .collect::<Vec<(String, f64, usize)>>();
+                let best = levels.iter().map(|x| x.1).fold(f64::NEG_INFINITY, f64::max);
+                let worst = levels.iter().map(|x| x.1).fold(f64::INFINITY, f64::min);
+                Sensitivity {
+                    name: name.clone(),
+                    levels,
+                    spread: (best - worst) / mean,
+                }
+            })
+            .collect::<Vec<Sensitivity>>();
+        sensitivities.sort_by(|x, y| y.spread.total_cmp(&x.spread));
+        sensitivities
+    }
+}
+
+/// Fastest evaluation among those with the most repeats, which were measured most precisely.
+fn winner(evaluations: &[Evaluation]) -> Option<usize> {
+    let repeats = evaluations
+        .iter()
+        .filter(|x| x.gflops.is_some())
+        .map(|x| x.repeats)
+        .max()?;
+    (0..evaluations.len())
+        .filter(|&x| evaluations[x].repeats == repeats)
+        .filter(|&x| evaluations[x].gflops.is_some())
+        .max_by(|&x, &y| {
+            evaluations[x]
+                .gflops
+                .unwrap()
+                .total_cmp(&evaluations[y].gflops.unwrap())
+        })
+}
+
+/// Completed evaluations of `log` whose candidate is still among `candidates`, to warm-start a
+/// search with. Failed ones are left out, as an edited kernel may no longer fail.
+pub fn seeds(log: &SearchLog, candidates: &[Candidate]) -> Vec<Evaluation> {
+    log.evaluations
+        .iter()
+        .filter(|x| x.gflops.is_some() && candidates.contains(&x.candidate))
+        .map(|x| Evaluation {
+            status: Status::Seeded,
+            ..x.clone()
+        })
+        .collect()
+}
+
+/// Drive `strategy` until it proposes nothing. `run` measures the proposals of a round and returns
+/// their medium GFLOPS, None for failed runs; `progress` sees the log after every round.
+/// The strategy sees `seeds` as evaluations of earlier rounds, and a proposal that a seed already
+/// measured with at least as many repeats reuses its GFLOPS instead of being run again.
+pub fn search(
+    space: &str,
+    candidates: &[Candidate],
+    seeds: Vec<Evaluation>,
+    strategy: &mut dyn SearchStrategy,
+    mut run: impl FnMut(usize, &[Proposal]) -> Vec<Option<f64>>,
+    mut progress: impl FnMut(&SearchLog),
+) -> SearchLog {
+    let mut log = SearchLog::new(space);
+    log.evaluations = seeds;
+    loop {
+        let proposals = strategy.propose(candidates, &log.evaluations);
+        if proposals.is_empty() {
+            break;
+        }
+        for evaluation in &mut log.evaluations {
+            let again = proposals.iter().any(|x| {
+                candidates[x.candidate] == evaluation.candidate && x.repeats > evaluation.repeats
+            });
+            if evaluation.status == Status::Completed && again {
+                evaluation.status = Status::Promoted;
+            }
+        }
+        let round = log
+            .evaluations
+            .iter()
+            .map(|x| x.round + 1)
+            .max()
+            .unwrap_or(0);
+        let reused = proposals
+            .iter()
+            .map(|proposal| {
+                log.evaluations
+                    .iter()
+                    .filter(|x| x.status == Status::Seeded && x.repeats >= proposal.repeats)
+                    .filter(|x| x.candidate == candidates[proposal.candidate])
+                    .max_by_key(|x| x.repeats)
+                    .and_then(|x| x.gflops)
+            })
+            .collect::<Vec<Option<f64>>>();
+        let measure = proposals
+            .iter()
+            .zip(&reused)
+            .filter(|x| x.1.is_none())
+            .map(|x| *x.0)
+            .collect::<Vec<Proposal>>();
+        let mut measured = if measure.is_empty() {
+            Vec::new()
+        } else {
+            run(round, &measure)
+        }
+        .into_iter();
+        let timestamp = time::SystemTime::now()
+            .duration_since(time::SystemTime::UNIX_EPOCH)
+            .map_or(0, |x| x.as_secs());
+        for (proposal, reused) in proposals.iter().zip(reused) {
+            let gflops = reused.or_else(|| measured.next().flatten());
+            log.evaluations.push(Evaluation {
+                candidate: candidates[proposal.candidate].clone(),
+                round,
+                repeats: proposal.repeats,
+                gflops,
+                status: if gflops.is_some() {
+                    Status::Completed
+                } else {
+                    Status::Failed
+                },
+                timestamp,
+                reused: reused.is_some(),
+            });
+        }
+        progress(&log);
+    }
+    if let Some(winner) = winner(&log.evaluations) {
+        log.evaluations[winner].status = Status::Winner;
+        log.winner = Some(log.evaluations[winner].candidate.clone());
+        progress(&log);
+    }
+    log
+}
+
+/// "MC=128, NR=8"
+pub fn format_candidate(candidate: &Candidate) -> String {
+    candidate
+        .iter()
+        .map(|(name, value)| [name.as_str(), "=", value].concat())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
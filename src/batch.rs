@@ -0,0 +1,381 @@
+use crate::{utils, verification, verifier};
+use library::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fs, path, slice};
+
+/// Problems of the same shape and parameters in a batch, as a group of `cblas_dgemm_batch`.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Group {
+    pub m: usize,
+    pub n: usize,
+    pub k: usize,
+    /// number of problems in the group
+    pub count: usize,
+    pub transpose: (CBLAS_TRANSPOSE, CBLAS_TRANSPOSE),
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub alpha: f64,
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub beta: f64,
+}
+
+impl Group {
+    pub fn flops(&self) -> f64 {
+        benchmark_core::statistics::flops((self.m, self.n, self.k), self.alpha, self.beta)
+            * self.count as f64
+    }
+}
+
+fn one() -> usize {
+    1
+}
+
+/// A group in a batch file; transposes, alpha and beta default to the command line.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct GroupSpec {
+    m: usize,
+    n: usize,
+    k: usize,
+    #[serde(default = "one")]
+    count: usize,
+    trans_a: Option<String>,
+    trans_b: Option<String>,
+    alpha: Option<f64>,
+    beta: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct Spec {
+    #[serde(rename = "group")]
+    groups: Vec<GroupSpec>,
+}
+
+/// Load the groups of a batch file:
+///
+/// ```toml
+/// [[group]]
+/// m = 128
+/// n = 64
+/// k = 512
+/// count = 96
+/// trans-b = "T"
+/// ```
+pub fn load(
+    file: &path::Path,
+    transpose: (CBLAS_TRANSPOSE, CBLAS_TRANSPOSE),
+    alpha: f64,
+    beta: f64,
+) -> Result<Vec<Group>, String> {
+    let spec = toml::from_str::<Spec>(&fs::read_to_string(file).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+    if spec.groups.is_empty() {
+        return Err("batch has no groups".to_string());
+    }
+    let trans = |value: Option<String>, default| {
+        value.map_or(Ok(default), |x| x.parse::<CBLAS_TRANSPOSE>())
+    };
+    spec.groups
+        .into_iter()
+        .map(|x| {
+            if x.count == 0 {
+                return Err("group count should not be 0".to_string());
+            }
+            let bytes = utils::element_counts((x.m, x.n, x.k))?
+                .iter()
+                .try_fold(0usize, |total, y| {
+                    y.checked_mul(x.count)?.checked_add(total)
+                })
+                .and_then(|y| y.checked_mul(size_of::<f64>()));
+            if bytes.is_none_or(|y| y > isize::MAX as usize) {
+                return Err(format!(
+                    "{} problems of {}x{}x{} do not fit in memory",
+                    x.count, x.m, x.n, x.k
+                ));
+            }
+            Ok(Group {
+                m: x.m,
+                n: x.n,
+                k: x.k,
+                count: x.count,
+                transpose: (
+                    trans(x.trans_a, transpose.0)?,
+                    trans(x.trans_b, transpose.1)?,
+                ),
+                alpha: x.alpha.unwrap_or(alpha),
+                beta: x.beta.unwrap_or(beta),
+            })
+        })
+        .collect()
+}
+
+/// Distances in elements between the matrices of consecutive problems of a strided batch.
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct Strides {
+    pub a: usize,
+    pub b: usize,
+    pub c: usize,
+}
+
+impl Strides {
+    /// Strides of `group`, where missing ones pack the matrices back to back.
+    pub fn new(
+        group: &Group,
+        (a, b, c): (Option<usize>, Option<usize>, Option<usize>),
+    ) -> Result<Self, String> {
+        let [size_a, size_b, size_c] = utils::element_counts((group.m, group.n, group.k))?;
+        let strides = Strides {
+            a: a.unwrap_or(size_a),
+            b: b.unwrap_or(size_b),
+            c: c.unwrap_or(size_c),
+        };
+        if strides.a != 0 && strides.a < size_a {
+            return Err(format!("stride of A should be 0 or at least {}", size_a));
+        }
+        if strides.b != 0 && strides.b < size_b {
+            return Err(format!("stride of B should be 0 or at least {}", size_b));
+        }
+        // problems writing to the same C would race.
+        if strides.c < size_c {
+            return Err(format!("stride of C should be at least {}", size_c));
+        }
+        let last = group.count - 1;
+        let fits = [
+            (strides.a, size_a),
+            (strides.b, size_b),
+            (strides.c, size_c),
+        ]
+        .iter()
+        .all(|&(stride, size)| {
+            stride
+                .checked_mul(last)
+                .and_then(|x| x.checked_add(size))
+                .is_some_and(|x| x <= isize::MAX as usize / size_of::<f64>())
+        });
+        if !fits {
+            return Err(format!(
+                "{} problems at these strides do not fit in memory",
+                group.count
+            ));
+        }
+        Ok(strides)
+    }
+
+    /// Elements of the A, B and C buffers holding every problem of `group`.
+    pub fn lengths(&self, group: &Group) -> (usize, usize, usize) {
+        let last = group.count - 1;
+        (
+            self.a * last + group.m * group.k,
+            self.b * last + group.k * group.n,
+            self.c * last + group.m * group.n,
+        )
+    }
+}
+
+/// Bytes of A, B and C over every problem of `groups`, or of the buffers of a strided batch.
+pub fn bytes(groups: &[Group], strides: Option<Strides>) -> (usize, usize, usize) {
+    let element = size_of::<f64>();
+    groups.iter().fold((0, 0, 0), |(a, b, c), x| {
+        let (m, n, k, count) = (x.m, x.n, x.k, x.count);
+        let (x, y, z) = strides.map_or((m * k * count, k * n * count, m * n * count), |s| {
+            s.lengths(x)
+        });
+        (a + x * element, b + y * element, c + z * element)
+    })
+}
+
+/// Arrays of a `cblas_dgemm_batch` call and the matrices they point to.
+/// Problems of a group are stored back to back in one buffer per matrix. Only its constructors
+/// build one, so the pointers always point into the buffers it owns.
+pub struct Operands {
+    pub(crate) trans_a: Vec<CBLAS_TRANSPOSE>,
+    pub(crate) trans_b: Vec<CBLAS_TRANSPOSE>,
+    pub(crate) m: Vec<usize>,
+    pub(crate) n: Vec<usize>,
+    pub(crate) k: Vec<usize>,
+    pub(crate) alpha: Vec<f64>,
+    pub(crate) lda: Vec<usize>,
+    pub(crate) ldb: Vec<usize>,
+    pub(crate) beta: Vec<f64>,
+    pub(crate) ldc: Vec<usize>,
+    pub(crate) group_size: Vec<usize>,
+    /// distances between the A, B and C of consecutive problems of a strided batch
+    pub(crate) strides: Option<Strides>,
+    /// one pointer per problem, in group order
+    pub(crate) a_array: Vec<*const f64>,
+    pub(crate) b_array: Vec<*const f64>,
+    pub(crate) c_array: Vec<*mut f64>,
+    pub(crate) a: Vec<Box<[f64]>>,
+    pub(crate) b: Vec<Box<[f64]>>,
+    pub(crate) c: Vec<Box<[f64]>>,
+}
+
+impl Operands {
+    /// Random A and B, and zeroed C, for every problem of `groups`.
+    pub fn new(layout: CBLAS_LAYOUT, groups: &[Group], seed: u64) -> Self {
+        let mut operands = Operands {
+            trans_a: Vec::new(),
+            trans_b: Vec::new(),
+            m: Vec::new(),
+            n: Vec::new(),
+            k: Vec::new(),
+            alpha: Vec::new(),
+            lda: Vec::new(),
+            ldb: Vec::new(),
+            beta: Vec::new(),
+            ldc: Vec::new(),
+            group_size: Vec::new(),
+            strides: None,
+            a_array: Vec::new(),
+            b_array: Vec::new(),
+            c_array: Vec::new(),
+            a: Vec::new(),
+            b: Vec::new(),
+            c: Vec::new(),
+        };
+        for (index, group) in groups.iter().enumerate() {
+            let (m, n, k) = (group.m, group.n, group.k);
+            let (trans_a, trans_b) = group.transpose;
+            let (lda, ldb, ldc) = utils::leading_dimensions(layout, trans_a, trans_b, (m, n, k));
+            let seed = seed + index as u64 * 10;
+            let a = utils::fill_rand(m * k * group.count, seed + 100, 0.0, 2.0);
+            let b = utils::fill_rand(k * n * group.count, seed + 200, 0.0, 2.0);
+            let mut c = unsafe { utils::malloc::<f64>(m * n * group.count) };
+            c.fill(0.0);
+            for problem in 0..group.count {
+                operands.a_array.push(a[problem * m * k..].as_ptr());
+                operands.b_array.push(b[problem * k * n..].as_ptr());
+                operands.c_array.push(c[problem * m * n..].as_mut_ptr());
+            }
+            operands.trans_a.push(trans_a);
+            operands.trans_b.push(trans_b);
+            operands.m.push(m);
+            operands.n.push(n);
+            operands.k.push(k);
+            operands.alpha.push(group.alpha);
+            operands.beta.push(group.beta);
+            operands.lda.push(lda);
+            operands.ldb.push(ldb);
+            operands.ldc.push(ldc);
+            operands.group_size.push(group.count);
+            operands.a.push(a);
+            operands.b.push(b);
+            operands.c.push(c);
+        }
+        operands
+    }
+
+    /// A strided batch of `group.count` problems in one buffer per matrix, like
+    /// `cblas_dgemm_batch_strided`. A stride of 0 makes every problem share the same A or B.
+    pub fn strided(layout: CBLAS_LAYOUT, group: &Group, strides: Strides, seed: u64) -> Self {
+        let (m, n, k) = (group.m, group.n, group.k);
+        let (trans_a, trans_b) = group.transpose;
+        let (lda, ldb, ldc) = utils::leading_dimensions(layout, trans_a, trans_b, (m, n, k));
+        let (a_len, b_len, c_len) = strides.lengths(group);
+        let a = utils::fill_rand(a_len, seed + 100, 0.0, 2.0);
+        let b = utils::fill_rand(b_len, seed + 200, 0.0, 2.0);
+        let mut c = unsafe { utils::malloc::<f64>(c_len) };
+        c.fill(0.0);
+        Operands {
+            trans_a: vec![trans_a],
+            trans_b: vec![trans_b],
+            m: vec![m],
+            n: vec![n],
+            k: vec![k],
+            alpha: vec![group.alpha],
+            lda: vec![lda],
+            ldb: vec![ldb],
+            beta: vec![group.beta],
+            ldc: vec![ldc],
+            group_size: vec![group.count],
+            strides: Some(strides),
+            a_array: (0..group.count)
+                .map(|x| a[x * strides.a..].as_ptr())
+                .collect(),
+            b_array: (0..group.count)
+                .map(|x| b[x * strides.b..].as_ptr())
+                .collect(),
+            c_array: (0..group.count)
+                .map(|x| c[x * strides.c..].as_mut_ptr())
+                .collect(),
+            a: vec![a],
+            b: vec![b],
+            c: vec![c],
+        }
+    }
+
+    /// Rows of a row-major C, or columns of a column-major one, of every problem with a beta of
+    /// 0, with their length and the index of their group. The elements between the C of a
+    /// strided batch are not among them, as a kernel never writes them.
+    fn outputs(&self, layout: CBLAS_LAYOUT) -> Vec<(usize, *mut f64, usize)> {
+        let mut outputs = Vec::new();
+        let mut first = 0;
+        for group in 0..self.group_size.len() {
+            let (m, n, ldc) = (self.m[group], self.n[group], self.ldc[group]);
+            let (lines, length) = if layout == CBLAS_LAYOUT::CblasRowMajor {
+                (m, n)
+            } else {
+                (n, m)
+            };
+            let last = first + self.group_size[group];
+            if self.beta[group] == 0.0 {
+                for c in &self.c_array[first..last] {
+                    outputs.extend((0..lines).map(|x| (group, c.wrapping_add(x * ldc), length)));
+                }
+            }
+            first = last;
+        }
+        outputs
+    }
+
+    /// `verification::poison` the m × n C of every problem with a beta of 0.
+    pub fn poison(&mut self, layout: CBLAS_LAYOUT) {
+        for (_, line, length) in self.outputs(layout) {
+            verification::poison(unsafe { slice::from_raw_parts_mut(line, length) });
+        }
+    }
+
+    /// First group with a beta of 0 where the m × n C of a problem is `verification::poisoned`.
+    pub fn poisoned(&self, layout: CBLAS_LAYOUT) -> Option<usize> {
+        self.outputs(layout)
+            .into_iter()
+            .find_map(|(group, line, length)| {
+                let line = unsafe { slice::from_raw_parts(line, length) };
+                verification::poisoned(line).then_some(group)
+            })
+    }
+
+    /// Every problem with the index of its group, for a `verifier::Verifier` to check.
+    pub fn problems(&self, layout: CBLAS_LAYOUT) -> Vec<(usize, verifier::Problem<'_>)> {
+        let mut problems = Vec::new();
+        for group in 0..self.group_size.len() {
+            let (m, n, k) = (self.m[group], self.n[group], self.k[group]);
+            for _ in 0..self.group_size[group] {
+                let problem = problems.len();
+                let (a, b, c) = unsafe {
+                    (
+                        slice::from_raw_parts(self.a_array[problem], m * k),
+                        slice::from_raw_parts(self.b_array[problem], k * n),
+                        slice::from_raw_parts(self.c_array[problem], m * n),
+                    )
+                };
+                problems.push((
+                    group,
+                    verifier::Problem {
+                        layout,
+                        transpose: (self.trans_a[group], self.trans_b[group]),
+                        dimensions: (m, n, k),
+                        alpha: self.alpha[group],
+                        beta: self.beta[group],
+                        a: (a, self.lda[group]),
+                        b: (b, self.ldb[group]),
+                        c: (c, self.ldc[group]),
+                    },
+                ));
+            }
+        }
+        problems
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{
+    autotune::{Candidate, Evaluation, Proposal, SearchStrategy, Status},
+    paired,
+};
+
+/// Length scale of the squared-exponential kernel, in units of a tunable's range.
+const LENGTH_SCALE: f64 = 0.25;
+/// Observation noise of the normalized GFLOPS, as repeated runs of a candidate differ.
+const NOISE: f64 = 1e-2;
+/// Improvement over the best candidate below which a candidate is not worth exploring.
+const EXPLORATION: f64 = 0.01;
+
+/// Bayesian optimization: a Gaussian process over the tunables models GFLOPS, and every round
+/// runs the unevaluated candidate with the highest expected improvement over the best so far.
+/// Numeric tunables are scaled to [0, 1], others are one-hot encoded. Seeded evaluations count
+/// towards the model but not towards the budget.
+pub struct Bayesian {
+    /// evaluations after which the search stops
+    pub budget: usize,
+    /// candidates picked at random before the model takes over
+    pub initial: usize,
+    pub repeats: usize,
+    /// state of the xorshift generator for the initial candidates
+    seed: u64,
+}
+
+impl Bayesian {
+    pub fn new(budget: usize, repeats: usize) -> Self {
+        Bayesian {
+            budget,
+            initial: budget.min(5),
+            repeats,
+            seed: 0x9e3779b97f4a7c15,
+        }
+    }
+
+    fn next_random(&mut self) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.seed
+    }
+}
+
+/// Feature vector of every candidate.
+fn features(candidates: &[Candidate]) -> Vec<Vec<f64>> {
+    let Some(first) = candidates.first() else {
+        return Vec::new();
+    };
+    let mut out = vec![Vec::new(); candidates.len()];
+    for name in first.keys() {
+        let values = candidates
+            .iter()
+            .map(|x| x.get(name).map_or("", |x| x.as_str()))
+            .collect::<Vec<&str>>();
+        let numbers = values
+            .iter()
+            .map(|x| x.parse::<f64>().ok())
+            .collect::<Option<Vec<f64>>>();
+        match numbers {
+            Some(numbers) => {
+                let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                let range = if max > min { max - min } else { 1.0 };
+                for (feature, number) in out.iter_mut().zip(numbers) {
+                    feature.push((number - min) / range);
+                }
+            }
+            None => {
+                let mut levels = values.clone();
+                levels.sort();
+                levels.dedup();
+                for (feature, value) in out.iter_mut().zip(&values) {
+                    feature.extend(levels.iter().map(|x| if x == value { 1.0 } else { 0.0 }));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn kernel(x: &[f64], y: &[f64]) -> f64 {
+    let distance = x.iter().zip(y).map(|(x, y)| (x - y).powi(2)).sum::<f64>();
+    (-distance / (2.0 * LENGTH_SCALE * LENGTH_SCALE)).exp()
+}
+
+/// Lower triangular L with L Lᵀ = `matrix`, None if it is not positive definite.
+fn cholesky(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut l = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum = matrix[i][j] - (0..j).map(|k| l[i][k] * l[j][k]).sum::<f64>();
+            if i == j {
+                if sum <= 0.0 {
+                    return None;
+                }
+                l[i][i] = sum.sqrt();
+            } else {
+                l[i][j] = sum / l[j][j];
+            }
+        }
+    }
+    Some(l)
+}
+
+/// Solve L x = `b` for lower triangular L.
+fn forward(l: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let mut x = vec![0.0; b.len()];
+    for i in 0..b.len() {
+        x[i] = (b[i] - (0..i).map(|k| l[i][k] * x[k]).sum::<f64>()) / l[i][i];
+    }
+    x
+}
+
+/// Solve Lᵀ x = `b` for lower triangular L.
+fn backward(l: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let mut x = vec![0.0; b.len()];
+    for i in (0..b.len()).rev() {
+        x[i] = (b[i] - (i + 1..b.len()).map(|k| l[k][i] * x[k]).sum::<f64>()) / l[i][i];
+    }
+    x
+}
+
+/// Expected improvement of every candidate over the best observation, from a Gaussian process
+/// fitted to the normalized `observed` values at the `points` indices of `features`.
+fn expected_improvement(
+    features: &[Vec<f64>],
+    points: &[usize],
+    observed: &[f64],
+) -> Option<Vec<f64>> {
+    let n = observed.len() as f64;
+    let mean = observed.iter().sum::<f64>() / n;
+    let scale = (observed.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n)
+        .sqrt()
+        .max(f64::EPSILON);
+    let y = observed
+        .iter()
+        .map(|x| (x - mean) / scale)
+        .collect::<Vec<f64>>();
+    let best = y.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    let covariance = points
+        .iter()
+        .map(|&i| {
+            points
+                .iter()
+                .map(|&j| kernel(&features[i], &features[j]) + if i == j { NOISE } else { 0.0 })
+                .collect()
+        })
+        .collect::<Vec<Vec<f64>>>();
+    let l = cholesky(&covariance)?;
+    let alpha = backward(&l, &forward(&l, &y));
+
+    let improvement = features
+        .iter()
+        .map(|x| {
+            let k = points
+                .iter()
+                .map(|&i| kernel(x, &features[i]))
+                .collect::<Vec<f64>>();
+            let mu = k.iter().zip(&alpha).map(|(k, a)| k * a).sum::<f64>();
+            let v = forward(&l, &k);
+            let sigma = (1.0 - v.iter().map(|x| x * x).sum::<f64>()).max(0.0).sqrt();
+            if sigma < 1e-9 {
+                return 0.0;
+            }
+            let gain = mu - best - EXPLORATION;
+            let z = gain / sigma;
+            let cdf = 0.5 * paired::erfc(-z / std::f64::consts::SQRT_2);
+            let pdf = (-z * z / 2.0).exp() / (2.0 * std::f64::consts::PI).sqrt();
+            gain * cdf + sigma * pdf
+        })
+        .collect();
+    Some(improvement)
+}
+
+impl SearchStrategy for Bayesian {
+    fn propose(&mut self, candidates: &[Candidate], evaluations: &[Evaluation]) -> Vec<Proposal> {
+        let runs = evaluations
+            .iter()
+            .filter(|x| x.status != Status::Seeded)
+            .count();
+        if runs >= self.budget {
+            return Vec::new();
+        }
+        let index = |candidate: &Candidate| candidates.iter().position(|x| x == candidate);
+        let evaluated = evaluations
+            .iter()
+            .filter_map(|x| index(&x.candidate))
+            .collect::<Vec<usize>>();
+        let open = (0..candidates.len())
+            .filter(|x| !evaluated.contains(x))
+            .collect::<Vec<usize>>();
+        if open.is_empty() {
+            return Vec::new();
+        }
+
+        let (points, observed): (Vec<usize>, Vec<f64>) = evaluations
+            .iter()
+            .filter_map(|x| Some((index(&x.candidate)?, x.gflops?)))
+            .unzip();
+        let features = features(candidates);
+        let model = (evaluations.len() >= self.initial && points.len() >= 2)
+            .then(|| expected_improvement(&features, &points, &observed))
+            .flatten();
+        let candidate = match model {
+            Some(improvement) => *open
+                .iter()
+                .max_by(|&&x, &&y| improvement[x].total_cmp(&improvement[y]))
+                .unwrap(),
+            None => open[(self.next_random() % open.len() as u64) as usize],
+        };
+        vec![Proposal {
+            candidate,
+            repeats: self.repeats,
+        }]
+    }
+}
@@ -1,7 +1,13 @@
 use argh::FromArgs;
 use benchmark::*;
-use library::{cblas_daxpy, cblas_dgemm, cblas_dnrm2, CBLAS_LAYOUT, CBLAS_TRANSPOSE};
-use std::{ffi::c_double, fs, io::Write, path, process, sync, time};
+use library::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+use std::{
+    cell, collections, env,
+    ffi::{c_int, c_uint},
+    fs,
+    io::{self, Read, Write},
+    net, path, process, sync, thread, time,
+};
 
 trait IsErrOr<T> {
     fn is_err_or(self, f: impl FnOnce(T) -> bool) -> bool;
@@ -26,10 +32,26 @@ fn parse_boolean(value: &str) -> Result<bool, String> {
     })
 }
 
+/// `NAME=VALUE`, or `NAME` alone, which defines it as 1 like `-DNAME` does.
+fn parse_define(value: &str) -> Result<(String, String), String> {
+    let (name, value) = value.split_once('=').unwrap_or((value, "1"));
+    if name.is_empty() || !name.chars().all(|x| x.is_ascii_alphanumeric() || x == '_') {
+        return Err(["expected 'NAME=VALUE', but got '", name, "'"].concat());
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+fn parse_key_value(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| ["expected 'KEY=VALUE', but got '", value, "'"].concat())
+}
+
 #[derive(FromArgs)]
 /// arguments
 struct Arguments {
-    /// path to kernel source file
+    /// path to kernel source file, builtin:<name> for a built-in example kernel, or - to read the source from stdin
     #[argh(positional, arg_name = "path-to-kernel")]
     kernel: String,
 
@@ -37,6 +59,28 @@ struct Arguments {
     #[argh(positional, arg_name = "path-to-out-file")]
     out: Option<String>,
 
+    /// read options from a TOML file, e.g. one written by --print-config; command-line options win
+    #[argh(option, arg_name = "path-to-config")]
+    // parse_arguments reads it from the raw arguments before argh runs; declared for --help.
+    #[allow(dead_code)]
+    config: Option<String>,
+
+    /// print the effective configuration as TOML and exit
+    #[argh(switch)]
+    print_config: bool,
+
+    /// name of this run; defaults to kernel file name
+    #[argh(option)]
+    name: Option<String>,
+
+    /// tag attached to benchmark result, can be repeated
+    #[argh(option)]
+    tag: Vec<String>,
+
+    /// metadata attached to benchmark result, can be repeated
+    #[argh(option, arg_name = "key=value", from_str_fn(parse_key_value))]
+    meta: Vec<(String, String)>,
+
     /// save benchmark result
     #[argh(option, arg_name = "path-to-report-file")]
     save_as: Option<String>,
@@ -45,14 +89,231 @@ struct Arguments {
     #[argh(option, arg_name = "path-to-history-file")]
     save_history_as: Option<String>,
 
+    /// archive benchmark result, kernel source and build information as .tar.zst
+    #[argh(option, arg_name = "path-to-bundle")]
+    bundle: Option<String>,
+
+    /// post benchmark result to collector
+    #[argh(option, arg_name = "url")]
+    post_to: Option<String>,
+
+    /// bearer token for --post-to
+    #[argh(option, arg_name = "token")]
+    post_token: Option<String>,
+
+    /// compress posted benchmark result with gzip
+    #[argh(switch)]
+    post_gzip: bool,
+
+    /// retries before giving up posting
+    #[argh(option, default = "3")]
+    post_retries: usize,
+
+    /// keep benchmark results that could not be posted and re-send them later
+    #[argh(option, arg_name = "path-to-spool-dir")]
+    spool_dir: Option<String>,
+
+    /// write JSON status events, one per line, to this inherited file descriptor
+    #[argh(option, arg_name = "fd")]
+    status_fd: Option<i32>,
+
+    /// write JSON status events, one per line, to this listening Unix-domain socket
+    #[argh(option, arg_name = "path")]
+    status_socket: Option<String>,
+
+    /// print details of the setup: -v the compiler command, -vv also the matrix generation throughput
+    #[argh(switch, short = 'v')]
+    verbose: u8,
+
+    /// unit of printed times and of the history file: us, ms or s
+    #[argh(
+        option,
+        arg_name = "unit",
+        from_str_fn(units::TimeUnit::try_from),
+        default = "units::TimeUnit::Ms"
+    )]
+    units: units::TimeUnit,
+
+    /// unit of printed throughput: gflops or tflops
+    #[argh(
+        option,
+        arg_name = "unit",
+        from_str_fn(units::FlopsUnit::try_from),
+        default = "units::FlopsUnit::Gflops"
+    )]
+    flops_units: units::FlopsUnit,
+
+    /// what to print after the run: full (every section of the report), summary or minimal (one line)
+    #[argh(
+        option,
+        arg_name = "detail",
+        from_str_fn(common::Show::try_from),
+        default = "common::Show::Summary"
+    )]
+    show: common::Show,
+
+    /// shell command run after the benchmark, with placeholders like {gflops_medium} filled from the result
+    #[argh(option, arg_name = "command")]
+    on_complete: Option<String>,
+
+    /// shell command run when throughput falls below the baseline, with {baseline_gflops} and {change} available
+    #[argh(option, arg_name = "command")]
+    on_regression: Option<String>,
+
+    /// benchmark report to detect regressions against
+    #[argh(option, arg_name = "path-to-report-file")]
+    baseline: Option<String>,
+
+    /// throughput drop from the baseline, in percent, that counts as a regression
+    #[argh(option, arg_name = "percent", default = "5.0")]
+    regression_threshold: f64,
+
+    /// ERROR: refuse a baseline measured on different hardware or libraries, WARN: only warn
+    #[argh(
+        option,
+        arg_name = "policy",
+        from_str_fn(machine::Mismatch::try_from),
+        default = "machine::Mismatch::Error"
+    )]
+    baseline_mismatch: machine::Mismatch,
+
+    /// exit with status 2 if the medium GFLOPS are below this
+    #[argh(option, arg_name = "gflops")]
+    expect_gflops_min: Option<f64>,
+
+    /// exit with status 2 if the standard deviation exceeds this fraction of the average, e.g. 5%
+    #[argh(
+        option,
+        arg_name = "fraction",
+        from_str_fn(verification::parse_fraction)
+    )]
+    expect_max_deviation: Option<f64>,
+
+    /// MADs from the median beyond which an iteration is reported as an outlier
+    #[argh(option, arg_name = "mads", default = "5.0")]
+    anomaly_mads: f64,
+
+    /// time this many clock reads and idle loops before the repeats to record the noise floor of the node, 0 to skip
+    #[argh(option, arg_name = "samples", default = "1000")]
+    noise_samples: usize,
+
+    /// run without taking the machine-wide lock, alongside other benchmark runs on this machine
+    #[argh(switch)]
+    no_lock: bool,
+
+    /// lock file runs on this machine take turns on (default: /tmp/benchmark-dgemm.lock)
+    #[argh(option, arg_name = "file", default = "lock::DEFAULT_PATH.to_string()")]
+    lock_file: String,
+
+    /// memory bandwidth of the machine in GB/s, to hint when a run is bound by memory traffic
+    #[argh(option, arg_name = "GB/s")]
+    memory_bandwidth: Option<f64>,
+
     /// TRUE: recompile anyway, FALSE: don't recompile
     #[argh(option, arg_name = "bool", from_str_fn(parse_boolean))]
     compile: Option<bool>,
 
+    /// record kernel build command into a JSON compilation database
+    #[argh(option, arg_name = "path-to-compile-commands")]
+    compile_commands: Option<String>,
+
+    /// rebuild kernel verbatim with the command recorded in a JSON compilation database
+    #[argh(option, arg_name = "path-to-compile-commands")]
+    replay_build: Option<String>,
+
+    /// environment variable applied to the kernel run, can be repeated
+    #[argh(option, arg_name = "key=value", from_str_fn(parse_key_value))]
+    kernel_env: Vec<(String, String)>,
+
+    /// shared object preloaded into the kernel run, can be repeated
+    #[argh(option, arg_name = "path-to-shared-object")]
+    preload: Vec<String>,
+
+    /// dlopen flags for loading the kernel; comma-separated LAZY, NOW, LOCAL, GLOBAL, DEEPBIND, NODELETE
+    #[argh(
+        option,
+        arg_name = "flags",
+        from_str_fn(loader::parse_flags),
+        default = "loader::DEFAULT_FLAGS"
+    )]
+    dlopen_flags: c_int,
+
+    /// load the kernel into an isolated linker namespace (dlmopen)
+    #[argh(switch)]
+    isolate: bool,
+
+    /// pin the benchmark to CPUs, e.g. 0-3,8 (Linux only)
+    #[argh(option, arg_name = "cpus", from_str_fn(affinity::parse_cpu_list))]
+    pin_cpus: Option<Vec<usize>>,
+
+    /// allocate memory on these NUMA nodes only, e.g. 0-1 (Linux only)
+    #[argh(option, arg_name = "nodes", from_str_fn(affinity::parse_node_list))]
+    membind: Option<Vec<usize>>,
+
+    /// generate A, B and C on threads pinned to the --pin-cpus (or all) in turn, blocks of this many rows each, so pages are first touched where a static schedule uses them
+    #[argh(option, arg_name = "rows")]
+    first_touch: Option<usize>,
+
+    /// write the placement of A, B and C of every session to this JSON file: address alignment, NUMA node of sampled pages and huge pages
+    #[argh(option, arg_name = "file")]
+    placement_manifest: Option<String>,
+
+    /// load a manifest of --placement-manifest from an earlier run and print how the placement of this one differs
+    #[argh(option, arg_name = "file")]
+    ld_from_file: Option<String>,
+
+    /// benchmark single-threaded on each of these CPUs in turn, e.g. all or 0-7
+    #[argh(option, arg_name = "cpus", from_str_fn(affinity::parse_cpu_list))]
+    per_core: Option<Vec<usize>>,
+
+    /// study scaling over SOCKET or NUMA units: threads confined to 1, 2, ... units with local memory
+    #[argh(option, arg_name = "domain", from_str_fn(affinity::Domain::try_from))]
+    scaling: Option<affinity::Domain>,
+
+    /// fail unless the kernel implements this ABI version (see kernel_abi_version)
+    #[argh(option, arg_name = "version")]
+    kernel_abi: Option<c_uint>,
+
+    /// also load this compiled object, interleave its iterations with the kernel's on the same matrices and report the paired speedup
+    #[argh(option, arg_name = "object")]
+    against: Option<String>,
+
+    /// p-value below which the paired difference of --against is significant
+    #[argh(option, default = "0.05", from_str_fn(verification::parse_fraction))]
+    significance: f64,
+
     /// compiler
-    #[argh(option, default = "Arguments::default_compiler()")]
+    #[argh(option, default = "compiler::default_compiler()")]
     compiler: String,
 
+    /// also compile the kernel to assembly with the same flags, saved next to the report or kernel
+    #[argh(switch)]
+    emit_asm: bool,
+
+    /// add the disassembly of call_dgemm and the functions it calls to the bundle
+    #[argh(switch)]
+    disassemble: bool,
+
+    /// objdump used by --disassemble; llvm-objdump is tried if objdump is missing
+    #[argh(option, arg_name = "path", default = "String::from(\"objdump\")")]
+    objdump: String,
+
+    /// preprocessor definition passed to the compiler, e.g. MC=256, can be repeated
+    #[argh(option, arg_name = "name=value", from_str_fn(parse_define))]
+    define: Vec<(String, String)>,
+
+    /// command-line conventions of the compiler; GNU or MSVC, inferred from compiler name by default
+    #[argh(option, arg_name = "profile", from_str_fn(compiler::Profile::try_from))]
+    compiler_profile: Option<compiler::Profile>,
+
+    /// build with a sanitizer (ADDRESS or UNDEFINED) and only run verification, printing its reports
+    #[argh(
+        option,
+        arg_name = "sanitizer",
+        from_str_fn(compiler::Sanitizer::try_from)
+    )]
+    sanitize: Option<compiler::Sanitizer>,
+
     /// compiler arguments
     #[argh(option, arg_name = "argument")]
     compiler_args: Option<String>,
@@ -69,10 +330,64 @@ struct Arguments {
     #[argh(option, short = 'r', default = "10")]
     repeats: usize,
 
-    /// skip dgemm result verification
+    /// spawn thread pools before the first kernel call, via the kernel's prewarm_threads symbol or a small dgemm
+    #[argh(switch)]
+    prewarm_threads: bool,
+
+    /// repeat the whole benchmark, reloading the kernel and regenerating matrices
+    #[argh(option, default = "1")]
+    sessions: usize,
+
+    /// pause between sessions, e.g. 500ms, 60s, 2m
+    #[argh(
+        option,
+        from_str_fn(utils::parse_duration),
+        default = "time::Duration::ZERO"
+    )]
+    session_gap: time::Duration,
+
+    /// random pause before every measured iteration but the first, e.g. 0-50ms, so periodic
+    /// system activity does not line up with the measurements
+    #[argh(option, arg_name = "range", from_str_fn(methodology::parse_jitter))]
+    jitter: Option<methodology::Jitter>,
+
+    /// skip dgemm result verification, same as --verify-with none
     #[argh(switch)]
     skip_verification: bool,
 
+    /// verify only a fraction of the rows and columns of C, e.g. 1%, against a compensated
+    /// reference, same as --verify-with sampled=FRACTION
+    #[argh(
+        option,
+        arg_name = "fraction",
+        from_str_fn(verification::parse_fraction)
+    )]
+    verify_sample: Option<f64>,
+
+    /// verify every entry of C against a double-double reference instead of the reference BLAS,
+    /// same as --verify-with pure-rust
+    #[argh(switch)]
+    extended_residual: bool,
+
+    /// how C of the verification call is checked: vendor-blas (default), pure-rust (every entry
+    /// in double-double), sampled[=FRACTION] (1% by default), file-compare=FILE (M·N
+    /// little-endian doubles, {session} in FILE is replaced) or none
+    #[argh(option, arg_name = "verifier", from_str_fn(verifier::Kind::try_from))]
+    verify_with: Option<verifier::Kind>,
+
+    /// fail verification if the normalized residual ||C - Ĉ|| / (||A|| ||B|| k ε) exceeds this,
+    /// instead of the difference exceeding the tolerance calibrated from the norms of A and B
+    #[argh(option, arg_name = "residual")]
+    max_residual: Option<f64>,
+
+    /// if verification fails, write a standalone C test that reproduces the failure on the same inputs to this file
+    #[argh(option, arg_name = "file")]
+    emit_reproducer: Option<String>,
+
+    /// map A and B read-only while the kernel runs and fail if it writes to them (unix only)
+    #[argh(switch)]
+    protect_inputs: bool,
+
     /// layout; ROW: row-major, COL: col-major
     #[argh(
         option,
@@ -82,36 +397,137 @@ struct Arguments {
     )]
     layout: CBLAS_LAYOUT,
 
-    /// transpose a; FALSE: not transposed, TRUE: transposed, CONJ: conjugate transposed
-    #[argh(
-        option,
-        arg_name = "trans",
-        from_str_fn(CBLAS_TRANSPOSE::try_from),
-        default = "CBLAS_TRANSPOSE::CblasNoTrans"
-    )]
+    /// transpose a; N (FALSE): not transposed, T (TRUE): transposed, C (CONJ): conjugate transposed
+    #[argh(option, arg_name = "trans", default = "CBLAS_TRANSPOSE::CblasNoTrans")]
     trans_a: CBLAS_TRANSPOSE,
 
-    /// transpose b; FALSE: not transposed, TRUE: transposed, CONJ: conjugate transposed
+    /// transpose b; N (FALSE): not transposed, T (TRUE): transposed, C (CONJ): conjugate transposed
+    #[argh(option, arg_name = "trans", default = "CBLAS_TRANSPOSE::CblasNoTrans")]
+    trans_b: CBLAS_TRANSPOSE,
+
+    /// m; accepts suffixes and expressions, e.g. 16k, 2x4096
     #[argh(
         option,
-        arg_name = "trans",
-        from_str_fn(CBLAS_TRANSPOSE::try_from),
-        default = "CBLAS_TRANSPOSE::CblasNoTrans"
+        short = 'm',
+        from_str_fn(utils::parse_dimension),
+        default = "10000"
     )]
-    trans_b: CBLAS_TRANSPOSE,
-
-    /// m
-    #[argh(option, short = 'm', default = "10000")]
     m: usize,
 
-    /// n
-    #[argh(option, short = 'n', default = "10000")]
+    /// n; accepts suffixes and expressions, e.g. 16k, 2x4096
+    #[argh(
+        option,
+        short = 'n',
+        from_str_fn(utils::parse_dimension),
+        default = "10000"
+    )]
     n: usize,
 
-    /// k
-    #[argh(option, short = 'k', default = "10000")]
+    /// k; accepts suffixes and expressions, e.g. 16k, 2x4096
+    #[argh(
+        option,
+        short = 'k',
+        from_str_fn(utils::parse_dimension),
+        default = "10000"
+    )]
     k: usize,
 
+    /// sweep m over START:END:*FACTOR or START:END:+STEP
+    #[argh(option, arg_name = "range", from_str_fn(sweep::parse_range))]
+    m_range: Option<Vec<usize>>,
+
+    /// sweep n over START:END:*FACTOR or START:END:+STEP
+    #[argh(option, arg_name = "range", from_str_fn(sweep::parse_range))]
+    n_range: Option<Vec<usize>>,
+
+    /// sweep k over START:END:*FACTOR or START:END:+STEP
+    #[argh(option, arg_name = "range", from_str_fn(sweep::parse_range))]
+    k_range: Option<Vec<usize>>,
+
+    /// sweep alpha over these values, e.g. 0,0.5,1,-1, alone or with a sweep over shapes
+    #[argh(option, arg_name = "values", from_str_fn(sweep::parse_scalars))]
+    alpha_sweep: Option<Vec<f64>>,
+
+    /// sweep beta over these values, e.g. 0,1,2, alone or with a sweep over shapes
+    #[argh(option, arg_name = "values", from_str_fn(sweep::parse_scalars))]
+    beta_sweep: Option<Vec<f64>>,
+
+    /// sweep over the shapes of a named preset, see `presets`
+    #[argh(option, arg_name = "name", from_str_fn(preset::parse_preset))]
+    preset: Option<&'static preset::Preset>,
+
+    /// in a sweep, abandon entries whose running median is this much slower than the best entry so far, e.g. 20%
+    #[argh(
+        option,
+        arg_name = "fraction",
+        from_str_fn(verification::parse_fraction)
+    )]
+    prune_slower_than: Option<f64>,
+
+    /// iterations measured before an entry can be abandoned by --prune-slower-than
+    #[argh(option, arg_name = "iterations", default = "3")]
+    prune_after: usize,
+
+    /// benchmark call_dgemm_batch on the groups of a batch file instead of call_dgemm
+    #[argh(option, arg_name = "path-to-batch-file")]
+    batch: Option<String>,
+
+    /// benchmark call_dgemm_strided_batched on this many problems of M x N x K instead of call_dgemm
+    #[argh(option, arg_name = "count")]
+    strided_batch: Option<usize>,
+
+    /// distance in elements between the A of consecutive problems of a strided batch, 0 to share one A (default: M*K)
+    #[argh(option, arg_name = "elements")]
+    stride_a: Option<usize>,
+
+    /// distance in elements between the B of consecutive problems of a strided batch, 0 to share one B (default: K*N)
+    #[argh(option, arg_name = "elements")]
+    stride_b: Option<usize>,
+
+    /// distance in elements between the C of consecutive problems of a strided batch (default: M*N)
+    #[argh(option, arg_name = "elements")]
+    stride_c: Option<usize>,
+
+    /// benchmark compute_packed on A and B packed once per session by pack_a and pack_b, timing packing separately
+    #[argh(switch)]
+    packed: bool,
+
+    /// in packed mode, pack B once per iteration and run this many products on it, each packing its own A
+    #[argh(option, arg_name = "calls")]
+    reuse_b: Option<usize>,
+
+    /// floating-point operations of an iteration as a formula over m, n, k and batch, for operations other than a GEMM, e.g. 2*m*n*k*batch (default: those of the GEMM)
+    #[argh(option, arg_name = "formula")]
+    flops_formula: Option<String>,
+
+    /// bytes an iteration moves as a formula over m, n, k and batch, to report the arithmetic intensity, e.g. 8*(m*k+k*n+2*m*n)*batch
+    #[argh(option, arg_name = "formula")]
+    bytes_formula: Option<String>,
+
+    /// benchmark the GEMM im2col lowers a convolution to, given as n=N,c=C,h=H,w=W,k=K,r=R,s=S[,stride=1][,padding=0]; sets m, n and k, and times the kernel's im2col if it exports one
+    #[argh(option, arg_name = "shape", from_str_fn(conv::parse))]
+    conv: Option<conv::Shape>,
+
+    /// calibrate every sweep point instead of extrapolating from the smallest one
+    #[argh(switch)]
+    calibrate_each: bool,
+
+    /// ask for confirmation when the estimated sweep time exceeds this, e.g. 30m, 2h
+    #[argh(
+        option,
+        from_str_fn(utils::parse_duration),
+        default = "time::Duration::from_secs(3600)"
+    )]
+    confirm_above: time::Duration,
+
+    /// do not ask for confirmation before sweeping
+    #[argh(switch, short = 'y')]
+    yes: bool,
+
+    /// set m, n and k at once
+    #[argh(option, arg_name = "size", from_str_fn(utils::parse_dimension))]
+    square: Option<usize>,
+
     /// alpha
     #[argh(option, default = "1.0")]
     alpha: f64,
@@ -122,221 +538,2697 @@ struct Arguments {
 }
 
 impl Arguments {
-    fn default_compiler() -> String {
-        #[cfg(target_arch = "aarch64")]
-        return String::from("armclang");
-        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-        return String::from("icc");
-    }
-}
-
-struct Kernel<'lib>(
-    libloading::Symbol<
-        'lib,
-        unsafe extern "C" fn(
-            layout: CBLAS_LAYOUT,
-            TransA: CBLAS_TRANSPOSE,
-            TransB: CBLAS_TRANSPOSE,
-            m: usize,
-            n: usize,
-            k: usize,
-            alpha: c_double,
-            A: *const c_double,
-            lda: usize,
-            B: *const c_double,
-            ldb: usize,
-            beta: c_double,
-            C: *mut c_double,
-            ldc: usize,
-        ),
-    >,
-);
-
-impl<'lib> Kernel<'lib> {
-    fn run(
-        &self,
-        layout: CBLAS_LAYOUT,
-        trans_a: CBLAS_TRANSPOSE,
-        trans_b: CBLAS_TRANSPOSE,
-        (m, n, k): (usize, usize, usize),
-        a: &Box<[f64]>,
-        lda: usize,
-        b: &Box<[f64]>,
-        ldb: usize,
-        c: &mut Box<[f64]>,
-        ldc: usize,
-        alpha: f64,
-        beta: f64,
-    ) -> common::Duration {
-        let a = a.as_ptr();
-        let b = b.as_ptr();
-        let c = c.as_mut_ptr();
-
-        let start_time = time::Instant::now();
-        unsafe {
-            self.0(
-                layout, trans_a, trans_b, m, n, k, alpha, a, lda, b, ldb, beta, c, ldc,
-            );
+    fn units(&self) -> units::Units {
+        units::Units {
+            time: self.units,
+            flops: self.flops_units,
         }
-        let end_time = time::Instant::now();
-        common::Duration((end_time - start_time).as_nanos())
     }
 }
 
-fn check_args(args: &Arguments) {
-    if args.repeats == 0 {
-        eprintln!("Error: repeats should be signed integer that is not 0");
-        process::exit(1)
-    }
+#[derive(FromArgs)]
+/// subcommands
+struct Commands {
+    #[argh(subcommand)]
+    command: Command,
 }
 
-#[cfg(target_arch = "aarch64")]
-fn build_extra_args(command: &mut process::Command) {
-    command.arg("-fopenmp");
-    command.arg("-lm");
-    command.arg("-armpl");
-    command.arg("-mcpu=native");
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum Command {
+    Autotune(AutotuneArguments),
+    Conformance(ConformanceArguments),
+    Examples(ExamplesArguments),
+    Init(InitArguments),
+    Presets(PresetsArguments),
+    Schema(SchemaArguments),
+    Suite(SuiteArguments),
+    Watch(WatchArguments),
+    Worker(WorkerArguments),
 }
 
-#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-fn build_extra_args(command: &mut process::Command) {
-    command.arg("-lmkl_rt");
-    command.arg("-march=native");
-}
+#[derive(FromArgs)]
+/// check that a kernel works with the harness and save the scorecard
+#[argh(subcommand, name = "conformance")]
+struct ConformanceArguments {
+    /// path to the kernel source, or to an object compiled beforehand
+    #[argh(positional, arg_name = "path-to-kernel")]
+    kernel: String,
 
-fn build(
+    /// compiler of the kernel source
+    #[argh(option, default = "compiler::default_compiler()")]
     compiler: String,
-    compiler_args: Option<String>,
-    override_mode: bool,
-    kernel: &String,
-    out: &String,
-) -> process::ExitStatus {
-    let mut command = process::Command::new(compiler);
-    if !override_mode {
-        command.arg("-O3");
-        command.arg("-lnuma");
-        build_extra_args(&mut command);
-        command.args(["-Wall", "-Werror"]);
-        command.args(["-L", env!("PATH_LIBRARY")]);
-        command.args(["-I", env!("PATH_INCLUDE")]);
-    }
-    if let Some(args) = compiler_args {
-        command.args(args.split_whitespace());
-    }
-    command.arg("-shared");
-    command.args(["-o", out]);
-    command.arg(kernel);
-    command
-        .spawn()
-        .expect("Error: failed to run compiler")
-        .wait()
-        .expect("Error: failed to wait compiler exit")
-}
 
-static FILENAME_TEMP: sync::LazyLock<String> = sync::LazyLock::new(|| "./.temp".to_string());
+    /// file to save the scorecard into as JSON
+    #[argh(option, default = "String::from(\"conformance.json\")")]
+    out: String,
+}
 
-fn main() {
-    let args: Arguments = argh::from_env();
-    check_args(&args);
+fn run_conformance(args: ConformanceArguments) {
+    let object = path::Path::new(&args.kernel)
+        .extension()
+        .is_some_and(|x| x == env::consts::DLL_EXTENSION);
+    let session = if object {
+        session::Session::open(&args.kernel)
+    } else {
+        session::Session::compile(&args.kernel, &args.compiler)
+    };
+    let scorecard = session.and_then(|x| x.conformance()).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1)
+    });
 
-    // these parts look really ugly, but they do what should be done.
-    // out=Some, compile=Some(true) => build(out) then run(out),
-    // out=Some, compile=Some(false) => run(out),
-    // out=Some, compile=None => auto then run(out),
-    // out=None, compile=Some(true) => build(".temp") then run(".temp"),
-    // out=None, compile=Some(false) => run(kernel),
-    // out=None, compile=None => build(".temp") then run(".temp"),
-    let (out, compile) = args.out.as_ref().map_or_else(
-        || {
-            // out=None
-            if args.compile.is_some_and(|x| !x) {
-                // compile=Some(false)
-                (&args.kernel, false)
-            } else {
-                // compile=Some(true) or compile=None
-                (&*FILENAME_TEMP, true)
-            }
-        },
-        |out| {
-            // out=Some
-            (
-                out,
-                args.compile.unwrap_or_else(|| {
-                    // compile or auto
-                    fs::File::open(out).is_err_or(|out| {
-                        let source = fs::File::open(&args.kernel)
-                            .expect("Error: kernel not found")
-                            .metadata()
-                            .expect("Error: failed to query metadata");
-                        source.accessed().expect("Error: unsupported platform")
-                            > out
-                                .metadata()
-                                .expect("Error: failed to query metadata")
-                                .created()
-                                .expect("Error: unsupported platform")
-                    })
-                }),
-            )
-        },
+    for check in &scorecard.checks {
+        match &check.failure {
+            None => println!("PASS\t{}\t{}", check.category, check.name),
+            Some(failure) => println!("FAIL\t{}\t{}: {}", check.category, check.name, failure),
+        }
+    }
+    println!(
+        "Conformance: {}/{} checks passed, ABI version {}",
+        scorecard.passed,
+        scorecard.total(),
+        scorecard.abi_version
     );
-    if compile
-        && !build(
-            args.compiler,
-            args.compiler_args,
-            args.override_compiler_args,
-            &args.kernel,
-            out,
-        )
-        .success()
-    {
-        eprintln!("Error: compilation failed");
+    fs::write(
+        &args.out,
+        serde_json::to_string_pretty(&scorecard).expect("Error: failed to serialize"),
+    )
+    .expect("Error: failed to save scorecard");
+    if scorecard.passed < scorecard.total() {
         process::exit(1)
     }
+}
 
-    let library =
-        unsafe { libloading::Library::new(out) }.expect("Error: failed to load compiled object");
-    let kernel = Kernel(
-        unsafe { library.get(b"call_dgemm") }
-            .expect("Error: compiled object does not contain symbol call_dgemm"),
-    );
+#[derive(FromArgs)]
+/// list or extract built-in example kernels
+#[argh(subcommand, name = "examples")]
+struct ExamplesArguments {
+    /// directory to extract example kernels into
+    #[argh(option, arg_name = "dir")]
+    write_to: Option<String>,
+}
 
-    let dimensions = (args.m, args.n, args.k);
-    let (m, n, k) = dimensions;
-    println!("M: {}, N: {}, K: {}", m, n, k);
-    println!("alpha: {:.4}, beta: {:.4}", args.alpha, args.beta);
-    println!("Layout: {}", args.layout);
+fn run_examples(args: ExamplesArguments) {
+    let Some(dir) = args.write_to else {
+        for example in examples::EXAMPLES {
+            println!(
+                "{}{}\t{}",
+                examples::PREFIX,
+                example.name,
+                example.description
+            );
+        }
+        return;
+    };
+    for example in examples::EXAMPLES {
+        let file = example
+            .write_to(path::Path::new(&dir))
+            .expect("Error: failed to write example kernel");
+        println!("{}", file.display());
+    }
+}
 
-    let transpose = (args.trans_a, args.trans_b);
-    let (trans_a, trans_b) = transpose;
-    println!("TransA: {}", trans_a == CBLAS_TRANSPOSE::CblasTrans);
-    println!("TransB: {}", trans_b == CBLAS_TRANSPOSE::CblasTrans);
+#[derive(FromArgs)]
+/// scaffold a new kernel project
+#[argh(subcommand, name = "init")]
+struct InitArguments {
+    /// directory to create the project in
+    #[argh(positional, arg_name = "dir")]
+    dir: String,
 
-    let lda = if (trans_a == CBLAS_TRANSPOSE::CblasTrans)
-        != (args.layout == CBLAS_LAYOUT::CblasRowMajor)
-    {
-        k
-    } else {
-        m
-    };
-    let ldb = if (trans_b == CBLAS_TRANSPOSE::CblasTrans)
-        != (args.layout == CBLAS_LAYOUT::CblasRowMajor)
-    {
-        n
-    } else {
-        k
+    /// target architecture; aarch64 or x86_64
+    #[argh(
+        option,
+        from_str_fn(init::parse_arch),
+        default = "init::default_arch()"
+    )]
+    arch: String,
+}
+
+fn run_init(args: InitArguments) {
+    let files = init::scaffold(path::Path::new(&args.dir), &args.arch).unwrap_or_else(|e| {
+        eprintln!("Error: failed to scaffold kernel project: {}", e);
+        process::exit(1)
+    });
+    for file in files {
+        println!("{}", file.display());
+    }
+}
+
+#[derive(FromArgs)]
+/// list presets of shapes, or the shapes of one
+#[argh(subcommand, name = "presets")]
+struct PresetsArguments {
+    /// preset to list the shapes of
+    #[argh(positional, arg_name = "name", from_str_fn(preset::parse_preset))]
+    preset: Option<&'static preset::Preset>,
+}
+
+fn run_presets(args: PresetsArguments) {
+    let Some(preset) = args.preset else {
+        for preset in preset::PRESETS {
+            println!(
+                "{}\t{} shapes\t{}",
+                preset.name,
+                preset.shapes.len(),
+                preset.description
+            );
+        }
+        return;
     };
-    let ldc = if args.layout == CBLAS_LAYOUT::CblasRowMajor {
-        n
+    for (m, n, k) in preset.shapes {
+        println!("{}x{}x{}", m, n, k);
+    }
+}
+
+#[derive(FromArgs)]
+/// print the JSON Schema of saved reports
+#[argh(subcommand, name = "schema")]
+struct SchemaArguments {
+    /// print the schema of suite reports instead
+    #[argh(switch)]
+    suite: bool,
+}
+
+fn run_schema(args: SchemaArguments) {
+    let schema = if args.suite {
+        schemars::schema_for!(common::SuiteReport)
     } else {
-        m
+        schemars::schema_for!(common::Report)
     };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&schema).expect("Error: failed to serialize")
+    );
+}
 
-    let a = utils::fill_rand(m * k, 100, 0.0, 2.0);
-    let b = utils::fill_rand(k * n, 200, 0.0, 2.0);
-    let mut c = unsafe { utils::malloc::<f64>(m * n) };
+#[derive(FromArgs)]
+/// recompile and rerun a short benchmark whenever the kernel source changes, with one line per build
+#[argh(subcommand, name = "watch")]
+struct WatchArguments {
+    /// how often to check whether the kernel changed, e.g. 500ms
+    #[argh(
+        option,
+        from_str_fn(utils::parse_duration),
+        default = "time::Duration::from_millis(300)"
+    )]
+    interval: time::Duration,
 
-    if !args.skip_verification {
-        kernel.run(
+    /// unit of the printed throughput: gflops or tflops
+    #[argh(
+        option,
+        arg_name = "unit",
+        from_str_fn(units::FlopsUnit::try_from),
+        default = "units::FlopsUnit::Gflops"
+    )]
+    flops_units: units::FlopsUnit,
+
+    /// path to the kernel source
+    #[argh(positional, arg_name = "path-to-kernel")]
+    kernel: String,
+
+    /// options of every benchmark run, e.g. -m 2048 -n 2048 -k 2048
+    #[argh(positional, greedy, arg_name = "options")]
+    options: Vec<String>,
+}
+
+/// Repeats of a watch run unless its options give them.
+const WATCH_REPEATS: usize = 5;
+
+/// Benchmark `args.kernel` as a child process every time it changes, printing the medium
+/// throughput against the previous and the best build, or the output of a failed one.
+fn run_watch(args: WatchArguments) {
+    if args.options.iter().any(|x| x == "--save-as") {
+        eprintln!("Error: watch saves the reports itself, so --save-as cannot be given");
+        process::exit(1)
+    }
+    let executable = env::current_exe().expect("Error: failed to locate executable");
+    let file = env::temp_dir().join(format!("benchmark-dgemm-watch-{}.json", process::id()));
+    let mut arguments = vec![args.kernel.clone()];
+    if !args.options.iter().any(|x| x == "-r" || x == "--repeats") {
+        arguments.extend(["--repeats".to_string(), WATCH_REPEATS.to_string()]);
+    }
+    arguments.extend(args.options);
+    arguments.extend(["--save-as".to_string(), file.to_string_lossy().to_string()]);
+
+    let modified = || fs::metadata(&args.kernel).and_then(|x| x.modified()).ok();
+    let flops = args.flops_units;
+    let mut seen = None;
+    let mut source = Vec::new();
+    let mut build = 0;
+    // builds that failed are skipped by both.
+    let mut previous = None::<(usize, f64)>;
+    let mut best = None::<(usize, f64)>;
+    println!("Watching {}, press Ctrl-C to stop", args.kernel);
+    loop {
+        let current = modified();
+        if current.is_none() || current == seen {
+            thread::sleep(args.interval);
+            continue;
+        }
+        // editors save in several writes, so the file has to settle first.
+        thread::sleep(args.interval);
+        if modified() != current {
+            continue;
+        }
+        seen = current;
+        // saving without an edit changes nothing worth measuring.
+        let content = fs::read(&args.kernel).unwrap_or_default();
+        if content == source {
+            continue;
+        }
+        source = content;
+        build += 1;
+
+        drop(fs::remove_file(&file));
+        let output = process::Command::new(&executable)
+            .args(&arguments)
+            .stdin(process::Stdio::null())
+            .stdout(process::Stdio::null())
+            .stderr(process::Stdio::piped())
+            .output()
+            .expect("Error: failed to run benchmark");
+        let report = fs::File::open(&file)
+            .ok()
+            .filter(|_| output.status.success())
+            .and_then(|x| serde_json::from_reader::<fs::File, common::Report>(x).ok());
+        let Some(report) = report else {
+            println!("Build {}: failed", build);
+            eprint!("{}", String::from_utf8_lossy(&output.stderr));
+            continue;
+        };
+
+        let gflops = hook::gflops(&report);
+        let mut line = format!("Build {}: {:.3} {}", build, flops.of(gflops), flops.label());
+        if let Some((index, x)) = previous {
+            line.push_str(&format!(
+                ", {:+.2}% against build {}",
+                (gflops / x - 1.0) * 100.0,
+                index
+            ));
+        }
+        match best {
+            Some((index, x)) if x >= gflops => line.push_str(&format!(
+                ", {:+.2}% against the best, build {}",
+                (gflops / x - 1.0) * 100.0,
+                index
+            )),
+            Some(_) => {
+                line.push_str(", best so far");
+                best = Some((build, gflops));
+            }
+            None => best = Some((build, gflops)),
+        }
+        println!("{}", line);
+        previous = Some((build, gflops));
+    }
+}
+
+#[derive(FromArgs)]
+/// benchmark every entry of a suite file
+#[argh(subcommand, name = "suite")]
+struct SuiteArguments {
+    /// path to suite file
+    #[argh(positional, arg_name = "path-to-suite")]
+    suite: String,
+
+    /// directory to save benchmark results into
+    #[argh(option, arg_name = "dir", default = "String::from(\"reports\")")]
+    out_dir: String,
+
+    /// write JSON status events, one per line, to this inherited file descriptor
+    #[argh(option, arg_name = "fd")]
+    status_fd: Option<i32>,
+
+    /// write JSON status events, one per line, to this listening Unix-domain socket
+    #[argh(option, arg_name = "path")]
+    status_socket: Option<String>,
+
+    /// checkpoint file recording completed entries (default: <out-dir>/checkpoint.json)
+    #[argh(option, arg_name = "file")]
+    checkpoint: Option<String>,
+
+    /// resume from a checkpoint file, skipping completed entries
+    #[argh(option, arg_name = "file")]
+    resume: Option<String>,
+
+    /// run entries even if the output directory already has a report of the same configuration
+    #[argh(switch)]
+    rerun: bool,
+
+    /// file collecting the reports of every entry with their shared environment (default: <out-dir>/suite.json)
+    #[argh(option, arg_name = "file")]
+    suite_report: Option<String>,
+
+    /// calibrate every entry instead of extrapolating from the smallest one
+    #[argh(switch)]
+    calibrate_each: bool,
+
+    /// ask for confirmation when the estimated time exceeds this, e.g. 30m, 2h
+    #[argh(
+        option,
+        from_str_fn(utils::parse_duration),
+        default = "time::Duration::from_secs(3600)"
+    )]
+    confirm_above: time::Duration,
+
+    /// do not ask for confirmation
+    #[argh(switch, short = 'y')]
+    yes: bool,
+
+    /// abandon entries whose running median is this much slower than the best entry so far, e.g. 20%
+    #[argh(
+        option,
+        arg_name = "fraction",
+        from_str_fn(verification::parse_fraction)
+    )]
+    prune_slower_than: Option<f64>,
+
+    /// run the entries in a random order, so position effects such as thermal drift average out
+    #[argh(switch)]
+    shuffle: bool,
+
+    /// seed of the random order, e.g. the shuffle_seed of an earlier suite report to run in its
+    /// order again; implies --shuffle
+    #[argh(option, arg_name = "seed")]
+    shuffle_seed: Option<u64>,
+
+    /// run the suite without taking the machine-wide lock, so its entries take it one by one
+    #[argh(switch)]
+    no_lock: bool,
+
+    /// lock file runs on this machine take turns on (default: /tmp/benchmark-dgemm.lock)
+    #[argh(option, arg_name = "file", default = "lock::DEFAULT_PATH.to_string()")]
+    lock_file: String,
+}
+
+/// Open the status output on the file descriptor `fd` or the unix socket `socket`, if any.
+fn open_status(fd: Option<i32>, socket: &Option<String>) {
+    let result = match (fd, socket) {
+        (Some(fd), _) => status::open_fd(fd),
+        (None, Some(socket)) => status::connect(socket),
+        (None, None) => Ok(()),
+    };
+    if let Err(e) = result {
+        eprintln!("Error: failed to open status output: {}", e);
+        process::exit(1)
+    }
+}
+
+/// Take the machine-wide lock at `path` unless `no_lock`; exits if it cannot be taken, as a
+/// run without it could share the machine unnoticed.
+fn take_lock(no_lock: bool, path: &str) -> Option<lock::Lock> {
+    if no_lock {
+        return None;
+    }
+    match lock::acquire(path) {
+        Ok(lock) => Some(lock),
+        Err(e) => {
+            eprintln!("Error: failed to take the benchmark lock: {}", e);
+            eprintln!("Pass --lock-file for another lock file, or --no-lock to run without it");
+            process::exit(1)
+        }
+    }
+}
+
+/// Run every entry as a child process, calling `on_success` with the arguments of each one that succeeded.
+fn run_entries(entries: &[Vec<String>], mut on_success: impl FnMut(&[String])) -> usize {
+    let mut failed = 0;
+    for (index, arguments) in entries.iter().enumerate() {
+        if run_entry(index, entries.len(), arguments) {
+            on_success(arguments);
+        } else {
+            failed += 1;
+        }
+    }
+    failed
+}
+
+/// Run entry `index` of `total` as a child process. Returns whether it succeeded.
+fn run_entry(index: usize, total: usize, arguments: &[String]) -> bool {
+    let executable = env::current_exe().expect("Error: failed to locate executable");
+    println!(
+        "=== Entry {}/{}: {} ===",
+        index + 1,
+        total,
+        arguments.join(" ")
+    );
+    status::emit(status::Event::EntryStarted {
+        index,
+        total,
+        arguments,
+    });
+    let status = process::Command::new(&executable)
+        .args(arguments)
+        .status()
+        .expect("Error: failed to run benchmark");
+    status::emit(status::Event::EntryFinished {
+        index,
+        total,
+        success: status.success(),
+    });
+    if !status.success() {
+        eprintln!("Error: entry {} failed", index + 1);
+    }
+    status.success()
+}
+
+/// `run_entries`, but the kernels are compiled into `dir` beforehand, up to `jobs` at a time,
+/// so measuring one entry overlaps with compiling the next ones. Compilers run on the CPUs no
+/// entry is pinned to, if there are any. Returns the number of failed entries.
+fn run_pipelined(entries: &[Vec<String>], jobs: usize, dir: &path::Path) -> usize {
+    let parsed = entries
+        .iter()
+        .map(|x| parse_entry(x))
+        .collect::<Vec<Arguments>>();
+    let pinned = parsed
+        .iter()
+        .flat_map(|x| x.pin_cpus.iter().flatten().copied())
+        .collect::<Vec<usize>>();
+    let build_cpus = affinity::available()
+        .into_iter()
+        .filter(|x| !pinned.contains(x))
+        .collect::<Vec<usize>>();
+    let object = |index: usize| {
+        dir.join(format!("{}{}", index, env::consts::DLL_SUFFIX))
+            .to_string_lossy()
+            .to_string()
+    };
+
+    let next = sync::atomic::AtomicUsize::new(0);
+    let (sender, receiver) = sync::mpsc::channel::<(usize, bool)>();
+    let mut failed = 0;
+    thread::scope(|scope| {
+        let (parsed, pinned, build_cpus, next, object) =
+            (&parsed, &pinned, &build_cpus, &next, &object);
+        for _ in 0..jobs.min(entries.len()) {
+            let sender = sender.clone();
+            scope.spawn(move || {
+                // compilers inherit the affinity of the thread that spawns them.
+                if !pinned.is_empty() && !build_cpus.is_empty() {
+                    drop(affinity::pin(build_cpus));
+                }
+                loop {
+                    let index = next.fetch_add(1, sync::atomic::Ordering::Relaxed);
+                    let Some(x) = parsed.get(index) else {
+                        break;
+                    };
+                    let mut command = compiler::build_command(
+                        &x.compiler,
+                        x.compiler_profile
+                            .unwrap_or_else(|| compiler::Profile::infer(&x.compiler)),
+                        &x.compiler_args,
+                        x.override_compiler_args,
+                        &x.define,
+                        None,
+                        &x.kernel,
+                        &object(index),
+                    );
+                    command.stdout(process::Stdio::null());
+                    let success = command.status().is_ok_and(|x| x.success());
+                    if sender.send((index, success)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut built = vec![None; entries.len()];
+        for index in 0..entries.len() {
+            while built[index].is_none() {
+                let Ok((compiled, success)) = receiver.recv() else {
+                    break;
+                };
+                built[compiled] = Some(success);
+            }
+            if built[index] != Some(true) {
+                eprintln!("Error: entry {} failed to compile", index + 1);
+                failed += 1;
+                continue;
+            }
+            let mut arguments = entries[index].clone();
+            arguments.insert(1, object(index));
+            arguments.extend(["--compile".to_string(), "false".to_string()]);
+            if !run_entry(index, entries.len(), &arguments) {
+                failed += 1;
+            }
+            drop(fs::remove_file(object(index)));
+        }
+    });
+    failed
+}
+
+fn parse_entry(arguments: &[String]) -> Arguments {
+    let arguments = arguments.iter().map(String::as_str).collect::<Vec<&str>>();
+    Arguments::from_args(&["benchmark"], &arguments).unwrap_or_else(|e| {
+        eprintln!("{}", e.output);
+        process::exit(1)
+    })
+}
+
+/// Options that must not leak into a calibration run.
+const CALIBRATION_OPTIONS: &[&str] = &[
+    "-r",
+    "--repeats",
+    "--warm-up",
+    "--sessions",
+    "--session-gap",
+    "--save-as",
+    "--save-history-as",
+    "--bundle",
+    "--compile-commands",
+    "--post-to",
+    "--on-complete",
+    "--on-regression",
+    "--baseline",
+    "--baseline-mismatch",
+    "--status-fd",
+    "--status-socket",
+];
+const CALIBRATION_SWITCHES: &[&str] = &["--skip-verification"];
+
+/// Run a single unverified iteration of an entry.
+/// Returns the wall time outside of the iteration (build, load, generation) and the iteration time, in seconds.
+fn calibrate(arguments: &[String]) -> Option<(f64, f64)> {
+    let executable = env::current_exe().ok()?;
+    let file = env::temp_dir().join(format!(
+        "benchmark-dgemm-calibration-{}.json",
+        process::id()
+    ));
+
+    let start = time::Instant::now();
+    let status = process::Command::new(executable)
+        .args(sweep::strip_options(
+            arguments,
+            CALIBRATION_OPTIONS,
+            CALIBRATION_SWITCHES,
+        ))
+        .args(["--repeats", "1", "--warm-up", "0", "--sessions", "1"])
+        .arg("--skip-verification")
+        .arg("--save-as")
+        .arg(&file)
+        .stdout(process::Stdio::null())
+        .status()
+        .ok()?;
+    let wall = start.elapsed().as_secs_f64();
+    if !status.success() {
+        return None;
+    }
+
+    let report = serde_json::from_reader::<fs::File, common::Report>(fs::File::open(&file).ok()?);
+    drop(fs::remove_file(&file));
+    let iteration = report.ok()?.statistics.minimum.as_secs();
+    Some(((wall - iteration).max(0.0), iteration))
+}
+
+/// Estimate the wall time of every entry and ask for confirmation if the total is too long.
+fn preflight(
+    entries: &[Vec<String>],
+    calibrate_each: bool,
+    confirm_above: time::Duration,
+    yes: bool,
+) {
+    let parsed = entries
+        .iter()
+        .map(|x| parse_entry(x))
+        .collect::<Vec<Arguments>>();
+    let flops = |x: &Arguments| 2.0 * x.m as f64 * x.n as f64 * x.k as f64;
+
+    let calibrated = if calibrate_each {
+        entries.iter().map(|x| calibrate(x)).collect::<Vec<_>>()
+    } else {
+        // extrapolate by FLOP count from the cheapest entry.
+        let Some(cheapest) =
+            (0..entries.len()).min_by(|&x, &y| flops(&parsed[x]).total_cmp(&flops(&parsed[y])))
+        else {
+            return;
+        };
+        let Some((overhead, iteration)) = calibrate(&entries[cheapest]) else {
+            eprintln!("Error: calibration of entry {} failed", cheapest + 1);
+            process::exit(1)
+        };
+        let rate = flops(&parsed[cheapest]) / iteration;
+        parsed
+            .iter()
+            .map(|x| Some((overhead, flops(x) / rate)))
+            .collect::<Vec<_>>()
+    };
+
+    let caches = cache::detect();
+    let mut total = 0.0;
+    println!("Plan: {} entries", entries.len());
+    for (index, (arguments, estimate)) in parsed.iter().zip(calibrated).enumerate() {
+        let Some((overhead, iteration)) = estimate else {
+            eprintln!("Error: calibration of entry {} failed", index + 1);
+            process::exit(1)
+        };
+        let calls = arguments.warm_up
+            + arguments.repeats * arguments.sessions
+            + verifies(arguments) as usize;
+        let seconds = overhead
+            + calls as f64 * iteration
+            + arguments.session_gap.as_secs_f64() * arguments.sessions.saturating_sub(1) as f64
+            + arguments.jitter.map_or(0.0, |x| {
+                (x.min.as_secs() + x.max.as_secs()) / 2.0
+                    * (arguments.repeats.saturating_sub(1) * arguments.sessions) as f64
+            });
+        total += seconds;
+        let working_set =
+            cache::WorkingSet::new((arguments.m, arguments.n, arguments.k), caches.clone());
+        println!(
+            "{}/{}\tM: {}, N: {}, K: {}\t{} ({})\t~{}",
+            index + 1,
+            entries.len(),
+            arguments.m,
+            arguments.n,
+            arguments.k,
+            cache::format_bytes(working_set.total()),
+            working_set.fits,
+            utils::format_duration(time::Duration::from_secs_f64(seconds))
+        );
+    }
+    let total = time::Duration::from_secs_f64(total);
+    println!("Estimated time: {}", utils::format_duration(total));
+
+    if total > confirm_above && !yes {
+        print!("Proceed? [y/N] ");
+        drop(io::stdout().flush());
+        let mut answer = String::new();
+        drop(io::stdin().read_line(&mut answer));
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            eprintln!("Error: aborted");
+            process::exit(1)
+        }
+    }
+}
+
+fn run_suite(args: SuiteArguments) {
+    let suite = suite::Suite::load(path::Path::new(&args.suite)).unwrap_or_else(|e| {
+        eprintln!("Error: failed to load suite: {}", e);
+        process::exit(1)
+    });
+    fs::create_dir_all(&args.out_dir).expect("Error: failed to create output directory");
+    open_status(args.status_fd, &args.status_socket);
+    // the whole suite runs under one lock, which its entries share.
+    let _lock = take_lock(args.no_lock, &args.lock_file);
+
+    // configurations that already have a report in the output directory.
+    let stored = glob::glob(
+        &path::Path::new(&args.out_dir)
+            .join("*.json")
+            .to_string_lossy(),
+    )
+    .expect("Error: invalid output directory")
+    .filter_map(Result::ok)
+    .filter_map(|x| fs::File::open(x).ok())
+    .filter_map(|x| serde_json::from_reader::<fs::File, common::Report>(x).ok())
+    .filter_map(|x| x.meta.get(suite::META_CONFIGURATION).cloned())
+    .collect::<collections::BTreeSet<String>>();
+
+    let configurations = suite
+        .arguments()
+        .iter()
+        .map(|x| suite::configuration(x))
+        .collect::<Vec<String>>();
+    let shuffle_seed = (args.shuffle || args.shuffle_seed.is_some()).then(|| {
+        args.shuffle_seed.unwrap_or_else(|| {
+            time::SystemTime::now()
+                .duration_since(time::SystemTime::UNIX_EPOCH)
+                .map_or(0, |x| x.as_nanos() as u64)
+        })
+    });
+    // collects the stored reports of this suite, whether they were run now or before.
+    let write_suite_report = || {
+        let mut reports = glob::glob(
+            &path::Path::new(&args.out_dir)
+                .join("*.json")
+                .to_string_lossy(),
+        )
+        .expect("Error: invalid output directory")
+        .filter_map(Result::ok)
+        .filter_map(|x| fs::File::open(x).ok())
+        .filter_map(|x| serde_json::from_reader::<fs::File, common::Report>(x).ok())
+        .filter_map(|x| {
+            let configuration = x.meta.get(suite::META_CONFIGURATION)?;
+            let index = configurations.iter().position(|y| y == configuration)?;
+            Some((index, x))
+        })
+        .collect::<Vec<(usize, common::Report)>>();
+        reports.sort_by_key(|(index, _)| *index);
+        let mut report =
+            common::SuiteReport::new(&args.suite, reports.into_iter().map(|(_, x)| x).collect());
+        report.shuffle_seed = shuffle_seed;
+        let file = args.suite_report.clone().unwrap_or_else(|| {
+            path::Path::new(&args.out_dir)
+                .join("suite.json")
+                .to_string_lossy()
+                .to_string()
+        });
+        if let Err(e) = fs::write(
+            &file,
+            serde_json::to_string(&report).expect("Error: failed to serialize"),
+        ) {
+            eprintln!("Error: failed to save suite report: {}", e);
+        }
+    };
+
+    let mut skipped = 0;
+    let entries = suite
+        .arguments()
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, mut arguments)| {
+            let configuration = suite::configuration(&arguments);
+            if !args.rerun && stored.contains(&configuration) {
+                skipped += 1;
+                return None;
+            }
+            if let Some(fd) = args.status_fd {
+                arguments.extend(["--status-fd".to_string(), fd.to_string()]);
+            }
+            if let Some(socket) = &args.status_socket {
+                arguments.extend(["--status-socket".to_string(), socket.clone()]);
+            }
+            arguments.push("--meta".to_string());
+            arguments.push([suite::META_CONFIGURATION, "=", &configuration].concat());
+            arguments.push("--save-as".to_string());
+            arguments.push(
+                path::Path::new(&args.out_dir)
+                    .join(format!("{}.json", index))
+                    .to_string_lossy()
+                    .to_string(),
+            );
+            Some(arguments)
+        })
+        .collect::<Vec<Vec<String>>>();
+    if skipped > 0 {
+        println!(
+            "Skipping {} entries with stored reports (use --rerun to run them again)",
+            skipped
+        );
+    }
+
+    let file = match (&args.checkpoint, &args.resume) {
+        (Some(file), _) | (None, Some(file)) => path::PathBuf::from(file),
+        (None, None) => path::Path::new(&args.out_dir).join("checkpoint.json"),
+    };
+    let mut checkpoint = match &args.resume {
+        Some(resume) => checkpoint::Checkpoint::load(path::Path::new(resume)).unwrap_or_else(|e| {
+            eprintln!("Error: failed to load checkpoint: {}", e);
+            process::exit(1)
+        }),
+        None => checkpoint::Checkpoint::new(&args.suite),
+    };
+    let mut entries = entries
+        .into_iter()
+        .filter(|x| !checkpoint.is_completed(x))
+        .collect::<Vec<Vec<String>>>();
+    if let Some(seed) = shuffle_seed {
+        suite::shuffle(&mut entries, seed);
+        println!("Shuffled entries with seed {}", seed);
+    }
+    // positions of a resumed run continue after the entries completed before.
+    let first = checkpoint.completed.len();
+    let entries = entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut arguments)| {
+            arguments.push("--meta".to_string());
+            arguments.push([suite::META_POSITION, "=", &(first + index).to_string()].concat());
+            arguments
+        })
+        .collect::<Vec<Vec<String>>>();
+    if args.resume.is_some() {
+        println!(
+            "Resuming: {} completed, {} remaining",
+            checkpoint.completed.len(),
+            entries.len()
+        );
+    }
+    if entries.is_empty() {
+        write_suite_report();
+        return;
+    }
+
+    preflight(&entries, args.calibrate_each, args.confirm_above, args.yes);
+    let mut tracker = prune::Tracker::default();
+    let failed = run_entries(&entries, |arguments| {
+        let report = parse_entry(arguments)
+            .save_as
+            .and_then(|x| fs::File::open(x).ok())
+            .and_then(|x| serde_json::from_reader::<fs::File, common::Report>(x).ok());
+        let Some(report) = report else {
+            eprintln!("Error: failed to read report of completed entry");
+            return;
+        };
+        if let Some(fraction) = args.prune_slower_than {
+            complete_pruning(&mut tracker, &report, fraction);
+        }
+        // the checkpoint identifies entries without their position, which differs between orders.
+        checkpoint.complete(arguments[..arguments.len() - 2].to_vec(), report);
+        if let Err(e) = checkpoint.save(&file) {
+            eprintln!("Error: failed to write checkpoint: {}", e);
+        }
+    });
+    write_suite_report();
+    if tracker.pruned > 0 {
+        println!("Pruned {} of {} entries", tracker.pruned, entries.len());
+    }
+    if failed > 0 {
+        process::exit(1)
+    }
+}
+
+#[derive(FromArgs)]
+/// search the tunables of a kernel for its fastest configuration
+#[argh(subcommand, name = "autotune")]
+struct AutotuneArguments {
+    /// path to search space file
+    #[argh(positional, arg_name = "path-to-space")]
+    space: String,
+
+    /// file to write the search log into
+    #[argh(option, arg_name = "file", default = "String::from(\"search.json\")")]
+    log: String,
+
+    /// also write the search log as CSV, one row per run, into this file
+    #[argh(option, arg_name = "file")]
+    csv: Option<String>,
+
+    /// unit of the printed and CSV throughput: gflops or tflops
+    #[argh(
+        option,
+        arg_name = "unit",
+        from_str_fn(units::FlopsUnit::try_from),
+        default = "units::FlopsUnit::Gflops"
+    )]
+    flops_units: units::FlopsUnit,
+
+    /// seed the search with the runs of a previous search log, e.g. after a small kernel edit
+    #[argh(option, arg_name = "file")]
+    warm_start: Option<String>,
+
+    /// search strategy: halving or bayesian
+    #[argh(
+        option,
+        arg_name = "strategy",
+        from_str_fn(autotune::Strategy::try_from),
+        default = "autotune::Strategy::Halving"
+    )]
+    strategy: autotune::Strategy,
+
+    /// repeats of every candidate in the first rung, or of every candidate of a bayesian search
+    #[argh(option, default = "2")]
+    min_repeats: usize,
+
+    /// factor by which every rung divides the candidates and multiplies the repeats
+    #[argh(option, default = "3")]
+    eta: usize,
+
+    /// candidates a bayesian search evaluates
+    #[argh(option, arg_name = "evaluations", default = "20")]
+    budget: usize,
+
+    /// compile the candidates of a round this many at a time while earlier ones are measured
+    #[argh(option, arg_name = "jobs")]
+    build_jobs: Option<usize>,
+
+    /// run the candidates on workers that register at this address instead of locally, e.g. 0.0.0.0:7070
+    #[argh(option, arg_name = "address")]
+    listen: Option<String>,
+
+    /// secret workers have to register with; required with --listen
+    #[argh(option, arg_name = "token")]
+    token: Option<String>,
+
+    /// fail a candidate its worker has not finished within this, e.g. 30m, 2h
+    #[argh(
+        option,
+        from_str_fn(utils::parse_duration),
+        default = "time::Duration::from_secs(3600)"
+    )]
+    job_timeout: time::Duration,
+}
+
+/// Workers registered with a distributed search. Every worker is served by a thread of its own,
+/// which hands it jobs from a shared queue and returns their reports.
+struct Coordinator {
+    jobs: sync::mpsc::Sender<distributed::Job>,
+    queue: sync::Arc<sync::Mutex<sync::mpsc::Receiver<distributed::Job>>>,
+    results: sync::mpsc::Receiver<(usize, Option<common::Report>)>,
+    timeout: time::Duration,
+    /// id of the first job of the next round; ids run on across rounds, so a late report of an
+    /// earlier round is told apart.
+    next: cell::Cell<usize>,
+}
+
+impl Coordinator {
+    fn listen(address: &str, token: String, timeout: time::Duration) -> Coordinator {
+        let listener = net::TcpListener::bind(address).unwrap_or_else(|e| {
+            eprintln!("Error: failed to listen on {}: {}", address, e);
+            process::exit(1)
+        });
+        println!("Waiting for workers on {}", address);
+        let (jobs, queue) = sync::mpsc::channel();
+        let queue = sync::Arc::new(sync::Mutex::new(queue));
+        let (finished, results) = sync::mpsc::channel();
+        let requeue = jobs.clone();
+        let machine = machine::detect();
+        let shared = queue.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().filter_map(Result::ok) {
+                let (queue, finished, requeue, machine, token) = (
+                    shared.clone(),
+                    finished.clone(),
+                    requeue.clone(),
+                    machine.clone(),
+                    token.clone(),
+                );
+                thread::spawn(move || {
+                    serve(
+                        stream, &machine, &token, timeout, &queue, &finished, &requeue,
+                    )
+                });
+            }
+        });
+        Coordinator {
+            jobs,
+            queue,
+            results,
+            timeout,
+            next: cell::Cell::new(0),
+        }
+    }
+
+    /// Run `jobs` on the workers and wait for all of them; returns their reports by id.
+    /// Jobs still waiting when no report arrived within the job timeout fail, e.g. as every
+    /// worker is gone.
+    fn run(&self, jobs: Vec<distributed::Job>) -> Vec<Option<common::Report>> {
+        let mut reports = (0..jobs.len()).map(|_| None).collect::<Vec<_>>();
+        let base = self.next.get();
+        self.next.set(base + jobs.len());
+        let mut pending = jobs.len();
+        for mut job in jobs {
+            job.id += base;
+            self.jobs.send(job).expect("Error: workers stopped");
+        }
+        while pending > 0 {
+            let Ok((id, report)) = self.results.recv_timeout(self.timeout) else {
+                eprintln!(
+                    "Warning: no worker finished a job within {:?}, {} jobs fail",
+                    self.timeout, pending
+                );
+                // a worker waiting for a job holds the queue, which is empty then.
+                if let Ok(queue) = self.queue.try_lock() {
+                    while queue.try_recv().is_ok() {}
+                }
+                break;
+            };
+            let Some(index) = id.checked_sub(base).filter(|&x| x < reports.len()) else {
+                continue;
+            };
+            println!(
+                "Job {} {}",
+                index + 1,
+                if report.is_some() {
+                    "finished"
+                } else {
+                    "failed"
+                }
+            );
+            reports[index] = report;
+            pending -= 1;
+        }
+        reports
+    }
+}
+
+/// Register a worker and run jobs on it until it disconnects; its unfinished job goes back
+/// into the queue, unless it ran out of time, which fails it.
+fn serve(
+    stream: net::TcpStream,
+    machine: &machine::Machine,
+    expected: &str,
+    timeout: time::Duration,
+    queue: &sync::Mutex<sync::mpsc::Receiver<distributed::Job>>,
+    finished: &sync::mpsc::Sender<(usize, Option<common::Report>)>,
+    requeue: &sync::mpsc::Sender<distributed::Job>,
+) {
+    let peer = stream
+        .peer_addr()
+        .map_or("unknown".to_string(), |x| x.to_string());
+    let Ok(reader) = stream.try_clone() else {
+        return;
+    };
+    let (mut reader, mut stream) = (io::BufReader::new(reader), stream);
+    // a peer that never registers must not hold the thread.
+    if stream.set_read_timeout(Some(timeout)).is_err() {
+        return;
+    }
+    let Ok(distributed::WorkerMessage::Register {
+        machine: worker,
+        token,
+    }) = distributed::receive(&mut reader)
+    else {
+        return;
+    };
+    let reason = if distributed::authorized(expected, &token) {
+        distributed::reject(machine, &worker)
+    } else {
+        Some("wrong token".to_string())
+    };
+    if let Some(reason) = reason {
+        eprintln!("Warning: rejected worker {}: {}", peer, reason);
+        drop(distributed::send(
+            &mut stream,
+            &distributed::CoordinatorMessage::Rejected { reason },
+        ));
+        return;
+    }
+    println!("Worker {} ({}) registered", peer, worker.hostname);
+
+    loop {
+        let Ok(job) = queue.lock().unwrap().recv() else {
+            return;
+        };
+        let id = job.id;
+        let run = distributed::CoordinatorMessage::Run { job: job.clone() };
+        let started = time::Instant::now();
+        match distributed::send(&mut stream, &run).and_then(|_| distributed::receive(&mut reader)) {
+            Ok(distributed::WorkerMessage::Finished { id: done, report }) if done == id => {
+                drop(finished.send((id, report)));
+            }
+            _ if started.elapsed() >= timeout => {
+                eprintln!(
+                    "Warning: worker {} did not finish its job within {:?}, it fails",
+                    peer, timeout
+                );
+                drop(finished.send((id, None)));
+                return;
+            }
+            _ => {
+                eprintln!("Warning: worker {} disconnected, its job runs again", peer);
+                drop(requeue.send(job));
+                return;
+            }
+        }
+    }
+}
+
+#[derive(FromArgs)]
+/// run candidates of a distributed autotune search for its coordinator
+#[argh(subcommand, name = "worker")]
+struct WorkerArguments {
+    /// address the coordinator listens on, e.g. tuner.example.org:7070
+    #[argh(positional, arg_name = "address")]
+    coordinator: String,
+
+    /// secret the coordinator was started with, see its --token
+    #[argh(option, arg_name = "token")]
+    token: String,
+}
+
+fn run_worker(args: WorkerArguments) {
+    let stream = net::TcpStream::connect(&args.coordinator).unwrap_or_else(|e| {
+        eprintln!("Error: failed to connect to {}: {}", args.coordinator, e);
+        process::exit(1)
+    });
+    let reader = stream
+        .try_clone()
+        .expect("Error: failed to clone connection");
+    let (mut reader, mut stream) = (io::BufReader::new(reader), stream);
+    let register = distributed::WorkerMessage::Register {
+        machine: machine::detect(),
+        token: args.token,
+    };
+    if let Err(e) = distributed::send(&mut stream, &register) {
+        eprintln!("Error: failed to register: {}", e);
+        process::exit(1)
+    }
+    println!("Registered with {}", args.coordinator);
+
+    let executable = env::current_exe().expect("Error: failed to locate executable");
+    let temp = env::temp_dir().join(format!("benchmark-dgemm-worker-{}", process::id()));
+    fs::create_dir_all(&temp).expect("Error: failed to create temporary directory");
+    let report_file = temp.join("report.json");
+    // the search is over once the coordinator closes the connection.
+    while let Ok(message) = distributed::receive(&mut reader) {
+        let job = match message {
+            distributed::CoordinatorMessage::Run { job } => job,
+            distributed::CoordinatorMessage::Rejected { reason } => {
+                eprintln!("Error: rejected by coordinator: {}", reason);
+                drop(fs::remove_dir_all(&temp));
+                process::exit(1)
+            }
+        };
+        if let Err(e) = distributed::check_arguments(&job.arguments) {
+            eprintln!("Warning: job {} is not run: {}", job.id + 1, e);
+            let finished = distributed::WorkerMessage::Finished {
+                id: job.id,
+                report: None,
+            };
+            if distributed::send(&mut stream, &finished).is_err() {
+                break;
+            }
+            continue;
+        }
+        // only the file name, so a job cannot write outside of the temporary directory.
+        let name = path::Path::new(&job.kernel_name)
+            .file_name()
+            .map_or("kernel.c".into(), |x| x.to_owned());
+        let kernel = temp.join(name);
+        drop(fs::remove_file(&report_file));
+        let report = fs::write(&kernel, &job.kernel).ok().and_then(|_| {
+            let mut arguments = vec![kernel.to_string_lossy().to_string()];
+            arguments.extend(job.arguments);
+            arguments.extend([
+                "--save-as".to_string(),
+                report_file.to_string_lossy().to_string(),
+            ]);
+            println!("=== Job {}: {} ===", job.id + 1, arguments.join(" "));
+            process::Command::new(&executable)
+                .args(&arguments)
+                .status()
+                .ok()
+                .filter(|x| x.success())?;
+            let file = fs::File::open(&report_file).ok()?;
+            serde_json::from_reader::<fs::File, common::Report>(file).ok()
+        });
+        let finished = distributed::WorkerMessage::Finished { id: job.id, report };
+        if distributed::send(&mut stream, &finished).is_err() {
+            break;
+        }
+    }
+    println!("Coordinator closed the connection");
+    drop(fs::remove_dir_all(&temp));
+}
+
+/// Search the candidates of the space with the selected strategy; every round runs its
+/// candidates as entries.
+fn run_autotune(args: AutotuneArguments) {
+    let space = autotune::Space::load(path::Path::new(&args.space)).unwrap_or_else(|e| {
+        eprintln!("Error: failed to load search space: {}", e);
+        process::exit(1)
+    });
+    if args.eta < 2 {
+        eprintln!("Error: --eta should be at least 2");
+        process::exit(1)
+    }
+    if args.min_repeats == 0 {
+        eprintln!("Error: --min-repeats should not be 0");
+        process::exit(1)
+    }
+    if args.build_jobs == Some(0) {
+        eprintln!("Error: --build-jobs should not be 0");
+        process::exit(1)
+    }
+    if args.build_jobs.is_some() && args.listen.is_some() {
+        eprintln!(
+            "Error: --build-jobs cannot be combined with --listen, as workers compile themselves"
+        );
+        process::exit(1)
+    }
+    let candidates = space.candidates(&cache::detect()).unwrap_or_else(|e| {
+        eprintln!("Error: failed to evaluate constraints: {}", e);
+        process::exit(1)
+    });
+    let total = suite::instances(&space.params).len();
+    if total > candidates.len() {
+        println!(
+            "Constraints: {} of {} candidates skipped",
+            total - candidates.len(),
+            total
+        );
+    }
+    if candidates.is_empty() {
+        eprintln!("Error: no candidate meets the constraints");
+        process::exit(1)
+    }
+    let seeds = args.warm_start.as_ref().map_or(Vec::new(), |file| {
+        let log = autotune::SearchLog::load(path::Path::new(file)).unwrap_or_else(|e| {
+            eprintln!("Error: failed to load search log to warm-start from: {}", e);
+            process::exit(1)
+        });
+        let seeds = autotune::seeds(&log, &candidates);
+        println!(
+            "Warm start: {} of {} runs of {} are candidates of this space",
+            seeds.len(),
+            log.evaluations.len(),
+            file
+        );
+        seeds
+    });
+    let mut strategy: Box<dyn autotune::SearchStrategy> = match args.strategy {
+        autotune::Strategy::Halving => {
+            let halving = autotune::Halving {
+                eta: args.eta,
+                min_repeats: args.min_repeats,
+            };
+            println!(
+                "Autotune: {} candidates, rungs: {}",
+                candidates.len(),
+                halving
+                    .rungs(candidates.len())
+                    .iter()
+                    .map(|(count, repeats)| format!("{}x{}", count, repeats))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+            Box::new(halving)
+        }
+        autotune::Strategy::Bayesian => {
+            println!(
+                "Autotune: {} candidates, {} evaluations",
+                candidates.len(),
+                args.budget.min(candidates.len())
+            );
+            Box::new(bayesian::Bayesian::new(args.budget, args.min_repeats))
+        }
+    };
+
+    let temp = env::temp_dir().join(format!("benchmark-dgemm-autotune-{}", process::id()));
+    fs::create_dir_all(&temp).expect("Error: failed to create temporary directory");
+    let report_file = |index: usize| temp.join(format!("{}.json", index));
+    let coordinator = args.listen.as_deref().map(|address| {
+        let Some(token) = args.token.clone().filter(|x| !x.is_empty()) else {
+            eprintln!("Error: --listen requires --token");
+            process::exit(1)
+        };
+        Coordinator::listen(address, token, args.job_timeout)
+    });
+    let kernel = coordinator.as_ref().map(|_| {
+        fs::read_to_string(&space.kernel).unwrap_or_else(|e| {
+            eprintln!("Error: failed to read kernel: {}", e);
+            process::exit(1)
+        })
+    });
+    let kernel_name = path::Path::new(&space.kernel)
+        .file_name()
+        .map_or(String::new(), |x| x.to_string_lossy().to_string());
+    let run = |round: usize, proposals: &[autotune::Proposal]| {
+        println!(
+            "=== Round {}: {} candidates ===",
+            round + 1,
+            proposals.len()
+        );
+        if let (Some(coordinator), Some(kernel)) = (&coordinator, &kernel) {
+            let jobs = proposals
+                .iter()
+                .enumerate()
+                .map(|(id, x)| {
+                    // workers put their own copy of the kernel first.
+                    let mut arguments = space.arguments(&candidates[x.candidate]).split_off(1);
+                    arguments.extend(["--repeats".to_string(), x.repeats.to_string()]);
+                    distributed::Job {
+                        id,
+                        arguments,
+                        kernel_name: kernel_name.clone(),
+                        kernel: kernel.clone(),
+                    }
+                })
+                .collect();
+            return coordinator
+                .run(jobs)
+                .iter()
+                .map(|x| x.as_ref().map(hook::gflops))
+                .collect();
+        }
+        let entries = proposals
+            .iter()
+            .map(|x| {
+                let mut arguments = space.arguments(&candidates[x.candidate]);
+                arguments.extend([
+                    "--repeats".to_string(),
+                    x.repeats.to_string(),
+                    "--save-as".to_string(),
+                    report_file(x.candidate).to_string_lossy().to_string(),
+                ]);
+                arguments
+            })
+            .collect::<Vec<Vec<String>>>();
+        match args.build_jobs {
+            Some(jobs) => run_pipelined(&entries, jobs, &temp),
+            None => run_entries(&entries, |_| {}),
+        };
+        proposals
+            .iter()
+            .map(|x| {
+                let file = fs::File::open(report_file(x.candidate)).ok()?;
+                let report = serde_json::from_reader::<fs::File, common::Report>(file).ok()?;
+                // a later failure must not find the report of an earlier round.
+                drop(fs::remove_file(report_file(x.candidate)));
+                Some(hook::gflops(&report))
+            })
+            .collect()
+    };
+    let log_file = path::Path::new(&args.log);
+    let log = autotune::search(
+        &args.space,
+        &candidates,
+        seeds,
+        strategy.as_mut(),
+        run,
+        |log| {
+            if let Err(e) = log.save(log_file) {
+                eprintln!("Error: failed to write search log: {}", e);
+            }
+            if let Some(Err(e)) = args
+                .csv
+                .as_ref()
+                .map(|x| fs::write(x, log.to_csv(args.flops_units)))
+            {
+                eprintln!("Error: failed to write search log as CSV: {}", e);
+            }
+        },
+    );
+    drop(fs::remove_dir_all(&temp));
+
+    let Some(winner) = log
+        .evaluations
+        .iter()
+        .find(|x| x.status == autotune::Status::Winner)
+    else {
+        eprintln!("Error: every candidate failed");
+        process::exit(1)
+    };
+    println!(
+        "Winner: {} ({:.3} {} at {} repeats)",
+        autotune::format_candidate(&winner.candidate),
+        args.flops_units.of(winner.gflops.unwrap_or_default()),
+        args.flops_units.label(),
+        winner.repeats
+    );
+}
+
+/// Switches of `Arguments`, which take no value.
+const SWITCHES: &[&str] = &[
+    "--help",
+    "--print-config",
+    "--post-gzip",
+    "--isolate",
+    "--emit-asm",
+    "--disassemble",
+    "--override-compiler-args",
+    "--prewarm-threads",
+    "--skip-verification",
+    "--extended-residual",
+    "--protect-inputs",
+    "--packed",
+    "--calibrate-each",
+    "--no-lock",
+    "--yes",
+    "--verbose",
+];
+const SHORT_OPTIONS: &[(&str, &str)] = &[
+    ("-r", "--repeats"),
+    ("-m", "--m"),
+    ("-n", "--n"),
+    ("-k", "--k"),
+    ("-y", "--yes"),
+    ("-v", "--verbose"),
+];
+
+/// Set for entries whose arguments were already merged with the environment and config file.
+const ENV_MERGED: &str = "BENCHMARK_DGEMM_MERGED";
+
+/// GFLOPS below which an entry of a sweep or suite abandons its run, see `--prune-slower-than`.
+const ENV_PRUNE_BELOW: &str = "BENCHMARK_DGEMM_PRUNE_BELOW";
+
+/// Record a completed entry, raising the threshold of the entries after it if it is the best so far.
+fn complete_pruning(tracker: &mut prune::Tracker, report: &common::Report, fraction: f64) {
+    if let Some(below) = tracker.complete(report, fraction) {
+        env::set_var(ENV_PRUNE_BELOW, below.to_string());
+    }
+}
+
+/// Parse command-line arguments merged over `BENCHMARK_DGEMM_*` variables, then the config file.
+/// Returns the merged arguments as well, for entries spawned from this run.
+fn parse_arguments() -> (Arguments, Vec<String>) {
+    // argh takes one short switch per argument, so -vv is passed as -v -v.
+    let cli = env::args()
+        .skip(1)
+        .flat_map(|x| match x.strip_prefix('-') {
+            Some(v) if v.len() > 1 && v.chars().all(|x| x == 'v') => {
+                vec!["-v".to_string(); v.len()]
+            }
+            _ => vec![x],
+        })
+        .collect::<Vec<String>>();
+    let mut arguments = cli.clone();
+    if env::var_os(ENV_MERGED).is_none() {
+        let table = config::from_env(SWITCHES).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            process::exit(1)
+        });
+        arguments = config::merge(&arguments, &table, SWITCHES, SHORT_OPTIONS);
+    }
+    if let Some(file) = config::find_option(&arguments, "--config") {
+        let table = config::load(path::Path::new(file)).unwrap_or_else(|e| {
+            eprintln!("Error: failed to load config: {}", e);
+            process::exit(1)
+        });
+        arguments = config::merge(&arguments, &table, SWITCHES, SHORT_OPTIONS);
+    }
+
+    let command = env::args()
+        .next()
+        .and_then(|x| {
+            path::Path::new(&x)
+                .file_name()
+                .map(|x| x.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| "benchmark".to_string());
+    // argh takes - for an option, so a kernel read from stdin and the other positional
+    // arguments go after --.
+    let positions = positionals(&arguments);
+    let stdin = positions
+        .first()
+        .is_some_and(|&x| arguments[x] == STDIN_KERNEL);
+    let strs = if stdin && !arguments.iter().any(|x| x == "--") {
+        let (positional, options) =
+            (0..arguments.len()).partition::<Vec<usize>, _>(|x| positions.contains(x));
+        options
+            .iter()
+            .map(|&x| arguments[x].as_str())
+            .chain(["--"])
+            .chain(positional.iter().map(|&x| arguments[x].as_str()))
+            .collect::<Vec<&str>>()
+    } else {
+        arguments.iter().map(String::as_str).collect::<Vec<&str>>()
+    };
+    match Arguments::from_args(&[&command], &strs) {
+        Ok(args) => (args, arguments),
+        Err(argh::EarlyExit { output, status }) => match status {
+            Ok(()) => {
+                println!("{}", output);
+                process::exit(0)
+            }
+            Err(()) => {
+                eprintln!("{}\nRun {} --help for more information.", output, command);
+                process::exit(1)
+            }
+        },
+    }
+}
+
+fn format_transpose(trans: CBLAS_TRANSPOSE) -> &'static str {
+    match trans {
+        CBLAS_TRANSPOSE::CblasTrans => "T",
+        CBLAS_TRANSPOSE::CblasConjTrans => "C",
+        _ => "N",
+    }
+}
+
+/// Every option after merging defaults, config file and command line, in the form `--config` reads back.
+fn effective_config(args: &Arguments) -> toml::Table {
+    fn string(value: impl ToString) -> toml::Value {
+        toml::Value::String(value.to_string())
+    }
+    fn pairs(values: &[(String, String)]) -> toml::Value {
+        toml::Value::Array(
+            values
+                .iter()
+                .map(|(k, v)| string(format!("{}={}", k, v)))
+                .collect(),
+        )
+    }
+    fn duration(value: time::Duration) -> toml::Value {
+        string(format!("{}ns", value.as_nanos()))
+    }
+    fn range(values: &[usize]) -> toml::Value {
+        string(
+            values
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<String>>()
+                .join(","),
+        )
+    }
+    fn scalars(values: &[f64]) -> toml::Value {
+        string(
+            values
+                .iter()
+                .map(f64::to_string)
+                .collect::<Vec<String>>()
+                .join(","),
+        )
+    }
+
+    let mut table = toml::Table::new();
+    let mut insert = |key: &str, value: Option<toml::Value>| {
+        if let Some(value) = value {
+            table.insert(key.to_string(), value);
+        }
+    };
+    insert("kernel", Some(string(&args.kernel)));
+    insert("out", args.out.as_ref().map(string));
+    insert("name", args.name.as_ref().map(string));
+    insert("tag", Some(args.tag.clone().into()));
+    insert("meta", Some(pairs(&args.meta)));
+    insert("save-as", args.save_as.as_ref().map(string));
+    insert("save-history-as", args.save_history_as.as_ref().map(string));
+    insert("bundle", args.bundle.as_ref().map(string));
+    insert("post-to", args.post_to.as_ref().map(string));
+    insert("post-token", args.post_token.as_ref().map(string));
+    insert("post-gzip", Some(args.post_gzip.into()));
+    insert("post-retries", Some((args.post_retries as i64).into()));
+    insert("spool-dir", args.spool_dir.as_ref().map(string));
+    insert("status-fd", args.status_fd.map(|x| (x as i64).into()));
+    insert("status-socket", args.status_socket.as_ref().map(string));
+    insert("verbose", Some((args.verbose as i64).into()));
+    insert("units", Some(string(args.units.suffix())));
+    insert("flops-units", Some(string(args.flops_units.name())));
+    insert("show", Some(string(args.show.name())));
+    insert("on-complete", args.on_complete.as_ref().map(string));
+    insert("on-regression", args.on_regression.as_ref().map(string));
+    insert("baseline", args.baseline.as_ref().map(string));
+    insert(
+        "regression-threshold",
+        Some(args.regression_threshold.into()),
+    );
+    insert(
+        "baseline-mismatch",
+        Some(string(args.baseline_mismatch.name())),
+    );
+    insert(
+        "expect-gflops-min",
+        args.expect_gflops_min.map(|x| x.into()),
+    );
+    insert(
+        "expect-max-deviation",
+        args.expect_max_deviation.map(|x| x.into()),
+    );
+    insert("anomaly-mads", Some(args.anomaly_mads.into()));
+    insert("noise-samples", Some((args.noise_samples as i64).into()));
+    insert("no-lock", Some(args.no_lock.into()));
+    insert("lock-file", Some(string(&args.lock_file)));
+    insert("memory-bandwidth", args.memory_bandwidth.map(|x| x.into()));
+    insert(
+        "compile",
+        args.compile
+            .map(|x| string(if x { "TRUE" } else { "FALSE" })),
+    );
+    insert(
+        "compile-commands",
+        args.compile_commands.as_ref().map(string),
+    );
+    insert("replay-build", args.replay_build.as_ref().map(string));
+    insert("kernel-env", Some(pairs(&args.kernel_env)));
+    insert("preload", Some(args.preload.clone().into()));
+    insert(
+        "dlopen-flags",
+        Some(string(loader::format_flags(args.dlopen_flags))).filter(|x| x.as_str() != Some("")),
+    );
+    insert("isolate", Some(args.isolate.into()));
+    insert(
+        "pin-cpus",
+        args.pin_cpus
+            .as_deref()
+            .map(|x| string(affinity::format_cpu_list(x))),
+    );
+    insert(
+        "membind",
+        args.membind
+            .as_deref()
+            .map(|x| string(affinity::format_cpu_list(x))),
+    );
+    insert("first-touch", args.first_touch.map(|x| (x as i64).into()));
+    insert(
+        "placement-manifest",
+        args.placement_manifest.as_ref().map(string),
+    );
+    insert("ld-from-file", args.ld_from_file.as_ref().map(string));
+    insert("scaling", args.scaling.map(|x| string(x.name())));
+    insert(
+        "per-core",
+        args.per_core
+            .as_deref()
+            .map(|x| string(affinity::format_cpu_list(x))),
+    );
+    insert("kernel-abi", args.kernel_abi.map(|x| (x as i64).into()));
+    insert("against", args.against.as_ref().map(string));
+    insert("significance", Some(args.significance.into()));
+    insert("compiler", Some(string(&args.compiler)));
+    insert(
+        "compiler-profile",
+        args.compiler_profile.map(|x| {
+            string(match x {
+                compiler::Profile::Gnu => "GNU",
+                compiler::Profile::Msvc => "MSVC",
+            })
+        }),
+    );
+    insert("sanitize", args.sanitize.map(|x| string(x.name())));
+    insert("emit-asm", Some(args.emit_asm.into()));
+    insert("disassemble", Some(args.disassemble.into()));
+    insert("objdump", Some(string(&args.objdump)));
+    insert("define", Some(pairs(&args.define)));
+    insert("compiler-args", args.compiler_args.as_ref().map(string));
+    insert(
+        "override-compiler-args",
+        Some(args.override_compiler_args.into()),
+    );
+    insert("warm-up", Some((args.warm_up as i64).into()));
+    insert("repeats", Some((args.repeats as i64).into()));
+    insert("prewarm-threads", Some(args.prewarm_threads.into()));
+    insert("sessions", Some((args.sessions as i64).into()));
+    insert("session-gap", Some(duration(args.session_gap)));
+    insert(
+        "jitter",
+        args.jitter.map(|x| {
+            string(format!(
+                "{}ns-{}ns",
+                x.min.as_nanos() as u64,
+                x.max.as_nanos() as u64
+            ))
+        }),
+    );
+    insert("skip-verification", Some(args.skip_verification.into()));
+    insert(
+        "verify-sample",
+        args.verify_sample
+            .map(|x| string(format!("{}%", x * 100.0))),
+    );
+    insert("extended-residual", Some(args.extended_residual.into()));
+    insert(
+        "verify-with",
+        args.verify_with.as_ref().map(|x| {
+            string(match x {
+                verifier::Kind::VendorBlas => "vendor-blas".to_string(),
+                verifier::Kind::PureRust => "pure-rust".to_string(),
+                verifier::Kind::Sampled(fraction) => format!("sampled={}%", fraction * 100.0),
+                verifier::Kind::FileCompare(file) => format!("file-compare={}", file),
+                verifier::Kind::None => "none".to_string(),
+            })
+        }),
+    );
+    insert("max-residual", args.max_residual.map(|x| x.into()));
+    insert("emit-reproducer", args.emit_reproducer.as_ref().map(string));
+    insert("protect-inputs", Some(args.protect_inputs.into()));
+    insert(
+        "layout",
+        Some(string(if args.layout == CBLAS_LAYOUT::CblasColMajor {
+            "COL"
+        } else {
+            "ROW"
+        })),
+    );
+    insert("trans-a", Some(string(format_transpose(args.trans_a))));
+    insert("trans-b", Some(string(format_transpose(args.trans_b))));
+    insert("m", Some((args.m as i64).into()));
+    insert("n", Some((args.n as i64).into()));
+    insert("k", Some((args.k as i64).into()));
+    insert("m-range", args.m_range.as_deref().map(range));
+    insert("n-range", args.n_range.as_deref().map(range));
+    insert("k-range", args.k_range.as_deref().map(range));
+    insert("alpha-sweep", args.alpha_sweep.as_deref().map(scalars));
+    insert("beta-sweep", args.beta_sweep.as_deref().map(scalars));
+    insert("preset", args.preset.map(|x| string(x.name)));
+    insert(
+        "prune-slower-than",
+        args.prune_slower_than.map(|x| x.into()),
+    );
+    insert("prune-after", Some((args.prune_after as i64).into()));
+    insert("batch", args.batch.as_ref().map(string));
+    insert(
+        "strided-batch",
+        args.strided_batch.map(|x| (x as i64).into()),
+    );
+    insert("stride-a", args.stride_a.map(|x| (x as i64).into()));
+    insert("stride-b", args.stride_b.map(|x| (x as i64).into()));
+    insert("stride-c", args.stride_c.map(|x| (x as i64).into()));
+    insert("packed", Some(args.packed.into()));
+    insert("reuse-b", args.reuse_b.map(|x| (x as i64).into()));
+    insert("conv", args.conv.map(string));
+    insert("flops-formula", args.flops_formula.as_ref().map(string));
+    insert("bytes-formula", args.bytes_formula.as_ref().map(string));
+    insert("calibrate-each", Some(args.calibrate_each.into()));
+    insert("confirm-above", Some(duration(args.confirm_above)));
+    insert("alpha", Some(args.alpha.into()));
+    insert("beta", Some(args.beta.into()));
+    table
+}
+
+/// Options that are replaced per point when sweeping.
+const SWEEP_OPTIONS: &[&str] = &[
+    "-m",
+    "--m",
+    "-n",
+    "--n",
+    "-k",
+    "--k",
+    "--square",
+    "--m-range",
+    "--n-range",
+    "--k-range",
+    "--alpha-sweep",
+    "--beta-sweep",
+    "--preset",
+    "--prune-slower-than",
+    "--save-as",
+    "--save-history-as",
+    "--bundle",
+    "--confirm-above",
+    "--config",
+];
+const SWEEP_SWITCHES: &[&str] = &["--calibrate-each", "--yes", "-y"];
+
+fn run_sweep(args: &Arguments, arguments: &[String]) {
+    let points = match args.preset {
+        Some(preset) => preset.shapes.to_vec(),
+        None => sweep::points(
+            args.m_range.as_deref().unwrap_or(&[args.m]),
+            args.n_range.as_deref().unwrap_or(&[args.n]),
+            args.k_range.as_deref().unwrap_or(&[args.k]),
+        ),
+    };
+    // every point runs with every swept alpha and beta, or the single one of the command line.
+    let alphas = args.alpha_sweep.clone().unwrap_or_else(|| vec![args.alpha]);
+    let betas = args.beta_sweep.clone().unwrap_or_else(|| vec![args.beta]);
+    let points = points
+        .into_iter()
+        .flat_map(|point| alphas.iter().map(move |&alpha| (point, alpha)))
+        .flat_map(|(point, alpha)| betas.iter().map(move |&beta| (point, alpha, beta)))
+        .collect::<Vec<_>>();
+    // entries get the merged arguments, so neither the environment nor a config file
+    // may bring back the stripped ranges.
+    let base = sweep::strip_options(arguments, SWEEP_OPTIONS, SWEEP_SWITCHES);
+    let base = sweep::strip_options(&base, &["--alpha", "--beta"], &[]);
+    env::set_var(ENV_MERGED, "1");
+
+    // pruning compares every entry against the best so far, so it needs their reports.
+    let temp = env::temp_dir().join(format!("benchmark-dgemm-sweep-{}", process::id()));
+    let save_as = args.save_as.clone().or_else(|| {
+        args.prune_slower_than
+            .map(|_| temp.join("report.json").to_string_lossy().to_string())
+    });
+
+    let entries = points
+        .iter()
+        .map(|((m, n, k), alpha, beta)| {
+            let mut point = format!("{}x{}x{}", m, n, k);
+            if args.alpha_sweep.is_some() {
+                point.push_str(&format!("-alpha{}", alpha));
+            }
+            if args.beta_sweep.is_some() {
+                point.push_str(&format!("-beta{}", beta));
+            }
+            let mut arguments = base.clone();
+            arguments.extend([
+                "-m".to_string(),
+                m.to_string(),
+                "-n".to_string(),
+                n.to_string(),
+                "-k".to_string(),
+                k.to_string(),
+                "--alpha".to_string(),
+                alpha.to_string(),
+                "--beta".to_string(),
+                beta.to_string(),
+            ]);
+            // reports of a preset are recognizable as such when compared.
+            if let Some(preset) = args.preset {
+                arguments.push("--meta".to_string());
+                arguments.push(format!("preset={}", preset.name));
+            }
+            for (option, file) in [
+                ("--save-as", &save_as),
+                ("--save-history-as", &args.save_history_as),
+                ("--bundle", &args.bundle),
+            ] {
+                if let Some(file) = file {
+                    arguments.push(option.to_string());
+                    arguments.push(sweep::suffixed(file, &point));
+                }
+            }
+            arguments
+        })
+        .collect::<Vec<Vec<String>>>();
+    if save_as != args.save_as {
+        fs::create_dir_all(&temp).expect("Error: failed to create temporary directory");
+    }
+    preflight(&entries, args.calibrate_each, args.confirm_above, args.yes);
+    let mut tracker = prune::Tracker::default();
+    let failed = run_entries(&entries, |arguments| {
+        let Some(fraction) = args.prune_slower_than else {
+            return;
+        };
+        let report = parse_entry(arguments)
+            .save_as
+            .and_then(|x| fs::File::open(x).ok())
+            .and_then(|x| serde_json::from_reader::<fs::File, common::Report>(x).ok());
+        match report {
+            Some(report) => complete_pruning(&mut tracker, &report, fraction),
+            None => eprintln!("Error: failed to read report of completed entry"),
+        }
+    });
+    if save_as != args.save_as {
+        drop(fs::remove_dir_all(&temp));
+    }
+    if tracker.pruned > 0 {
+        println!("Pruned {} of {} entries", tracker.pruned, entries.len());
+    }
+    if failed > 0 {
+        process::exit(1)
+    }
+}
+
+/// Run the benchmark once per configuration, labelled and with extra arguments, and collect the reports.
+/// `options` are stripped from the arguments first, like in a sweep.
+fn run_study(
+    args: &Arguments,
+    arguments: &[String],
+    options: &[&str],
+    configurations: &[(String, Vec<String>)],
+) -> Vec<Option<common::Report>> {
+    let base = sweep::strip_options(
+        arguments,
+        &[options, SWEEP_OPTIONS].concat(),
+        SWEEP_SWITCHES,
+    );
+    env::set_var(ENV_MERGED, "1");
+
+    let temp = env::temp_dir().join(format!("benchmark-dgemm-study-{}", process::id()));
+    let entries = configurations
+        .iter()
+        .map(|(label, extra)| {
+            let mut arguments = base.clone();
+            arguments.extend(extra.iter().cloned());
+            let save_as = args
+                .save_as
+                .clone()
+                .unwrap_or_else(|| temp.join("report.json").to_string_lossy().to_string());
+            for (option, file) in [
+                ("--save-as", &Some(save_as)),
+                ("--save-history-as", &args.save_history_as),
+                ("--bundle", &args.bundle),
+            ] {
+                if let Some(file) = file {
+                    arguments.push(option.to_string());
+                    arguments.push(sweep::suffixed(file, label));
+                }
+            }
+            arguments
+        })
+        .collect::<Vec<Vec<String>>>();
+    if args.save_as.is_none() {
+        fs::create_dir_all(&temp).expect("Error: failed to create temporary directory");
+    }
+    preflight(&entries, args.calibrate_each, args.confirm_above, args.yes);
+    let failed = run_entries(&entries, |_| {});
+
+    let reports = entries
+        .iter()
+        .map(|x| {
+            let file = fs::File::open(parse_entry(x).save_as?).ok()?;
+            serde_json::from_reader::<fs::File, common::Report>(file).ok()
+        })
+        .collect();
+    drop(fs::remove_dir_all(&temp));
+    if failed > 0 {
+        eprintln!("Error: {} of {} runs failed", failed, entries.len());
+    }
+    reports
+}
+
+fn run_per_core(args: &Arguments, arguments: &[String], cpus: &[usize]) {
+    let configurations = cpus
+        .iter()
+        .map(|cpu| {
+            (
+                format!("cpu{}", cpu),
+                vec![
+                    "--pin-cpus".to_string(),
+                    cpu.to_string(),
+                    "--kernel-env".to_string(),
+                    "OMP_NUM_THREADS=1".to_string(),
+                    "--kernel-env".to_string(),
+                    "MKL_NUM_THREADS=1".to_string(),
+                ],
+            )
+        })
+        .collect::<Vec<(String, Vec<String>)>>();
+    let reports = run_study(
+        args,
+        arguments,
+        &["--per-core", "--pin-cpus"],
+        &configurations,
+    );
+
+    let gflops = reports
+        .iter()
+        .map(|x| x.as_ref().map(hook::gflops))
+        .collect::<Vec<Option<f64>>>();
+    let best = gflops.iter().flatten().fold(0.0, |x: f64, &y| x.max(y));
+    println!("CPU\t {} \t Relative", args.flops_units.label());
+    for (cpu, gflops) in cpus.iter().zip(&gflops) {
+        match gflops {
+            Some(x) => println!(
+                "{}\t {:.3} \t {:.1}%",
+                cpu,
+                args.flops_units.of(*x),
+                x / best * 100.0
+            ),
+            None => println!("{}\t failed", cpu),
+        }
+    }
+    if gflops.iter().any(Option::is_none) {
+        process::exit(1)
+    }
+}
+
+fn run_scaling(args: &Arguments, arguments: &[String], domain: affinity::Domain) {
+    let units = domain.units();
+    if units.len() < 2 {
+        eprintln!(
+            "Error: found {} {} unit(s), nothing to scale over",
+            units.len(),
+            domain.name()
+        );
+        process::exit(1)
+    }
+
+    let mut cpus = Vec::new();
+    let mut nodes = Vec::new();
+    let mut threads = Vec::new();
+    let configurations = units
+        .iter()
+        .enumerate()
+        .map(|(index, unit)| {
+            cpus.extend(unit.0.iter().copied());
+            nodes.extend(unit.1.iter().copied());
+            nodes.sort_unstable();
+            nodes.dedup();
+            threads.push(cpus.len());
+            let mut extra = vec![
+                "--pin-cpus".to_string(),
+                affinity::format_cpu_list(&cpus),
+                "--kernel-env".to_string(),
+                format!("OMP_NUM_THREADS={}", cpus.len()),
+                "--kernel-env".to_string(),
+                format!("MKL_NUM_THREADS={}", cpus.len()),
+            ];
+            if !nodes.is_empty() {
+                extra.extend(["--membind".to_string(), affinity::format_cpu_list(&nodes)]);
+            }
+            (format!("{}{}", domain.name(), index + 1), extra)
+        })
+        .collect::<Vec<(String, Vec<String>)>>();
+    let reports = run_study(
+        args,
+        arguments,
+        &["--scaling", "--pin-cpus", "--membind"],
+        &configurations,
+    );
+
+    let gflops = reports
+        .iter()
+        .map(|x| x.as_ref().map(hook::gflops))
+        .collect::<Vec<Option<f64>>>();
+    println!(
+        "Units\t Threads\t {} \t Speedup\t Efficiency",
+        args.flops_units.label()
+    );
+    for (index, (current, threads)) in gflops.iter().zip(&threads).enumerate() {
+        let units = index + 1;
+        match (current, gflops[0]) {
+            (Some(x), Some(base)) => println!(
+                "{}\t {}\t {:.3} \t {:.2}x\t {:.1}%",
+                units,
+                threads,
+                args.flops_units.of(*x),
+                x / base,
+                x / base / units as f64 * 100.0
+            ),
+            (Some(x), None) => println!("{}\t {}\t {:.3}", units, threads, args.flops_units.of(*x)),
+            (None, _) => println!("{}\t {}\t failed", units, threads),
+        }
+    }
+    if gflops.iter().any(Option::is_none) {
+        process::exit(1)
+    }
+}
+
+fn check_args(args: &Arguments) {
+    if args.repeats == 0 {
+        eprintln!("Error: repeats should be signed integer that is not 0");
+        process::exit(1)
+    }
+    if args.sessions == 0 {
+        eprintln!("Error: sessions should be signed integer that is not 0");
+        process::exit(1)
+    }
+    if let Err(e) = utils::element_counts((args.m, args.n, args.k)) {
+        eprintln!("Error: {}", e);
+        process::exit(1)
+    }
+    if args.on_regression.is_some() && args.baseline.is_none() {
+        eprintln!("Error: --on-regression requires --baseline");
+        process::exit(1)
+    }
+    for (option, template) in [
+        ("--on-complete", &args.on_complete),
+        ("--on-regression", &args.on_regression),
+    ] {
+        if let Some(Err(e)) = template
+            .as_ref()
+            .map(|x| hook::check(x, args.baseline.is_some()))
+        {
+            eprintln!("Error: {}: {}", option, e);
+            process::exit(1)
+        }
+    }
+}
+
+/// Set in the re-executed process to the preload value of the original process.
+const ENV_KERNEL_RUN: &str = "BENCHMARK_DGEMM_KERNEL_RUN";
+
+#[cfg(target_os = "macos")]
+const ENV_PRELOAD: &str = "DYLD_INSERT_LIBRARIES";
+#[cfg(not(target_os = "macos"))]
+const ENV_PRELOAD: &str = "LD_PRELOAD";
+
+/// Set in the re-executed process that runs the sanitized build.
+const ENV_SANITIZE_RUN: &str = "BENCHMARK_DGEMM_SANITIZE_RUN";
+
+/// This process re-executed with the kernel environment and `runtime` preloaded before
+/// everything else.
+fn kernel_env_command(args: &Arguments, runtime: Option<String>) -> process::Command {
+    let original = env::var(ENV_PRELOAD).unwrap_or_default();
+    let mut command =
+        process::Command::new(env::current_exe().expect("Error: failed to locate executable"));
+    command.args(env::args_os().skip(1));
+    command.envs(args.kernel_env.iter().map(|(key, value)| (key, value)));
+    let mut preload = runtime.into_iter().collect::<Vec<String>>();
+    preload.extend(args.preload.iter().cloned());
+    if !preload.is_empty() {
+        if !original.is_empty() {
+            preload.push(original.clone());
+        }
+        command.env(ENV_PRELOAD, preload.join(":"));
+    }
+    command.env(ENV_KERNEL_RUN, original);
+    command
+}
+
+/// Kernel path that reads the source from stdin, e.g. `gen.py | benchmark - -m 4096`.
+const STDIN_KERNEL: &str = "-";
+
+/// Indices of the positional arguments among `arguments`, the kernel path first; every option
+/// but a switch takes the argument after it.
+fn positionals<T: AsRef<str>>(arguments: &[T]) -> Vec<usize> {
+    let switch = |x: &str| {
+        let long = SHORT_OPTIONS
+            .iter()
+            .find(|(short, _)| *short == x)
+            .map_or(x, |(_, long)| *long);
+        // -vv is one argument of repeated -v.
+        SWITCHES.contains(&long)
+            || x.strip_prefix('-')
+                .is_some_and(|v| !v.is_empty() && v.chars().all(|x| x == 'v'))
+    };
+    let mut positionals = Vec::new();
+    let mut index = 0;
+    while let Some(x) = arguments.get(index).map(AsRef::as_ref) {
+        if x == "--" {
+            positionals.extend(index + 1..arguments.len());
+            break;
+        }
+        if x == STDIN_KERNEL || !x.starts_with('-') {
+            positionals.push(index);
+            index += 1;
+        } else {
+            index += if switch(x) { 1 } else { 2 };
+        }
+    }
+    positionals
+}
+
+/// Write the kernel source on stdin to a temporary file and run this process again on it, so
+/// sweep entries and re-executed runs compile it as well; the file is removed once that run
+/// and every run it started are done.
+fn run_stdin_kernel() -> ! {
+    let mut source = String::new();
+    io::stdin().read_to_string(&mut source).unwrap_or_else(|e| {
+        eprintln!("Error: failed to read kernel source from stdin: {}", e);
+        process::exit(1)
+    });
+    if source.trim().is_empty() {
+        eprintln!("Error: no kernel source on stdin");
+        process::exit(1)
+    }
+    let dir = env::temp_dir().join(format!("benchmark-dgemm-stdin-{}", process::id()));
+    let file = dir.join("stdin.c");
+    fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(&file, source))
+        .unwrap_or_else(|e| {
+            eprintln!("Error: failed to write kernel source: {}", e);
+            process::exit(1)
+        });
+
+    // only the kernel path, as an option such as --name may have - as its value.
+    let mut arguments = env::args_os().skip(1).collect::<Vec<_>>();
+    let strs = arguments
+        .iter()
+        .map(|x| x.to_string_lossy())
+        .collect::<Vec<_>>();
+    if let Some(&index) = positionals(&strs).first() {
+        arguments[index] = file.into_os_string();
+    }
+    let status =
+        process::Command::new(env::current_exe().expect("Error: failed to locate executable"))
+            .args(arguments)
+            .status();
+    drop(fs::remove_dir_all(&dir));
+    let status = status.unwrap_or_else(|e| {
+        eprintln!(
+            "Error: failed to run benchmark on the kernel from stdin: {}",
+            e
+        );
+        process::exit(1)
+    });
+    process::exit(status.code().unwrap_or(1))
+}
+
+/// Environment variables such as OMP_PROC_BIND and LD_PRELOAD are only honored at process start,
+/// so re-execute the benchmark as a child process with them applied.
+fn respawn_with_kernel_env(args: &Arguments) -> ! {
+    let status = kernel_env_command(args, None)
+        .status()
+        .expect("Error: failed to run benchmark with kernel environment");
+    process::exit(status.code().unwrap_or(1))
+}
+
+/// Run the sanitized build in a child process, since a sanitizer error aborts it,
+/// and print the reports the sanitizer logged.
+fn respawn_sanitized(args: &Arguments, sanitizer: compiler::Sanitizer) -> ! {
+    let runtime = match sanitizer {
+        compiler::Sanitizer::Address => {
+            Some(compiler::asan_runtime(&args.compiler).unwrap_or_else(|| {
+                eprintln!(
+                    "Error: AddressSanitizer runtime of {} not found",
+                    args.compiler
+                );
+                process::exit(1)
+            }))
+        }
+        compiler::Sanitizer::Undefined => None,
+    };
+    let name = format!("benchmark-dgemm-sanitizer-{}", process::id());
+    let log = env::temp_dir().join(&name);
+
+    let mut command = kernel_env_command(args, runtime);
+    command.env(ENV_SANITIZE_RUN, "1");
+    // the benchmark and BLAS libraries are not instrumented, so their leaks are noise.
+    let options = [
+        "log_path=",
+        log.to_string_lossy().as_ref(),
+        ":detect_leaks=0:print_stacktrace=1",
+    ]
+    .concat();
+    let variable = sanitizer.options_variable();
+    command.env(
+        variable,
+        match env::var(variable) {
+            Ok(x) if !x.is_empty() => [options.as_str(), ":", x.as_str()].concat(),
+            _ => options,
+        },
+    );
+    let status = command
+        .status()
+        .expect("Error: failed to run sanitized benchmark");
+
+    // the sanitizer appends the pid to log_path.
+    let mut reports = fs::read_dir(env::temp_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|x| {
+            x.file_name()
+                .to_string_lossy()
+                .starts_with(&[name.as_str(), "."].concat())
+        })
+        .map(|x| x.path())
+        .collect::<Vec<path::PathBuf>>();
+    reports.sort();
+    let report = reports
+        .iter()
+        .filter_map(|x| {
+            let report = fs::read_to_string(x).ok();
+            drop(fs::remove_file(x));
+            report
+        })
+        .collect::<String>();
+
+    if report.is_empty() {
+        process::exit(status.code().unwrap_or(1))
+    }
+    println!("Sanitizer report:");
+    print!("{}", report);
+    if let Some(save_as) = &args.save_as {
+        let file = path::Path::new(save_as).with_extension("sanitizer.log");
+        fs::write(&file, &report).expect("Error: failed to save sanitizer report");
+        println!("Sanitizer report saved to {}", file.display());
+    }
+    process::exit(1)
+}
+
+/// Verification failure of a kernel that left a NaN of `verification::poison` in C.
+const READ_C: &str =
+    "C holds NaN after a call with a beta of 0, so the kernel read C or left entries unwritten";
+
+/// What a session measured besides durations.
+#[derive(Default)]
+struct SessionOutcome {
+    records: Vec<common::Duration>,
+    openmp: Option<openmp::OpenMP>,
+    /// normalized verification residual
+    residual: Option<f64>,
+    /// fraction of the calibrated verification tolerance the difference used
+    tolerance_used: Option<f64>,
+    /// peak memory the first kernel call allocated
+    workspace: Option<usize>,
+    /// threads that ran during the first measured call
+    active_threads: Option<usize>,
+    /// time pack_a and pack_b took in packed mode
+    packing: Option<common::Packing>,
+    /// durations of the kernel's im2col with `--conv`, one per record
+    im2col: Vec<common::Duration>,
+    /// durations of the kernel of `--against`, paired with `records`
+    against: Vec<common::Duration>,
+    /// wall time of loading, generation, verification, warm-up and measurement
+    phases: common::Phases,
+    /// whether the measurement was abandoned by `pruner`
+    pruned: bool,
+    /// placement of A, B and C, described for `--placement-manifest` and `--ld-from-file`
+    buffers: Vec<placement::Buffer>,
+}
+
+/// Seconds since `clock`, which restarts for the next phase.
+fn lap(clock: &mut time::Instant) -> f64 {
+    let now = time::Instant::now();
+    let seconds = (now - *clock).as_secs_f64();
+    *clock = now;
+    seconds
+}
+
+/// Whether `pruner` abandons the run after `records`.
+fn should_prune(pruner: Option<&prune::Pruner>, records: &[common::Duration]) -> bool {
+    let Some(pruner) = pruner else {
+        return false;
+    };
+    let Some(gflops) = pruner.check(records) else {
+        return false;
+    };
+    println!(
+        "Pruned: running median of {:.3} GFLOPS is below {:.3} GFLOPS",
+        gflops, pruner.below
+    );
+    true
+}
+
+/// Verifier of `--verify-with`, or of the switches that select one.
+fn verifier_kind(args: &Arguments) -> verifier::Kind {
+    match (
+        &args.verify_with,
+        args.skip_verification,
+        args.verify_sample,
+        args.extended_residual,
+    ) {
+        (Some(kind), ..) => kind.clone(),
+        (None, true, _, _) => verifier::Kind::None,
+        (None, false, Some(fraction), _) => verifier::Kind::Sampled(fraction),
+        (None, false, None, true) => verifier::Kind::PureRust,
+        (None, false, None, false) => verifier::Kind::VendorBlas,
+    }
+}
+
+/// The call of `args` on these matrices, for a verifier.
+fn problem<'a>(
+    args: &Arguments,
+    a: (&'a [f64], usize),
+    b: (&'a [f64], usize),
+    c: (&'a [f64], usize),
+) -> verifier::Problem<'a> {
+    verifier::Problem {
+        layout: args.layout,
+        transpose: (args.trans_a, args.trans_b),
+        dimensions: (args.m, args.n, args.k),
+        alpha: args.alpha,
+        beta: args.beta,
+        a,
+        b,
+        c,
+    }
+}
+
+/// Whether sessions of `args` make a verification call.
+fn verifies(args: &Arguments) -> bool {
+    verifier_kind(args) != verifier::Kind::None
+}
+
+/// How the numbers of a run of `args` are obtained.
+fn describe_methodology(args: &Arguments) -> methodology::Methodology {
+    let verification = verifier_kind(args).methodology();
+    methodology::Methodology {
+        warm_up: args.warm_up,
+        session_gap: args.session_gap.into(),
+        jitter: args.jitter,
+        flush: methodology::Flush::None,
+        pinned: args.pin_cpus.is_some(),
+        timer: methodology::timer().to_string(),
+        verification,
+        max_residual: args.max_residual,
+        regeneration: methodology::Regeneration::PerSession,
+    }
+}
+
+/// Sleep for the `--jitter` pause before measured `iteration` of `session`.
+fn jitter(args: &Arguments, session: usize, iteration: usize) {
+    if let Some(jitter) = args.jitter.filter(|_| iteration > 0) {
+        thread::sleep(jitter.pause(session, iteration));
+    }
+}
+
+/// A, B and C of a session.
+type Matrices = (Box<[f64]>, Box<[f64]>, Box<[f64]>);
+
+/// A, B and a zeroed C of a session, placed by first touch with `--first-touch`. C is made
+/// resident, so the first kernel call's peak memory is only its workspace.
+fn generate_matrices(
+    args: &Arguments,
+    session: usize,
+    (lda, ldb, ldc): (usize, usize, usize),
+) -> Matrices {
+    let (m, n, k) = (args.m, args.n, args.k);
+    let seed = seed(session);
+    let Some(rows) = args.first_touch else {
+        let a = utils::fill_rand(m * k, seed + 100, 0.0, 2.0);
+        let b = utils::fill_rand(k * n, seed + 200, 0.0, 2.0);
+        let mut c = unsafe { utils::malloc::<f64>(m * n) };
+        c.fill(0.0);
+        return (a, b, c);
+    };
+    let placement = utils::Placement {
+        cpus: args.pin_cpus.clone().unwrap_or_else(affinity::available),
+        rows,
+    };
+    let place = |x: Result<Box<[f64]>, String>| {
+        x.unwrap_or_else(|e| {
+            eprintln!("Error: failed to place matrices: {}", e);
+            process::exit(1)
+        })
+    };
+    (
+        place(placement.fill_rand((m * k, lda), seed + 100, 0.0, 2.0)),
+        place(placement.fill_rand((k * n, ldb), seed + 200, 0.0, 2.0)),
+        place(placement.zeroed((m * n, ldc))),
+    )
+}
+
+/// Placement of the matrices of a session if `--placement-manifest` or `--ld-from-file` asks
+/// for it.
+fn describe_matrices(
+    args: &Arguments,
+    session: usize,
+    matrices: [(&str, &[f64], usize); 3],
+) -> Vec<placement::Buffer> {
+    if args.placement_manifest.is_none() && args.ld_from_file.is_none() {
+        return Vec::new();
+    }
+    matrices
+        .iter()
+        .map(|&(name, data, ld)| placement::Buffer::describe(session, name, data, ld))
+        .collect()
+}
+
+/// Write the C test of `--emit-reproducer` for the failed verification of `session`.
+fn emit_reproducer(
+    args: &Arguments,
+    library: &kernel::Library,
+    session: usize,
+    (a, b): (&[f64], &[f64]),
+    message: &str,
+) {
+    let Some(file) = &args.emit_reproducer else {
+        return;
+    };
+    let dimensions = (args.m, args.n, args.k);
+    let (m, n, k) = dimensions;
+    let leading_dimensions =
+        utils::leading_dimensions(args.layout, args.trans_a, args.trans_b, dimensions);
+    let (lda, ldb, _) = leading_dimensions;
+    // the test checks every entry, whichever verifier the run used.
+    let limit = match args.max_residual {
+        Some(max) => max / verification::normalized_residual(1.0, a, b, k),
+        None => {
+            let norms = (
+                verification::infinity_norm(args.layout, args.trans_a, lda, (m, k), a),
+                verification::infinity_norm(args.layout, args.trans_b, ldb, (k, n), b),
+            );
+            verification::tolerance(m * n, k, args.alpha, norms)
+        }
+    };
+    let failure = reproducer::Failure {
+        kernel: &args.kernel,
+        abi_version: library.version(args.kernel_abi).unwrap_or(abi::V1),
+        session,
+        seed: seed(session),
+        layout: args.layout,
+        transpose: (args.trans_a, args.trans_b),
+        dimensions,
+        leading_dimensions,
+        alpha: args.alpha,
+        beta: args.beta,
+        a,
+        b,
+        limit,
+        message: message.to_string(),
+    };
+    match fs::write(file, failure.c_test()) {
+        Ok(()) => eprintln!("Reproducer written to {}", file),
+        Err(e) => eprintln!("Error: failed to write reproducer: {}", e),
+    }
+}
+
+/// Seed of the matrices of `session`: every session runs on freshly generated ones.
+fn seed(session: usize) -> u64 {
+    session as u64 * 1000
+}
+
+/// Load the compiled object at `out`, or exit.
+fn load(args: &Arguments, out: &str) -> kernel::Library {
+    kernel::Library::open(out, args.dlopen_flags, args.isolate).unwrap_or_else(|e| {
+        eprintln!("Error: failed to load compiled object: {}", e);
+        process::exit(1)
+    })
+}
+
+/// Run `call`, iteration `iteration` of `phase`. Without verification, the first warm-up or
+/// measured call is the first of the session, so its workspace is probed into `workspace`.
+fn first_call<T>(
+    args: &Arguments,
+    (phase, iteration): (abi::Phase, usize),
+    workspace: &mut Option<usize>,
+    call: impl FnOnce() -> T,
+) -> T {
+    let first =
+        !verifies(args) && iteration == 0 && (phase == abi::Phase::WarmUp || args.warm_up == 0);
+    let probe = first.then(workspace::Probe::start);
+    let result = call();
+    if let Some(probe) = probe {
+        *workspace = probe.finish();
+    }
+    result
+}
+
+/// Run `call`, measured iteration `iteration`, with the threads active during it if it is the
+/// first one.
+fn probe_threads<T>(iteration: usize, call: impl FnOnce() -> T) -> (T, Option<usize>) {
+    // thread pools exist by now, unless this is the first call.
+    let threads = (iteration == 0).then(threads::Probe::start);
+    let result = call();
+    (result, threads.and_then(threads::Probe::finish))
+}
+
+/// Measure the iterations of a session until `pruner` abandons it. `call` runs one and returns
+/// its duration with the threads `probe_threads` found active. Returns the durations, the
+/// threads of the first iteration and whether the session was pruned.
+fn measure(
+    args: &Arguments,
+    session: usize,
+    pruner: Option<&prune::Pruner>,
+    openmp: Option<&openmp::OpenMP>,
+    mut call: impl FnMut(usize) -> (common::Duration, Option<usize>),
+) -> (Vec<common::Duration>, Option<usize>, bool) {
+    let mut records = Vec::with_capacity(args.repeats);
+    let mut active_threads = None;
+    for iteration in 0..args.repeats {
+        jitter(args, session, iteration);
+        let (duration, active) = call(iteration);
+        if let Some(active) = active {
+            println!("Active threads: {}", active);
+            if let Some(openmp) = openmp.filter(|x| active > x.max_threads as usize) {
+                eprintln!(
+                    "Warning: {} threads were active, but OpenMP allows {}",
+                    active, openmp.max_threads
+                );
+            }
+            active_threads = Some(active);
+        }
+        match args.reuse_b {
+            Some(reuse) => println!(
+                "Duration: {} ({} per product)",
+                args.units().time(duration),
+                args.units()
+                    .time_millis(duration.as_millis() / reuse as f64)
+            ),
+            None => println!("Duration: {}", args.units().time(duration)),
+        }
+        status::emit(status::Event::IterationCompleted {
+            session,
+            iteration,
+            repeats: args.repeats,
+            duration: duration.as_nanos() as u128,
+        });
+        records.push(duration);
+        if should_prune(pruner, &records) {
+            return (records, active_threads, true);
+        }
+    }
+    (records, active_threads, false)
+}
+
+/// Verifiers of `problems`, prepared on C before the verification call, each sampling its own
+/// entries from the seed of the session.
+fn prepare(
+    args: &Arguments,
+    session: usize,
+    problems: &[(usize, verifier::Problem)],
+) -> Vec<Box<dyn verifier::Verifier>> {
+    let kind = verifier_kind(args);
+    problems
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_, problem))| {
+            let mut verifier = kind.build(seed(session) + 400 + index as u64, session)?;
+            verifier.prepare(problem);
+            Some(verifier)
+        })
+        .collect()
+}
+
+/// Check C of `problems`, paired with their groups, with the `verifiers` prepared on them. Every
+/// problem gets a tolerance calibrated from its own A and B, and the largest residual and share
+/// of the tolerance used are returned. A wrong result exits after `wrong` got its description.
+fn verify(
+    args: &Arguments,
+    session: usize,
+    problems: &[(usize, verifier::Problem)],
+    verifiers: &mut [Box<dyn verifier::Verifier>],
+    wrong: impl FnOnce(&str),
+) -> (f64, f64) {
+    let mut largest = (0.0f64, 0.0f64, 0.0f64);
+    // tolerance of the problem that used the largest share of its own.
+    let mut tolerance = 0.0;
+    for (index, ((group, problem), verifier)) in problems.iter().zip(verifiers).enumerate() {
+        let (difference, entries) = verifier.check(problem).unwrap_or_else(|e| {
+            eprintln!("Error: verification failed: {}", e);
+            process::exit(1)
+        });
+        let (m, n, k) = problem.dimensions;
+        let normalized = verification::normalized_residual(difference, problem.a.0, problem.b.0, k);
+        let calibrated = verification::tolerance(entries, k, problem.alpha, problem.norms());
+        let used = verification::used(difference, calibrated);
+        let failed = match args.max_residual {
+            Some(max) => normalized.is_nan() || normalized > max,
+            None => used.is_nan() || used > 1.0,
+        };
+        if failed {
+            println!("Normalized residual: {:.3}", normalized);
+            println!("Tolerance: {:.3e}, {:.1}% used", calibrated, used * 100.0);
+            if problems.len() == 1 {
+                eprintln!("WRONG RESULT!");
+            } else {
+                eprintln!(
+                    "WRONG RESULT in problem {} (group {}, {}x{}x{})!",
+                    index, group, m, n, k
+                );
+            }
+            wrong(&format!(
+                "||C - C_expected|| is {:.3e}, {:.1}% of the tolerance, normalized residual {:.3}",
+                difference,
+                used * 100.0,
+                normalized
+            ));
+            process::exit(1)
+        }
+        if used >= largest.2 {
+            tolerance = calibrated;
+        }
+        largest = (
+            largest.0.max(difference),
+            largest.1.max(normalized),
+            largest.2.max(used),
+        );
+    }
+    let (difference, residual, used) = largest;
+    println!("Normalized residual: {:.3}", residual);
+    println!("Tolerance: {:.3e}, {:.1}% used", tolerance, used * 100.0);
+    status::emit(status::Event::VerificationPassed {
+        session,
+        difference,
+        residual,
+        tolerance_used: used,
+    });
+    (residual, used)
+}
+
+/// Load the kernel and benchmark it once on freshly generated matrices.
+fn run_session(
+    args: &Arguments,
+    out: &str,
+    session: usize,
+    pruner: Option<&prune::Pruner>,
+) -> SessionOutcome {
+    let mut phases = common::Phases::default();
+    let mut clock = time::Instant::now();
+    let library = load(args, out);
+    let kernel = library.kernel(args.kernel_abi).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1)
+    });
+    let openmp = library.openmp();
+    let other = args.against.as_ref().map(|against| {
+        kernel::Library::open(against, args.dlopen_flags, args.isolate).unwrap_or_else(|e| {
+            eprintln!("Error: failed to load {}: {}", against, e);
+            process::exit(1)
+        })
+    });
+    let other_kernel = other.as_ref().map(|x| {
+        x.kernel(args.kernel_abi).unwrap_or_else(|e| {
+            eprintln!("Error: --against: {}", e);
+            process::exit(1)
+        })
+    });
+
+    let dimensions = (args.m, args.n, args.k);
+    let (m, n, k) = dimensions;
+    let (trans_a, trans_b) = (args.trans_a, args.trans_b);
+
+    let (lda, ldb, ldc) = utils::leading_dimensions(args.layout, trans_a, trans_b, dimensions);
+
+    // spawn thread pools of the kernel before the first call, so it is not measured.
+    if args.prewarm_threads && !library.prewarm_threads() {
+        let dimensions = (m.min(64), n.min(64), k.min(64));
+        let (lda, ldb, ldc) = utils::leading_dimensions(args.layout, trans_a, trans_b, dimensions);
+        let a = utils::fill_rand(dimensions.0 * dimensions.2, 100, 0.0, 2.0);
+        let b = utils::fill_rand(dimensions.2 * dimensions.1, 200, 0.0, 2.0);
+        let mut c = utils::fill_rand(dimensions.0 * dimensions.1, 300, 0.0, 2.0);
+        kernel.run(
+            abi::Context::new(abi::Phase::Prewarm, session, 0),
             args.layout,
             trans_a,
             trans_b,
@@ -350,94 +3242,1349 @@ fn main() {
             args.alpha,
             args.beta,
         );
+    }
+
+    phases.load = lap(&mut clock);
+
+    let (a, mut b, mut c) = generate_matrices(args, session, (lda, ldb, ldc));
+
+    // a convolution's B is its input lowered by im2col, zeros of the padding included.
+    let input = args.conv.map(|shape| {
+        let input = utils::fill_rand(shape.input_size(), seed(session) + 300, 0.0, 2.0);
+        shape.im2col(&input, args.layout, trans_b, (&mut b, ldb));
+        (shape, input)
+    });
+    let lowering = match &input {
+        Some(_) => library.im2col(args.kernel_abi).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            process::exit(1)
+        }),
+        None => None,
+    };
+    // the kernel's im2col lowers into a buffer of its own, so B stays as verified.
+    let mut columns = vec![0.0; if lowering.is_some() { b.len() } else { 0 }];
+    let lower = |phase: abi::Phase, iteration: usize, columns: &mut [f64]| {
+        lowering
+            .as_ref()
+            .zip(input.as_ref())
+            .map(|(im2col, (shape, input))| {
+                im2col.run(
+                    abi::Context::new(phase, session, iteration),
+                    args.layout,
+                    trans_b,
+                    shape,
+                    input,
+                    (columns, ldb),
+                )
+            })
+    };
+    if lower(abi::Phase::Verification, 0, &mut columns).is_some() {
+        if let Some(i) = (0..b.len()).find(|&i| columns[i] != b[i]) {
+            eprintln!(
+                "Error: im2col is wrong: element {} of B is {}, expected {}",
+                i, columns[i], b[i]
+            );
+            process::exit(1)
+        }
+    }
+
+    // the kernel gets read-only copies, so a write to its inputs faults and is recorded.
+    let protected = args.protect_inputs.then(|| {
+        let protect = |data: &[f64], slot: usize| {
+            protect::ReadOnly::new(data, slot)
+                .and_then(|x| x.arm().map(|_| x))
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: failed to protect inputs: {}", e);
+                    process::exit(1)
+                })
+        };
+        (protect(&a, 0), protect(&b, 1))
+    });
+    let (a, b): (&[f64], &[f64]) = match &protected {
+        Some((a, b)) => (a.as_slice(), b.as_slice()),
+        None => (&a, &b),
+    };
+    let buffers = describe_matrices(
+        args,
+        session,
+        [("A", a, lda), ("B", b, ldb), ("C", &c, ldc)],
+    );
+    let check_inputs = |phase: &str| {
+        for (name, matrix) in protected.iter().flat_map(|(a, b)| [("A", a), ("B", b)]) {
+            if let Some(violation) = matrix.check() {
+                eprintln!(
+                    "Error: kernel wrote to {} during {}: {} page(s), first at {:#x} (element {})",
+                    name, phase, violation.pages, violation.address, violation.element
+                );
+                process::exit(1)
+            }
+        }
+    };
+    let call = |kernel: &kernel::Kernel, phase: abi::Phase, iteration: usize, c: &mut [f64]| {
+        kernel.run(
+            abi::Context::new(phase, session, iteration),
+            args.layout,
+            trans_a,
+            trans_b,
+            dimensions,
+            a,
+            lda,
+            b,
+            ldb,
+            c,
+            ldc,
+            args.alpha,
+            args.beta,
+        )
+    };
+    let run =
+        |phase: abi::Phase, iteration: usize, c: &mut [f64]| call(&kernel, phase, iteration, c);
+
+    phases.generation = lap(&mut clock);
+
+    let mut outcome = SessionOutcome {
+        openmp,
+        phases,
+        buffers,
+        ..Default::default()
+    };
+    if verifies(args) {
+        if args.beta == 0.0 {
+            verification::poison(&mut c);
+        }
+        let mut verifiers = prepare(
+            args,
+            session,
+            &[(0, problem(args, (a, lda), (b, ldb), (&c, ldc)))],
+        );
+        let probe = workspace::Probe::start();
+        run(abi::Phase::Verification, 0, &mut c);
+        outcome.workspace = probe.finish();
+        check_inputs("verification");
+        if args.beta == 0.0 && verification::poisoned(&c) {
+            eprintln!("Error: {}", READ_C);
+            emit_reproducer(args, &library, session, (a, b), READ_C);
+            process::exit(1)
+        }
+
+        let problems = [(0, problem(args, (a, lda), (b, ldb), (&c, ldc)))];
+        let (residual, used) = verify(args, session, &problems, &mut verifiers, |message| {
+            emit_reproducer(args, &library, session, (a, b), message)
+        });
+        outcome.residual = Some(residual);
+        outcome.tolerance_used = Some(used);
+    }
+    outcome.phases.verification = lap(&mut clock);
+    if args.sanitize.is_some() {
+        drop(library.close());
+        return outcome;
+    }
+
+    for iteration in 0..args.warm_up {
+        lower(abi::Phase::WarmUp, iteration, &mut columns);
+        first_call(
+            args,
+            (abi::Phase::WarmUp, iteration),
+            &mut outcome.workspace,
+            || run(abi::Phase::WarmUp, iteration, &mut c),
+        );
+    }
+    // the kernel of --against runs on the same A, B and C, and warms up as well.
+    let run_other = |phase: abi::Phase, iteration: usize, c: &mut [f64]| {
+        other_kernel
+            .as_ref()
+            .map(|kernel| call(kernel, phase, iteration, c))
+    };
+    for iteration in 0..args.warm_up {
+        run_other(abi::Phase::WarmUp, iteration, &mut c);
+    }
+    check_inputs("warm-up");
+    outcome.phases.warm_up = lap(&mut clock);
+
+    let (records, active_threads, pruned) = measure(
+        args,
+        session,
+        pruner,
+        outcome.openmp.as_ref(),
+        |iteration| {
+            // the kernels take turns going first, so neither always finds the caches and clocks
+            // the other left behind.
+            let other_first = iteration % 2 == 1;
+            if other_first {
+                outcome
+                    .against
+                    .extend(run_other(abi::Phase::Measurement, iteration, &mut c));
+            }
+            let measured = probe_threads(iteration, || {
+                outcome
+                    .im2col
+                    .extend(lower(abi::Phase::Measurement, iteration, &mut columns));
+                first_call(
+                    args,
+                    (abi::Phase::Measurement, iteration),
+                    &mut outcome.workspace,
+                    || run(abi::Phase::Measurement, iteration, &mut c),
+                )
+            });
+            if !other_first {
+                outcome
+                    .against
+                    .extend(run_other(abi::Phase::Measurement, iteration, &mut c));
+            }
+            measured
+        },
+    );
+    check_inputs("measurement");
+    outcome.phases.measurement = lap(&mut clock);
+    if let Some(other) = other {
+        drop(other.close());
+    }
+    drop(library.close());
+    SessionOutcome {
+        records,
+        active_threads,
+        pruned,
+        ..outcome
+    }
+}
+
+/// `run_session` for a batch: every problem is verified separately.
+fn run_batch_session(
+    args: &Arguments,
+    out: &str,
+    session: usize,
+    groups: &[batch::Group],
+    strides: Option<batch::Strides>,
+    pruner: Option<&prune::Pruner>,
+) -> SessionOutcome {
+    let mut phases = common::Phases::default();
+    let mut clock = time::Instant::now();
+    let library = load(args, out);
+    let kernel = library
+        .batch_kernel(args.kernel_abi, strides.is_some())
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            process::exit(1)
+        });
+    let openmp = library.openmp();
+    phases.load = lap(&mut clock);
+
+    let mut operands = match strides {
+        Some(strides) => batch::Operands::strided(args.layout, &groups[0], strides, seed(session)),
+        None => batch::Operands::new(args.layout, groups, seed(session)),
+    };
+    let run = |phase: abi::Phase, iteration: usize, operands: &batch::Operands| {
+        kernel.run(
+            abi::Context::new(phase, session, iteration),
+            args.layout,
+            operands,
+        )
+    };
+
+    phases.generation = lap(&mut clock);
+
+    let mut outcome = SessionOutcome {
+        openmp,
+        phases,
+        ..Default::default()
+    };
+    if verifies(args) {
+        operands.poison(args.layout);
+        let mut verifiers = prepare(args, session, &operands.problems(args.layout));
+        let probe = workspace::Probe::start();
+        run(abi::Phase::Verification, 0, &operands);
+        outcome.workspace = probe.finish();
+        if let Some(group) = operands.poisoned(args.layout) {
+            eprintln!("Error: {} (group {})", READ_C, group);
+            process::exit(1)
+        }
+
+        let problems = operands.problems(args.layout);
+        let (residual, used) = verify(args, session, &problems, &mut verifiers, |_| ());
+        outcome.residual = Some(residual);
+        outcome.tolerance_used = Some(used);
+    }
+    outcome.phases.verification = lap(&mut clock);
+    if args.sanitize.is_some() {
+        drop(library.close());
+        return outcome;
+    }
+
+    for iteration in 0..args.warm_up {
+        first_call(
+            args,
+            (abi::Phase::WarmUp, iteration),
+            &mut outcome.workspace,
+            || run(abi::Phase::WarmUp, iteration, &operands),
+        );
+    }
+    outcome.phases.warm_up = lap(&mut clock);
+
+    let (records, active_threads, pruned) = measure(
+        args,
+        session,
+        pruner,
+        outcome.openmp.as_ref(),
+        |iteration| {
+            probe_threads(iteration, || {
+                first_call(
+                    args,
+                    (abi::Phase::Measurement, iteration),
+                    &mut outcome.workspace,
+                    || run(abi::Phase::Measurement, iteration, &operands),
+                )
+            })
+        },
+    );
+    outcome.phases.measurement = lap(&mut clock);
+    drop(library.close());
+    SessionOutcome {
+        records,
+        active_threads,
+        pruned,
+        ..outcome
+    }
+}
+
+/// `run_session` for a packed kernel: A and B are packed once, and only `compute_packed` is
+/// measured.
+fn run_packed_session(
+    args: &Arguments,
+    out: &str,
+    session: usize,
+    pruner: Option<&prune::Pruner>,
+) -> SessionOutcome {
+    let mut phases = common::Phases::default();
+    let mut clock = time::Instant::now();
+    let library = load(args, out);
+    let kernel = library.packed_kernel(args.kernel_abi).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1)
+    });
+    let openmp = library.openmp();
+    phases.load = lap(&mut clock);
+
+    let dimensions = (args.m, args.n, args.k);
+    let (trans_a, trans_b) = (args.trans_a, args.trans_b);
+    let (lda, ldb, ldc) = utils::leading_dimensions(args.layout, trans_a, trans_b, dimensions);
+
+    let (a, b, mut c) = generate_matrices(args, session, (lda, ldb, ldc));
+    let buffers = describe_matrices(
+        args,
+        session,
+        [("A", &a, lda), ("B", &b, ldb), ("C", &c, ldc)],
+    );
+
+    let pack = |phase: abi::Phase| {
+        let context = abi::Context::new(phase, session, 0);
+        let (packed_a, a_duration) = kernel.pack(
+            context,
+            args.layout,
+            trans_a,
+            dimensions,
+            args.alpha,
+            (&a, lda),
+            false,
+        );
+        let (packed_b, b_duration) = kernel.pack(
+            context,
+            args.layout,
+            trans_b,
+            dimensions,
+            1.0,
+            (&b, ldb),
+            true,
+        );
+        if packed_a.is_null() || packed_b.is_null() {
+            eprintln!("Error: pack_a or pack_b returned a null pointer");
+            process::exit(1)
+        }
+        ((packed_a, packed_b), (a_duration, b_duration))
+    };
+
+    phases.generation = lap(&mut clock);
+
+    let mut outcome = SessionOutcome {
+        openmp,
+        phases,
+        buffers,
+        ..Default::default()
+    };
+    if verifies(args) {
+        if args.beta == 0.0 {
+            verification::poison(&mut c);
+        }
+        let mut verifiers = prepare(
+            args,
+            session,
+            &[(0, problem(args, (&a, lda), (&b, ldb), (&c, ldc)))],
+        );
+        let probe = workspace::Probe::start();
+        let (packed, _) = pack(abi::Phase::Verification);
+        kernel.compute(
+            abi::Context::new(abi::Phase::Verification, session, 0),
+            args.layout,
+            dimensions,
+            (&packed.0, &packed.1),
+            args.beta,
+            &mut c,
+            ldc,
+        );
+        outcome.workspace = probe.finish();
+        drop(packed);
+        if args.beta == 0.0 && verification::poisoned(&c) {
+            eprintln!("Error: {}", READ_C);
+            process::exit(1)
+        }
+
+        // the packed layout is the kernel's own, so the reference uses A and B as generated.
+        let problems = [(0, problem(args, (&a, lda), (&b, ldb), (&c, ldc)))];
+        let (residual, used) = verify(args, session, &problems, &mut verifiers, |_| ());
+        outcome.residual = Some(residual);
+        outcome.tolerance_used = Some(used);
+    }
+    outcome.phases.verification = lap(&mut clock);
+    if args.sanitize.is_some() {
+        drop(library.close());
+        return outcome;
+    }
+
+    // with --reuse-b, every iteration packs B once and runs that many products on it, each
+    // packing its own A, as when weights are reused across the activations of many requests.
+    let probe = (!verifies(args)).then(workspace::Probe::start);
+    let (packed, mut packing) = match args.reuse_b {
+        Some(_) => (None, Vec::new()),
+        None => {
+            let (packed, packing) = pack(abi::Phase::Measurement);
+            println!(
+                "Packing: A {}, B {}",
+                args.units().time(packing.0),
+                args.units().time(packing.1)
+            );
+            (Some(packed), vec![packing])
+        }
+    };
+    if let Some(probe) = probe.filter(|_| packed.is_some()) {
+        outcome.workspace = probe.finish();
+    }
+
+    let mut run = |phase: abi::Phase, iteration: usize| {
+        let Some(reuse) = args.reuse_b else {
+            let packed = packed.as_ref().unwrap();
+            return kernel.compute(
+                abi::Context::new(phase, session, iteration),
+                args.layout,
+                dimensions,
+                (&packed.0, &packed.1),
+                args.beta,
+                &mut c,
+                ldc,
+            );
+        };
+        let start_time = time::Instant::now();
+        let (packed_b, b_duration) = kernel.pack(
+            abi::Context::new(phase, session, iteration),
+            args.layout,
+            trans_b,
+            dimensions,
+            1.0,
+            (&b, ldb),
+            true,
+        );
+        let mut a_duration = common::Duration::ZERO;
+        for call in 0..reuse {
+            let context = abi::Context::new(phase, session, iteration * reuse + call);
+            let (packed_a, duration) = kernel.pack(
+                context,
+                args.layout,
+                trans_a,
+                dimensions,
+                args.alpha,
+                (&a, lda),
+                false,
+            );
+            a_duration = a_duration + duration;
+            kernel.compute(
+                context,
+                args.layout,
+                dimensions,
+                (&packed_a, &packed_b),
+                args.beta,
+                &mut c,
+                ldc,
+            );
+            drop(packed_a);
+        }
+        drop(packed_b);
+        let end_time = time::Instant::now();
+        if phase == abi::Phase::Measurement {
+            packing.push((a_duration / reuse as f64, b_duration));
+        }
+        common::Duration::from(end_time - start_time)
+    };
+
+    for iteration in 0..args.warm_up {
+        run(abi::Phase::WarmUp, iteration);
+    }
+
+    outcome.phases.warm_up = lap(&mut clock);
+
+    let (records, active_threads, pruned) = measure(
+        args,
+        session,
+        pruner,
+        outcome.openmp.as_ref(),
+        |iteration| probe_threads(iteration, || run(abi::Phase::Measurement, iteration)),
+    );
+    outcome.phases.measurement = lap(&mut clock);
+    // packed buffers are freed by the library, so they go first.
+    drop(packed);
+    drop(library.close());
+    let average = |f: fn(&(common::Duration, common::Duration)) -> common::Duration| {
+        common::Duration::mean(packing.iter().map(f))
+    };
+    SessionOutcome {
+        records,
+        active_threads,
+        packing: (!packing.is_empty()).then(|| common::Packing {
+            a: average(|x| x.0),
+            b: average(|x| x.1),
+        }),
+        pruned,
+        ..outcome
+    }
+}
+
+// without an extension, LoadLibrary would look for ".temp.dll" instead.
+#[cfg(windows)]
+static FILENAME_TEMP: sync::LazyLock<String> = sync::LazyLock::new(|| "./.temp.dll".to_string());
+#[cfg(not(windows))]
+static FILENAME_TEMP: sync::LazyLock<String> = sync::LazyLock::new(|| "./.temp".to_string());
+
+fn main() {
+    let started = time::Instant::now();
+    // the benchmark itself takes a positional kernel path, so subcommands are matched by hand.
+    if env::args().nth(1).is_some_and(|x| {
+        <Command as argh::SubCommands>::COMMANDS
+            .iter()
+            .any(|command| command.name == x)
+    }) {
+        let commands: Commands = argh::from_env();
+        match commands.command {
+            Command::Autotune(args) => run_autotune(args),
+            Command::Conformance(args) => run_conformance(args),
+            Command::Examples(args) => run_examples(args),
+            Command::Init(args) => run_init(args),
+            Command::Presets(args) => run_presets(args),
+            Command::Schema(args) => run_schema(args),
+            Command::Suite(args) => run_suite(args),
+            Command::Watch(args) => run_watch(args),
+            Command::Worker(args) => run_worker(args),
+        }
+        return;
+    }
+
+    let (mut args, arguments) = parse_arguments();
+    if let Some(size) = args.square {
+        (args.m, args.n, args.k) = (size, size, size);
+    }
+    if let Some(shape) = &args.conv {
+        (args.m, args.n, args.k) = shape.gemm();
+    }
+    if args.print_config {
+        print!(
+            "{}",
+            toml::to_string(&effective_config(&args)).expect("Error: failed to serialize")
+        );
+        return;
+    }
+    check_args(&args);
+    utils::set_verbosity(args.verbose);
+    if args.kernel == STDIN_KERNEL {
+        run_stdin_kernel();
+    }
+
+    let ranges = args.m_range.is_some() || args.n_range.is_some() || args.k_range.is_some();
+    if ranges && args.preset.is_some() {
+        eprintln!("Error: --preset cannot be combined with ranges");
+        process::exit(1)
+    }
+    let sweeping =
+        ranges || args.preset.is_some() || args.alpha_sweep.is_some() || args.beta_sweep.is_some();
+    if args.prune_slower_than.is_some() && !sweeping {
+        eprintln!("Error: --prune-slower-than requires a sweep");
+        process::exit(1)
+    }
+    if [sweeping, args.per_core.is_some(), args.scaling.is_some()]
+        .iter()
+        .filter(|&&x| x)
+        .count()
+        > 1
+    {
+        eprintln!("Error: a sweep, --per-core and --scaling cannot be combined");
+        process::exit(1)
+    }
+    if args.strided_batch.is_none()
+        && (args.stride_a.is_some() || args.stride_b.is_some() || args.stride_c.is_some())
+    {
+        eprintln!("Error: strides require --strided-batch");
+        process::exit(1)
+    }
+    if args.reuse_b.is_some() && !args.packed {
+        eprintln!("Error: --reuse-b requires --packed");
+        process::exit(1)
+    }
+    if args.reuse_b == Some(0) {
+        eprintln!("Error: --reuse-b should not be 0");
+        process::exit(1)
+    }
+    if args.strided_batch == Some(0) {
+        eprintln!("Error: --strided-batch should not be 0");
+        process::exit(1)
+    }
+    if args.first_touch == Some(0) {
+        eprintln!("Error: --first-touch should not be 0");
+        process::exit(1)
+    }
+    if args.first_touch.is_some() && (args.batch.is_some() || args.strided_batch.is_some()) {
+        eprintln!("Error: --first-touch is not supported in batched mode");
+        process::exit(1)
+    }
+    if (args.placement_manifest.is_some() || args.ld_from_file.is_some())
+        && (args.batch.is_some() || args.strided_batch.is_some())
+    {
+        eprintln!("Error: placement manifests are not supported in batched mode");
+        process::exit(1)
+    }
+    if args.first_touch.is_some() && args.protect_inputs {
+        // the read-only copies would be touched by the main thread.
+        eprintln!("Error: --first-touch cannot be combined with --protect-inputs");
+        process::exit(1)
+    }
+    if args.memory_bandwidth.is_some_and(|x| x <= 0.0) {
+        eprintln!("Error: --memory-bandwidth should be positive");
+        process::exit(1)
+    }
+    if args.verify_with.is_some()
+        && (args.skip_verification || args.verify_sample.is_some() || args.extended_residual)
+    {
+        eprintln!(
+            "Error: --verify-with cannot be combined with --skip-verification, --verify-sample or --extended-residual"
+        );
+        process::exit(1)
+    }
+    if let Some(against) = &args.against {
+        if !path::Path::new(against).is_file() {
+            eprintln!("Error: --against: {} does not exist", against);
+            process::exit(1)
+        }
+        if sweeping || args.per_core.is_some() || args.scaling.is_some() {
+            eprintln!("Error: --against cannot be combined with a sweep, --per-core or --scaling");
+            process::exit(1)
+        }
+    }
+    if args.emit_reproducer.is_some() && !verifies(&args) {
+        eprintln!("Error: --emit-reproducer requires verification");
+        process::exit(1)
+    }
+    if args.anomaly_mads <= 0.0 {
+        eprintln!("Error: --anomaly-mads should be positive");
+        process::exit(1)
+    }
+    if args.conv.is_some() {
+        let unsupported = [
+            (sweeping, "a sweep"),
+            (args.square.is_some(), "--square"),
+            (args.batch.is_some(), "--batch"),
+            (args.strided_batch.is_some(), "--strided-batch"),
+            (args.packed, "--packed"),
+            (args.emit_reproducer.is_some(), "--emit-reproducer"),
+        ];
+        if let Some((_, name)) = unsupported.iter().find(|(x, _)| *x) {
+            eprintln!("Error: --conv cannot be combined with {}", name);
+            process::exit(1)
+        }
+    }
+    for formula in args.flops_formula.iter().chain(&args.bytes_formula) {
+        if let Err(e) = counter::parse(formula) {
+            eprintln!("Error: {}", e);
+            process::exit(1)
+        }
+    }
+    let mut modes = [
+        (args.batch.is_some(), "--batch"),
+        (args.strided_batch.is_some(), "--strided-batch"),
+        (args.packed, "--packed"),
+    ]
+    .into_iter()
+    .filter(|(x, _)| *x)
+    .map(|(_, name)| name);
+    if let Some(mode) = modes.next() {
+        let other = modes.next();
+        let unsupported = [
+            (other.is_some(), other.unwrap_or_default()),
+            (sweeping, "a sweep"),
+            (args.per_core.is_some(), "--per-core"),
+            (args.scaling.is_some(), "--scaling"),
+            (
+                matches!(verifier_kind(&args), verifier::Kind::FileCompare(_))
+                    && mode != "--packed",
+                "--verify-with file-compare",
+            ),
+            (args.protect_inputs, "--protect-inputs"),
+            (args.prewarm_threads, "--prewarm-threads"),
+            (args.against.is_some(), "--against"),
+            (args.emit_reproducer.is_some(), "--emit-reproducer"),
+        ];
+        if let Some((_, name)) = unsupported.iter().find(|(x, _)| *x) {
+            eprintln!("Error: {} cannot be combined with {}", mode, name);
+            process::exit(1)
+        }
+    }
+    // sweeps and studies hold the lock for all of their runs.
+    let lock = take_lock(args.no_lock, &args.lock_file);
+    if sweeping {
+        open_status(args.status_fd, &args.status_socket);
+        run_sweep(&args, &arguments);
+        return;
+    }
+    if let Some(cpus) = &args.per_core {
+        open_status(args.status_fd, &args.status_socket);
+        run_per_core(&args, &arguments, cpus);
+        return;
+    }
+    if let Some(domain) = args.scaling {
+        open_status(args.status_fd, &args.status_socket);
+        run_scaling(&args, &arguments, domain);
+        return;
+    }
 
-        let difference = unsafe {
-            let mut d = utils::malloc::<f64>(m * n);
-            cblas_dgemm(
-                args.layout,
-                trans_a,
-                trans_b,
-                m as _,
-                n as _,
-                k as _,
-                args.alpha,
-                a.as_ptr(),
-                lda as _,
-                b.as_ptr(),
-                ldb as _,
-                args.beta,
-                d.as_mut_ptr(),
-                ldc as _,
-            );
+    if let Some(kernel) = args.kernel.strip_prefix(examples::PREFIX).map(|name| {
+        examples::find(name)
+            .unwrap_or_else(|| {
+                eprintln!("Error: unknown built-in kernel '{}'", name);
+                process::exit(1)
+            })
+            .write_to(&env::temp_dir().join("benchmark-dgemm"))
+            .expect("Error: failed to extract built-in kernel")
+            .to_string_lossy()
+            .to_string()
+    }) {
+        args.kernel = kernel;
+    }
+
+    if cfg!(windows) && !args.preload.is_empty() {
+        eprintln!("Error: --preload is not supported on this platform");
+        process::exit(1)
+    }
+
+    if args.sanitize.is_some() {
+        if cfg!(windows) {
+            eprintln!("Error: --sanitize is not supported on this platform");
+            process::exit(1)
+        }
+        if args.compile.is_some_and(|x| !x) || args.replay_build.is_some() {
+            eprintln!("Error: --sanitize requires compiling the kernel");
+            process::exit(1)
+        }
+        if !verifies(&args) {
+            eprintln!("Error: --sanitize only runs verification");
+            process::exit(1)
+        }
+    }
+    if let Some(sanitizer) = args.sanitize {
+        if env::var(ENV_SANITIZE_RUN).is_err() {
+            respawn_sanitized(&args, sanitizer);
+        }
+    }
+
+    let original_preload = env::var(ENV_KERNEL_RUN).ok();
+    if (!args.kernel_env.is_empty() || !args.preload.is_empty()) && original_preload.is_none() {
+        respawn_with_kernel_env(&args);
+    }
+    open_status(args.status_fd, &args.status_socket);
+    // pin before the kernel is loaded, so its thread pools only see these CPUs.
+    if let Some(cpus) = &args.pin_cpus {
+        affinity::pin(cpus).unwrap_or_else(|e| {
+            eprintln!("Error: failed to pin to CPUs: {}", e);
+            process::exit(1)
+        });
+    }
+    if let Some(nodes) = &args.membind {
+        affinity::bind_memory(nodes).unwrap_or_else(|e| {
+            eprintln!("Error: failed to bind memory: {}", e);
+            process::exit(1)
+        });
+    }
+
+    // these parts look really ugly, but they do what should be done.
+    // out=Some, compile=Some(true) => build(out) then run(out),
+    // out=Some, compile=Some(false) => run(out),
+    // out=Some, compile=None => auto then run(out),
+    // out=None, compile=Some(true) => build(".temp") then run(".temp"),
+    // out=None, compile=Some(false) => run(kernel),
+    // out=None, compile=None => build(".temp") then run(".temp"),
+    let (out, compile) = args.out.as_ref().map_or_else(
+        || {
+            // out=None
+            if args.compile.is_some_and(|x| !x) {
+                // compile=Some(false)
+                (&args.kernel, false)
+            } else {
+                // compile=Some(true) or compile=None
+                (&*FILENAME_TEMP, true)
+            }
+        },
+        |out| {
+            // out=Some
+            (
+                out,
+                args.compile.unwrap_or_else(|| {
+                    // compile or auto; definitions are not recorded in the binary, so always rebuild.
+                    !args.define.is_empty()
+                        || fs::File::open(out).is_err_or(|out| {
+                            let source = fs::File::open(&args.kernel)
+                                .expect("Error: kernel not found")
+                                .metadata()
+                                .expect("Error: failed to query metadata");
+                            // modification time is the only timestamp every platform keeps.
+                            source.modified().expect("Error: unsupported platform")
+                                > out
+                                    .metadata()
+                                    .expect("Error: failed to query metadata")
+                                    .modified()
+                                    .expect("Error: unsupported platform")
+                        })
+                }),
+            )
+        },
+    );
+    let replay = args.replay_build.as_ref().map(|database| {
+        compile_commands::load(path::Path::new(database))
+            .expect("Error: failed to load compilation database")
+            .into_iter()
+            .find(|x| x.is_for(&args.kernel))
+            .unwrap_or_else(|| {
+                eprintln!("Error: compilation database has no entry for the kernel");
+                process::exit(1)
+            })
+    });
+    let (out, compile) = match &replay {
+        Some(entry) => (
+            entry.output.as_ref().unwrap_or_else(|| {
+                eprintln!("Error: compilation database entry has no output");
+                process::exit(1)
+            }),
+            false,
+        ),
+        None => (out, compile),
+    };
+    // a sanitized build must not replace the benchmarking one.
+    let (out, compile) = if args.sanitize.is_some() {
+        (&*FILENAME_TEMP, true)
+    } else {
+        (out, compile)
+    };
+    let mut phases = common::Phases::default();
+    let mut clock = time::Instant::now();
+    if let Some(entry) = &replay {
+        let mut command = entry.command().unwrap_or_else(|| {
+            eprintln!("Error: compilation database entry has no arguments");
+            process::exit(1)
+        });
+        if !command
+            .spawn()
+            .expect("Error: failed to run compiler")
+            .wait()
+            .expect("Error: failed to wait compiler exit")
+            .success()
+        {
+            eprintln!("Error: compilation failed");
+            process::exit(1)
+        }
+        status::emit(status::Event::Compiled {
+            kernel: &args.kernel,
+        });
+    }
+
+    // the kernel may change while benchmarking, so take a snapshot beforehand.
+    let source = args
+        .bundle
+        .as_ref()
+        .map(|_| fs::read(&args.kernel).expect("Error: failed to read kernel"));
+
+    if args.disassemble && args.bundle.is_none() {
+        eprintln!("Error: --disassemble requires --bundle");
+        process::exit(1)
+    }
+    if args.emit_asm && !compile {
+        eprintln!("Error: --emit-asm requires compiling the kernel");
+        process::exit(1)
+    }
 
-            let n = (m * n) as _;
-            cblas_daxpy(n, -1.0, c.as_ptr(), 1, d.as_mut_ptr(), 1);
-            cblas_dnrm2(n, d.as_ptr(), 1)
+    let mut build = None;
+    let mut assembly = None;
+    if compile {
+        let profile = args
+            .compiler_profile
+            .unwrap_or_else(|| compiler::Profile::infer(&args.compiler));
+        if profile == compiler::Profile::Msvc
+            && args.sanitize == Some(compiler::Sanitizer::Undefined)
+        {
+            eprintln!("Error: MSVC has no UndefinedBehaviorSanitizer");
+            process::exit(1)
+        }
+        // the compiler runs without the kernel environment.
+        let restore_environment = |command: &mut process::Command| {
+            for (key, _) in &args.kernel_env {
+                command.env_remove(key);
+            }
+            match original_preload.as_deref() {
+                Some("") => {
+                    command.env_remove(ENV_PRELOAD);
+                }
+                Some(original) => {
+                    command.env(ENV_PRELOAD, original);
+                }
+                None => {}
+            }
         };
-        if difference > 0.0001 {
-            eprintln!("WRONG RESULT!");
+        let mut command = compiler::build_command(
+            &args.compiler,
+            profile,
+            &args.compiler_args,
+            args.override_compiler_args,
+            &args.define,
+            args.sanitize,
+            &args.kernel,
+            out,
+        );
+        restore_environment(&mut command);
+        if args.verbose >= 1 {
+            eprintln!("Compiling: {}", compiler::command_line(&command));
+        }
+        build = Some(common::Build {
+            command: compiler::command_line(&command),
+            compiler_version: compiler::compiler_version(&args.compiler),
+        });
+        if let Some(database) = &args.compile_commands {
+            compile_commands::update(
+                path::Path::new(database),
+                compile_commands::CompileCommand::new(&command, &args.kernel, out),
+            )
+            .expect("Error: failed to update compilation database");
+        }
+        if !command
+            .spawn()
+            .expect("Error: failed to run compiler")
+            .wait()
+            .expect("Error: failed to wait compiler exit")
+            .success()
+        {
+            eprintln!("Error: compilation failed");
             process::exit(1)
         }
+        status::emit(status::Event::Compiled {
+            kernel: &args.kernel,
+        });
+
+        if args.emit_asm {
+            // next to the report if there is one, otherwise next to the kernel.
+            let asm = path::Path::new(args.save_as.as_ref().unwrap_or(&args.kernel))
+                .with_extension("s")
+                .to_string_lossy()
+                .to_string();
+            let mut command = compiler::assembly_command(
+                &args.compiler,
+                profile,
+                &args.compiler_args,
+                args.override_compiler_args,
+                &args.define,
+                args.sanitize,
+                &args.kernel,
+                &asm,
+            );
+            restore_environment(&mut command);
+            let status = command.status().expect("Error: failed to run compiler");
+            if profile == compiler::Profile::Msvc {
+                drop(fs::remove_file([asm.as_str(), ".obj"].concat()));
+            }
+            if status.success() {
+                println!("Assembly: {}", asm);
+                assembly = Some(asm);
+            } else {
+                eprintln!("Error: failed to emit assembly");
+            }
+        }
     }
+    phases.compile = lap(&mut clock);
 
-    for _ in 0..args.warm_up {
-        kernel.run(
-            args.layout,
-            trans_a,
-            trans_b,
-            dimensions,
-            &a,
-            lda,
-            &b,
-            ldb,
-            &mut c,
-            ldc,
+    let groups = args.batch.as_ref().map(|file| {
+        batch::load(
+            path::Path::new(file),
+            (args.trans_a, args.trans_b),
             args.alpha,
             args.beta,
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("Error: failed to load batch: {}", e);
+            process::exit(1)
+        })
+    });
+    // a strided batch is a single group of problems.
+    let groups = groups.or_else(|| {
+        args.strided_batch.map(|count| {
+            vec![batch::Group {
+                m: args.m,
+                n: args.n,
+                k: args.k,
+                count,
+                transpose: (args.trans_a, args.trans_b),
+                alpha: args.alpha,
+                beta: args.beta,
+            }]
+        })
+    });
+    let strides = groups
+        .as_ref()
+        .filter(|_| args.strided_batch.is_some())
+        .map(|x| {
+            batch::Strides::new(&x[0], (args.stride_a, args.stride_b, args.stride_c))
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    process::exit(1)
+                })
+        });
+
+    let dimensions = groups
+        .as_ref()
+        .map_or((args.m, args.n, args.k), |x| (x[0].m, x[0].n, x[0].k));
+    let (m, n, k) = dimensions;
+    if verifier_kind(&args) == verifier::Kind::VendorBlas {
+        let shapes = groups.as_ref().map_or(vec![dimensions], |x| {
+            x.iter().map(|x| (x.m, x.n, x.k)).collect()
+        });
+        if let Some(reason) = shapes.into_iter().find_map(utils::blas_int_overflow) {
+            eprintln!("Warning: {}; verification against it may be wrong", reason);
+        }
+    }
+    if let Some(groups) = &groups {
+        println!(
+            "Batch: {} groups, {} problems",
+            groups.len(),
+            groups.iter().map(|x| x.count).sum::<usize>()
         );
+        for group in groups {
+            println!(
+                "M: {}, N: {}, K: {}, count: {}, alpha: {:.4}, beta: {:.4}",
+                group.m, group.n, group.k, group.count, group.alpha, group.beta
+            );
+        }
+        if let Some(strides) = strides {
+            println!(
+                "Strides: A: {}, B: {}, C: {}",
+                strides.a, strides.b, strides.c
+            );
+        }
+    } else {
+        println!("M: {}, N: {}, K: {}", m, n, k);
+        println!("alpha: {:.4}, beta: {:.4}", args.alpha, args.beta);
     }
+    println!("Layout: {}", args.layout);
+    let working_set = match &groups {
+        Some(groups) => {
+            cache::WorkingSet::from_bytes(batch::bytes(groups, strides), cache::detect())
+        }
+        None => cache::WorkingSet::new(dimensions, cache::detect()),
+    };
+    println!(
+        "Working set: {} (fits {})",
+        cache::format_bytes(working_set.total()),
+        working_set.fits
+    );
 
-    let mut records = Vec::with_capacity(args.repeats);
-    for _ in 0..args.repeats {
-        let duration = kernel.run(
-            args.layout,
-            trans_a,
-            trans_b,
-            dimensions,
-            &a,
-            lda,
-            &b,
-            ldb,
-            &mut c,
-            ldc,
-            args.alpha,
-            args.beta,
+    let transpose = (args.trans_a, args.trans_b);
+    let (trans_a, trans_b) = transpose;
+    println!("TransA: {}", trans_a == CBLAS_TRANSPOSE::CblasTrans);
+    println!("TransB: {}", trans_b == CBLAS_TRANSPOSE::CblasTrans);
+
+    let counters = counter::Counters::evaluate(
+        (args.flops_formula.as_deref(), args.bytes_formula.as_deref()),
+        dimensions,
+        groups.as_deref().unwrap_or_default(),
+        args.reuse_b,
+    )
+    .unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1)
+    });
+
+    // set by a sweep or suite once an entry completed, see --prune-slower-than.
+    let pruner = env::var(ENV_PRUNE_BELOW)
+        .ok()
+        .and_then(|x| x.parse::<f64>().ok())
+        .map(|below| prune::Pruner {
+            flops: match (&counters, &groups) {
+                (Some(counter::Counters { flops: Some(x), .. }), _) => x.value,
+                (_, Some(groups)) => groups.iter().map(batch::Group::flops).sum(),
+                (_, None) => {
+                    benchmark_core::statistics::flops(dimensions, args.alpha, args.beta)
+                        * args.reuse_b.unwrap_or(1) as f64
+                }
+            },
+            below,
+            after: args.prune_after,
+        });
+    let run = |session: usize| match &groups {
+        Some(groups) => run_batch_session(&args, out, session, groups, strides, pruner.as_ref()),
+        None if args.packed => run_packed_session(&args, out, session, pruner.as_ref()),
+        None => run_session(&args, out, session, pruner.as_ref()),
+    };
+
+    if let Some(sanitizer) = args.sanitize {
+        run(0);
+        drop(fs::remove_file(&*FILENAME_TEMP));
+        println!("Verification passed with {} sanitizer", sanitizer.name());
+        return;
+    }
+
+    let mut sessions = Vec::with_capacity(args.sessions);
+    let mut openmp = None;
+    let mut residual = None::<f64>;
+    let mut tolerance_used = None::<f64>;
+    let mut workspace = None::<usize>;
+    let mut active_threads = None::<usize>;
+    let mut packing = Vec::new();
+    let mut im2col = Vec::new();
+    let mut against = Vec::new();
+    let mut pruned = false;
+    let mut buffers = Vec::new();
+    // fail before the run rather than after it.
+    let placement_before = args.ld_from_file.as_ref().map(|file| {
+        placement::Manifest::load(path::Path::new(file)).unwrap_or_else(|e| {
+            eprintln!("Error: failed to load placement manifest: {}", e);
+            process::exit(1)
+        })
+    });
+    // the noise floor is taken where the kernel will run, before it warms anything up.
+    let noise = noise::measure(args.noise_samples);
+    if let Some(noise) = &noise {
+        println!(
+            "Noise floor: {:.2}% over {} idle loops, clock read {:.0} ns",
+            noise.floor * 100.0,
+            noise.samples,
+            noise.clock_read
         );
-        println!("Duration: {:.6}ms", duration.as_milis());
-        records.push(duration);
     }
-    drop(library.close());
-    let records = records;
+    for session in 0..args.sessions {
+        if session > 0 {
+            thread::sleep(args.session_gap);
+        }
+        if args.sessions > 1 {
+            println!("Session {}/{}", session + 1, args.sessions);
+        }
+        status::emit(status::Event::SessionStarted {
+            session,
+            sessions: args.sessions,
+        });
+        let outcome = run(session);
+        sessions.push(outcome.records);
+        openmp = openmp.or(outcome.openmp);
+        if let Some(x) = outcome.residual {
+            residual = Some(residual.map_or(x, |y| y.max(x)));
+        }
+        if let Some(x) = outcome.tolerance_used {
+            tolerance_used = Some(tolerance_used.map_or(x, |y| y.max(x)));
+        }
+        if let Some(x) = outcome.workspace {
+            workspace = Some(workspace.map_or(x, |y| y.max(x)));
+        }
+        if let Some(x) = outcome.active_threads {
+            active_threads = Some(active_threads.map_or(x, |y| y.max(x)));
+        }
+        packing.extend(outcome.packing);
+        im2col.extend(outcome.im2col);
+        against.extend(outcome.against);
+        phases.accumulate(&outcome.phases);
+        buffers.extend(outcome.buffers);
+        // the remaining sessions would be abandoned as well.
+        if outcome.pruned {
+            pruned = true;
+            break;
+        }
+    }
+    let sessions = sessions;
+    let records = sessions.concat();
+    lap(&mut clock);
+    let against = args.against.as_ref().map(|other| {
+        let name = path::Path::new(other)
+            .file_name()
+            .map_or(other.clone(), |x| x.to_string_lossy().to_string());
+        let against = paired::against(&name, &records, &against, args.significance);
+        println!(
+            "Against {}: {:+.2}% throughput over {} pairs, p {}, {}",
+            against.kernel,
+            against.change,
+            against.samples.len(),
+            against.p.map_or("-".to_string(), |x| format!("{:.4}", x)),
+            against.verdict
+        );
+        against
+    });
+
+    let manifest = placement::Manifest { buffers };
+    if let Some(before) = &placement_before {
+        let differences = manifest.compare(before);
+        if differences.is_empty() {
+            println!("Placement: as in {}", args.ld_from_file.as_ref().unwrap());
+        }
+        for difference in differences {
+            println!("Placement: {}", difference);
+        }
+    }
+    if let Some(file) = &args.placement_manifest {
+        manifest.save(path::Path::new(file)).unwrap_or_else(|e| {
+            eprintln!("Error: failed to write placement manifest: {}", e);
+            process::exit(1)
+        });
+    }
+
+    // disassemble before the temporary object is removed; without objdump only --disassemble
+    // fails, the scans are left out.
+    let listing = disassembly::disassemble(&args.objdump, out);
+    let disassembly = if args.disassemble {
+        match listing.clone().and_then(|x| {
+            disassembly::extract(&x, "call_dgemm")
+                .ok_or_else(|| "call_dgemm not found in disassembly".to_string())
+        }) {
+            Ok(x) => Some(x),
+            Err(e) => {
+                eprintln!("Error: failed to disassemble kernel: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let footprint = footprint::measure(out);
+    // only x86-64 has AVX-512 and AMX.
+    let extensions = listing
+        .as_ref()
+        .ok()
+        .filter(|_| cfg!(target_arch = "x86_64"))
+        .map(|x| disassembly::extensions(x));
+    let hot = if args.packed {
+        "compute_packed"
+    } else if args.strided_batch.is_some() {
+        "call_dgemm_strided_batched"
+    } else if args.batch.is_some() {
+        "call_dgemm_batch"
+    } else {
+        "call_dgemm"
+    };
+    let instruction_mix = listing.as_ref().ok().and_then(|x| disassembly::mix(x, hot));
 
     if out.as_ptr() == FILENAME_TEMP.as_ptr() {
         drop(fs::remove_file(&*FILENAME_TEMP));
     }
 
-    let report = common::Report {
-        name: path::PathBuf::from(args.kernel)
-            .file_name()
-            .unwrap()
-            .to_string_lossy()
-            .to_string(),
+    let kernel_name = path::PathBuf::from(&args.kernel)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    let history = records
+        .iter()
+        .map(|x| format!("{:.6}", args.units.of(*x)))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let methodology = describe_methodology(&args);
+    let mut report = common::Report {
+        name: args.name.unwrap_or_else(|| kernel_name.clone()),
         dimensions,
-        repeats: args.repeats,
+        repeats: records.len(),
         alpha: args.alpha,
         beta: args.beta,
         layout: args.layout,
         transpose,
         statistics: common::Statistics::from(&records),
+        anova: (sessions.len() > 1).then(|| common::Anova::from(&sessions)),
+        tags: args.tag,
+        meta: args.meta.into_iter().collect(),
+        defines: args.define.into_iter().collect(),
+        prewarmed: args.prewarm_threads,
+        environment: common::Environment {
+            variables: args.kernel_env.into_iter().collect(),
+            preload: args.preload,
+            openmp,
+            affinity: args.pin_cpus.unwrap_or_default(),
+            membind: args.membind.unwrap_or_default(),
+            first_touch: args.first_touch,
+            machine: Some(machine::detect_with(&args.compiler)),
+        },
+        working_set: Some(working_set),
+        footprint,
+        extensions,
+        instruction_mix,
+        residual,
+        tolerance_used,
+        workspace_bytes: workspace,
+        active_threads,
+        batch: groups.unwrap_or_default(),
+        batch_strides: strides,
+        reuse_b: args.reuse_b,
+        counters,
+        // packing happens once per session, so sessions are averaged.
+        packing: (!packing.is_empty()).then(|| {
+            let average = |f: fn(&common::Packing) -> common::Duration| {
+                common::Duration::mean(packing.iter().map(f))
+            };
+            common::Packing {
+                a: average(|x| x.a),
+                b: average(|x| x.b),
+            }
+        }),
+        convolution: args.conv.map(|shape| common::Convolution {
+            shape,
+            im2col: (!im2col.is_empty()).then(|| common::Duration::mean(im2col)),
+        }),
+        against,
+        noise,
+        lock: lock.as_ref().map(|x| x.locking.clone()),
+        phases: None,
+        expectations: Vec::new(),
+        samples: records,
+        pruned,
+        diagnostics: None,
+        hints: Vec::new(),
+        units: units::Metadata::default(),
+        build: build.clone(),
+        seed: Some(0),
+        methodology: Some(methodology),
+    };
+    report.diagnostics = Some(diagnostics::analyze(&report.samples, args.anomaly_mads));
+    report.hints = hints::collect(&report, args.memory_bandwidth);
+    report.expectations =
+        expect::evaluate(&report, args.expect_gflops_min, args.expect_max_deviation);
+    phases.report = lap(&mut clock);
+    phases.total = started.elapsed().as_secs_f64();
+    report.phases = Some(phases);
+    let units = units::Units {
+        time: args.units,
+        flops: args.flops_units,
     };
-    println!("{}", report.summary().unwrap());
+    match args.show {
+        common::Show::Full => println!("{}", report.full_in(units).unwrap()),
+        common::Show::Summary => println!("{}", report.summary_in(units).unwrap()),
+        common::Show::Minimal => println!("{}", report.minimal_in(units)),
+    }
+    status::emit(status::Event::Completed {
+        name: &report.name,
+        gflops: hook::gflops(&report),
+    });
+    let mut placeholders = hook::placeholders(&report, args.save_as.as_deref());
 
     if let Some(mut file) = args.save_as.and_then(|x| fs::File::create(x).ok()) {
         file.write_all(
@@ -449,14 +4596,118 @@ fn main() {
     }
 
     if let Some(mut file) = args.save_history_as.and_then(|x| fs::File::create(x).ok()) {
-        file.write_all(
-            records
-                .into_iter()
-                .map(|x| format!("{:.6}", x.as_milis()))
-                .collect::<Vec<String>>()
-                .join("\n")
-                .as_bytes(),
-        )
-        .expect("Error: failed to save benchmark history");
+        file.write_all(history.as_bytes())
+            .expect("Error: failed to save benchmark history");
+    }
+
+    if let Some(out) = args.bundle {
+        let mut bundle = bundle::Bundle::new();
+        bundle.add(
+            "report.json",
+            serde_json::to_vec(&report).expect("Error: failed to serialize"),
+        );
+        bundle.add(
+            ["kernel/", kernel_name.as_str()].concat(),
+            source.unwrap_or_default(),
+        );
+        if let Some(build) = build {
+            bundle.add("build/command.txt", build.command);
+            bundle.add("build/compiler-version.txt", build.compiler_version);
+        }
+        if let Some(disassembly) = disassembly {
+            bundle.add("build/disassembly.txt", disassembly);
+        }
+        if let Some(asm) = assembly.and_then(|x| fs::read(x).ok()) {
+            bundle.add("build/kernel.s", asm);
+        }
+        bundle.add("history.txt", history);
+        bundle
+            .write(path::Path::new(&out))
+            .expect("Error: failed to save bundle");
+    }
+
+    if let Some(url) = args.post_to {
+        let sink = sink::HttpSink {
+            url,
+            token: args.post_token,
+            gzip: args.post_gzip,
+            retries: args.post_retries,
+            spool: args.spool_dir.map(path::PathBuf::from),
+        };
+        if let Err(e) = sink.flush_spool() {
+            eprintln!("Error: failed to re-send spooled benchmark results: {}", e);
+        }
+        let body = serde_json::to_vec(&report).expect("Error: failed to serialize");
+        match sink.submit(&body) {
+            Ok(sink::Submission::Posted) => {}
+            Ok(sink::Submission::Spooled(file)) => {
+                eprintln!(
+                    "Error: collector unreachable, benchmark result spooled to {}",
+                    file.display()
+                );
+            }
+            Err(e) => {
+                eprintln!("Error: failed to post benchmark result: {}", e);
+                process::exit(1)
+            }
+        }
+    }
+
+    let mut regressed = false;
+    if let Some(baseline) = args.baseline {
+        let baseline = fs::File::open(&baseline)
+            .map_err(|e| e.to_string())
+            .and_then(|x| {
+                serde_json::from_reader::<fs::File, common::Report>(x).map_err(|e| e.to_string())
+            })
+            .unwrap_or_else(|e| {
+                eprintln!("Error: failed to load baseline: {}", e);
+                process::exit(1)
+            });
+        let mut differences = match (&report.environment.machine, &baseline.environment.machine) {
+            (Some(current), Some(previous)) => current.differences(previous),
+            _ => vec!["baseline does not record its machine".to_string()],
+        };
+        let threads = |x: &common::Report| x.environment.openmp.as_ref().map(|x| x.max_threads);
+        if threads(&report) != threads(&baseline) {
+            differences.push(format!(
+                "{} OpenMP threads differ from {}",
+                threads(&report).map_or("unknown".to_string(), |x| x.to_string()),
+                threads(&baseline).map_or("unknown".to_string(), |x| x.to_string())
+            ));
+        }
+        if !differences.is_empty() {
+            let error = args.baseline_mismatch == machine::Mismatch::Error;
+            for difference in &differences {
+                eprintln!(
+                    "{}: baseline mismatch: {}",
+                    if error { "Error" } else { "Warning" },
+                    difference
+                );
+            }
+            if error {
+                eprintln!("Error: refusing to compare with a baseline from another machine, see --baseline-mismatch");
+                process::exit(1)
+            }
+        }
+        let (current, previous) = (hook::gflops(&report), hook::gflops(&baseline));
+        let change = (current / previous - 1.0) * 100.0;
+        println!("Baseline\t {:.3} \t({:+.2}%)", previous, change);
+        regressed = change < -args.regression_threshold;
+        placeholders.insert("baseline_gflops".to_string(), format!("{:.3}", previous));
+        placeholders.insert("change".to_string(), format!("{:.2}", change));
+    }
+
+    for command in [args.on_complete, args.on_regression.filter(|_| regressed)]
+        .into_iter()
+        .flatten()
+    {
+        if let Err(e) = hook::render(&command, &placeholders).and_then(|x| hook::run(&x)) {
+            eprintln!("Error: {}", e);
+        }
+    }
+
+    if report.expectations.iter().any(|x| !x.passed) {
+        process::exit(expect::EXIT_FAILED)
     }
 }
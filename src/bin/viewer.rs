@@ -1,6 +1,6 @@
 use argh::FromArgs;
 use benchmark::*;
-use std::{fs, io::Write, process};
+use std::{collections::BTreeMap, fs, io::Write, path, process};
 
 #[derive(FromArgs)]
 /// arguments
@@ -8,86 +8,654 @@ struct Arguments {
     #[argh(positional)]
     reports: Vec<String>,
 
-    /// merge reports into one file
+    /// merge reports into one file; with --campaign, write the summary there instead, as HTML
+    /// if the file ends in .html and Markdown otherwise
     #[argh(option, short = 'o')]
     out: Option<String>,
+
+    /// only include reports that have this tag, can be repeated
+    #[argh(option)]
+    tag: Vec<String>,
+
+    /// only include reports whose name contains this
+    #[argh(option)]
+    name: Option<String>,
+
+    /// write every matched report, e.g. of a suite report, into this directory as its own file
+    #[argh(option, arg_name = "dir")]
+    flatten: Option<String>,
+
+    /// group reports by the cache level their working set fits in and summarize each group
+    #[argh(switch)]
+    group_by_cache: bool,
+
+    /// strip hostnames, paths and/or usernames, comma-separated, and print the report as JSON
+    #[argh(option, arg_name = "fields", from_str_fn(redact::parse_fields))]
+    redact: Option<Vec<redact::Field>>,
+
+    /// pair reports with those of another run of the same suite by configuration and test
+    /// whether they differ, can be repeated
+    #[argh(option, arg_name = "reports")]
+    pair_with: Vec<String>,
+
+    /// p-value below which a paired difference or a drift is significant
+    #[argh(option, default = "0.05", from_str_fn(verification::parse_fraction))]
+    significance: f64,
+
+    /// test whether the throughput of suite entries drifts with the position they ran at, e.g. as
+    /// the machine heats up; only shuffled suites separate that from the order of the entries
+    #[argh(switch)]
+    drift: bool,
+
+    /// fit t = a·mnk + b·(mk+kn+mn) + c to the reports of a sweep and flag points far off the model
+    #[argh(switch)]
+    fit_model: bool,
+
+    /// with --fit-model, predict the time of these dimensions, e.g. 4096x4096x4096, can be repeated
+    #[argh(option, arg_name = "MxNxK", from_str_fn(model::parse_dimensions))]
+    predict: Vec<(usize, usize, usize)>,
+
+    /// check every matched file for internal inconsistencies, e.g. statistics that do not match
+    /// the samples, instead of merging the reports; exits with 1 if any file is suspicious
+    #[argh(switch)]
+    validate: bool,
+
+    /// compare this history file, saved with --save-history-as, with the one given as the
+    /// report: a chart of both by iteration and how far their distributions overlap
+    #[argh(option, arg_name = "file")]
+    diff_history: Option<String>,
+
+    /// with --diff-history, draw the chart into this SVG file instead of the terminal
+    #[argh(option, arg_name = "file")]
+    svg: Option<String>,
+
+    /// also write the printed table to this file, as CSV or JSON by its extension; the merged
+    /// report is exported as one row per matched report
+    #[argh(option, arg_name = "file", from_str_fn(table::parse_export))]
+    export: Option<(String, table::Format)>,
+
+    /// summarize every report and suite report under this directory and its subdirectories:
+    /// the best known result of every configuration with its provenance, kernels by
+    /// configuration and the dates they ran on, as Markdown
+    #[argh(option, arg_name = "dir")]
+    campaign: Option<String>,
+
+    /// summarize the search log of an autotune run instead, with the influence of every tunable
+    #[argh(option, arg_name = "file")]
+    search_log: Option<String>,
+
+    /// unit of printed times: us, ms or s
+    #[argh(
+        option,
+        arg_name = "unit",
+        from_str_fn(units::TimeUnit::try_from),
+        default = "units::TimeUnit::Ms"
+    )]
+    units: units::TimeUnit,
+
+    /// unit of printed throughput: gflops or tflops
+    #[argh(
+        option,
+        arg_name = "unit",
+        from_str_fn(units::FlopsUnit::try_from),
+        default = "units::FlopsUnit::Gflops"
+    )]
+    flops_units: units::FlopsUnit,
 }
 
-fn main() {
-    let args: Arguments = argh::from_env();
+/// Whether `report` passes the `--tag` and `--name` filters.
+fn matches(report: &common::Report, args: &Arguments) -> bool {
+    args.tag.iter().all(|tag| report.tags.contains(tag))
+        && args.name.as_ref().is_none_or(|x| report.name.contains(x))
+}
 
+/// Reports of files matching `patterns` that pass the filters, redacted if requested.
+fn load(patterns: &[String], args: &Arguments) -> Vec<common::Report> {
     let mut reports = Vec::new();
-    for report in args.reports {
+    for report in patterns {
         let glob = glob::glob(report.as_str());
         if glob.is_err() {
             continue;
         }
         for matched in glob.unwrap() {
-            reports.push(
-                serde_json::from_reader::<fs::File, common::Report>(
-                    fs::File::open(matched.expect("Error: glob failed"))
-                        .expect("Error: could not open file"),
-                )
-                .expect("Error: unknown format"),
-            );
+            let file = fs::read_to_string(matched.expect("Error: glob failed"))
+                .expect("Error: could not open file");
+            let loaded = common::parse_reports(&file).expect("Error: unknown format");
+            for report in loaded {
+                if !matches(&report, args) {
+                    continue;
+                }
+                match &args.redact {
+                    Some(fields) => {
+                        reports.push(redact::redact(&report, fields).unwrap_or_else(|e| {
+                            eprintln!("Error: failed to redact report: {}", e);
+                            process::exit(1)
+                        }))
+                    }
+                    None => reports.push(report),
+                }
+            }
         }
     }
-    let reports = reports;
-
-    let mut report = common::Report {
-        name: reports[0].name.clone(),
-        dimensions: reports[0].dimensions,
-        repeats: reports.iter().fold(0, |acc, x| acc + x.repeats),
-        alpha: reports[0].alpha,
-        beta: reports[0].beta,
-        layout: reports[0].layout,
-        transpose: reports[0].transpose,
-        statistics: common::Statistics::new(),
-    };
+    reports
+}
 
-    for v in &reports[1..] {
-        if v.dimensions != report.dimensions
-            || v.alpha != report.alpha
-            || v.beta != report.beta
-            || v.layout != report.layout
-            || v.transpose != report.transpose
-        {
-            eprintln!("Error: cannot merge reports that have different parameters.");
-            process::exit(1)
+/// Print the inconsistencies of the reports of every file matching `patterns` that pass the
+/// filters. Returns whether all of them are consistent.
+fn validate(patterns: &[String], args: &Arguments) -> bool {
+    let mut valid = true;
+    for file in patterns
+        .iter()
+        .filter_map(|x| glob::glob(x).ok())
+        .flatten()
+        .filter_map(Result::ok)
+    {
+        let reports = fs::read_to_string(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|x| common::parse_reports(&x));
+        let reports = match reports {
+            Ok(x) => x,
+            Err(e) => {
+                println!("{}: not a report: {}", file.display(), e);
+                valid = false;
+                continue;
+            }
+        };
+        let mut problems = 0;
+        for report in reports.iter().filter(|x| matches(x, args)) {
+            for problem in validate::check(report) {
+                println!("{}: {}: {}", file.display(), report.name, problem);
+                problems += 1;
+            }
+        }
+        if problems == 0 {
+            println!("{}: ok", file.display());
         }
+        valid &= problems == 0;
     }
+    valid
+}
 
-    if reports.len() == 1 {
-        report.statistics.medium = reports[0].statistics.medium;
+/// Print the paired test of every configuration in both runs, then an overall test over the
+/// change of medium GFLOPS of all of them.
+fn compare(
+    before: &[common::Report],
+    after: &[common::Report],
+    significance: f64,
+    flops: units::FlopsUnit,
+) -> table::Table {
+    let pairs = paired::pair(before, after);
+    if pairs.is_empty() {
+        eprintln!("Error: no configuration is in both runs.");
+        process::exit(1)
     }
 
-    {
-        let mut maximum = common::Duration::MIN;
-        for report in &reports {
-            if maximum < report.statistics.maximum {
-                maximum = report.statistics.maximum;
+    let before_label = format!("{} before", flops.label());
+    let after_label = format!("{} after", flops.label());
+    let mut table = table::Table::new(&[
+        "Name",
+        "Configuration",
+        &before_label,
+        &after_label,
+        "Change (%)",
+        "p",
+        "Verdict",
+    ]);
+    let mut ratios = Vec::with_capacity(pairs.len());
+    for pair in &pairs {
+        let (x, y) = (hook::gflops(pair.before), hook::gflops(pair.after));
+        let change = (y / x - 1.0) * 100.0;
+        ratios.push((y / x).ln());
+        let test = pair.test();
+        table.push(vec![
+            pair.before.name.clone(),
+            pair.configuration.to_string(),
+            format!("{:.3}", flops.of(x)),
+            format!("{:.3}", flops.of(y)),
+            format!("{:+.2}", change),
+            test.as_ref()
+                .map_or("-".to_string(), |x| format!("{:.4}", x.p)),
+            paired::verdict(test.as_ref(), change, significance).to_string(),
+        ]);
+    }
+    table.print();
+
+    let overall = paired::wilcoxon(&ratios);
+    let change = paired::geomean_change(&ratios);
+    println!(
+        "Overall: {} configurations, {:+.2}% (geomean), p {}, {}",
+        pairs.len(),
+        change,
+        overall
+            .as_ref()
+            .map_or("-".to_string(), |x| format!("{:.4}", x.p)),
+        paired::verdict(overall.as_ref(), change, significance)
+    );
+    table
+}
+
+/// Print the throughput of suite reports in the order they ran and its rank correlation with
+/// their position.
+fn drift(reports: &[common::Report], significance: f64, flops: units::FlopsUnit) -> table::Table {
+    let mut positioned = reports
+        .iter()
+        .filter_map(|x| Some((suite::position(x)?, x)))
+        .collect::<Vec<(usize, &common::Report)>>();
+    if positioned.is_empty() {
+        eprintln!("Error: no report has a suite position.");
+        process::exit(1)
+    }
+    positioned.sort_by_key(|(position, _)| *position);
+
+    let mut table = table::Table::new(&["Position", "Name", flops.label()]);
+    for (position, report) in &positioned {
+        table.push(vec![
+            position.to_string(),
+            report.name.clone(),
+            format!("{:.3}", flops.of(hook::gflops(report))),
+        ]);
+    }
+    table.print();
+
+    let (positions, gflops): (Vec<f64>, Vec<f64>) = positioned
+        .iter()
+        .map(|(position, report)| (*position as f64, hook::gflops(report)))
+        .unzip();
+    match paired::spearman(&positions, &gflops) {
+        Some(x) => println!(
+            "Drift: rho {:+.3} over {} entries, p {:.4}, {}",
+            x.rho,
+            x.n,
+            x.p,
+            match x {
+                x if x.p >= significance => "no drift",
+                x if x.rho < 0.0 => "slowing down",
+                _ => "speeding up",
             }
+        ),
+        None => println!("Drift: untested"),
+    }
+    table
+}
+
+/// Print the coefficients of the performance model fitted to `reports`, how far every report is
+/// off it, and the predictions of `predict`.
+fn fit_model(
+    reports: &[common::Report],
+    predict: &[(usize, usize, usize)],
+    units: units::Units,
+) -> table::Table {
+    let model = model::Model::fit(reports).unwrap_or_else(|e| {
+        eprintln!("Error: failed to fit model: {}", e);
+        process::exit(1)
+    });
+    println!(
+        "Model: t = a·mnk + b·(mk+kn+mn) + c, fitted to {} reports, R² {:.4}",
+        model.points.len(),
+        model.r_squared
+    );
+    println!(
+        "a	 {:.6e}ns	 ({:.3} {} peak)",
+        model.a,
+        units.flops.of(model.peak_gflops()),
+        units.flops.label()
+    );
+    println!("b	 {:.6e}ns	 ({:.3} GB/s)", model.b, model.bandwidth());
+    println!("c	 {:.3}us", model.c / 1000.0);
+
+    let measured = format!("Measured ({})", units.time.suffix());
+    let predicted = format!("Predicted ({})", units.time.suffix());
+    let mut table = table::Table::new(&[
+        "Dimensions",
+        &measured,
+        &predicted,
+        "Residual (%)",
+        "Anomalous",
+    ]);
+    for point in &model.points {
+        table.push(vec![
+            format!(
+                "{}x{}x{}",
+                point.dimensions.0, point.dimensions.1, point.dimensions.2
+            ),
+            format!("{:.6}", units.time.of_millis(point.measured / 1e6)),
+            format!("{:.6}", units.time.of_millis(point.predicted / 1e6)),
+            format!("{:+.2}", point.residual() * 100.0),
+            point.anomalous.to_string(),
+        ]);
+    }
+    table.print();
+
+    for &dimensions in predict {
+        let time = model.predict(dimensions);
+        println!(
+            "Predicted {}x{}x{}: {}, {:.3} {}",
+            dimensions.0,
+            dimensions.1,
+            dimensions.2,
+            units.time_millis(time / 1e6),
+            units
+                .flops
+                .of(2.0 * dimensions.0 as f64 * dimensions.1 as f64 * dimensions.2 as f64 / time),
+            units.flops.label()
+        );
+    }
+    table
+}
+
+/// Print the outcome of an autotune search and the mean GFLOPS of every value of every tunable.
+fn search_log(file: &str, flops: units::FlopsUnit) -> table::Table {
+    let log = autotune::SearchLog::load(path::Path::new(file)).unwrap_or_else(|e| {
+        eprintln!("Error: failed to load search log: {}", e);
+        process::exit(1)
+    });
+    let failed = log
+        .evaluations
+        .iter()
+        .filter(|x| x.status == autotune::Status::Failed)
+        .count();
+    println!(
+        "Search of {}: {} runs, {} failed",
+        log.space,
+        log.evaluations.len(),
+        failed
+    );
+    if let Some(winner) = &log.winner {
+        println!("Winner: {}", autotune::format_candidate(winner));
+    }
+
+    let levels = format!("{} (mean) by value", flops.label());
+    let mut table = table::Table::new(&["Tunable", "Spread (%)", &levels]);
+    for sensitivity in log.sensitivities() {
+        table.push(vec![
+            sensitivity.name,
+            format!("{:.1}", sensitivity.spread * 100.0),
+            sensitivity
+                .levels
+                .iter()
+                .map(|(value, mean, count)| {
+                    format!("{}: {:.3} ({})", value, flops.of(*mean), count)
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+        ]);
+    }
+    table.print();
+    table
+}
+
+/// Chart the iteration times of the history files `a` and `b` and print the quantiles of both
+/// and how far their distributions overlap.
+fn diff_history(a: &str, b: &str, args: &Arguments) -> table::Table {
+    let load = |file: &str| {
+        history::load(path::Path::new(file)).unwrap_or_else(|e| {
+            eprintln!("Error: failed to load history {}: {}", file, e);
+            process::exit(1)
+        })
+    };
+    let (a, b) = (load(a), load(b));
+    let Some(overlap) = history::Overlap::new(&a, &b) else {
+        eprintln!("Error: a history file is empty.");
+        process::exit(1)
+    };
+
+    match &args.svg {
+        Some(file) => {
+            let svg = history::svg(&a, &b, args.units.suffix()).expect("Error: failed to draw");
+            fs::write(file, svg).expect("Error: failed to save chart");
         }
-        report.statistics.maximum = maximum;
+        None => println!("{}\n", history::chart(&a, &b, 100)),
+    }
+
+    let a_label = format!("a ({})", args.units.suffix());
+    let b_label = format!("b ({})", args.units.suffix());
+    let mut table = table::Table::new(&["Quantile", &a_label, &b_label, "Change (%)"]);
+    for &(fraction, x, y) in &overlap.quantiles {
+        table.push(vec![
+            format!("{}", fraction),
+            format!("{:.6}", x),
+            format!("{:.6}", y),
+            format!("{:+.2}", (y / x - 1.0) * 100.0),
+        ]);
+    }
+    table.print();
+    println!(
+        "Distance: {:.3} over {} and {} iterations, p {:.4}, overlap {:.1}%, {}",
+        overlap.distance,
+        a.len(),
+        b.len(),
+        overlap.p,
+        overlap.coefficient * 100.0,
+        overlap.shift(args.significance)
+    );
+    table
+}
+
+/// Print the geometric-mean GFLOPS of the reports in each cache regime.
+fn group_by_cache(reports: &[common::Report], flops: units::FlopsUnit) -> table::Table {
+    let mut groups = BTreeMap::<Option<cache::Regime>, Vec<f64>>::new();
+    for report in reports {
+        groups
+            .entry(report.working_set.as_ref().map(|x| x.fits))
+            .or_default()
+            .push(hook::gflops(report));
     }
 
+    let geomean = format!("{} (geomean)", flops.label());
+    let mut table = table::Table::new(&["Regime", "Reports", &geomean]);
+    for (regime, gflops) in groups {
+        let geomean = (gflops.iter().map(|x| x.ln()).sum::<f64>() / gflops.len() as f64).exp();
+        table.push(vec![
+            regime.map_or("unknown".to_string(), |x| x.to_string()),
+            gflops.len().to_string(),
+            format!("{:.3}", flops.of(geomean)),
+        ]);
+    }
+    table.print();
+    table
+}
+
+/// One row per report with its shape, times and throughput, as `--export` writes the reports
+/// behind a merged report.
+fn reports_table(reports: &[common::Report], units: units::Units) -> table::Table {
+    let suffix = units.time.suffix();
+    let columns = [
+        format!("Medium ({})", suffix),
+        format!("Average ({})", suffix),
+        format!("Deviation ({})", suffix),
+    ];
+    let mut table = table::Table::new(&[
+        "Name",
+        "M",
+        "N",
+        "K",
+        "Repeats",
+        &columns[0],
+        &columns[1],
+        &columns[2],
+        units.flops.label(),
+    ]);
+    for report in reports {
+        let statistics = &report.statistics;
+        let (m, n, k) = report.dimensions;
+        table.push(vec![
+            report.name.clone(),
+            m.to_string(),
+            n.to_string(),
+            k.to_string(),
+            report.repeats.to_string(),
+            statistics
+                .medium
+                .map_or("-".to_string(), |x| format!("{:.6}", units.time.of(x))),
+            format!("{:.6}", units.time.of_millis(statistics.average)),
+            format!("{:.6}", units.time.of_millis(statistics.deviation)),
+            format!("{:.3}", units.flops.of(hook::gflops(report))),
+        ]);
+    }
+    table
+}
+
+/// Summarize the campaign under `directory`, print it or write it to `--out`, and return the
+/// table of best known results.
+fn campaign(directory: &str, args: &Arguments) -> table::Table {
+    let mut campaign = campaign::Campaign::walk(directory).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1)
+    });
+    campaign.entries.retain(|x| matches(&x.report, args));
+    if campaign.entries.is_empty() {
+        eprintln!("Error: no report matched.");
+        process::exit(1)
+    }
+    if !campaign.skipped.is_empty() {
+        eprintln!(
+            "Warning: skipped {} JSON files that hold no report, e.g. {}",
+            campaign.skipped.len(),
+            campaign.skipped[0]
+        );
+    }
+
+    let flops = args.flops_units;
+    match &args.out {
+        Some(out) => {
+            let summary = if out.ends_with(".html") {
+                campaign.html(flops)
+            } else {
+                campaign.markdown(flops)
+            };
+            fs::write(out, summary).unwrap_or_else(|e| {
+                eprintln!("Error: failed to save campaign summary: {}", e);
+                process::exit(1)
+            });
+        }
+        None => print!("{}", campaign.markdown(flops)),
+    }
+    campaign.best(flops)
+}
+
+/// Write `table` to the file of `--export`, if any.
+fn export(table: &table::Table, args: &Arguments) {
+    if let Some(export) = &args.export {
+        table.export(export).unwrap_or_else(|e| {
+            eprintln!("Error: failed to export table: {}", e);
+            process::exit(1)
+        });
+    }
+}
+
+fn main() {
+    let args: Arguments = argh::from_env();
+    let units = units::Units {
+        time: args.units,
+        flops: args.flops_units,
+    };
+
+    if let Some(file) = &args.search_log {
+        export(&search_log(file, units.flops), &args);
+        return;
+    }
+
+    if let Some(a) = &args.diff_history {
+        let [b] = args.reports.as_slice() else {
+            eprintln!("Error: --diff-history compares with exactly one other history file.");
+            process::exit(1)
+        };
+        export(&diff_history(a, b, &args), &args);
+        return;
+    }
+    if args.svg.is_some() {
+        eprintln!("Error: --svg requires --diff-history");
+        process::exit(1)
+    }
+
+    if let Some(directory) = &args.campaign {
+        if !args.reports.is_empty() {
+            eprintln!("Error: --campaign reads its reports from the directory alone.");
+            process::exit(1)
+        }
+        export(&campaign(directory, &args), &args);
+        return;
+    }
+
+    if args.export.is_some()
+        && (args.validate || args.flatten.is_some() || args.out.is_some() || args.redact.is_some())
     {
-        let mut minimum = common::Duration::MAX;
-        for report in &reports {
-            if minimum > report.statistics.minimum {
-                minimum = report.statistics.minimum;
-            }
+        eprintln!(
+            "Error: --export cannot be combined with --validate, --flatten, --out or --redact"
+        );
+        process::exit(1)
+    }
+
+    if args.validate {
+        if !validate(&args.reports, &args) {
+            process::exit(1)
         }
-        report.statistics.minimum = minimum;
+        return;
     }
 
-    report.statistics.average = reports.iter().fold(0.0, |acc, x| {
-        acc + x.statistics.average * x.repeats as f64 / report.repeats as f64
-    });
+    let reports = load(&args.reports, &args);
+
+    if reports.is_empty() {
+        eprintln!("Error: no report matched.");
+        process::exit(1)
+    }
+
+    if !args.pair_with.is_empty() {
+        let table = compare(
+            &reports,
+            &load(&args.pair_with, &args),
+            args.significance,
+            units.flops,
+        );
+        export(&table, &args);
+        return;
+    }
+
+    if args.drift {
+        export(&drift(&reports, args.significance, units.flops), &args);
+        return;
+    }
+
+    if args.fit_model {
+        export(&fit_model(&reports, &args.predict, units), &args);
+        return;
+    }
+
+    if args.group_by_cache {
+        export(&group_by_cache(&reports, units.flops), &args);
+        return;
+    }
+
+    if let Some(dir) = args.flatten {
+        fs::create_dir_all(&dir).expect("Error: failed to create output directory");
+        for (index, report) in reports.iter().enumerate() {
+            let file = path::Path::new(&dir).join(format!("{}.json", index));
+            fs::write(
+                &file,
+                serde_json::to_string(report).expect("Error: failed to serialize"),
+            )
+            .expect("Error: failed to save report");
+            println!("{}", file.display());
+        }
+        return;
+    }
 
-    // TODO: deviation
+    // a single redacted report is shared as is, without going through the merge.
+    if args.redact.is_some() && reports.len() == 1 {
+        let json = serde_json::to_string(&reports[0]).expect("Error: failed to serialize");
+        match args.out {
+            Some(out) => fs::write(out, json).expect("Error: failed to save redacted report"),
+            None => println!("{}", json),
+        }
+        return;
+    }
+
+    let report = common::Report::merge(&reports).unwrap_or_else(|e| {
+        eprintln!("Error: {}.", e);
+        process::exit(1)
+    });
 
-    if let Some(mut file) = args.out.and_then(|x| fs::File::create(x).ok()) {
+    if let Some(mut file) = args.out.as_ref().and_then(|x| fs::File::create(x).ok()) {
         file.write_all(
             serde_json::to_string(&report)
                 .expect("Error: failed to serialize")
@@ -97,5 +665,14 @@ fn main() {
         return;
     }
 
-    println!("{}", report.full().unwrap());
+    if args.redact.is_some() {
+        println!(
+            "{}",
+            serde_json::to_string(&report).expect("Error: failed to serialize")
+        );
+        return;
+    }
+
+    println!("{}", report.full_in(units).unwrap());
+    export(&reports_table(&reports, units), &args);
 }
@@ -0,0 +1,38 @@
+use std::{fs, io, path, time::SystemTime};
+
+/// Archive of everything needed to reproduce a benchmark result.
+#[derive(Default)]
+pub struct Bundle {
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+impl Bundle {
+    pub fn new() -> Self {
+        Bundle {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, data: impl Into<Vec<u8>>) {
+        self.entries.push((name.into(), data.into()));
+    }
+
+    /// Write entries as a zstd-compressed tarball.
+    pub fn write(&self, out: &path::Path) -> io::Result<()> {
+        let mtime = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |x| x.as_secs());
+
+        let encoder = zstd::stream::write::Encoder::new(fs::File::create(out)?, 0)?;
+        let mut builder = tar::Builder::new(encoder);
+        for (name, data) in &self.entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(mtime);
+            builder.append_data(&mut header, name, data.as_slice())?;
+        }
+        builder.into_inner()?.finish()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A data or unified cache level, as seen from one CPU.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Cache {
+    pub level: u8,
+    /// bytes
+    pub size: usize,
+}
+
+/// Where a working set fits.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub enum Regime {
+    L1,
+    L2,
+    L3,
+    L4,
+    Dram,
+}
+
+impl fmt::Display for Regime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Regime::L1 => "L1",
+            Regime::L2 => "L2",
+            Regime::L3 => "L3",
+            Regime::L4 => "L4",
+            Regime::Dram => "DRAM",
+        })
+    }
+}
+
+impl Regime {
+    fn of_level(level: u8) -> Regime {
+        match level {
+            1 => Regime::L1,
+            2 => Regime::L2,
+            3 => Regime::L3,
+            4 => Regime::L4,
+            _ => Regime::Dram,
+        }
+    }
+}
+
+/// Bytes of A, B and C, and the smallest cache level all of them fit in together.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkingSet {
+    pub a: usize,
+    pub b: usize,
+    pub c: usize,
+    pub fits: Regime,
+    /// Caches the working set was compared against.
+    pub caches: Vec<Cache>,
+}
+
+impl WorkingSet {
+    pub fn new((m, n, k): (usize, usize, usize), caches: Vec<Cache>) -> Self {
+        let element = size_of::<f64>();
+        let bytes = |x: usize, y: usize| x.saturating_mul(y).saturating_mul(element);
+        WorkingSet::from_bytes((bytes(m, k), bytes(k, n), bytes(m, n)), caches)
+    }
+
+    /// Working set of A, B and C taking `a`, `b` and `c` bytes, e.g. of a whole batch.
+    pub fn from_bytes((a, b, c): (usize, usize, usize), caches: Vec<Cache>) -> Self {
+        let total = a.saturating_add(b).saturating_add(c);
+        let fits = caches
+            .iter()
+            .filter(|x| x.size >= total)
+            .map(|x| Regime::of_level(x.level))
+            .min()
+            .unwrap_or(Regime::Dram);
+        WorkingSet {
+            a,
+            b,
+            c,
+            fits,
+            caches,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.a.saturating_add(self.b).saturating_add(self.c)
+    }
+}
+
+/// Human-readable byte count, e.g. "1.5MiB".
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}B", bytes)
+    } else {
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+}
+
+/// Parse a sysfs cache size, e.g. "32K" or "1024K".
+#[cfg(target_os = "linux")]
+fn parse_size(value: &str) -> Option<usize> {
+    let value = value.trim();
+    let (number, factor) = match value.chars().last()? {
+        'K' => (&value[..value.len() - 1], 1 << 10),
+        'M' => (&value[..value.len() - 1], 1 << 20),
+        'G' => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1),
+    };
+    number.parse::<usize>().ok().map(|x| x * factor)
+}
+
+/// Data and unified caches of CPU 0, from sysfs.
+#[cfg(target_os = "linux")]
+pub fn detect() -> Vec<Cache> {
+    use std::fs;
+
+    let mut caches = Vec::new();
+    for index in 0.. {
+        let dir = format!("/sys/devices/system/cpu/cpu0/cache/index{}", index);
+        let read = |name: &str| fs::read_to_string(format!("{}/{}", dir, name)).ok();
+        let Some(kind) = read("type") else {
+            break;
+        };
+        if kind.trim() == "Instruction" {
+            continue;
+        }
+        let level = read("level").and_then(|x| x.trim().parse::<u8>().ok());
+        let size = read("size").and_then(|x| parse_size(&x));
+        if let (Some(level), Some(size)) = (level, size) {
+            caches.push(Cache { level, size });
+        }
+    }
+    caches.sort_by_key(|x| x.level);
+    caches
+}
+
+/// Data and unified caches, from sysctl.
+#[cfg(target_os = "macos")]
+pub fn detect() -> Vec<Cache> {
+    use std::ffi::CString;
+
+    let query = |name: &str| -> Option<usize> {
+        let name = CString::new(name).ok()?;
+        let mut value: u64 = 0;
+        let mut length = size_of::<u64>();
+        let result = unsafe {
+            libc::sysctlbyname(
+                name.as_ptr(),
+                &mut value as *mut u64 as *mut libc::c_void,
+                &mut length,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        (result == 0 && value > 0).then_some(value as usize)
+    };
+    [
+        (1, "hw.l1dcachesize"),
+        (2, "hw.l2cachesize"),
+        (3, "hw.l3cachesize"),
+    ]
+    .into_iter()
+    .filter_map(|(level, name)| query(name).map(|size| Cache { level, size }))
+    .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn detect() -> Vec<Cache> {
+    Vec::new()
+}
@@ -0,0 +1,299 @@
+use crate::{common::Report, hook, table::Table, units::FlopsUnit};
+use library::CBLAS_TRANSPOSE;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, path, time,
+};
+
+/// A report of a campaign and where it came from.
+pub struct Entry {
+    /// file the report was read from, relative to the campaign directory
+    pub file: String,
+    /// day the file was last written, YYYY-MM-DD in UTC
+    pub date: String,
+    pub report: Report,
+}
+
+impl Entry {
+    /// Shape, layout and transposes, which a kernel's best result is kept for.
+    pub fn configuration(&self) -> String {
+        let report = &self.report;
+        let (m, n, k) = report.dimensions;
+        let trans = |x| {
+            if x == CBLAS_TRANSPOSE::CblasTrans {
+                "T"
+            } else {
+                "N"
+            }
+        };
+        format!(
+            "{}x{}x{} {} {}{}",
+            m,
+            n,
+            k,
+            report.layout,
+            trans(report.transpose.0),
+            trans(report.transpose.1)
+        )
+    }
+
+    /// Hostname and CPU model of the machine, if the report recorded it.
+    pub fn machine(&self) -> String {
+        self.report
+            .environment
+            .machine
+            .as_ref()
+            .map_or("-".to_string(), |x| {
+                format!("{} ({})", x.hostname, x.cpu_model)
+            })
+    }
+
+    /// First line of the compiler version, or `-` for an object built beforehand.
+    pub fn compiler(&self) -> String {
+        self.report
+            .build
+            .as_ref()
+            .and_then(|x| x.compiler_version.lines().next())
+            .map_or("-".to_string(), str::to_string)
+    }
+}
+
+/// Every report found under a directory.
+pub struct Campaign {
+    pub directory: String,
+    pub entries: Vec<Entry>,
+    /// JSON files that hold no report, e.g. conformance scorecards
+    pub skipped: Vec<String>,
+}
+
+/// Date of `time` as YYYY-MM-DD in UTC, from the days since 1970 by the civil calendar.
+fn date(time: time::SystemTime) -> String {
+    let seconds = time
+        .duration_since(time::UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs()) as i64;
+    let days = seconds.div_euclid(86400) + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// JSON files under `directory` and its subdirectories, sorted.
+fn files(directory: &path::Path, out: &mut Vec<path::PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(directory)
+        .map_err(|e| format!("failed to read {}: {}", directory.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            files(&path, out)?;
+        } else if path.extension().is_some_and(|x| x == "json") {
+            out.push(path);
+        }
+    }
+    out.sort();
+    Ok(())
+}
+
+impl Campaign {
+    /// Read every report and suite report under `directory`.
+    pub fn walk(directory: &str) -> Result<Campaign, String> {
+        let root = path::Path::new(directory);
+        let mut paths = Vec::new();
+        files(root, &mut paths)?;
+
+        let mut campaign = Campaign {
+            directory: directory.to_string(),
+            entries: Vec::new(),
+            skipped: Vec::new(),
+        };
+        for file in paths {
+            let name = file
+                .strip_prefix(root)
+                .unwrap_or(&file)
+                .display()
+                .to_string();
+            let reports = fs::read_to_string(&file)
+                .map_err(|e| e.to_string())
+                .and_then(|x| crate::common::parse_reports(&x));
+            let Ok(reports) = reports else {
+                campaign.skipped.push(name);
+                continue;
+            };
+            let date = fs::metadata(&file)
+                .and_then(|x| x.modified())
+                .map_or("-".to_string(), date);
+            campaign
+                .entries
+                .extend(reports.into_iter().map(|report| Entry {
+                    file: name.clone(),
+                    date: date.clone(),
+                    report,
+                }));
+        }
+        Ok(campaign)
+    }
+
+    /// Files, reports, kernels, configurations and dates of the campaign.
+    pub fn overview(&self) -> Table {
+        let count =
+            |f: &dyn Fn(&Entry) -> String| self.entries.iter().map(f).collect::<BTreeSet<String>>();
+        let dates = count(&|x| x.date.clone());
+        let mut table = Table::new(&["Item", "Value"]);
+        for (item, value) in [
+            ("Files", count(&|x| x.file.clone()).len().to_string()),
+            ("Reports", self.entries.len().to_string()),
+            (
+                "Kernels",
+                count(&|x| x.report.name.clone()).len().to_string(),
+            ),
+            (
+                "Configurations",
+                count(&Entry::configuration).len().to_string(),
+            ),
+            (
+                "Dates",
+                match (dates.first(), dates.last()) {
+                    (Some(first), Some(last)) => format!("{} to {}", first, last),
+                    _ => "-".to_string(),
+                },
+            ),
+            ("Skipped files", self.skipped.len().to_string()),
+        ] {
+            table.push(vec![item.to_string(), value]);
+        }
+        table
+    }
+
+    /// The fastest report of every configuration over all kernels and dates, with where it
+    /// came from.
+    pub fn best(&self, flops: FlopsUnit) -> Table {
+        let mut best = BTreeMap::<String, (&Entry, f64, usize)>::new();
+        for entry in &self.entries {
+            let gflops = hook::gflops(&entry.report);
+            let slot = best
+                .entry(entry.configuration())
+                .or_insert((entry, gflops, 0));
+            slot.2 += 1;
+            if gflops > slot.1 {
+                (slot.0, slot.1) = (entry, gflops);
+            }
+        }
+        let mut table = Table::new(&[
+            "Configuration",
+            "Kernel",
+            flops.label(),
+            "Reports",
+            "Date",
+            "Machine",
+            "Compiler",
+            "File",
+        ]);
+        for (configuration, (entry, gflops, reports)) in best {
+            table.push(vec![
+                configuration,
+                entry.report.name.clone(),
+                format!("{:.3}", flops.of(gflops)),
+                reports.to_string(),
+                entry.date.clone(),
+                entry.machine(),
+                entry.compiler(),
+                entry.file.clone(),
+            ]);
+        }
+        table
+    }
+
+    /// Kernels by configurations, with the best throughput of every pair and `-` where a
+    /// kernel never ran a configuration.
+    pub fn index(&self, flops: FlopsUnit) -> Table {
+        let configurations = self
+            .entries
+            .iter()
+            .map(Entry::configuration)
+            .collect::<BTreeSet<String>>();
+        let mut kernels = BTreeMap::<&str, BTreeMap<String, f64>>::new();
+        for entry in &self.entries {
+            let gflops = hook::gflops(&entry.report);
+            let cell = kernels
+                .entry(&entry.report.name)
+                .or_default()
+                .entry(entry.configuration())
+                .or_insert(gflops);
+            *cell = cell.max(gflops);
+        }
+        let mut columns = vec!["Kernel"];
+        columns.extend(configurations.iter().map(String::as_str));
+        let mut table = Table::new(&columns);
+        for (kernel, cells) in kernels {
+            let mut row = vec![kernel.to_string()];
+            row.extend(configurations.iter().map(|x| {
+                cells
+                    .get(x)
+                    .map_or("-".to_string(), |x| format!("{:.3}", flops.of(*x)))
+            }));
+            table.push(row);
+        }
+        table
+    }
+
+    /// Dates with the kernels and configurations measured on them.
+    pub fn dates(&self) -> Table {
+        let mut dates = BTreeMap::<&str, (BTreeSet<&str>, BTreeSet<String>, usize)>::new();
+        for entry in &self.entries {
+            let date = dates.entry(&entry.date).or_default();
+            date.0.insert(&entry.report.name);
+            date.1.insert(entry.configuration());
+            date.2 += 1;
+        }
+        let mut table = Table::new(&["Date", "Reports", "Kernels", "Configurations"]);
+        for (date, (kernels, configurations, reports)) in dates {
+            table.push(vec![
+                date.to_string(),
+                reports.to_string(),
+                kernels.into_iter().collect::<Vec<&str>>().join(", "),
+                configurations.len().to_string(),
+            ]);
+        }
+        table
+    }
+
+    /// Sections of the summary: title and table.
+    fn sections(&self, flops: FlopsUnit) -> [(&'static str, Table); 4] {
+        [
+            ("Overview", self.overview()),
+            ("Best known results", self.best(flops)),
+            ("Kernels by configuration", self.index(flops)),
+            ("Dates", self.dates()),
+        ]
+    }
+
+    pub fn markdown(&self, flops: FlopsUnit) -> String {
+        let mut out = format!("# Campaign {}\n", self.directory);
+        for (title, table) in self.sections(flops) {
+            out.push_str(&format!("\n## {}\n\n", title));
+            out.push_str(&table.to_markdown());
+        }
+        out
+    }
+
+    /// A standalone HTML page of the summary.
+    pub fn html(&self, flops: FlopsUnit) -> String {
+        let title = format!("Campaign {}", crate::table::html_escape(&self.directory));
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\nbody {{ font-family: sans-serif; }}\ntable {{ border-collapse: collapse; }}\nth, td {{ border: 1px solid #ccc; padding: 2px 6px; }}\n</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+            title, title
+        );
+        for (title, table) in self.sections(flops) {
+            out.push_str(&format!("<h2>{}</h2>\n", title));
+            out.push_str(&table.to_html());
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{common, compiler, hook, session};
+use std::{
+    cell::RefCell,
+    env,
+    ffi::{c_char, c_int, CStr, CString},
+    panic, path, ptr,
+};
+
+/// Returned by the functions of `include/bench_dgemm.h` on success.
+pub const OK: c_int = 0;
+/// Returned on failure; `bench_dgemm_last_error` tells why.
+pub const ERROR: c_int = -1;
+
+/// `bench_dgemm_config`, the parameters of one `bench_dgemm_run`.
+#[repr(C)]
+pub struct Config {
+    /// kernel source, or an object built beforehand if it has the platform's library extension
+    pub kernel_path: *const c_char,
+    /// compiler of the kernel source, NULL for the platform default
+    pub compiler: *const c_char,
+    pub m: usize,
+    pub n: usize,
+    pub k: usize,
+    pub repeats: usize,
+    pub warm_up: usize,
+    pub alpha: f64,
+    pub beta: f64,
+    pub seed: u64,
+}
+
+/// `bench_dgemm_report`, opaque to C.
+pub struct Report(common::Report);
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn fail(message: String) -> c_int {
+    // an interior NUL would truncate the message, so it is cut there.
+    let message = CString::new(message).unwrap_or_else(|e| {
+        let end = e.nul_position();
+        CString::new(&e.into_vec()[..end]).unwrap()
+    });
+    LAST_ERROR.with(|x| *x.borrow_mut() = message);
+    ERROR
+}
+
+unsafe fn string(value: *const c_char, name: &str) -> Result<String, String> {
+    if value.is_null() {
+        return Err(format!("{} is NULL", name));
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map(str::to_string)
+        .map_err(|_| format!("{} is not UTF-8", name))
+}
+
+unsafe fn run(config: &Config) -> Result<common::Report, String> {
+    let kernel = string(config.kernel_path, "kernel_path")?;
+    let compiler = if config.compiler.is_null() {
+        compiler::default_compiler()
+    } else {
+        string(config.compiler, "compiler")?
+    };
+    let mut session = if path::Path::new(&kernel)
+        .extension()
+        .is_some_and(|x| x == env::consts::DLL_EXTENSION)
+    {
+        session::Session::open(&kernel)?
+    } else {
+        session::Session::compile(&kernel, &compiler)?
+    };
+    let mut run = session::Run::new((config.m, config.n, config.k));
+    run.repeats = config.repeats;
+    run.warm_up = config.warm_up;
+    run.alpha = config.alpha;
+    run.beta = config.beta;
+    run.seed = config.seed;
+    session.run(&run)?;
+    Ok(session.take_reports().remove(0))
+}
+
+/// Fill `config` with the defaults of the command line.
+///
+/// # Safety
+/// `config` is NULL or points to a writable `bench_dgemm_config`.
+#[no_mangle]
+pub unsafe extern "C" fn bench_dgemm_config_init(config: *mut Config) {
+    if let Some(config) = config.as_mut() {
+        *config = Config {
+            kernel_path: ptr::null(),
+            compiler: ptr::null(),
+            m: 0,
+            n: 0,
+            k: 0,
+            repeats: 10,
+            warm_up: 0,
+            alpha: 1.0,
+            beta: 1.0,
+            seed: 0,
+        };
+    }
+}
+
+/// Benchmark the kernel of `config` and store its report in `*report`, to be freed with
+/// `bench_dgemm_report_free`.
+///
+/// # Safety
+/// The strings of `config` are NUL-terminated, and `report` points to a writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bench_dgemm_run(config: *const Config, report: *mut *mut Report) -> c_int {
+    let (Some(config), false) = (config.as_ref(), report.is_null()) else {
+        return fail("config and report should not be NULL".to_string());
+    };
+    // a panic must not unwind into the caller's frames.
+    match panic::catch_unwind(|| run(config)) {
+        Ok(Ok(x)) => {
+            *report = Box::into_raw(Box::new(Report(x)));
+            OK
+        }
+        Ok(Err(e)) => fail(e),
+        Err(_) => fail("the benchmark panicked".to_string()),
+    }
+}
+
+/// Why the last failed call of this thread failed, valid until the next one fails.
+#[no_mangle]
+pub extern "C" fn bench_dgemm_last_error() -> *const c_char {
+    LAST_ERROR.with(|x| x.borrow().as_ptr())
+}
+
+/// Medium GFLOPS of `report`.
+///
+/// # Safety
+/// `report` is NULL or was returned by `bench_dgemm_run` and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn bench_dgemm_report_gflops(report: *const Report) -> f64 {
+    report.as_ref().map_or(f64::NAN, |x| hook::gflops(&x.0))
+}
+
+/// Medium time of one iteration in nanoseconds.
+///
+/// # Safety
+/// `report` is NULL or was returned by `bench_dgemm_run` and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn bench_dgemm_report_medium_ns(report: *const Report) -> f64 {
+    report
+        .as_ref()
+        .and_then(|x| x.0.statistics.medium)
+        .map_or(f64::NAN, |x| x.as_nanos())
+}
+
+/// `report` as JSON, as `--save-as` writes it, to be freed with `bench_dgemm_string_free`.
+///
+/// # Safety
+/// `report` is NULL or was returned by `bench_dgemm_run` and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn bench_dgemm_report_json(report: *const Report) -> *mut c_char {
+    report
+        .as_ref()
+        .and_then(|x| serde_json::to_string(&x.0).ok())
+        .and_then(|x| CString::new(x).ok())
+        .map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// # Safety
+/// `report` is NULL or was returned by `bench_dgemm_run` and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn bench_dgemm_report_free(report: *mut Report) {
+    if !report.is_null() {
+        drop(Box::from_raw(report));
+    }
+}
+
+/// # Safety
+/// `string` is NULL or was returned by `bench_dgemm_report_json` and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn bench_dgemm_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
@@ -0,0 +1,49 @@
+use crate::common::Report;
+use serde::{Deserialize, Serialize};
+use std::{fs, path};
+
+/// A suite entry that ran to completion.
+#[derive(Serialize, Deserialize)]
+pub struct Completed {
+    /// Benchmark arguments of the entry, which identify it across runs.
+    pub arguments: Vec<String>,
+    pub report: Report,
+}
+
+/// Progress of a suite, written after every entry so an interrupted run can be resumed.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub suite: String,
+    pub completed: Vec<Completed>,
+}
+
+impl Checkpoint {
+    pub fn new(suite: &str) -> Self {
+        Checkpoint {
+            suite: suite.to_string(),
+            completed: Vec::new(),
+        }
+    }
+
+    pub fn load(file: &path::Path) -> Result<Checkpoint, String> {
+        let file = fs::File::open(file).map_err(|e| e.to_string())?;
+        serde_json::from_reader(file).map_err(|e| e.to_string())
+    }
+
+    pub fn is_completed(&self, arguments: &[String]) -> bool {
+        self.completed.iter().any(|x| x.arguments == arguments)
+    }
+
+    pub fn complete(&mut self, arguments: Vec<String>, report: Report) {
+        self.completed.retain(|x| x.arguments != arguments);
+        self.completed.push(Completed { arguments, report });
+    }
+
+    /// Write through a temporary file, so a crash never leaves a truncated checkpoint behind.
+    pub fn save(&self, file: &path::Path) -> Result<(), String> {
+        let temp = file.with_extension("tmp");
+        let out = fs::File::create(&temp).map_err(|e| e.to_string())?;
+        serde_json::to_writer_pretty(out, self).map_err(|e| e.to_string())?;
+        fs::rename(&temp, file).map_err(|e| e.to_string())
+    }
+}
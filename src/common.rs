@@ -1,29 +1,259 @@
+use crate::{
+    affinity, batch,
+    cache::{self, WorkingSet},
+    conv,
+    counter::{self, Counters},
+    diagnostics::Diagnostics,
+    disassembly::{Extensions, Mix},
+    expect,
+    footprint::Footprint,
+    hints::Hint,
+    hook, lock, machine, methodology,
+    noise::Noise,
+    openmp::OpenMP,
+    units::{self, Units},
+};
 use library::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
 use rayon::{
     iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator},
     slice::ParallelSliceMut,
 };
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Metadata, NumberValidation, Schema, SchemaObject},
+    JsonSchema,
+};
 use serde::{Deserialize, Serialize};
-use std::fmt::{self, Write};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+};
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
-pub struct Duration(pub u128);
+/// A measured time in nanoseconds, which may have a fractional part, e.g. after averaging or
+/// subtracting an overhead. Never negative or NaN. Serialized as a number of nanoseconds, an
+/// integer when it is whole, as in reports written before fractions were kept.
+#[derive(Clone, Copy, Default)]
+pub struct Duration(f64);
 
 impl Duration {
-    const ZERO: Duration = Duration(0);
+    pub const ZERO: Duration = Duration(0.0);
     pub const MIN: Duration = Duration::ZERO;
-    pub const MAX: Duration = Duration(u128::MAX);
-}
+    pub const MAX: Duration = Duration(f64::MAX);
+
+    /// Negative and NaN `nanos` saturate to zero.
+    #[inline(always)]
+    pub fn from_nanos(nanos: f64) -> Self {
+        Duration(if nanos > 0.0 { nanos } else { 0.0 })
+    }
 
-impl Duration {
     #[inline(always)]
-    pub fn as_nanos(&self) -> u128 {
+    pub fn as_nanos(&self) -> f64 {
         self.0
     }
 
+    #[inline(always)]
+    pub fn as_micros(&self) -> f64 {
+        self.0 / 1000.0
+    }
+
+    #[inline(always)]
+    pub fn as_millis(&self) -> f64 {
+        self.0 / 1000.0 / 1000.0
+    }
+
+    #[inline(always)]
+    pub fn as_secs(&self) -> f64 {
+        self.0 / 1000.0 / 1000.0 / 1000.0
+    }
+
+    #[deprecated(note = "use `as_millis`")]
     #[inline(always)]
     pub fn as_milis(&self) -> f64 {
-        self.0 as f64 / 1000.0 / 1000.0
+        self.as_millis()
+    }
+
+    /// Difference to a shorter `other`, zero if `other` is longer.
+    #[inline(always)]
+    pub fn saturating_sub(self, other: Duration) -> Duration {
+        Duration::from_nanos(self.0 - other.0)
+    }
+
+    /// Mean of `durations`, zero if there are none.
+    pub fn mean(durations: impl IntoIterator<Item = Duration>) -> Duration {
+        let (sum, count) = durations
+            .into_iter()
+            .fold((0.0, 0usize), |(sum, count), x| (sum + x.0, count + 1));
+        Duration::from_nanos(sum / count.max(1) as f64)
+    }
+}
+
+impl From<std::time::Duration> for Duration {
+    fn from(value: std::time::Duration) -> Self {
+        Duration(value.as_nanos() as f64)
+    }
+}
+
+impl std::ops::Add for Duration {
+    type Output = Duration;
+
+    fn add(self, other: Duration) -> Duration {
+        Duration(self.0 + other.0)
+    }
+}
+
+impl std::ops::Div<f64> for Duration {
+    type Output = Duration;
+
+    fn div(self, divisor: f64) -> Duration {
+        Duration::from_nanos(self.0 / divisor)
+    }
+}
+
+// durations are never NaN, so they are totally ordered.
+impl PartialEq for Duration {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.total_cmp(&other.0).is_eq()
+    }
+}
+
+impl Eq for Duration {}
+
+impl PartialOrd for Duration {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Duration {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Serialize for Duration {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // whole nanoseconds below 2^53 are exact as integers.
+        if self.0.fract() == 0.0 && self.0 < 9007199254740992.0 {
+            serializer.serialize_u64(self.0 as u64)
+        } else {
+            serializer.serialize_f64(self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Duration::from_nanos)
+    }
+}
+
+impl JsonSchema for Duration {
+    fn schema_name() -> String {
+        "Duration".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::Number.into()),
+            number: Some(Box::new(NumberValidation {
+                minimum: Some(0.0),
+                ..Default::default()
+            })),
+            metadata: Some(Box::new(Metadata {
+                description: Some("nanoseconds".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+/// Time `pack_a` and `pack_b` of a packed kernel took.
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct Packing {
+    pub a: Duration,
+    pub b: Duration,
+}
+
+/// A convolution benchmarked as the GEMM im2col lowers it to, `--conv`.
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct Convolution {
+    pub shape: conv::Shape,
+    /// time of the kernel's `im2col` per iteration, averaged; None if it has none
+    pub im2col: Option<Duration>,
+}
+
+/// Paired comparison with another kernel whose iterations were interleaved with these on the
+/// same matrices, `--against`.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Against {
+    /// file name of the other kernel
+    pub kernel: String,
+    /// its durations, paired with `samples` by position
+    pub samples: Vec<Duration>,
+    /// change of throughput against it in percent, the geometric mean of the paired ratios
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub change: f64,
+    /// p-value of the Wilcoxon signed-rank test on the paired durations, None without
+    /// differences
+    #[serde(default, with = "crate::number::option")]
+    #[schemars(schema_with = "crate::number::option::schema")]
+    pub p: Option<f64>,
+    /// "faster", "slower", "no difference" or "untested"
+    pub verdict: String,
+}
+
+/// Wall time of the phases of a run in seconds, summed over sessions, to show where it goes
+/// besides the kernel.
+#[derive(PartialEq, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Phases {
+    /// compiling the kernel, 0 for an object built beforehand
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub compile: f64,
+    /// loading the compiled object and spawning its threads
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub load: f64,
+    /// generating and placing the matrices
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub generation: f64,
+    /// the verification call and the check of C
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub verification: f64,
+    /// warm-up calls, and packing once in packed mode
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub warm_up: f64,
+    /// the measured iterations and the harness's work between them
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub measurement: f64,
+    /// disassembling the kernel, detecting the machine and analyzing the samples
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub report: f64,
+    /// from the start of the process to the finished report, before it is written
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub total: f64,
+}
+
+impl Phases {
+    /// Add the phases of another session or run.
+    pub fn accumulate(&mut self, other: &Phases) {
+        self.compile += other.compile;
+        self.load += other.load;
+        self.generation += other.generation;
+        self.verification += other.verification;
+        self.warm_up += other.warm_up;
+        self.measurement += other.measurement;
+        self.report += other.report;
+        self.total += other.total;
     }
 }
 
@@ -64,12 +294,16 @@ impl Average<f64> for Vec<f64> {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Statistics {
     pub medium: Option<Duration>,
     pub maximum: Duration,
     pub minimum: Duration,
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
     pub average: f64,
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
     pub deviation: f64,
 }
 
@@ -100,7 +334,7 @@ impl From<&Vec<Duration>> for Statistics {
 
         let vec = records
             .par_iter()
-            .map(|x| x.as_milis())
+            .map(|x| x.as_millis())
             .collect::<Vec<f64>>();
         let average = {
             let average = vec.average();
@@ -125,61 +359,718 @@ impl From<&Vec<Duration>> for Statistics {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// One-way analysis of variance with sessions as groups.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Anova {
+    pub sessions: usize,
+    /// Between-session variance component in ms².
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub between: f64,
+    /// Within-session variance component in ms².
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub within: f64,
+    /// infinite or NaN without within-session variance, e.g. with one iteration per session
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub f: f64,
+}
+
+impl From<&Vec<Vec<Duration>>> for Anova {
+    fn from(sessions: &Vec<Vec<Duration>>) -> Self {
+        assert!(sessions.len() > 1);
+
+        let groups = sessions
+            .iter()
+            .map(|x| x.iter().map(|x| x.as_millis()).collect::<Vec<f64>>())
+            .collect::<Vec<Vec<f64>>>();
+        let means = groups
+            .iter()
+            .map(|x| unsafe { x.average().unwrap_unchecked() })
+            .collect::<Vec<f64>>();
+        let total = groups.iter().map(|x| x.len()).sum::<usize>();
+        let grand = groups.concat().average().unwrap();
+
+        let ss_between = groups
+            .iter()
+            .zip(&means)
+            .map(|(x, mean)| x.len() as f64 * (mean - grand).powi(2))
+            .sum::<f64>();
+        let ss_within = groups
+            .iter()
+            .zip(&means)
+            .map(|(x, mean)| x.iter().map(|x| (x - mean).powi(2)).sum::<f64>())
+            .sum::<f64>();
+
+        let ms_between = ss_between / (groups.len() - 1) as f64;
+        let ms_within = if total > groups.len() {
+            ss_within / (total - groups.len()) as f64
+        } else {
+            0.0
+        };
+        let size = total as f64 / groups.len() as f64;
+
+        Anova {
+            sessions: groups.len(),
+            between: ((ms_between - ms_within) / size).max(0.0),
+            within: ms_within,
+            f: ms_between / ms_within,
+        }
+    }
+}
+
+#[derive(Default, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Environment {
+    /// Environment variables injected into the kernel run.
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    #[serde(default)]
+    pub preload: Vec<String>,
+    #[serde(default)]
+    pub openmp: Option<OpenMP>,
+    /// CPUs the benchmark was pinned to; empty if not pinned.
+    #[serde(default)]
+    pub affinity: Vec<usize>,
+    /// NUMA nodes memory was bound to; empty if not bound.
+    #[serde(default)]
+    pub membind: Vec<usize>,
+    /// Rows per block of the first-touch placement of the matrices; None if not placed.
+    #[serde(default)]
+    pub first_touch: Option<usize>,
+    #[serde(default)]
+    pub machine: Option<machine::Machine>,
+}
+
+/// How the kernel was compiled.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Build {
+    /// command line of the compiler
+    pub command: String,
+    /// what the compiler printed when asked for its version
+    pub compiler_version: String,
+}
+
+/// What the benchmark prints after a run, chosen with `--show`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Show {
+    /// every section of `Report::full`
+    Full,
+    Summary,
+    /// one line of `Report::minimal_in`
+    Minimal,
+}
+
+impl<'a> TryFrom<&'a str> for Show {
+    type Error = String;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "full" => Ok(Show::Full),
+            "summary" => Ok(Show::Summary),
+            "minimal" => Ok(Show::Minimal),
+            x => Err(["expected one of [full, summary, minimal], but got ", x].concat()),
+        }
+    }
+}
+
+impl Show {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Show::Full => "full",
+            Show::Summary => "summary",
+            Show::Minimal => "minimal",
+        }
+    }
+}
+
+/// Result of a run. Reports round-trip through JSON bit-exactly, including values JSON numbers
+/// cannot express:
+///
+/// ```
+/// use benchmark::common::Report;
+///
+/// let json = r#"{
+///     "name": "kernel.c", "dimensions": [64, 32, 16], "repeats": 1,
+///     "alpha": 0.1, "beta": -0.0, "layout": 101, "transpose": [111, 112],
+///     "statistics": {
+///         "medium": 1234, "maximum": 1234, "minimum": 1234,
+///         "average": 0.001234, "deviation": 0.0
+///     },
+///     "anova": { "sessions": 2, "between": 1e-310, "within": 0.0, "f": "Infinity" },
+///     "residual": "NaN",
+///     "samples": [1234]
+/// }"#;
+/// let report = serde_json::from_str::<Report>(json).unwrap();
+/// let written = serde_json::to_string(&report).unwrap();
+/// let again = serde_json::from_str::<Report>(&written).unwrap();
+/// assert_eq!(serde_json::to_string(&again).unwrap(), written);
+///
+/// assert_eq!(again.alpha.to_bits(), 0.1f64.to_bits());
+/// assert_eq!(again.beta.to_bits(), (-0.0f64).to_bits());
+/// let anova = again.anova.unwrap();
+/// assert_eq!(anova.between.to_bits(), 1e-310f64.to_bits());
+/// assert_eq!(anova.f, f64::INFINITY);
+/// assert!(again.residual.unwrap().is_nan());
+/// ```
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Report {
     pub name: String,
     pub dimensions: (usize, usize, usize),
     pub repeats: usize,
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
     pub alpha: f64,
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
     pub beta: f64,
     pub layout: CBLAS_LAYOUT,
     pub transpose: (CBLAS_TRANSPOSE, CBLAS_TRANSPOSE),
     pub statistics: Statistics,
+    #[serde(default)]
+    pub anova: Option<Anova>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub meta: BTreeMap<String, String>,
+    /// Preprocessor definitions the kernel was compiled with.
+    #[serde(default)]
+    pub defines: BTreeMap<String, String>,
+    /// Whether thread pools were spawned before the first kernel call.
+    #[serde(default)]
+    pub prewarmed: bool,
+    #[serde(default)]
+    pub environment: Environment,
+    #[serde(default)]
+    pub working_set: Option<WorkingSet>,
+    /// Code size of the compiled kernel, if it is an ELF object.
+    #[serde(default)]
+    pub footprint: Option<Footprint>,
+    /// AVX-512 and AMX instructions in the kernel object, counted in its objdump disassembly on
+    /// x86-64; None elsewhere or without objdump.
+    #[serde(default)]
+    pub extensions: Option<Extensions>,
+    /// FMA, load, store and scalar instructions of the measured entry point and the functions
+    /// it calls, with the widest vector used; None without objdump.
+    #[serde(default)]
+    pub instruction_mix: Option<Mix>,
+    /// Largest normalized verification residual ||C - Ĉ|| / (||A|| ||B|| k ε) of all sessions.
+    #[serde(default, with = "crate::number::option")]
+    #[schemars(schema_with = "crate::number::option::schema")]
+    pub residual: Option<f64>,
+    /// Largest fraction of the calibrated tolerance 2·√(mn)·k·ε·|alpha|·‖A‖∞·‖B‖∞ that
+    /// ‖C - Ĉ‖ used, of all sessions; verification passed by a margin of its inverse.
+    #[serde(default, with = "crate::number::option")]
+    #[schemars(schema_with = "crate::number::option::schema")]
+    pub tolerance_used: Option<f64>,
+    /// Peak memory the first kernel call of a session allocated, e.g. for packing buffers,
+    /// the largest of all sessions.
+    #[serde(default)]
+    pub workspace_bytes: Option<usize>,
+    /// Most threads that ran during the first measured call of a session.
+    #[serde(default)]
+    pub active_threads: Option<usize>,
+    /// Groups of a batched run; `dimensions` are those of the first group then.
+    #[serde(default)]
+    pub batch: Vec<batch::Group>,
+    /// Strides of a strided batch, whose only group is in `batch`.
+    #[serde(default)]
+    pub batch_strides: Option<batch::Strides>,
+    /// Packing time of a packed kernel, averaged over sessions; the statistics only cover
+    /// `compute_packed` then.
+    #[serde(default)]
+    pub packing: Option<Packing>,
+    /// Convolution of `--conv` the GEMM is lowered from; the statistics are those of the GEMM
+    /// alone.
+    #[serde(default)]
+    pub convolution: Option<Convolution>,
+    /// Paired comparison with the kernel of `--against`, measured in the same run.
+    #[serde(default)]
+    pub against: Option<Against>,
+    /// Jitter of the node with no kernel running, timed before the repeats; None with
+    /// `--noise-samples 0`.
+    #[serde(default)]
+    pub noise: Option<Noise>,
+    /// How the run got the machine-wide lock; None with `--no-lock` or if it could not be
+    /// taken.
+    #[serde(default)]
+    pub lock: Option<lock::Locking>,
+    /// Where the wall time of the run went; None in reports of older versions.
+    #[serde(default)]
+    pub phases: Option<Phases>,
+    /// Products per packed B with `--reuse-b`; an iteration then covers all of them.
+    #[serde(default)]
+    pub reuse_b: Option<usize>,
+    /// Operations and bytes of an iteration by the formulas of `--flops-formula` and
+    /// `--bytes-formula`; None counts the operations of the GEMM.
+    #[serde(default)]
+    pub counters: Option<Counters>,
+    /// Outcomes of `--expect-*` assertions.
+    #[serde(default)]
+    pub expectations: Vec<expect::Expectation>,
+    /// Measured durations in order, for paired comparisons across runs.
+    #[serde(default)]
+    pub samples: Vec<Duration>,
+    /// Whether `--prune-slower-than` abandoned the run, which then has fewer `repeats` than requested.
+    #[serde(default)]
+    pub pruned: bool,
+    /// Outliers, clusters of slow iterations and bimodality found in `samples`.
+    #[serde(default)]
+    pub diagnostics: Option<Diagnostics>,
+    /// Advice derived from the run, printed at the end of the summary.
+    #[serde(default)]
+    pub hints: Vec<Hint>,
+    /// Units of the numbers in this report.
+    #[serde(default)]
+    pub units: units::Metadata,
+    /// How the kernel was compiled; None if an object built beforehand was loaded.
+    #[serde(default)]
+    pub build: Option<Build>,
+    /// Seed the input matrices were generated from: A from `seed + 100` and B from
+    /// `seed + 200`, plus 1000 for every further session of the command line.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// How the numbers of this report were obtained; None if the run did not record it.
+    #[serde(default)]
+    pub methodology: Option<methodology::Methodology>,
+}
+
+/// Reports of every configuration of a suite in one file, with the environment they share.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct SuiteReport {
+    /// Path of the suite file.
+    pub suite: String,
+    /// Environment of every report, whose own `environment` is left empty then.
+    #[serde(default)]
+    pub environment: Option<Environment>,
+    /// Seed the entries were shuffled with, which `--shuffle-seed` takes to run them in the same
+    /// order again; the position of every report is in its meta.
+    #[serde(default)]
+    pub shuffle_seed: Option<u64>,
+    pub reports: Vec<Report>,
+}
+
+impl SuiteReport {
+    /// Hoist the environment out of `reports` if they all share it.
+    pub fn new(suite: &str, mut reports: Vec<Report>) -> Self {
+        let shared = reports
+            .first()
+            .map(|x| x.environment.clone())
+            .filter(|x| reports.iter().all(|y| y.environment == *x));
+        if shared.is_some() {
+            for report in &mut reports {
+                report.environment = Environment::default();
+            }
+        }
+        SuiteReport {
+            suite: suite.to_string(),
+            environment: shared,
+            shuffle_seed: None,
+            reports,
+        }
+    }
+
+    /// Standalone reports, each with the shared environment restored.
+    pub fn flatten(self) -> Vec<Report> {
+        let mut reports = self.reports;
+        if let Some(environment) = self.environment {
+            for report in &mut reports {
+                report.environment = environment.clone();
+            }
+        }
+        reports
+    }
+}
+
+/// Reports of a report or suite report file.
+pub fn parse_reports(json: &str) -> Result<Vec<Report>, String> {
+    // a suite report holds the reports of all its entries.
+    match serde_json::from_str::<Report>(json) {
+        Ok(report) => Ok(vec![report]),
+        Err(_) => serde_json::from_str::<SuiteReport>(json)
+            .map(SuiteReport::flatten)
+            .map_err(|e| e.to_string()),
+    }
 }
 
 impl Report {
+    /// One report of all iterations of `reports`, which must share their parameters. Tags,
+    /// metadata, defines and the environment are kept where all of them agree.
+    pub fn merge(reports: &[Report]) -> Result<Report, String> {
+        if reports.is_empty() {
+            return Err("no report to merge".to_string());
+        }
+
+        let mut report = Report {
+            name: reports[0].name.clone(),
+            dimensions: reports[0].dimensions,
+            repeats: reports.iter().fold(0, |acc, x| acc + x.repeats),
+            alpha: reports[0].alpha,
+            beta: reports[0].beta,
+            layout: reports[0].layout,
+            transpose: reports[0].transpose,
+            statistics: Statistics::new(),
+            anova: None,
+            tags: reports[0]
+                .tags
+                .iter()
+                .filter(|tag| reports.iter().all(|x| x.tags.contains(tag)))
+                .cloned()
+                .collect(),
+            meta: reports[0]
+                .meta
+                .iter()
+                .filter(|(key, value)| reports.iter().all(|x| x.meta.get(*key) == Some(*value)))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            defines: reports[0]
+                .defines
+                .iter()
+                .filter(|(key, value)| reports.iter().all(|x| x.defines.get(*key) == Some(*value)))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            prewarmed: reports.iter().all(|x| x.prewarmed),
+            environment: if reports
+                .iter()
+                .all(|x| x.environment == reports[0].environment)
+            {
+                reports[0].environment.clone()
+            } else {
+                Environment::default()
+            },
+            working_set: reports[0].working_set.clone(),
+            footprint: reports
+                .iter()
+                .all(|x| x.footprint == reports[0].footprint)
+                .then(|| reports[0].footprint.clone())
+                .flatten(),
+            extensions: reports
+                .iter()
+                .all(|x| x.extensions == reports[0].extensions)
+                .then(|| reports[0].extensions.clone())
+                .flatten(),
+            instruction_mix: reports
+                .iter()
+                .all(|x| x.instruction_mix == reports[0].instruction_mix)
+                .then(|| reports[0].instruction_mix.clone())
+                .flatten(),
+            residual: reports.iter().filter_map(|x| x.residual).reduce(f64::max),
+            tolerance_used: reports
+                .iter()
+                .filter_map(|x| x.tolerance_used)
+                .reduce(f64::max),
+            workspace_bytes: reports.iter().filter_map(|x| x.workspace_bytes).max(),
+            active_threads: reports.iter().filter_map(|x| x.active_threads).max(),
+            batch: reports[0].batch.clone(),
+            batch_strides: reports[0].batch_strides,
+            reuse_b: reports[0].reuse_b,
+            counters: reports[0].counters.clone(),
+            expectations: Vec::new(),
+            samples: reports
+                .iter()
+                .flat_map(|x| x.samples.iter().copied())
+                .collect(),
+            pruned: reports.iter().any(|x| x.pruned),
+            // iterations of different runs are not one history.
+            diagnostics: (reports.len() == 1)
+                .then(|| reports[0].diagnostics.clone())
+                .flatten(),
+            hints: if reports.len() == 1 {
+                reports[0].hints.clone()
+            } else {
+                Vec::new()
+            },
+            units: units::Metadata::default(),
+            build: reports
+                .iter()
+                .all(|x| x.build == reports[0].build)
+                .then(|| reports[0].build.clone())
+                .flatten(),
+            seed: reports
+                .iter()
+                .all(|x| x.seed == reports[0].seed)
+                .then_some(reports[0].seed)
+                .flatten(),
+            methodology: reports
+                .iter()
+                .all(|x| x.methodology == reports[0].methodology)
+                .then(|| reports[0].methodology.clone())
+                .flatten(),
+            packing: {
+                let packing = reports.iter().filter_map(|x| x.packing).collect::<Vec<_>>();
+                (!packing.is_empty()).then(|| Packing {
+                    a: Duration::mean(packing.iter().map(|x| x.a)),
+                    b: Duration::mean(packing.iter().map(|x| x.b)),
+                })
+            },
+            convolution: reports[0].convolution.map(|x| {
+                let im2col = reports
+                    .iter()
+                    .filter_map(|x| x.convolution.and_then(|x| x.im2col))
+                    .collect::<Vec<Duration>>();
+                Convolution {
+                    im2col: (!im2col.is_empty()).then(|| Duration::mean(im2col)),
+                    ..x
+                }
+            }),
+            // the test covers the pairs of one run, so it is not pooled.
+            against: reports
+                .iter()
+                .all(|x| x.against == reports[0].against)
+                .then(|| reports[0].against.clone())
+                .flatten(),
+            // the noisiest node bounds what the merged deviation can be judged against.
+            noise: reports
+                .iter()
+                .filter_map(|x| x.noise.clone())
+                .max_by(|x, y| x.floor.total_cmp(&y.floor)),
+            lock: reports
+                .iter()
+                .filter_map(|x| x.lock.clone())
+                .reduce(|x, y| lock::Locking {
+                    contended: x.contended || y.contended,
+                    waited: x.waited + y.waited,
+                    ..x
+                }),
+            phases: reports.iter().filter_map(|x| x.phases).reduce(|mut x, y| {
+                x.accumulate(&y);
+                x
+            }),
+        };
+
+        for v in &reports[1..] {
+            if v.dimensions != report.dimensions
+                || v.alpha != report.alpha
+                || v.beta != report.beta
+                || v.layout != report.layout
+                || v.transpose != report.transpose
+                || v.batch != report.batch
+                || v.batch_strides != report.batch_strides
+                || v.reuse_b != report.reuse_b
+                || v.counters != report.counters
+                || v.convolution.map(|x| x.shape) != report.convolution.map(|x| x.shape)
+            {
+                return Err("cannot merge reports that have different parameters".to_string());
+            }
+        }
+
+        if reports.len() == 1 {
+            report.statistics.medium = reports[0].statistics.medium;
+        }
+
+        {
+            let mut maximum = Duration::MIN;
+            for report in reports {
+                if maximum < report.statistics.maximum {
+                    maximum = report.statistics.maximum;
+                }
+            }
+            report.statistics.maximum = maximum;
+        }
+
+        {
+            let mut minimum = Duration::MAX;
+            for report in reports {
+                if minimum > report.statistics.minimum {
+                    minimum = report.statistics.minimum;
+                }
+            }
+            report.statistics.minimum = minimum;
+        }
+
+        report.statistics.average = reports.iter().fold(0.0, |acc, x| {
+            acc + x.statistics.average * x.repeats as f64 / report.repeats as f64
+        });
+
+        // TODO: deviation
+
+        Ok(report)
+    }
+
+    /// Floating-point operations of one measured iteration.
+    pub fn flops(&self) -> f64 {
+        if let Some(flops) = self.counters.as_ref().and_then(|x| x.flops.as_ref()) {
+            flops.value
+        } else if self.batch.is_empty() {
+            benchmark_core::statistics::flops(self.dimensions, self.alpha, self.beta)
+                * self.reuse_b.unwrap_or(1) as f64
+        } else {
+            self.batch.iter().map(batch::Group::flops).sum()
+        }
+    }
+
+    /// Bytes one measured iteration moves by `--bytes-formula`.
+    pub fn bytes(&self) -> Option<f64> {
+        self.counters
+            .as_ref()
+            .and_then(|x| x.bytes.as_ref())
+            .map(|x| x.value)
+    }
+
     pub fn summary(&self) -> Result<String, fmt::Error> {
+        self.summary_in(Units::default())
+    }
+
+    /// Summary with times and throughput in `units`.
+    pub fn summary_in(&self, units: Units) -> Result<String, fmt::Error> {
         let mut out = String::new();
-        let ops = 2.0 * (self.dimensions.0 * self.dimensions.1 * self.dimensions.2) as f64;
+        let ops = self.flops();
         if let Some(medium) = self.statistics.medium {
             writeln!(
                 &mut out,
-                "Medium\t {:.6}ms \t {}",
-                medium.as_milis(),
-                ops / medium.as_nanos() as f64
+                "Medium\t {} \t {}",
+                units.time(medium),
+                units.flops(ops, medium)
             )?;
+            // the medium is that of the GEMM alone.
+            if let Some(im2col) = self.convolution.and_then(|x| x.im2col) {
+                writeln!(
+                    &mut out,
+                    "End-to-end\t {} \t {}",
+                    units.time(medium + im2col),
+                    units.flops(ops, medium + im2col)
+                )?;
+            }
         }
         writeln!(
             &mut out,
-            "Average\t {:.6}ms \t({})",
-            self.statistics.average,
-            ops / self.statistics.average / 1000.0 / 1000.0
+            "Average\t {} \t({})",
+            units.time_millis(self.statistics.average),
+            units
+                .flops
+                .of(ops / self.statistics.average / 1000.0 / 1000.0)
         )?;
         writeln!(
             &mut out,
-            "Worst\t {:.6}ms \t {}",
-            self.statistics.maximum.as_milis(),
-            ops / self.statistics.maximum.as_nanos() as f64
+            "Worst\t {} \t {}",
+            units.time(self.statistics.maximum),
+            units.flops(ops, self.statistics.maximum)
         )?;
         writeln!(
             &mut out,
-            "Best\t {:.6}ms \t {}",
-            self.statistics.minimum.as_milis(),
-            ops / self.statistics.minimum.as_nanos() as f64
+            "Best\t {} \t {}",
+            units.time(self.statistics.minimum),
+            units.flops(ops, self.statistics.minimum)
+        )?;
+        write!(
+            &mut out,
+            "Deviation\t {}",
+            units.time.of_millis(self.statistics.deviation)
         )?;
-        write!(&mut out, "Deviation\t {}", self.statistics.deviation)?;
+        if let Some(anova) = &self.anova {
+            write!(
+                &mut out,
+                "\nBetween-session\t {} \nWithin-session\t {} \nF({}, {})\t {:.4}",
+                units.time_millis(anova.between.sqrt()),
+                units.time_millis(anova.within.sqrt()),
+                anova.sessions - 1,
+                self.repeats - anova.sessions,
+                anova.f
+            )?;
+        }
+        for expectation in &self.expectations {
+            write!(
+                &mut out,
+                "\n{}\t {}: {:.3} (limit {:.3})",
+                if expectation.passed { "PASS" } else { "FAIL" },
+                expectation.name,
+                expectation.actual,
+                expectation.limit
+            )?;
+        }
+        if let Some(diagnostics) = &self.diagnostics {
+            let format = |x: &[usize]| {
+                x.iter()
+                    .map(|x| format!("#{}", x + 1))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            };
+            if !diagnostics.outliers.is_empty() {
+                write!(
+                    &mut out,
+                    "\nOutliers\t {} iterations beyond {} MADs ({})",
+                    diagnostics.outliers.len(),
+                    diagnostics.threshold,
+                    format(&diagnostics.outliers)
+                )?;
+            }
+            if !diagnostics.slow_clusters.is_empty() {
+                write!(
+                    &mut out,
+                    "\nSlow clusters\t {} (possible throttling)",
+                    diagnostics
+                        .slow_clusters
+                        .iter()
+                        .map(|x| format!("#{}-#{}", x.start + 1, x.start + x.length))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )?;
+            }
+            if let Some(bimodal) = &diagnostics.bimodal {
+                write!(
+                    &mut out,
+                    "\nBimodal\t {} and {} ({:.0}% slow, possibly NUMA page placement)",
+                    units.time(bimodal.low),
+                    units.time(bimodal.high),
+                    bimodal.high_fraction * 100.0
+                )?;
+            }
+        }
+        for hint in &self.hints {
+            write!(&mut out, "\nHint\t {}", hint.message)?;
+        }
         Ok(out)
     }
 
     pub fn full(&self) -> Result<String, fmt::Error> {
+        self.full_in(Units::default())
+    }
+
+    /// Full report with times and throughput in `units`.
+    pub fn full_in(&self, units: Units) -> Result<String, fmt::Error> {
         let mut out = String::new();
         writeln!(&mut out, "=== {} ===", self.name)?;
+        if !self.tags.is_empty() {
+            writeln!(&mut out, "Tags: {}", self.tags.join(", "))?;
+        }
+        for (key, value) in &self.meta {
+            writeln!(&mut out, "{}: {}", key, value)?;
+        }
+
+        writeln!(&mut out, "\n--- Problem ---")?;
         writeln!(
             &mut out,
             "M: {}, N: {}, K: {}",
             self.dimensions.0, self.dimensions.1, self.dimensions.2
         )?;
         writeln!(&mut out, "alpha: {:.4}, beta: {:.4}", self.alpha, self.beta)?;
+        for group in &self.batch {
+            writeln!(
+                &mut out,
+                "Batch: M: {}, N: {}, K: {}, count: {}, alpha: {:.4}, beta: {:.4}",
+                group.m, group.n, group.k, group.count, group.alpha, group.beta
+            )?;
+        }
+        if let Some(strides) = &self.batch_strides {
+            writeln!(
+                &mut out,
+                "Strides: A: {}, B: {}, C: {}",
+                strides.a, strides.b, strides.c
+            )?;
+        }
+        if let Some(convolution) = &self.convolution {
+            let (p, q) = convolution.shape.output();
+            writeln!(
+                &mut out,
+                "Convolution: {}, output {}x{}, im2col {}",
+                convolution.shape,
+                p,
+                q,
+                convolution
+                    .im2col
+                    .map_or("by the harness".to_string(), |x| units.time(x))
+            )?;
+        }
         writeln!(&mut out, "Layout: {}", self.layout)?;
         writeln!(
             &mut out,
@@ -191,7 +1082,374 @@ impl Report {
             "TransB: {}",
             self.transpose.1 == CBLAS_TRANSPOSE::CblasTrans
         )?;
-        out.write_str(self.summary()?.as_str())?;
+        if let Some(seed) = self.seed {
+            writeln!(&mut out, "Seed: {}", seed)?;
+        }
+        if let Some(working_set) = &self.working_set {
+            writeln!(
+                &mut out,
+                "Working set: A {}, B {}, C {}, total {} (fits {})",
+                cache::format_bytes(working_set.a),
+                cache::format_bytes(working_set.b),
+                cache::format_bytes(working_set.c),
+                cache::format_bytes(working_set.total()),
+                working_set.fits
+            )?;
+        }
+
+        if self.build.is_some()
+            || !self.defines.is_empty()
+            || self.footprint.is_some()
+            || self.extensions.is_some()
+            || self.instruction_mix.is_some()
+        {
+            writeln!(&mut out, "\n--- Build ---")?;
+        }
+        if let Some(build) = &self.build {
+            writeln!(&mut out, "Command: {}", build.command)?;
+            if let Some(version) = build.compiler_version.lines().next() {
+                writeln!(&mut out, "Compiler: {}", version)?;
+            }
+        }
+        for (name, value) in &self.defines {
+            writeln!(&mut out, "Define: {}={}", name, value)?;
+        }
+        if let Some(footprint) = &self.footprint {
+            writeln!(
+                &mut out,
+                "Code: .text {}, {}",
+                cache::format_bytes(footprint.text),
+                footprint
+                    .functions
+                    .iter()
+                    .map(|(name, size)| format!("{} {}", name, cache::format_bytes(*size)))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )?;
+        }
+        if let Some(extensions) = &self.extensions {
+            writeln!(
+                &mut out,
+                "Instructions: {} AVX-512, {} AMX",
+                extensions.avx512, extensions.amx
+            )?;
+        }
+        if let Some(mix) = &self.instruction_mix {
+            writeln!(
+                &mut out,
+                "Mix of {}: {} instructions, {} FMA, {} loads, {} stores, {} scalar, widest vector {}",
+                mix.symbol,
+                mix.instructions,
+                mix.fma,
+                mix.loads,
+                mix.stores,
+                mix.scalar,
+                if mix.scalable {
+                    "SVE".to_string()
+                } else if mix.vector_bits > 0 {
+                    format!("{} bits", mix.vector_bits)
+                } else {
+                    "none".to_string()
+                }
+            )?;
+        }
+
+        writeln!(&mut out, "\n--- Environment ---")?;
+        if let Some(machine) = &self.environment.machine {
+            writeln!(
+                &mut out,
+                "Machine: {} ({}, {} CPUs, governor {})",
+                machine.hostname,
+                machine.cpu_model,
+                machine.logical_cpus,
+                machine.governor.as_deref().unwrap_or("unknown")
+            )?;
+            if let Some(arm) = &machine.arm {
+                writeln!(
+                    &mut out,
+                    "ARM: SVE {}, mcpu {}",
+                    arm.sve_vector_length
+                        .map_or("none".to_string(), |x| format!("{} bits", x * 8)),
+                    arm.mcpu.as_deref().unwrap_or("unknown")
+                )?;
+                for core in &arm.cores {
+                    writeln!(
+                        &mut out,
+                        "Core: CPU {} part {}, capacity {}, max {} kHz",
+                        core.cpu,
+                        core.part,
+                        core.capacity.map_or("-".to_string(), |x| x.to_string()),
+                        core.max_frequency
+                            .map_or("-".to_string(), |x| x.to_string())
+                    )?;
+                }
+                for (name, frequency) in &arm.uncore_frequencies {
+                    writeln!(&mut out, "Uncore: {} at {} Hz", name, frequency)?;
+                }
+            }
+            if let Some(x86) = &machine.x86 {
+                writeln!(
+                    &mut out,
+                    "x86: AVX-512 {}{}, AMX {}, caches {}",
+                    if x86.avx512.is_empty() {
+                        "none".to_string()
+                    } else {
+                        x86.avx512.join(",")
+                    },
+                    if x86.avx512.is_empty() || x86.avx512_enabled {
+                        ""
+                    } else {
+                        " (disabled by the OS)"
+                    },
+                    if x86.amx.is_empty() {
+                        "none".to_string()
+                    } else {
+                        x86.amx.join(",")
+                    },
+                    x86.caches
+                        .iter()
+                        .map(|x| format!("L{} {}", x.level, cache::format_bytes(x.size)))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )?;
+                if let Some(hint) = &x86.frequency_hint {
+                    writeln!(&mut out, "Frequency: {}", hint)?;
+                }
+                if let Some(extensions) = &self.extensions {
+                    if !x86.avx512.is_empty() && extensions.avx512 == 0 {
+                        writeln!(
+                            &mut out,
+                            "Note: the CPU has AVX-512, but the kernel has no AVX-512 instructions"
+                        )?;
+                    }
+                }
+            }
+            for library in &machine.libraries {
+                writeln!(&mut out, "Library: {}", library)?;
+            }
+        }
+        for (key, value) in &self.environment.variables {
+            writeln!(&mut out, "Env: {}={}", key, value)?;
+        }
+        if !self.environment.preload.is_empty() {
+            writeln!(&mut out, "Preload: {}", self.environment.preload.join(":"))?;
+        }
+        if !self.environment.affinity.is_empty() {
+            writeln!(
+                &mut out,
+                "Affinity: {}",
+                affinity::format_cpu_list(&self.environment.affinity)
+            )?;
+        }
+        if !self.environment.membind.is_empty() {
+            writeln!(
+                &mut out,
+                "Memory bound to nodes: {}",
+                affinity::format_cpu_list(&self.environment.membind)
+            )?;
+        }
+        if let Some(rows) = self.environment.first_touch {
+            writeln!(&mut out, "First touch: blocks of {} rows", rows)?;
+        }
+        if let Some(openmp) = &self.environment.openmp {
+            writeln!(
+                &mut out,
+                "OpenMP: {} threads, dynamic: {}, bind: {}, places: {}",
+                openmp.max_threads,
+                openmp.dynamic,
+                openmp.proc_bind,
+                openmp.places.len()
+            )?;
+        }
+        writeln!(&mut out, "Prewarmed: {}", self.prewarmed)?;
+
+        if let Some(methodology) = &self.methodology {
+            writeln!(&mut out, "\n--- Methodology ---")?;
+            writeln!(
+                &mut out,
+                "Warm-up: {} calls per session",
+                methodology.warm_up
+            )?;
+            writeln!(
+                &mut out,
+                "Session gap: {}",
+                units.time(methodology.session_gap)
+            )?;
+            match methodology.jitter {
+                Some(jitter) => writeln!(
+                    &mut out,
+                    "Jitter: {} to {} before every measured iteration but the first",
+                    units.time(jitter.min),
+                    units.time(jitter.max)
+                )?,
+                None => writeln!(&mut out, "Jitter: none")?,
+            }
+            match methodology.flush {
+                methodology::Flush::None => {
+                    writeln!(&mut out, "Flush: none, caches stay warm between iterations")?
+                }
+            }
+            writeln!(&mut out, "Pinned: {}", methodology.pinned)?;
+            writeln!(&mut out, "Timer: {}", methodology.timer)?;
+            match methodology.max_residual {
+                Some(max) if methodology.verification != methodology::Verification::Skipped => {
+                    writeln!(
+                        &mut out,
+                        "Verification: {}, normalized residual at most {}",
+                        methodology.verification.describe(),
+                        max
+                    )?
+                }
+                _ => writeln!(
+                    &mut out,
+                    "Verification: {}",
+                    methodology.verification.describe()
+                )?,
+            }
+            writeln!(
+                &mut out,
+                "Regeneration: {}",
+                match methodology.regeneration {
+                    methodology::Regeneration::PerSession => "A and B anew for every session",
+                    methodology::Regeneration::Pooled => "A and B once per shape and seed",
+                }
+            )?;
+        }
+
+        writeln!(&mut out, "\n--- Run ---")?;
+        if let Some(reuse) = self.reuse_b {
+            writeln!(
+                &mut out,
+                "Reuse: B packed once per {} products, {} per product",
+                reuse,
+                units.time_millis(self.statistics.average / reuse as f64)
+            )?;
+            if let Some(packing) = self.packing {
+                writeln!(
+                    &mut out,
+                    "Packing: A {} per product, B {}",
+                    units.time(packing.a),
+                    units.time(packing.b)
+                )?;
+            }
+        } else if let Some(packing) = self.packing {
+            writeln!(
+                &mut out,
+                "Packing: A {}, B {} ({:.1} compute calls)",
+                units.time(packing.a),
+                units.time(packing.b),
+                (packing.a.as_millis() + packing.b.as_millis()) / self.statistics.average
+            )?;
+        }
+        if let Some(counters) = &self.counters {
+            let formula = |x: &Option<counter::Formula>, default: &str| {
+                x.as_ref().map_or(default.to_string(), |x| {
+                    format!("{} = {:e}", x.source, x.value)
+                })
+            };
+            writeln!(
+                &mut out,
+                "Counters: flops {}, bytes {}{}",
+                formula(&counters.flops, "of the GEMM"),
+                formula(&counters.bytes, "-"),
+                self.bytes().map_or(String::new(), |x| format!(
+                    ", arithmetic intensity {:.2} flop/byte",
+                    self.flops() / x
+                ))
+            )?;
+        }
+        if let Some(against) = &self.against {
+            writeln!(
+                &mut out,
+                "Against {}: {:+.2}% over {} pairs, p {}, {}",
+                against.kernel,
+                against.change,
+                against.samples.len(),
+                against.p.map_or("-".to_string(), |x| format!("{:.4}", x)),
+                against.verdict
+            )?;
+        }
+        if let Some(noise) = &self.noise {
+            writeln!(
+                &mut out,
+                "Noise floor: {:.2}% (kernel {:.2}%), idle loop {}, worst {:.1}x, clock read {:.0} ns",
+                noise.floor * 100.0,
+                self.statistics.deviation / self.statistics.average * 100.0,
+                units.time_millis(noise.loop_time / 1e6),
+                noise.worst,
+                noise.clock_read
+            )?;
+        }
+        if let Some(lock) = &self.lock {
+            if lock.contended {
+                writeln!(
+                    &mut out,
+                    "Lock: {}, waited {:.1} s for another run",
+                    lock.path, lock.waited
+                )?;
+            } else {
+                writeln!(&mut out, "Lock: {}, uncontended", lock.path)?;
+            }
+        }
+        if let Some(phases) = &self.phases {
+            let kernel = self.samples.iter().map(|x| x.as_secs()).sum::<f64>();
+            writeln!(
+                &mut out,
+                "Phases: compile {:.2} s, load {:.2} s, generation {:.2} s, verification {:.2} s, warm-up {:.2} s, measurement {:.2} s, report {:.2} s",
+                phases.compile,
+                phases.load,
+                phases.generation,
+                phases.verification,
+                phases.warm_up,
+                phases.measurement,
+                phases.report
+            )?;
+            writeln!(
+                &mut out,
+                "Wall time: {:.2} s, {:.2} s in measured kernel calls, {:.1}% overhead",
+                phases.total,
+                kernel,
+                (1.0 - kernel / phases.total) * 100.0
+            )?;
+        }
+        if self.pruned {
+            writeln!(&mut out, "Pruned: after {} iterations", self.repeats)?;
+        }
+        if let Some(residual) = self.residual {
+            writeln!(&mut out, "Normalized residual: {:.3}", residual)?;
+        }
+        match self.tolerance_used {
+            Some(used) if used > 0.0 => writeln!(
+                &mut out,
+                "Tolerance used: {:.1}% (margin {:.1}x)",
+                used * 100.0,
+                1.0 / used
+            )?,
+            Some(_) => writeln!(&mut out, "Tolerance used: none, C is exact")?,
+            None => {}
+        }
+        if let Some(active_threads) = self.active_threads {
+            writeln!(&mut out, "Active threads: {}", active_threads)?;
+        }
+        if let Some(workspace) = self.workspace_bytes {
+            writeln!(&mut out, "Workspace: {}", cache::format_bytes(workspace))?;
+        }
+        out.write_str(self.summary_in(units)?.as_str())?;
         Ok(out)
     }
+
+    /// One line of the name, medium time and throughput, for `--show minimal`.
+    pub fn minimal_in(&self, units: Units) -> String {
+        let time = match self.statistics.medium {
+            Some(medium) => units.time(medium),
+            None => units.time_millis(self.statistics.average),
+        };
+        format!(
+            "{}\t {} \t {:.3} {}",
+            self.name,
+            time,
+            units.flops.of(hook::gflops(self)),
+            units.flops.label()
+        )
+    }
 }
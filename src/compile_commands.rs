@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use std::{env, fs, path, process};
+
+/// An entry of clang's JSON compilation database.
+#[derive(Serialize, Deserialize)]
+pub struct CompileCommand {
+    pub directory: String,
+    pub arguments: Vec<String>,
+    pub file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+fn absolute(path: &str) -> String {
+    path::absolute(path)
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+impl CompileCommand {
+    pub fn new(command: &process::Command, file: &str, output: &str) -> Self {
+        CompileCommand {
+            directory: env::current_dir()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            arguments: [command.get_program()]
+                .into_iter()
+                .chain(command.get_args())
+                .map(|x| x.to_string_lossy().to_string())
+                .collect(),
+            file: absolute(file),
+            output: Some(absolute(output)),
+        }
+    }
+
+    pub fn is_for(&self, file: &str) -> bool {
+        path::Path::new(&self.directory).join(&self.file) == path::Path::new(&absolute(file))
+    }
+
+    /// Reconstruct the command verbatim, to be run in the original directory.
+    pub fn command(&self) -> Option<process::Command> {
+        let (program, args) = self.arguments.split_first()?;
+        let mut command = process::Command::new(program);
+        command.args(args);
+        command.current_dir(&self.directory);
+        Some(command)
+    }
+}
+
+pub fn load(database: &path::Path) -> Result<Vec<CompileCommand>, String> {
+    if !database.exists() {
+        return Ok(Vec::new());
+    }
+    let file = fs::File::open(database).map_err(|e| e.to_string())?;
+    serde_json::from_reader(file).map_err(|e| e.to_string())
+}
+
+/// Add `entry` to the database, replacing the previous entry for the same file.
+pub fn update(database: &path::Path, entry: CompileCommand) -> Result<(), String> {
+    let mut entries = load(database)?;
+    entries.retain(|x| x.file != entry.file);
+    entries.push(entry);
+
+    let file = fs::File::create(database).map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(file, &entries).map_err(|e| e.to_string())
+}
@@ -0,0 +1,312 @@
+use std::{path, process};
+
+/// Command-line conventions of a compiler driver.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// gcc, clang, icc, armclang and friends.
+    Gnu,
+    /// cl, clang-cl and icx-cl.
+    Msvc,
+}
+
+impl Profile {
+    pub fn infer(compiler: &str) -> Profile {
+        let name = path::Path::new(compiler)
+            .file_stem()
+            .map(|x| x.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if ["cl", "clang-cl", "icx-cl", "icl"].contains(&name.as_str()) {
+            Profile::Msvc
+        } else {
+            Profile::Gnu
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Profile {
+    type Error = String;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_uppercase().as_str() {
+            "GNU" => Ok(Profile::Gnu),
+            "MSVC" => Ok(Profile::Msvc),
+            x => Err(["expected one of [GNU, MSVC], but got ", x].concat()),
+        }
+    }
+}
+
+/// Sanitizer a verification-only build is instrumented with.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Sanitizer {
+    Address,
+    Undefined,
+}
+
+impl<'a> TryFrom<&'a str> for Sanitizer {
+    type Error = String;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_uppercase().as_str() {
+            "ADDRESS" => Ok(Sanitizer::Address),
+            "UNDEFINED" => Ok(Sanitizer::Undefined),
+            x => Err(["expected one of [ADDRESS, UNDEFINED], but got ", x].concat()),
+        }
+    }
+}
+
+impl Sanitizer {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sanitizer::Address => "address",
+            Sanitizer::Undefined => "undefined",
+        }
+    }
+
+    /// Runtime options variable, e.g. ASAN_OPTIONS.
+    pub fn options_variable(&self) -> &'static str {
+        match self {
+            Sanitizer::Address => "ASAN_OPTIONS",
+            Sanitizer::Undefined => "UBSAN_OPTIONS",
+        }
+    }
+}
+
+/// clang and its derivatives link sanitizer runtimes into executables only,
+/// so a shared object needs `-shared-libsan`.
+fn is_clang(compiler: &String) -> bool {
+    let version = compiler_version(compiler);
+    version.contains("clang") || version.contains("LLVM")
+}
+
+/// Shared AddressSanitizer runtime of `compiler`, which has to be preloaded into the benchmark
+/// because an instrumented shared object cannot be loaded into an uninstrumented process otherwise.
+pub fn asan_runtime(compiler: &String) -> Option<String> {
+    let print = |arg: &str| {
+        process::Command::new(compiler)
+            .arg(arg)
+            .output()
+            .ok()
+            .filter(|x| x.status.success())
+            .map(|x| String::from_utf8_lossy(&x.stdout).trim().to_string())
+    };
+    // gcc prints the name back unchanged if it does not have the library.
+    if let Some(x) =
+        print("-print-file-name=libasan.so").filter(|x| path::Path::new(x).is_absolute())
+    {
+        return Some(x);
+    }
+    let directory = print("-print-runtime-dir")?;
+    [
+        ["libclang_rt.asan-", std::env::consts::ARCH, ".so"].concat(),
+        "libclang_rt.asan.so".to_string(),
+        "libclang_rt.asan_osx_dynamic.dylib".to_string(),
+    ]
+    .into_iter()
+    .map(|x| path::Path::new(&directory).join(x))
+    .find(|x| x.exists())
+    .map(|x| x.to_string_lossy().to_string())
+}
+
+pub fn default_compiler() -> String {
+    #[cfg(windows)]
+    return String::from("cl");
+    #[cfg(target_os = "macos")]
+    return String::from("clang");
+    #[cfg(all(not(windows), not(target_os = "macos"), target_arch = "aarch64"))]
+    return String::from("armclang");
+    #[cfg(all(
+        not(windows),
+        not(target_os = "macos"),
+        any(target_arch = "x86", target_arch = "x86_64")
+    ))]
+    return String::from("icc");
+}
+
+#[cfg(target_os = "macos")]
+fn build_extra_args(command: &mut process::Command) {
+    command.args(["-framework", "Accelerate"]);
+    command.arg("-mcpu=native");
+}
+
+#[cfg(all(not(target_os = "macos"), target_arch = "aarch64"))]
+fn build_extra_args(command: &mut process::Command) {
+    command.arg("-fopenmp");
+    command.arg("-lm");
+    command.arg("-armpl");
+    command.arg("-mcpu=native");
+}
+
+#[cfg(all(
+    not(target_os = "macos"),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+fn build_extra_args(command: &mut process::Command) {
+    command.arg("-lmkl_rt");
+    command.arg("-march=native");
+}
+
+enum Output<'a> {
+    SharedObject(&'a String),
+    Assembly(&'a String),
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn build_command(
+    compiler: &String,
+    profile: Profile,
+    compiler_args: &Option<String>,
+    override_mode: bool,
+    defines: &[(String, String)],
+    sanitizer: Option<Sanitizer>,
+    kernel: &String,
+    out: &String,
+) -> process::Command {
+    command(
+        compiler,
+        profile,
+        compiler_args,
+        override_mode,
+        defines,
+        sanitizer,
+        kernel,
+        Output::SharedObject(out),
+    )
+}
+
+/// Same as `build_command`, but emits the assembly of the kernel to `asm` instead.
+#[allow(clippy::too_many_arguments)]
+pub fn assembly_command(
+    compiler: &String,
+    profile: Profile,
+    compiler_args: &Option<String>,
+    override_mode: bool,
+    defines: &[(String, String)],
+    sanitizer: Option<Sanitizer>,
+    kernel: &String,
+    asm: &String,
+) -> process::Command {
+    command(
+        compiler,
+        profile,
+        compiler_args,
+        override_mode,
+        defines,
+        sanitizer,
+        kernel,
+        Output::Assembly(asm),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn command(
+    compiler: &String,
+    profile: Profile,
+    compiler_args: &Option<String>,
+    override_mode: bool,
+    defines: &[(String, String)],
+    sanitizer: Option<Sanitizer>,
+    kernel: &String,
+    output: Output,
+) -> process::Command {
+    let mut command = process::Command::new(compiler);
+    match profile {
+        Profile::Gnu => {
+            if !override_mode {
+                command.arg("-O3");
+                #[cfg(target_os = "linux")]
+                command.arg("-lnuma");
+                build_extra_args(&mut command);
+                command.args(["-Wall", "-Werror"]);
+                command.args(["-L", env!("PATH_LIBRARY")]);
+                command.args(["-I", env!("PATH_INCLUDE")]);
+            }
+            for (name, value) in defines {
+                command.arg(["-D", name, "=", value].concat());
+            }
+            if let Some(sanitizer) = sanitizer {
+                command.arg(["-fsanitize=", sanitizer.name()].concat());
+                command.args(["-fno-omit-frame-pointer", "-g"]);
+                if is_clang(compiler) {
+                    command.arg("-shared-libsan");
+                }
+            }
+            if let Some(args) = compiler_args {
+                command.args(args.split_whitespace());
+            }
+            match output {
+                Output::SharedObject(out) => {
+                    #[cfg(target_os = "macos")]
+                    command.arg("-dynamiclib");
+                    #[cfg(not(target_os = "macos"))]
+                    command.arg("-shared");
+                    command.args(["-o", out]);
+                }
+                // linking flags do not change code generation.
+                Output::Assembly(asm) => {
+                    command.args(["-S", "-o", asm]);
+                }
+            }
+            command.arg(kernel);
+        }
+        Profile::Msvc => {
+            command.arg("/nologo");
+            if !override_mode {
+                command.args(["/O2", "/openmp", "/W3", "/WX"]);
+                command.arg(["/I", env!("PATH_INCLUDE")].concat());
+            }
+            for (name, value) in defines {
+                command.arg(["/D", name, "=", value].concat());
+            }
+            if sanitizer == Some(Sanitizer::Address) {
+                command.arg("/fsanitize=address");
+            }
+            if let Some(args) = compiler_args {
+                command.args(args.split_whitespace());
+            }
+            match output {
+                Output::SharedObject(out) => {
+                    command.arg("/LD");
+                    command.arg(["/Fe", out].concat());
+                    command.arg(kernel);
+                    if !override_mode {
+                        // oneAPI ships the single dynamic library interface as mkl_rt.lib.
+                        command.args(["/link", &["/LIBPATH:", env!("PATH_LIBRARY")].concat()]);
+                        command.arg("mkl_rt.lib");
+                    }
+                }
+                Output::Assembly(asm) => {
+                    command.args(["/c", "/FA"]);
+                    command.arg(["/Fa", asm].concat());
+                    command.arg(["/Fo", asm, ".obj"].concat());
+                    command.arg(kernel);
+                }
+            }
+        }
+    }
+    command
+}
+
+pub fn command_line(command: &process::Command) -> String {
+    [command.get_program()]
+        .into_iter()
+        .chain(command.get_args())
+        .map(|x| x.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn compiler_version(compiler: &String) -> String {
+    let mut command = process::Command::new(compiler);
+    // cl prints its version banner when run without arguments.
+    if !path::Path::new(compiler)
+        .file_stem()
+        .is_some_and(|x| x.eq_ignore_ascii_case("cl"))
+    {
+        command.arg("--version");
+    }
+    command
+        .output()
+        .map(|x| [x.stdout, x.stderr].concat())
+        .map(|x| String::from_utf8_lossy(&x).to_string())
+        .unwrap_or_else(|e| e.to_string())
+}
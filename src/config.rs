@@ -0,0 +1,137 @@
+use std::{collections::BTreeSet, env, fs, path};
+
+/// Append `option` with `value` to command-line arguments; `true` becomes a switch,
+/// `false` is dropped and arrays repeat the option.
+pub fn push_value(out: &mut Vec<String>, option: &str, value: &toml::Value) {
+    match value {
+        toml::Value::Boolean(true) => out.push(option.to_string()),
+        toml::Value::Boolean(false) => {}
+        toml::Value::String(x) => out.extend([option.to_string(), x.clone()]),
+        toml::Value::Array(values) => {
+            for value in values {
+                push_value(out, option, value);
+            }
+        }
+        x => out.extend([option.to_string(), x.to_string()]),
+    }
+}
+
+pub fn load(file: &path::Path) -> Result<toml::Table, String> {
+    toml::from_str(&fs::read_to_string(file).map_err(|e| e.to_string())?).map_err(|e| e.to_string())
+}
+
+/// Prefix of environment variables that set options, e.g. `BENCHMARK_DGEMM_REPEATS=20`.
+pub const ENV_PREFIX: &str = "BENCHMARK_DGEMM_";
+
+/// Variables under `ENV_PREFIX` that are not options.
+pub const ENV_RESERVED: &[&str] = &[
+    "BLAS",
+    "KERNEL_RUN",
+    "MERGED",
+    "PRUNE_BELOW",
+    "SANITIZE_RUN",
+];
+
+/// Options set through environment variables, as a table for `merge`.
+/// `BENCHMARK_DGEMM_SKIP_VERIFICATION=1` becomes `skip-verification = true`; switches accept
+/// 1/0, true/false and yes/no.
+pub fn from_env(switches: &[&str]) -> Result<toml::Table, String> {
+    let mut table = toml::Table::new();
+    for (name, value) in env::vars() {
+        let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        if ENV_RESERVED.contains(&key) {
+            continue;
+        }
+        let key = key.to_lowercase().replace('_', "-");
+        let value = if switches.contains(&["--", key.as_str()].concat().as_str()) {
+            match value.to_lowercase().as_str() {
+                "1" | "true" | "yes" => toml::Value::Boolean(true),
+                "" | "0" | "false" | "no" => toml::Value::Boolean(false),
+                // a counted switch, e.g. BENCHMARK_DGEMM_VERBOSE=2.
+                x if x.parse::<u8>().is_ok() => toml::Value::Integer(x.parse().unwrap()),
+                x => {
+                    return Err([
+                        "expected a boolean for ",
+                        name.as_str(),
+                        ", but got '",
+                        x,
+                        "'",
+                    ]
+                    .concat())
+                }
+            }
+        } else {
+            toml::Value::String(value)
+        };
+        table.insert(key, value);
+    }
+    Ok(table)
+}
+
+/// Value of `option` in command-line arguments, if given.
+pub fn find_option<'a>(args: &'a [String], option: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|x| x == option)
+        .and_then(|x| args.get(x + 1))
+        .map(String::as_str)
+}
+
+/// Merge a configuration below command-line arguments.
+///
+/// Keys of `table` are long option names, with `_` or `-`; `kernel` and `out` are the positionals.
+/// An option given in `args` replaces the key entirely, so repeated options are not appended to.
+/// `switches` lists options that take no value and `short` maps short options to long ones,
+/// which is needed to tell values from positionals.
+pub fn merge(
+    args: &[String],
+    table: &toml::Table,
+    switches: &[&str],
+    short: &[(&str, &str)],
+) -> Vec<String> {
+    let mut given = BTreeSet::new();
+    let mut positionals = 0;
+    let mut tokens = args.iter();
+    while let Some(token) = tokens.next() {
+        if !token.starts_with('-') || token == "-" {
+            positionals += 1;
+            continue;
+        }
+        let option = short
+            .iter()
+            .find(|(x, _)| x == token)
+            .map_or(token.as_str(), |(_, x)| x);
+        if !switches.contains(&option) {
+            tokens.next();
+        }
+        given.insert(option.to_string());
+    }
+
+    let mut out = Vec::new();
+    if positionals == 0 {
+        for key in ["kernel", "out"] {
+            if let Some(toml::Value::String(x)) = table.get(key) {
+                out.push(x.clone());
+            }
+        }
+    }
+    out.extend(args.iter().cloned());
+    for (key, value) in table {
+        if key == "kernel" || key == "out" {
+            continue;
+        }
+        let option = ["--", key.replace('_', "-").as_str()].concat();
+        if given.contains(&option) {
+            continue;
+        }
+        match value {
+            // a counted switch, e.g. `verbose = 2` for -v -v.
+            toml::Value::Integer(count) if switches.contains(&option.as_str()) => {
+                out.extend((0..*count).map(|_| option.clone()));
+            }
+            _ => push_value(&mut out, &option, value),
+        }
+    }
+    out
+}
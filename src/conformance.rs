@@ -0,0 +1,344 @@
+use crate::{abi, kernel::Kernel, utils, verification};
+use library::{cblas_dgemm, CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+use serde::{Deserialize, Serialize};
+
+const LAYOUTS: [CBLAS_LAYOUT; 2] = [CBLAS_LAYOUT::CblasRowMajor, CBLAS_LAYOUT::CblasColMajor];
+const TRANSPOSES: [(CBLAS_TRANSPOSE, CBLAS_TRANSPOSE); 4] = [
+    (CBLAS_TRANSPOSE::CblasNoTrans, CBLAS_TRANSPOSE::CblasNoTrans),
+    (CBLAS_TRANSPOSE::CblasNoTrans, CBLAS_TRANSPOSE::CblasTrans),
+    (CBLAS_TRANSPOSE::CblasTrans, CBLAS_TRANSPOSE::CblasNoTrans),
+    (CBLAS_TRANSPOSE::CblasTrans, CBLAS_TRANSPOSE::CblasTrans),
+];
+/// Shapes every layout and transpose is checked at: degenerate, odd and larger than a block.
+const SIZES: [(usize, usize, usize); 4] = [(1, 1, 1), (7, 5, 3), (64, 64, 64), (129, 67, 33)];
+/// Shape of the checks of scalars, leading dimensions and state.
+const SHAPE: (usize, usize, usize) = (33, 17, 9);
+/// Value of the padding of C between its rows or columns, which a kernel must not write.
+const SENTINEL: f64 = -12345.0;
+
+/// Whether `x` and `y` hold the same bits, NaN included.
+fn same(x: &[f64], y: &[f64]) -> bool {
+    x.len() == y.len() && x.iter().zip(y).all(|(x, y)| x.to_bits() == y.to_bits())
+}
+
+/// One call of the kernel, checked against the reference BLAS.
+#[derive(Clone, Copy)]
+struct Case {
+    layout: CBLAS_LAYOUT,
+    transpose: (CBLAS_TRANSPOSE, CBLAS_TRANSPOSE),
+    dimensions: (usize, usize, usize),
+    alpha: f64,
+    beta: f64,
+    /// elements added to every leading dimension, filled with NaN in A and B
+    padding: usize,
+}
+
+impl Case {
+    fn new(layout: CBLAS_LAYOUT, dimensions: (usize, usize, usize)) -> Self {
+        Case {
+            layout,
+            transpose: TRANSPOSES[0],
+            dimensions,
+            alpha: 1.0,
+            beta: 1.0,
+            padding: 0,
+        }
+    }
+
+    fn name(&self) -> String {
+        let trans = |x| {
+            if x == CBLAS_TRANSPOSE::CblasTrans {
+                "T"
+            } else {
+                "N"
+            }
+        };
+        let (m, n, k) = self.dimensions;
+        let mut name = format!(
+            "{} {}{} {}x{}x{}, alpha {}, beta {}",
+            if self.layout == CBLAS_LAYOUT::CblasRowMajor {
+                "row-major"
+            } else {
+                "column-major"
+            },
+            trans(self.transpose.0),
+            trans(self.transpose.1),
+            m,
+            n,
+            k,
+            self.alpha,
+            self.beta
+        );
+        if self.padding > 0 {
+            name.push_str(&format!(", ld +{}", self.padding));
+        }
+        name
+    }
+
+    /// Call the kernel once on inputs generated from `seed` and check the result. Returns C
+    /// as the kernel left it.
+    fn run(&self, kernel: &Kernel, seed: u64) -> Result<Box<[f64]>, String> {
+        let (m, n, k) = self.dimensions;
+        let (trans_a, trans_b) = self.transpose;
+        let (lda, ldb, ldc) = utils::leading_dimensions(self.layout, trans_a, trans_b, (m, n, k));
+        // every line of a matrix is followed by `padding` elements.
+        let padded = |ld: usize, elements: usize, seed: u64, fill: f64| {
+            let lines = elements / ld;
+            let mut x = utils::fill_rand((ld + self.padding) * lines, seed, -1.0, 1.0);
+            for line in x.chunks_mut(ld + self.padding) {
+                line[ld..].fill(fill);
+            }
+            (x, ld + self.padding)
+        };
+        let (a, lda) = padded(lda, m * k, seed + 100, f64::NAN);
+        let (b, ldb) = padded(ldb, k * n, seed + 200, f64::NAN);
+        // elements of a line of C before its padding.
+        let width = ldc;
+        let (mut c, ldc) = padded(ldc, m * n, seed + 300, SENTINEL);
+        let entries = |x: &[f64]| {
+            (0..m)
+                .flat_map(|i| (0..n).map(move |j| (i, j)))
+                .map(|(i, j)| {
+                    x[verification::index(self.layout, CBLAS_TRANSPOSE::CblasNoTrans, ldc, i, j)]
+                })
+                .collect::<Vec<f64>>()
+        };
+        // with a beta of 0 the reference starts from zeros, and the kernel from NaN it must
+        // not read.
+        let mut reference = c.clone();
+        if self.beta == 0.0 {
+            for line in c.chunks_mut(ldc) {
+                verification::poison(&mut line[..width]);
+            }
+            for line in reference.chunks_mut(ldc) {
+                line[..width].fill(0.0);
+            }
+        }
+        let before = entries(&c);
+        let (a_before, b_before) = (a.clone(), b.clone());
+
+        kernel.run(
+            abi::Context::new(abi::Phase::Verification, 0, 0),
+            self.layout,
+            trans_a,
+            trans_b,
+            (m, n, k),
+            &a,
+            lda,
+            &b,
+            ldb,
+            &mut c,
+            ldc,
+            self.alpha,
+            self.beta,
+        );
+        unsafe {
+            cblas_dgemm(
+                self.layout,
+                trans_a,
+                trans_b,
+                m as _,
+                n as _,
+                k as _,
+                self.alpha,
+                a.as_ptr(),
+                lda as _,
+                b.as_ptr(),
+                ldb as _,
+                self.beta,
+                reference.as_mut_ptr(),
+                ldc as _,
+            );
+        }
+
+        if !same(&a, &a_before) || !same(&b, &b_before) {
+            return Err("the kernel wrote to A or B".to_string());
+        }
+        if c.chunks(ldc).any(|line| {
+            line[width..]
+                .iter()
+                .any(|x| x.to_bits() != SENTINEL.to_bits())
+        }) {
+            return Err("the kernel wrote to the padding of C".to_string());
+        }
+        let (actual, expected) = (entries(&c), entries(&reference));
+        if actual.iter().any(|x| x.is_nan()) {
+            return Err(if self.beta == 0.0 {
+                "C holds NaN, so the kernel read C although beta is 0 or left entries unwritten"
+            } else if self.padding > 0 {
+                "C holds NaN, so the kernel read the padding of A or B"
+            } else {
+                "C holds NaN"
+            }
+            .to_string());
+        }
+
+        let difference = actual
+            .iter()
+            .zip(&expected)
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f64>()
+            .sqrt();
+        let norms = (
+            verification::infinity_norm(self.layout, trans_a, lda, (m, k), &a),
+            verification::infinity_norm(self.layout, trans_b, ldb, (k, n), &b),
+        );
+        // scaling C by beta and adding it rounds as well.
+        let largest = before
+            .iter()
+            .filter(|x| !x.is_nan())
+            .fold(0.0f64, |x, y| x.max(y.abs()));
+        let tolerance = verification::tolerance(m * n, k, self.alpha, norms)
+            + 4.0 * ((m * n) as f64).sqrt() * f64::EPSILON * self.beta.abs() * largest;
+        let used = verification::used(difference, tolerance);
+        if used.is_nan() || used > 1.0 {
+            return Err(format!(
+                "||C - Ĉ|| is {:.3e}, {:.1}x the tolerance of {:.3e}",
+                difference, used, tolerance
+            ));
+        }
+        Ok(c)
+    }
+}
+
+/// Outcome of one check of a scorecard.
+#[derive(Serialize, Deserialize)]
+pub struct Check {
+    pub category: String,
+    pub name: String,
+    pub passed: bool,
+    #[serde(default)]
+    pub failure: Option<String>,
+}
+
+/// Checks a kernel has to pass to work with the harness, and how it did.
+#[derive(Serialize, Deserialize)]
+pub struct Scorecard {
+    pub kernel: String,
+    pub abi_version: u32,
+    pub passed: usize,
+    pub checks: Vec<Check>,
+}
+
+impl Scorecard {
+    pub fn total(&self) -> usize {
+        self.checks.len()
+    }
+
+    fn record(&mut self, category: &str, name: String, outcome: Result<(), String>) {
+        self.passed += outcome.is_ok() as usize;
+        self.checks.push(Check {
+            category: category.to_string(),
+            name,
+            passed: outcome.is_ok(),
+            failure: outcome.err(),
+        });
+    }
+}
+
+/// Run every check on `kernel`, which implements `abi_version`: all layouts and transposes at
+/// several sizes, special alphas and betas, the NaN trap of a beta of 0, padded leading
+/// dimensions and whether calls depend on earlier ones.
+pub fn run(name: &str, kernel: &Kernel, abi_version: u32) -> Scorecard {
+    let mut scorecard = Scorecard {
+        kernel: name.to_string(),
+        abi_version,
+        passed: 0,
+        checks: Vec::new(),
+    };
+    let mut check = |category: &str, case: Case| {
+        let outcome = case.run(kernel, 0).map(drop);
+        scorecard.record(category, case.name(), outcome);
+    };
+
+    for layout in LAYOUTS {
+        for transpose in TRANSPOSES {
+            for dimensions in SIZES {
+                check(
+                    "layout and transpose",
+                    Case {
+                        transpose,
+                        ..Case::new(layout, dimensions)
+                    },
+                );
+            }
+        }
+    }
+    for layout in LAYOUTS {
+        for (alpha, beta) in [
+            (0.0, 1.0),
+            (0.0, 2.0),
+            (-1.0, 2.0),
+            (0.5, -0.5),
+            (2.0, 1.0),
+            (-1.0, -1.0),
+        ] {
+            check(
+                "alpha and beta",
+                Case {
+                    alpha,
+                    beta,
+                    ..Case::new(layout, SHAPE)
+                },
+            );
+        }
+    }
+    for layout in LAYOUTS {
+        for transpose in [TRANSPOSES[0], TRANSPOSES[3]] {
+            for alpha in [1.0, -2.0, 0.0] {
+                check(
+                    "beta of 0",
+                    Case {
+                        transpose,
+                        alpha,
+                        beta: 0.0,
+                        ..Case::new(layout, SHAPE)
+                    },
+                );
+            }
+        }
+    }
+    for layout in LAYOUTS {
+        for transpose in TRANSPOSES {
+            for padding in [3, 16] {
+                check(
+                    "leading dimensions",
+                    Case {
+                        transpose,
+                        padding,
+                        ..Case::new(layout, SHAPE)
+                    },
+                );
+            }
+        }
+    }
+
+    for layout in LAYOUTS {
+        let case = Case {
+            beta: 0.0,
+            ..Case::new(layout, SHAPE)
+        };
+        scorecard.record("statelessness", case.name(), stateless(kernel, case));
+    }
+    scorecard
+}
+
+/// Whether `case` gives the same bits twice in a row, and again after a call of another shape.
+fn stateless(kernel: &Kernel, case: Case) -> Result<(), String> {
+    let other = Case {
+        transpose: TRANSPOSES[3],
+        ..Case::new(case.layout, SIZES[3])
+    };
+    let first = case.run(kernel, 0)?;
+    if !same(&case.run(kernel, 0)?, &first) {
+        return Err("two identical calls gave different results".to_string());
+    }
+    other.run(kernel, 1)?;
+    if !same(&case.run(kernel, 0)?, &first) {
+        return Err(format!(
+            "the result changed after a call of {}",
+            other.name()
+        ));
+    }
+    Ok(())
+}
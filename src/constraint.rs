@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Not,
+}
+
+#[derive(PartialEq, Debug)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Operator(Operator),
+    Open,
+    Close,
+}
+
+enum Expression {
+    Number(f64),
+    Name(String),
+    Unary(Operator, Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+}
+
+/// A condition on the tunables of a candidate, e.g. `MC % MR == 0` or `MC*KC*8 <= L2_BYTES/2`.
+/// Arithmetic is in floating point, and comparisons and `&&`, `||` and `!` take 0 as false.
+pub struct Constraint {
+    pub source: String,
+    expression: Expression,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let (token, length) = if c.is_ascii_digit() || c == '.' {
+            let length = rest
+                .find(|x: char| !x.is_ascii_digit() && x != '.')
+                .unwrap_or(rest.len());
+            let number = rest[..length]
+                .parse::<f64>()
+                .map_err(|_| format!("invalid number '{}'", &rest[..length]))?;
+            (Token::Number(number), length)
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let length = rest
+                .find(|x: char| !x.is_ascii_alphanumeric() && x != '_')
+                .unwrap_or(rest.len());
+            (Token::Name(rest[..length].to_string()), length)
+        } else {
+            let two = rest.get(..2).unwrap_or_default();
+            match two {
+                "||" => (Token::Operator(Operator::Or), 2),
+                "&&" => (Token::Operator(Operator::And), 2),
+                "==" => (Token::Operator(Operator::Equal), 2),
+                "!=" => (Token::Operator(Operator::NotEqual), 2),
+                "<=" => (Token::Operator(Operator::LessEqual), 2),
+                ">=" => (Token::Operator(Operator::GreaterEqual), 2),
+                _ => (
+                    match c {
+                        '<' => Token::Operator(Operator::Less),
+                        '>' => Token::Operator(Operator::Greater),
+                        '+' => Token::Operator(Operator::Add),
+                        '-' => Token::Operator(Operator::Subtract),
+                        '*' => Token::Operator(Operator::Multiply),
+                        '/' => Token::Operator(Operator::Divide),
+                        '%' => Token::Operator(Operator::Remainder),
+                        '!' => Token::Operator(Operator::Not),
+                        '(' => Token::Open,
+                        ')' => Token::Close,
+                        x => return Err(format!("unexpected '{}'", x)),
+                    },
+                    1,
+                ),
+            }
+        };
+        tokens.push(token);
+        rest = rest[length..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Binary operators by precedence, loosest first.
+const LEVELS: &[&[Operator]] = &[
+    &[Operator::Or],
+    &[Operator::And],
+    &[
+        Operator::Equal,
+        Operator::NotEqual,
+        Operator::Less,
+        Operator::LessEqual,
+        Operator::Greater,
+        Operator::GreaterEqual,
+    ],
+    &[Operator::Add, Operator::Subtract],
+    &[Operator::Multiply, Operator::Divide, Operator::Remainder],
+];
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expression, String> {
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(&Token::Operator(operator)) = self.peek() {
+            if !LEVELS[level].contains(&operator) {
+                break;
+            }
+            self.position += 1;
+            let right = self.binary(level + 1)?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some(&Token::Operator(operator @ (Operator::Subtract | Operator::Not))) => {
+                self.position += 1;
+                Ok(Expression::Unary(operator, Box::new(self.unary()?)))
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expression, String> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        match token {
+            Some(Token::Number(x)) => Ok(Expression::Number(*x)),
+            Some(Token::Name(x)) => Ok(Expression::Name(x.clone())),
+            Some(Token::Open) => {
+                let expression = self.binary(0)?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err("expected ')'".to_string());
+                }
+                self.position += 1;
+                Ok(expression)
+            }
+            Some(x) => Err(format!("unexpected {:?}", x)),
+            None => Err("unexpected end".to_string()),
+        }
+    }
+}
+
+pub fn parse(source: &str) -> Result<Constraint, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+    };
+    let expression = parser
+        .binary(0)
+        .map_err(|e| format!("{} in '{}'", e, source))?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("unexpected {:?} in '{}'", token, source));
+    }
+    Ok(Constraint {
+        source: source.to_string(),
+        expression,
+    })
+}
+
+fn evaluate(expression: &Expression, variables: &BTreeMap<String, f64>) -> Result<f64, String> {
+    let truth = |x: bool| if x { 1.0 } else { 0.0 };
+    Ok(match expression {
+        Expression::Number(x) => *x,
+        Expression::Name(x) => *variables
+            .get(x)
+            .ok_or_else(|| format!("unknown name '{}'", x))?,
+        Expression::Unary(Operator::Not, x) => truth(evaluate(x, variables)? == 0.0),
+        Expression::Unary(_, x) => -evaluate(x, variables)?,
+        Expression::Binary(operator, left, right) => {
+            let left = evaluate(left, variables)?;
+            // short-circuit, so `MR != 0 && MC % MR == 0` never divides by zero.
+            match operator {
+                Operator::And if left == 0.0 => return Ok(0.0),
+                Operator::Or if left != 0.0 => return Ok(1.0),
+                _ => {}
+            }
+            let right = evaluate(right, variables)?;
+            match operator {
+                Operator::Or | Operator::And => truth(right != 0.0),
+                Operator::Equal => truth(left == right),
+                Operator::NotEqual => truth(left != right),
+                Operator::Less => truth(left < right),
+                Operator::LessEqual => truth(left <= right),
+                Operator::Greater => truth(left > right),
+                Operator::GreaterEqual => truth(left >= right),
+                Operator::Add => left + right,
+                Operator::Subtract => left - right,
+                Operator::Multiply => left * right,
+                Operator::Divide => left / right,
+                Operator::Remainder => left % right,
+                Operator::Not => unreachable!(),
+            }
+        }
+    })
+}
+
+impl Constraint {
+    /// Whether the constraint holds for `variables`, the numeric tunables and constants.
+    pub fn holds(&self, variables: &BTreeMap<String, f64>) -> Result<bool, String> {
+        evaluate(&self.expression, variables)
+            .map(|x| x != 0.0)
+            .map_err(|e| format!("{} in '{}'", e, self.source))
+    }
+
+    /// Value of the expression for `variables`, e.g. a count of `--flops-formula`.
+    pub fn value(&self, variables: &BTreeMap<String, f64>) -> Result<f64, String> {
+        evaluate(&self.expression, variables).map_err(|e| format!("{} in '{}'", e, self.source))
+    }
+}
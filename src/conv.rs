@@ -0,0 +1,182 @@
+use library::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A forward convolution of an NCHW input with KCRS filters, benchmarked as the GEMM im2col
+/// lowers it to: the filters as A, K×CRS, times the lowered input as B, CRS×NPQ.
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct Shape {
+    /// images, N
+    pub batch: usize,
+    /// input channels, C
+    pub channels: usize,
+    /// H
+    pub height: usize,
+    /// W
+    pub width: usize,
+    /// output channels, K
+    pub filters: usize,
+    /// R
+    pub filter_height: usize,
+    /// S
+    pub filter_width: usize,
+    /// (vertical, horizontal)
+    pub stride: (usize, usize),
+    /// zeros added on either side, (vertical, horizontal)
+    pub padding: (usize, usize),
+}
+
+/// `2x1` as (2, 1), and `2` as (2, 2).
+fn parse_pair(value: &str) -> Result<(usize, usize), String> {
+    let number = |x: &str| {
+        x.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("invalid number '{}'", x))
+    };
+    match value.split_once('x') {
+        Some((x, y)) => Ok((number(x)?, number(y)?)),
+        None => number(value).map(|x| (x, x)),
+    }
+}
+
+/// Parse `n=32,c=64,h=56,w=56,k=64,r=3,s=3`, with optional `stride` (1) and `padding` (0),
+/// e.g. `stride=2` or `padding=1x0`.
+pub fn parse(value: &str) -> Result<Shape, String> {
+    let mut sizes = [None; 7];
+    let (mut stride, mut padding) = ((1, 1), (0, 0));
+    for pair in value.split(',') {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, got '{}'", pair))?;
+        let key = key.trim().to_lowercase();
+        match key.as_str() {
+            "stride" => stride = parse_pair(value)?,
+            "padding" => padding = parse_pair(value)?,
+            _ => {
+                let index = ["n", "c", "h", "w", "k", "r", "s"]
+                    .iter()
+                    .position(|&x| x == key)
+                    .ok_or_else(|| format!("unknown convolution parameter '{}'", key))?;
+                sizes[index] = Some(
+                    value
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|_| format!("invalid number '{}'", value))?,
+                );
+            }
+        }
+    }
+    if sizes.contains(&None) {
+        return Err("a convolution needs n, c, h, w, k, r and s".to_string());
+    }
+    if sizes.contains(&Some(0)) || stride.0 == 0 || stride.1 == 0 {
+        return Err("convolution parameters and strides should not be 0".to_string());
+    }
+    let [batch, channels, height, width, filters, filter_height, filter_width] =
+        sizes.map(Option::unwrap_or_default);
+    let shape = Shape {
+        batch,
+        channels,
+        height,
+        width,
+        filters,
+        filter_height,
+        filter_width,
+        stride,
+        padding,
+    };
+    if height + 2 * padding.0 < filter_height || width + 2 * padding.1 < filter_width {
+        return Err("the filter is larger than the padded input".to_string());
+    }
+    Ok(shape)
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pair = |(x, y): (usize, usize)| {
+            if x == y {
+                x.to_string()
+            } else {
+                format!("{}x{}", x, y)
+            }
+        };
+        write!(
+            f,
+            "n={},c={},h={},w={},k={},r={},s={},stride={},padding={}",
+            self.batch,
+            self.channels,
+            self.height,
+            self.width,
+            self.filters,
+            self.filter_height,
+            self.filter_width,
+            pair(self.stride),
+            pair(self.padding)
+        )
+    }
+}
+
+impl Shape {
+    /// Height and width of the output, P and Q.
+    pub fn output(&self) -> (usize, usize) {
+        (
+            (self.height + 2 * self.padding.0 - self.filter_height) / self.stride.0 + 1,
+            (self.width + 2 * self.padding.1 - self.filter_width) / self.stride.1 + 1,
+        )
+    }
+
+    /// (m, n, k) of the GEMM: K, N·P·Q and C·R·S.
+    pub fn gemm(&self) -> (usize, usize, usize) {
+        let (p, q) = self.output();
+        (
+            self.filters,
+            self.batch * p * q,
+            self.channels * self.filter_height * self.filter_width,
+        )
+    }
+
+    /// Elements of the input tensor.
+    pub fn input_size(&self) -> usize {
+        self.batch * self.channels * self.height * self.width
+    }
+
+    /// Lower `input` into `columns`, laid out like B of a GEMM with `layout` and `trans`: row
+    /// (c·R + r)·S + s, column (n·P + p)·Q + q holds the input element filter tap (r, s) sees at
+    /// output pixel (p, q), or 0 in the padding.
+    pub fn im2col(
+        &self,
+        input: &[f64],
+        layout: CBLAS_LAYOUT,
+        trans: CBLAS_TRANSPOSE,
+        (columns, ld): (&mut [f64], usize),
+    ) {
+        let (p, q) = self.output();
+        // rows of B are contiguous if it is row-major and not transposed, or the other way round.
+        let contiguous =
+            (layout == CBLAS_LAYOUT::CblasRowMajor) == (trans == CBLAS_TRANSPOSE::CblasNoTrans);
+        let (r, s) = (self.filter_height, self.filter_width);
+        for row in 0..self.channels * r * s {
+            let (channel, tap) = (row / (r * s), row % (r * s));
+            let (dy, dx) = (tap / s, tap % s);
+            for column in 0..self.batch * p * q {
+                let (image, pixel) = (column / (p * q), column % (p * q));
+                let y = (pixel / q * self.stride.0 + dy).checked_sub(self.padding.0);
+                let x = (pixel % q * self.stride.1 + dx).checked_sub(self.padding.1);
+                let value = match (y, x) {
+                    (Some(y), Some(x)) if y < self.height && x < self.width => {
+                        input
+                            [((image * self.channels + channel) * self.height + y) * self.width + x]
+                    }
+                    _ => 0.0,
+                };
+                let index = if contiguous {
+                    row * ld + column
+                } else {
+                    row + column * ld
+                };
+                columns[index] = value;
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{
+    batch,
+    constraint::{self, Constraint},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Names a formula can use.
+pub const VARIABLES: &[&str] = &["m", "n", "k", "batch"];
+
+/// A count of one measured iteration and the formula it came from.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Formula {
+    /// formula over m, n, k and batch, e.g. `2*m*n*k*batch`
+    pub source: String,
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub value: f64,
+}
+
+/// Counts of `--flops-formula` and `--bytes-formula`, for operations that are not a plain
+/// GEMM, e.g. a convolution lowered to one or a symmetric update.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Counters {
+    /// floating-point operations, None for those of the GEMM
+    pub flops: Option<Formula>,
+    /// bytes moved to and from memory
+    pub bytes: Option<Formula>,
+}
+
+fn variables(m: usize, n: usize, k: usize, batch: usize) -> BTreeMap<String, f64> {
+    VARIABLES
+        .iter()
+        .zip([m, n, k, batch])
+        .map(|(name, x)| (name.to_string(), x as f64))
+        .collect()
+}
+
+/// Parse `source` and check that it only uses `VARIABLES`.
+pub fn parse(source: &str) -> Result<Constraint, String> {
+    let formula = constraint::parse(source)?;
+    formula
+        .value(&variables(1, 1, 1, 1))
+        .map_err(|e| format!("{}; a formula can use {}", e, VARIABLES.join(", ")))?;
+    Ok(formula)
+}
+
+/// Value of `formula` for an iteration: for a batch, the sum over its groups with `batch` the
+/// problems of a group, otherwise `batch` is the products per B of `--reuse-b`, or 1.
+fn count(
+    formula: &Constraint,
+    (m, n, k): (usize, usize, usize),
+    groups: &[batch::Group],
+    reuse_b: Option<usize>,
+) -> Result<f64, String> {
+    if groups.is_empty() {
+        formula.value(&variables(m, n, k, reuse_b.unwrap_or(1)))
+    } else {
+        groups
+            .iter()
+            .map(|x| formula.value(&variables(x.m, x.n, x.k, x.count)))
+            .sum()
+    }
+}
+
+impl Counters {
+    /// Evaluate the formulas for an iteration on `dimensions`, or on `groups` of a batch; None
+    /// if there are none. Fails if one is not finite, e.g. divided by `batch-1` with one problem.
+    pub fn evaluate(
+        (flops, bytes): (Option<&str>, Option<&str>),
+        dimensions: (usize, usize, usize),
+        groups: &[batch::Group],
+        reuse_b: Option<usize>,
+    ) -> Result<Option<Counters>, String> {
+        let formula = |source: Option<&str>| {
+            source
+                .map(|x| {
+                    let value = count(&parse(x)?, dimensions, groups, reuse_b)?;
+                    if !value.is_finite() {
+                        return Err(format!("formula '{}' evaluates to {}", x, value));
+                    }
+                    Ok(Formula {
+                        source: x.to_string(),
+                        value,
+                    })
+                })
+                .transpose()
+        };
+        let counters = Counters {
+            flops: formula(flops)?,
+            bytes: formula(bytes)?,
+        };
+        Ok((counters.flops.is_some() || counters.bytes.is_some()).then_some(counters))
+    }
+}
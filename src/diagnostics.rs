@@ -0,0 +1,162 @@
+use crate::common::Duration;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// MADs above the median beyond which an iteration counts towards a cluster of slow ones.
+const SLOW: f64 = 2.0;
+/// Consecutive slow iterations that make a cluster.
+const CLUSTER: usize = 3;
+/// Iterations below which bimodality is not tested.
+const BIMODAL_MIN: usize = 10;
+/// Smallest share of iterations in either mode of a bimodal distribution.
+const MODE_MIN: f64 = 0.1;
+/// Ashman's D beyond which the two halves of the best split are separate modes; splitting a
+/// normal distribution gives 2.7, a uniform one 3.5.
+const SEPARATION: f64 = 4.0;
+
+/// Consecutive iterations that were all slow.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Cluster {
+    /// index of the first iteration
+    pub start: usize,
+    pub length: usize,
+}
+
+/// Two clearly separated modes of the iteration durations.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Bimodal {
+    /// mean of the faster mode
+    pub low: Duration,
+    /// mean of the slower mode
+    pub high: Duration,
+    /// share of iterations in the slower mode
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub high_fraction: f64,
+    /// Ashman's D of the two modes
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub separation: f64,
+}
+
+/// Findings of the analysis of the iteration history of a run.
+#[derive(Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Diagnostics {
+    /// MADs from the median beyond which an iteration is an outlier
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub threshold: f64,
+    /// indices of the outlying iterations
+    pub outliers: Vec<usize>,
+    /// runs of slow iterations, which suggest thermal or power throttling
+    pub slow_clusters: Vec<Cluster>,
+    /// modes of the durations if they are bimodal, which suggests lucky and unlucky NUMA page
+    /// placement
+    pub bimodal: Option<Bimodal>,
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    sorted[sorted.len() / 2]
+}
+
+/// Split sorted `values` into the two groups with the least within-group variance, as the index
+/// of the first value of the upper group.
+fn split(sorted: &[f64]) -> usize {
+    let total = sorted.iter().sum::<f64>();
+    let mut lower = 0.0;
+    let mut best = (0, f64::NEG_INFINITY);
+    for index in 1..sorted.len() {
+        lower += sorted[index - 1];
+        let (n, m) = (index as f64, (sorted.len() - index) as f64);
+        // least within-group variance is most between-group variance.
+        let between = n * m * (lower / n - (total - lower) / m).powi(2);
+        if between > best.1 {
+            best = (index, between);
+        }
+    }
+    best.0
+}
+
+fn moments(values: &[f64]) -> (f64, f64) {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    (mean, variance)
+}
+
+fn bimodal(sorted: &[f64]) -> Option<Bimodal> {
+    if sorted.len() < BIMODAL_MIN {
+        return None;
+    }
+    let index = split(sorted);
+    let high_fraction = (sorted.len() - index) as f64 / sorted.len() as f64;
+    if !(MODE_MIN..=1.0 - MODE_MIN).contains(&high_fraction) {
+        return None;
+    }
+    let (low, low_variance) = moments(&sorted[..index]);
+    let (high, high_variance) = moments(&sorted[index..]);
+    let separation =
+        2f64.sqrt() * (high - low) / (low_variance + high_variance).sqrt().max(f64::EPSILON);
+    (separation > SEPARATION).then(|| Bimodal {
+        low: Duration::from_nanos(low),
+        high: Duration::from_nanos(high),
+        high_fraction,
+        separation,
+    })
+}
+
+/// Analyze `records` in the order they were measured; outliers are more than `threshold` MADs
+/// from the median.
+pub fn analyze(records: &[Duration], threshold: f64) -> Diagnostics {
+    let values = records.iter().map(|x| x.as_nanos()).collect::<Vec<f64>>();
+    let mut sorted = values.clone();
+    sorted.sort_by(f64::total_cmp);
+    let mut diagnostics = Diagnostics {
+        threshold,
+        outliers: Vec::new(),
+        slow_clusters: Vec::new(),
+        bimodal: None,
+    };
+    if sorted.is_empty() {
+        return diagnostics;
+    }
+
+    let center = median(&sorted);
+    let mut deviations = sorted
+        .iter()
+        .map(|x| (x - center).abs())
+        .collect::<Vec<f64>>();
+    deviations.sort_by(f64::total_cmp);
+    // a coarse timer can make most iterations equal.
+    let mad = median(&deviations).max(1.0);
+
+    diagnostics.outliers = (0..values.len())
+        .filter(|&x| (values[x] - center).abs() > threshold * mad)
+        .collect();
+
+    let mut start = None;
+    for index in 0..=values.len() {
+        let slow = index < values.len() && values[index] > center + SLOW * mad;
+        match (slow, start) {
+            (true, None) => start = Some(index),
+            (false, Some(first)) => {
+                if index - first >= CLUSTER {
+                    diagnostics.slow_clusters.push(Cluster {
+                        start: first,
+                        length: index - first,
+                    });
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    diagnostics.bimodal = bimodal(&sorted);
+    diagnostics
+}
+
+impl Diagnostics {
+    pub fn is_empty(&self) -> bool {
+        self.outliers.is_empty() && self.slow_clusters.is_empty() && self.bimodal.is_none()
+    }
+}
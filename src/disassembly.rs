@@ -0,0 +1,328 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    process,
+};
+
+/// Mnemonics of the AMX tile instructions.
+const AMX: &[&str] = &[
+    "ldtilecfg",
+    "sttilecfg",
+    "tileloadd",
+    "tileloaddt1",
+    "tilestored",
+    "tilerelease",
+    "tilezero",
+    "tdpbf16ps",
+    "tdpfp16ps",
+    "tdpbssd",
+    "tdpbsud",
+    "tdpbusd",
+    "tdpbuud",
+];
+
+/// Instructions of a kernel object that need AVX-512 or AMX, which tell whether a build can
+/// use them at all on a machine that has them.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Extensions {
+    /// instructions on zmm registers, mask registers or xmm16 to ymm31
+    pub avx512: usize,
+    /// tile instructions
+    pub amx: usize,
+}
+
+/// Disassemble `object` with objdump, falling back to llvm-objdump if `objdump` cannot be run.
+pub fn disassemble(objdump: &str, object: &str) -> Result<String, String> {
+    let run = |tool: &str| {
+        process::Command::new(tool)
+            .args(["-d", "--no-show-raw-insn", "-C", object])
+            .output()
+    };
+    let output = match run(objdump) {
+        Err(_) if objdump == "objdump" => run("llvm-objdump"),
+        x => x,
+    }
+    .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Split an objdump listing into functions, e.g. "0000000000001139 <call_dgemm>:" starts one.
+fn functions(listing: &str) -> BTreeMap<String, Vec<&str>> {
+    let mut functions = BTreeMap::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+    for line in listing.lines() {
+        let header = line
+            .split_once(" <")
+            .filter(|(address, rest)| {
+                rest.ends_with(">:") && address.chars().all(|x| x.is_ascii_hexdigit())
+            })
+            .map(|(_, rest)| rest.trim_end_matches(">:").to_string());
+        if let Some(name) = header {
+            functions.extend(current.take());
+            current = Some((name, vec![line]));
+        } else if line.starts_with("Disassembly of section") {
+            functions.extend(current.take());
+        } else if let Some((_, lines)) = current.as_mut() {
+            if !line.trim().is_empty() {
+                lines.push(line);
+            }
+        }
+    }
+    functions.extend(current);
+    functions
+}
+
+/// Symbols referenced as `<name>` or `<name+0x10>` in an instruction.
+fn references(line: &str) -> impl Iterator<Item = &str> {
+    line.split('<').skip(1).filter_map(|x| {
+        let name = x.split('>').next()?;
+        Some(name.split('+').next().unwrap_or(name))
+    })
+}
+
+/// Disassembly of `root` and every function of the object it reaches through calls and jumps,
+/// which picks up inner microkernels. Imported functions (`name@plt`) are not followed.
+pub fn extract(listing: &str, root: &str) -> Option<String> {
+    let functions = functions(listing);
+    functions.get(root)?;
+
+    let mut visited = BTreeSet::new();
+    let mut queue = VecDeque::from([root]);
+    let mut out = Vec::new();
+    while let Some(name) = queue.pop_front() {
+        if !visited.insert(name) {
+            continue;
+        }
+        let Some(lines) = functions.get(name) else {
+            continue;
+        };
+        out.push(lines.join("\n"));
+        for reference in lines[1..].iter().flat_map(|x| references(x)) {
+            if !reference.contains('@') && reference != name {
+                queue.push_back(reference);
+            }
+        }
+    }
+    Some(out.join("\n\n") + "\n")
+}
+
+/// Whether `operand` is a register only EVEX encodes: zmm, k0 to k7, or xmm/ymm 16 to 31.
+fn evex_register(operand: &str) -> bool {
+    let name = operand.trim_start_matches('%');
+    if name.starts_with("zmm") {
+        return true;
+    }
+    if let Some(number) = name.strip_prefix('k') {
+        return number.len() == 1 && number.chars().all(|x| ('0'..='7').contains(&x));
+    }
+    ["xmm", "ymm"].iter().any(|prefix| {
+        name.strip_prefix(prefix)
+            .and_then(|x| x.parse::<u32>().ok())
+            .is_some_and(|x| x >= 16)
+    })
+}
+
+/// Count the AVX-512 and AMX instructions of an x86-64 objdump listing in AT&T or Intel syntax.
+pub fn extensions(listing: &str) -> Extensions {
+    let mut extensions = Extensions { avx512: 0, amx: 0 };
+    // instructions are "  1139:\tvfmadd231pd %zmm1,%zmm2,%zmm0".
+    for instruction in listing.lines().filter_map(|x| x.split_once(":\t")) {
+        let mut words = instruction.1.split_whitespace();
+        let Some(mnemonic) = words.next() else {
+            continue;
+        };
+        if AMX.contains(&mnemonic) {
+            extensions.amx += 1;
+        } else if words
+            .flat_map(|x| x.split(|x: char| !x.is_ascii_alphanumeric() && x != '%'))
+            .any(evex_register)
+        {
+            extensions.avx512 += 1;
+        }
+    }
+    extensions
+}
+
+/// Mnemonics of fused multiply-adds on AArch64; x86 ones all start with vfmadd, vfmsub,
+/// vfnmadd or vfnmsub.
+const AARCH64_FMA: &[&str] = &["fmla", "fmls", "fmadd", "fmsub", "fnmadd", "fnmsub"];
+
+/// Instructions of the hot code of a kernel by kind, which shows whether the compiler
+/// vectorized it.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Mix {
+    /// hot symbol the count starts from, with every function it calls
+    pub symbol: String,
+    pub instructions: usize,
+    /// fused multiply-adds, scalar or vector
+    pub fma: usize,
+    /// instructions reading memory
+    pub loads: usize,
+    /// instructions writing memory
+    pub stores: usize,
+    /// floating-point instructions on a single element
+    pub scalar: usize,
+    /// bits of the widest vector of floating-point elements computed on; 0 if none
+    pub vector_bits: usize,
+    /// whether SVE registers are used, whose width is only known at run time
+    pub scalable: bool,
+}
+
+impl Mix {
+    /// Whether the code does floating-point arithmetic on single elements only. Vectorized
+    /// loops keep scalar remainders, so scalar instructions alone prove nothing.
+    pub fn unvectorized(&self) -> bool {
+        self.scalar > 0 && self.vector_bits == 0 && !self.scalable
+    }
+}
+
+/// Operands separated by commas outside of parentheses, brackets and braces.
+fn operands(text: &str) -> Vec<&str> {
+    let (mut depth, mut start, mut out) = (0, 0, Vec::new());
+    for (index, character) in text.char_indices() {
+        match character {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                out.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if !text[start..].trim().is_empty() {
+        out.push(text[start..].trim());
+    }
+    out
+}
+
+/// x86 prefixes objdump prints as words of their own, e.g. "cs nopw 0x0(%rax,%rax,1)".
+const PREFIXES: &[&str] = &[
+    "cs", "ds", "data16", "lock", "rep", "repz", "repnz", "notrack", "bnd",
+];
+
+/// Mnemonic and operands of every instruction of a listing, without prefixes and the symbols
+/// objdump annotates branches and addresses with.
+fn instructions(listing: &str) -> impl Iterator<Item = (&str, Vec<&str>)> {
+    listing.lines().filter_map(|x| {
+        let (_, instruction) = x.split_once(":\t")?;
+        let mut instruction = instruction.split(['#', '<', ';']).next()?.trim();
+        loop {
+            let (mnemonic, rest) = instruction
+                .split_once(char::is_whitespace)
+                .unwrap_or((instruction, ""));
+            if mnemonic.is_empty() {
+                return None;
+            }
+            if !PREFIXES.contains(&mnemonic) {
+                return Some((mnemonic, operands(rest.trim())));
+            }
+            instruction = rest.trim();
+        }
+    })
+}
+
+/// Bits of an x86 vector register, e.g. 512 for %zmm3.
+fn x86_bits(operand: &str) -> usize {
+    let name = operand.trim_start_matches('%');
+    [("xmm", 128), ("ymm", 256), ("zmm", 512)]
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map_or(0, |x| x.1)
+}
+
+/// Bits of an AArch64 SIMD operand from its arrangement, e.g. 128 for v0.2d and 64 for v1.2s.
+fn neon_bits(operand: &str) -> usize {
+    if operand.starts_with('q') && operand[1..].parse::<u32>().is_ok() {
+        return 128;
+    }
+    let Some((register, arrangement)) = operand.split_once('.') else {
+        return 0;
+    };
+    if !register.starts_with('v') || register[1..].parse::<u32>().is_err() {
+        return 0;
+    }
+    let lanes = arrangement
+        .trim_end_matches(|x: char| x.is_ascii_alphabetic())
+        .parse::<usize>()
+        .unwrap_or(0);
+    let size = match arrangement.chars().last() {
+        Some('b') => 8,
+        Some('h') => 16,
+        Some('s') => 32,
+        Some('d') => 64,
+        _ => 0,
+    };
+    lanes * size
+}
+
+/// Count the instructions of `symbol` and the functions it calls in an x86-64 (AT&T syntax) or
+/// AArch64 objdump listing by kind; None if the listing lacks `symbol`.
+pub fn mix(listing: &str, symbol: &str) -> Option<Mix> {
+    let aarch64 = listing.contains("aarch64");
+    let code = extract(listing, symbol)?;
+    let mut mix = Mix {
+        symbol: symbol.to_string(),
+        instructions: 0,
+        fma: 0,
+        loads: 0,
+        stores: 0,
+        scalar: 0,
+        vector_bits: 0,
+        scalable: false,
+    };
+    for (mnemonic, operands) in instructions(&code) {
+        mix.instructions += 1;
+        if aarch64 {
+            mix.fma += AARCH64_FMA.contains(&mnemonic) as usize;
+            mix.loads += mnemonic.starts_with("ld") as usize;
+            mix.stores += mnemonic.starts_with("st") as usize;
+            let first = operands.first().copied().unwrap_or("");
+            let scalar_register = first.len() > 1
+                && first.starts_with(['d', 's', 'h'])
+                && first[1..].parse::<u32>().is_ok();
+            mix.scalar += (mnemonic.starts_with('f') && scalar_register) as usize;
+            for operand in &operands {
+                mix.vector_bits = mix.vector_bits.max(neon_bits(operand));
+                mix.scalable |= operand.starts_with('z')
+                    && operand[1..]
+                        .split('.')
+                        .next()
+                        .is_some_and(|x| x.parse::<u32>().is_ok());
+            }
+        } else {
+            if matches!(mnemonic, "lea" | "nop" | "nopl" | "nopw")
+                || mnemonic.starts_with("prefetch")
+            {
+                continue;
+            }
+            // AT&T syntax puts the destination last.
+            let memory = |x: &&str| x.contains('(');
+            if let Some((destination, sources)) = operands.split_last() {
+                mix.loads += sources.iter().any(memory) as usize;
+                mix.stores += (!sources.is_empty() && memory(destination)) as usize;
+            }
+            let base = mnemonic.strip_prefix('v').unwrap_or(mnemonic);
+            mix.fma += ["fmadd", "fmsub", "fnmadd", "fnmsub"]
+                .iter()
+                .any(|x| base.starts_with(x)) as usize;
+            // packed integer instructions such as vpabsd end in sd as well.
+            let scalar = ["sd", "ss", "sh"].iter().any(|x| base.ends_with(x))
+                && !base.starts_with('p')
+                && !base.contains("broadcast");
+            mix.scalar += scalar as usize;
+            // moves and zeroing with xor say nothing about the width of the arithmetic.
+            let packed = ["pd", "ps", "ph"].iter().any(|x| base.ends_with(x));
+            if packed && !base.starts_with("mov") && !base.starts_with("xor") {
+                for operand in &operands {
+                    mix.vector_bits = mix.vector_bits.max(x86_bits(operand));
+                }
+            }
+        }
+    }
+    Some(mix)
+}
@@ -0,0 +1,140 @@
+use crate::{common::Report, machine::Machine};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::{BufRead, Write};
+
+/// A candidate run handed to a worker.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: usize,
+    /// benchmark arguments without the kernel path
+    pub arguments: Vec<String>,
+    /// file name of the kernel, whose extension selects the language
+    pub kernel_name: String,
+    /// source of the kernel, which the worker compiles itself
+    pub kernel: String,
+}
+
+/// Messages of a worker to the coordinator of a distributed search.
+// one message per job, so the size of a report does not matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkerMessage {
+    Register { machine: Machine, token: String },
+    Finished { id: usize, report: Option<Report> },
+}
+
+/// Messages of the coordinator to a worker.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CoordinatorMessage {
+    Rejected { reason: String },
+    Run { job: Job },
+}
+
+/// Write `message` as one line of JSON.
+pub fn send<T: Serialize>(stream: &mut impl Write, message: &T) -> Result<(), String> {
+    let mut line = serde_json::to_string(message).map_err(|e| e.to_string())?;
+    line.push('\n');
+    stream
+        .write_all(line.as_bytes())
+        .and_then(|_| stream.flush())
+        .map_err(|e| e.to_string())
+}
+
+/// Read the next line of JSON.
+pub fn receive<T: DeserializeOwned>(reader: &mut impl BufRead) -> Result<T, String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+        return Err("connection closed".to_string());
+    }
+    serde_json::from_str(&line).map_err(|e| e.to_string())
+}
+
+/// Options of a job that take a value, as far as a worker runs them. Anything that names a
+/// command, a compiler, a library or a file to write is left out, so a job can only shape the
+/// measurement of the kernel it comes with.
+const OPTIONS: &[&str] = &[
+    "--name",
+    "--tag",
+    "--meta",
+    "--define",
+    "--compiler-profile",
+    "--kernel-abi",
+    "--pin-cpus",
+    "--membind",
+    "--first-touch",
+    "--per-core",
+    "--warm-up",
+    "--repeats",
+    "--sessions",
+    "--jitter",
+    "--verify-sample",
+    "--max-residual",
+    "--layout",
+    "--trans-a",
+    "--trans-b",
+    "--m",
+    "--n",
+    "--k",
+    "--square",
+    "--alpha",
+    "--beta",
+    "--batch",
+    "--strided-batch",
+    "--stride-a",
+    "--stride-b",
+    "--stride-c",
+    "--reuse-b",
+    "--flops-formula",
+    "--bytes-formula",
+    "--conv",
+];
+
+/// Switches of a job a worker runs.
+const SWITCHES: &[&str] = &[
+    "--prewarm-threads",
+    "--skip-verification",
+    "--extended-residual",
+    "--protect-inputs",
+    "--packed",
+];
+
+/// Why a worker does not run a job with `arguments`, if it does not: every argument has to be
+/// one of the options or switches above.
+pub fn check_arguments(arguments: &[String]) -> Result<(), String> {
+    let mut arguments = arguments.iter();
+    while let Some(argument) = arguments.next() {
+        if SWITCHES.contains(&argument.as_str()) {
+            continue;
+        }
+        if !OPTIONS.contains(&argument.as_str()) {
+            return Err(format!("argument '{}' is not allowed", argument));
+        }
+        if arguments.next().is_none() {
+            return Err(format!("option '{}' has no value", argument));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `token` is the token of the coordinator, compared in constant time.
+pub fn authorized(expected: &str, token: &str) -> bool {
+    expected.len() == token.len()
+        && expected
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+/// Why a worker on `worker` cannot measure for a coordinator on `coordinator`, if it cannot.
+/// Only the CPU model has to match, as results of different CPUs are not comparable.
+pub fn reject(coordinator: &Machine, worker: &Machine) -> Option<String> {
+    (coordinator.cpu_model != worker.cpu_model).then(|| {
+        format!(
+            "CPU model '{}' differs from '{}' of the coordinator",
+            worker.cpu_model, coordinator.cpu_model
+        )
+    })
+}
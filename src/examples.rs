@@ -0,0 +1,50 @@
+use std::{fs, io, path};
+
+pub struct Example {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub source: &'static str,
+}
+
+/// Reference kernels shipped with the crate, runnable as `builtin:<name>`.
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "naive",
+        description: "naive triple loop",
+        source: include_str!("../kernels/naive.c"),
+    },
+    Example {
+        name: "blocked",
+        description: "cache-blocked loop nest",
+        source: include_str!("../kernels/blocked.c"),
+    },
+    Example {
+        name: "openmp",
+        description: "cache-blocked loop nest parallelized with OpenMP",
+        source: include_str!("../kernels/openmp.c"),
+    },
+    Example {
+        name: "vendor",
+        description: "passthrough to vendor BLAS",
+        source: include_str!("../kernels/vendor.c"),
+    },
+];
+
+pub const PREFIX: &str = "builtin:";
+
+pub fn find(name: &str) -> Option<&'static Example> {
+    EXAMPLES.iter().find(|x| x.name == name)
+}
+
+impl Example {
+    pub fn file_name(&self) -> String {
+        [self.name, ".c"].concat()
+    }
+
+    pub fn write_to(&self, dir: &path::Path) -> io::Result<path::PathBuf> {
+        fs::create_dir_all(dir)?;
+        let file = dir.join(self.file_name());
+        fs::write(&file, self.source)?;
+        Ok(file)
+    }
+}
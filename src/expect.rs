@@ -0,0 +1,49 @@
+use crate::{common::Report, hook};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Exit status of a run that completed but failed an expectation; errors exit with 1.
+pub const EXIT_FAILED: i32 = 2;
+
+/// Outcome of an assertion on a finished run, like `--expect-gflops-min 800`.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Expectation {
+    /// option that set it, without the dashes
+    pub name: String,
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub limit: f64,
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub actual: f64,
+    pub passed: bool,
+}
+
+/// Check the medium GFLOPS against `gflops_min` and the relative standard deviation against
+/// `max_deviation`, a fraction; deviations are reported in percent.
+pub fn evaluate(
+    report: &Report,
+    gflops_min: Option<f64>,
+    max_deviation: Option<f64>,
+) -> Vec<Expectation> {
+    let mut expectations = Vec::new();
+    if let Some(limit) = gflops_min {
+        let actual = hook::gflops(report);
+        expectations.push(Expectation {
+            name: "expect-gflops-min".to_string(),
+            limit,
+            actual,
+            passed: actual >= limit,
+        });
+    }
+    if let Some(limit) = max_deviation {
+        let actual = report.statistics.deviation / report.statistics.average * 100.0;
+        expectations.push(Expectation {
+            name: "expect-max-deviation".to_string(),
+            limit: limit * 100.0,
+            actual,
+            passed: actual <= limit * 100.0,
+        });
+    }
+    expectations
+}
@@ -0,0 +1,105 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs};
+
+/// Code size of the compiled kernel, which bounds its instruction-cache footprint.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Footprint {
+    /// bytes of the .text section
+    pub text: usize,
+    /// bytes of each function defined by the kernel
+    pub functions: BTreeMap<String, usize>,
+}
+
+const SHT_SYMTAB: u32 = 2;
+const SHT_DYNSYM: u32 = 11;
+const STT_FUNC: u8 = 2;
+
+/// Functions every shared object gets from the C runtime.
+const RUNTIME: &[&str] = &["deregister_tm_clones", "register_tm_clones", "frame_dummy"];
+
+fn read<const N: usize>(data: &[u8], offset: usize) -> Option<[u8; N]> {
+    data.get(offset..offset.checked_add(N)?)?.try_into().ok()
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<usize> {
+    read::<2>(data, offset).map(|x| u16::from_le_bytes(x) as usize)
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    read::<4>(data, offset).map(u32::from_le_bytes)
+}
+
+fn u64_at(data: &[u8], offset: usize) -> Option<usize> {
+    read::<8>(data, offset).map(|x| u64::from_le_bytes(x) as usize)
+}
+
+fn string_at(data: &[u8], offset: usize) -> Option<String> {
+    let bytes = data.get(offset..)?;
+    let end = bytes.iter().position(|&x| x == 0)?;
+    Some(String::from_utf8_lossy(&bytes[..end]).to_string())
+}
+
+struct Section {
+    name: usize,
+    kind: u32,
+    offset: usize,
+    size: usize,
+    link: usize,
+}
+
+/// Measure a 64-bit little-endian ELF shared object; other formats are not supported.
+pub fn measure(object: &str) -> Option<Footprint> {
+    let data = fs::read(object).ok()?;
+    // magic, ELFCLASS64 and ELFDATA2LSB
+    if data.get(..6)? != b"\x7fELF\x02\x01" {
+        return None;
+    }
+
+    let offset = u64_at(&data, 0x28)?;
+    let entry = u16_at(&data, 0x3a)?;
+    let count = u16_at(&data, 0x3c)?;
+    let sections = (0..count)
+        .map(|index| {
+            let at = offset + index * entry;
+            Some(Section {
+                name: u32_at(&data, at)? as usize,
+                kind: u32_at(&data, at + 4)?,
+                offset: u64_at(&data, at + 24)?,
+                size: u64_at(&data, at + 32)?,
+                link: u32_at(&data, at + 40)? as usize,
+            })
+        })
+        .collect::<Option<Vec<Section>>>()?;
+    let names = sections.get(u16_at(&data, 0x3e)?)?;
+    let section_name = |x: &Section| string_at(&data, names.offset + x.name);
+
+    let text_index = sections
+        .iter()
+        .position(|x| section_name(x).as_deref() == Some(".text"))?;
+
+    // the full symbol table has static functions too, but stripped objects only keep the dynamic one.
+    let symbols = sections
+        .iter()
+        .find(|x| x.kind == SHT_SYMTAB)
+        .or_else(|| sections.iter().find(|x| x.kind == SHT_DYNSYM))?;
+    let strings = sections.get(symbols.link)?;
+    let mut functions = BTreeMap::new();
+    for at in (symbols.offset..symbols.offset + symbols.size).step_by(24) {
+        let info = *data.get(at + 4)?;
+        let section = u16_at(&data, at + 6)?;
+        let size = u64_at(&data, at + 16)?;
+        if info & 0xf != STT_FUNC || section != text_index || size == 0 {
+            continue;
+        }
+        let name = string_at(&data, strings.offset + u32_at(&data, at)? as usize)?;
+        if !name.starts_with('_') && !RUNTIME.contains(&name.as_str()) {
+            functions.insert(name, size);
+        }
+    }
+
+    Some(Footprint {
+        text: sections[text_index].size,
+        functions,
+    })
+}
@@ -0,0 +1,144 @@
+use crate::{common::Report, hook};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Standard deviation, relative to the average, beyond which a run is noisy.
+const NOISY: f64 = 0.05;
+/// Ratio of the first iteration to the median beyond which the first one ran cold.
+const COLD: f64 = 1.5;
+/// Share of the memory-bound roof beyond which a run is limited by memory traffic.
+const NEAR_ROOF: f64 = 0.8;
+
+/// Advice on how to get more reliable or faster numbers out of a run.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Hint {
+    /// stable name of the heuristic, e.g. `pin-cpus`
+    pub kind: String,
+    pub message: String,
+}
+
+fn hint(kind: &str, message: String) -> Hint {
+    Hint {
+        kind: kind.to_string(),
+        message,
+    }
+}
+
+/// Apply every heuristic to a finished run. `memory_bandwidth` in GB/s enables the memory-bound
+/// check.
+pub fn collect(report: &Report, memory_bandwidth: Option<f64>) -> Vec<Hint> {
+    let mut hints = Vec::new();
+    let statistics = &report.statistics;
+    let machine = report.environment.machine.as_ref();
+
+    let deviation = statistics.deviation / statistics.average;
+    if deviation > NOISY {
+        if report.environment.affinity.is_empty() {
+            hints.push(hint(
+                "pin-cpus",
+                format!(
+                    "the deviation is {:.1}% of the average; pin the benchmark with --pin-cpus so the scheduler cannot migrate it",
+                    deviation * 100.0
+                ),
+            ));
+        } else if let Some(governor) = machine
+            .and_then(|x| x.governor.as_deref())
+            .filter(|x| *x != "performance")
+        {
+            hints.push(hint(
+                "governor",
+                format!(
+                    "the deviation is {:.1}% of the average although the benchmark is pinned; the cpufreq governor is {}, set it to performance",
+                    deviation * 100.0,
+                    governor
+                ),
+            ));
+        }
+    }
+
+    // the node itself is as noisy, so the deviation says little about the kernel.
+    if let Some(noise) = report
+        .noise
+        .as_ref()
+        .filter(|x| deviation > NOISY && x.floor > NOISY)
+    {
+        hints.push(hint(
+            "noisy-node",
+            format!(
+                "the deviation is {:.1}% of the average, but idle loops already deviate by {:.1}% on this node; stop background load or isolate the CPUs before reading anything into it",
+                deviation * 100.0,
+                noise.floor * 100.0
+            ),
+        ));
+    }
+
+    if let (Some(first), Some(medium)) = (report.samples.first(), statistics.medium) {
+        let ratio = first.as_nanos() / medium.as_nanos().max(1.0);
+        if report.samples.len() > 1 && ratio > COLD {
+            hints.push(hint(
+                "warm-up",
+                format!(
+                    "the first iteration took {:.1}x the medium; add a few --warm-up iterations so caches, page tables and thread pools are ready before measuring",
+                    ratio
+                ),
+            ));
+        }
+    }
+
+    if let Some(diagnostics) = &report.diagnostics {
+        if !diagnostics.slow_clusters.is_empty() {
+            hints.push(hint(
+                "throttling",
+                format!(
+                    "{} runs of consecutive slow iterations suggest thermal or power throttling; check the cooling, power limits and --session-gap",
+                    diagnostics.slow_clusters.len()
+                ),
+            ));
+        }
+        let environment = &report.environment;
+        if diagnostics.bimodal.is_some()
+            && environment.membind.is_empty()
+            && environment.first_touch.is_none()
+        {
+            hints.push(hint(
+                "membind",
+                "the durations are bimodal, as if the matrices landed on different NUMA nodes; bind the memory with --membind or place it by first touch with --first-touch".to_string(),
+            ));
+        }
+    }
+
+    if let Some(mix) = report.instruction_mix.as_ref().filter(|x| x.unvectorized()) {
+        hints.push(hint(
+            "vectorize",
+            format!(
+                "{} does its {} floating-point instructions on single elements; the compiler did not vectorize it, check the optimization level, -march and aliasing of the pointers",
+                mix.symbol, mix.scalar
+            ),
+        ));
+    }
+
+    let single = report.batch.is_empty() && report.reuse_b.is_none();
+    // every element of A and B read once, C read and written once, unless --bytes-formula says.
+    let bytes = report.bytes().or_else(|| {
+        let (m, n, k) = report.dimensions;
+        let (m, n, k) = (m as f64, n as f64, k as f64);
+        single.then_some(8.0 * (m * k + k * n + 2.0 * m * n))
+    });
+    if let Some((bandwidth, bytes)) = memory_bandwidth.zip(bytes) {
+        let roof = report.flops() / bytes * bandwidth;
+        let gflops = hook::gflops(report);
+        if gflops > NEAR_ROOF * roof {
+            hints.push(hint(
+                "blocking",
+                format!(
+                    "{:.3} GFLOPS are {:.0}% of the memory-bound roof of {:.3} GFLOPS at {} GB/s; the run is limited by memory traffic, so blocking changes that keep tiles of A and B in cache matter more than tuning the arithmetic",
+                    gflops,
+                    gflops / roof * 100.0,
+                    roof,
+                    bandwidth
+                ),
+            ));
+        }
+    }
+    hints
+}
@@ -0,0 +1,301 @@
+use std::{
+    fmt::{self, Write},
+    fs, path,
+};
+
+/// Quantiles `Overlap` compares, from the body to the tail of the distributions.
+pub const QUANTILES: [f64; 6] = [0.1, 0.25, 0.5, 0.75, 0.9, 0.99];
+
+/// Bins of the histograms the overlap coefficient is computed from.
+const BINS: usize = 50;
+
+/// Iteration times of a file saved with `--save-history-as`, one per line in its `--units`.
+pub fn load(file: &path::Path) -> Result<Vec<f64>, String> {
+    let text = fs::read_to_string(file).map_err(|e| e.to_string())?;
+    text.lines()
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .map(|x| {
+            x.parse::<f64>()
+                .map_err(|_| ["expected a time per line, but got '", x, "'"].concat())
+        })
+        .collect()
+}
+
+/// Value below which `fraction` of `sorted` lies, interpolated between neighbours.
+fn quantile(sorted: &[f64], fraction: f64) -> f64 {
+    let position = fraction * (sorted.len() - 1) as f64;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// Asymptotic p-value of a Kolmogorov-Smirnov distance `d` between samples of `n` and `m`.
+fn kolmogorov_p(d: f64, n: usize, m: usize) -> f64 {
+    let effective = (n * m) as f64 / (n + m) as f64;
+    let lambda = (effective.sqrt() + 0.12 + 0.11 / effective.sqrt()) * d;
+    // the series converges too slowly for small distances, whose p-value is 1 anyway.
+    if lambda < 0.3 {
+        return 1.0;
+    }
+    let sum = (1..=100)
+        .map(|j| {
+            let sign = if j % 2 == 1 { 1.0 } else { -1.0 };
+            sign * (-2.0 * (j * j) as f64 * lambda * lambda).exp()
+        })
+        .sum::<f64>();
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+/// How the distributions of the iteration times of two runs differ.
+pub struct Overlap {
+    /// largest distance between the empirical distribution functions (Kolmogorov-Smirnov)
+    pub distance: f64,
+    /// p-value of `distance`
+    pub p: f64,
+    /// shared area of the two histograms: 1 for the same distribution, 0 for disjoint ones
+    pub coefficient: f64,
+    /// every fraction of `QUANTILES` with the quantile of both runs
+    pub quantiles: Vec<(f64, f64, f64)>,
+}
+
+impl Overlap {
+    /// Compare the iteration times `a` and `b`; None if either is empty.
+    pub fn new(a: &[f64], b: &[f64]) -> Option<Self> {
+        if a.is_empty() || b.is_empty() {
+            return None;
+        }
+        let sort = |x: &[f64]| {
+            let mut x = x.to_vec();
+            x.sort_by(f64::total_cmp);
+            x
+        };
+        let (a, b) = (sort(a), sort(b));
+
+        // walk both sorted samples and track the gap of their distribution functions.
+        let (mut i, mut j, mut distance) = (0, 0, 0.0f64);
+        while i < a.len() && j < b.len() {
+            let x = a[i].min(b[j]);
+            while i < a.len() && a[i] <= x {
+                i += 1;
+            }
+            while j < b.len() && b[j] <= x {
+                j += 1;
+            }
+            distance = distance.max((i as f64 / a.len() as f64 - j as f64 / b.len() as f64).abs());
+        }
+
+        let (min, max) = (a[0].min(b[0]), a[a.len() - 1].max(b[b.len() - 1]));
+        let histogram = |x: &[f64]| {
+            let mut bins = [0.0; BINS];
+            for value in x {
+                let bin = if max > min {
+                    (((value - min) / (max - min)) * BINS as f64) as usize
+                } else {
+                    0
+                };
+                bins[bin.min(BINS - 1)] += 1.0 / x.len() as f64;
+            }
+            bins
+        };
+        let (x, y) = (histogram(&a), histogram(&b));
+        Some(Overlap {
+            distance,
+            p: kolmogorov_p(distance, a.len(), b.len()),
+            coefficient: x.iter().zip(&y).map(|(x, y)| x.min(*y)).sum(),
+            quantiles: QUANTILES
+                .iter()
+                .map(|&x| (x, quantile(&a, x), quantile(&b, x)))
+                .collect(),
+        })
+    }
+
+    /// Quantiles of both runs at `fraction`, one of `QUANTILES`.
+    fn at(&self, fraction: f64) -> (f64, f64) {
+        self.quantiles
+            .iter()
+            .find(|x| x.0 == fraction)
+            .map_or((f64::NAN, f64::NAN), |x| (x.1, x.2))
+    }
+
+    /// Whether `b` shifted as a whole, which the distance tests at `significance`, only in its
+    /// tail, or not at all. Few slow iterations hardly move the distance, so the tail shifted
+    /// if the 99th percentile moved by more than the interquartile range of `a` and more than
+    /// twice as far as the median.
+    pub fn shift(&self, significance: f64) -> &'static str {
+        let (median, tail) = (self.at(0.5), self.at(0.99));
+        let spread = self.at(0.75).0 - self.at(0.25).0;
+        let tail = (tail.1 - tail.0).abs();
+        if self.p < significance {
+            "whole distribution"
+        } else if tail > spread && tail > 2.0 * (median.1 - median.0).abs() {
+            "tail only"
+        } else {
+            "no shift"
+        }
+    }
+}
+
+/// Means of `values` over `count` equal slices of `length` iterations, None past their end.
+fn columns(values: &[f64], length: usize, count: usize) -> Vec<Option<f64>> {
+    (0..count)
+        .map(|column| {
+            let start = column * length / count;
+            let end = ((column + 1) * length / count).min(values.len());
+            (start < end).then(|| values[start..end].iter().sum::<f64>() / (end - start) as f64)
+        })
+        .collect()
+}
+
+/// Rows of a plot of every series with its mark, `*` where marks meet, labelled with the range.
+fn plot(series: &[(&[Option<f64>], char)], height: usize) -> Vec<String> {
+    let (min, max) = series
+        .iter()
+        .flat_map(|(x, _)| x.iter().flatten())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+            (min.min(*x), max.max(*x))
+        });
+    let width = series.iter().map(|(x, _)| x.len()).max().unwrap_or(0);
+    let mut grid = vec![vec![' '; width]; height];
+    for (values, mark) in series {
+        for (column, value) in values.iter().enumerate() {
+            let Some(value) = value else {
+                continue;
+            };
+            let row = if max > min {
+                ((max - value) / (max - min) * (height - 1) as f64).round() as usize
+            } else {
+                height / 2
+            };
+            let cell = &mut grid[row][column];
+            *cell = if *cell == ' ' || cell == mark {
+                *mark
+            } else {
+                '*'
+            };
+        }
+    }
+    grid.iter()
+        .enumerate()
+        .map(|(row, cells)| {
+            let label = match row {
+                0 => format!("{:>12.3}", max),
+                x if x == height - 1 => format!("{:>12.3}", min),
+                _ => " ".repeat(12),
+            };
+            format!("{} |{}", label, cells.iter().collect::<String>())
+        })
+        .collect()
+}
+
+/// The iteration times of `a` and `b` overlaid, then their difference b - a, as text at most
+/// `width` columns wide; every column averages the iterations it covers.
+pub fn chart(a: &[f64], b: &[f64], width: usize) -> String {
+    let length = a.len().max(b.len());
+    let count = width.min(length).max(1);
+    let (a, b) = (columns(a, length, count), columns(b, length, count));
+    let delta = a
+        .iter()
+        .zip(&b)
+        .map(|(x, y)| Some((*y)? - (*x)?))
+        .collect::<Vec<Option<f64>>>();
+    let mut lines = vec!["Iterations of a, b (* both)".to_string()];
+    lines.extend(plot(&[(&a, 'a'), (&b, 'b')], 16));
+    lines.push("b - a".to_string());
+    lines.extend(plot(&[(&delta, '#')], 8));
+    lines.join("\n")
+}
+
+/// Points of a polyline of `values` inside the box at (`x`, `y`) of `width` by `height`.
+fn polyline(
+    values: &[f64],
+    (min, max): (f64, f64),
+    (x, y, width, height): (f64, f64, f64, f64),
+) -> String {
+    let step = width / (values.len().max(2) - 1) as f64;
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let fraction = if max > min {
+                (value - min) / (max - min)
+            } else {
+                0.5
+            };
+            format!(
+                "{:.1},{:.1}",
+                x + index as f64 * step,
+                y + height * (1.0 - fraction)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// The charts of `chart` as an SVG image, with every iteration and times in `unit`.
+pub fn svg(a: &[f64], b: &[f64], unit: &str) -> Result<String, fmt::Error> {
+    let range = |x: &mut dyn Iterator<Item = f64>| {
+        x.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+            (min.min(x), max.max(x))
+        })
+    };
+    let times = range(&mut a.iter().chain(b).copied());
+    let delta = a.iter().zip(b).map(|(x, y)| y - x).collect::<Vec<f64>>();
+    let differences = range(&mut delta.iter().copied().chain([0.0]));
+    let (overlay, bottom) = ((60.0, 30.0, 720.0, 280.0), (60.0, 360.0, 720.0, 140.0));
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="800" height="540" font-family="sans-serif" font-size="12">"#
+    )?;
+    writeln!(out, r#"<rect width="800" height="540" fill="white"/>"#)?;
+    for (title, (x, y, width, height), (min, max)) in [
+        (format!("Iterations ({})", unit), overlay, times),
+        (format!("b - a ({})", unit), bottom, differences),
+    ] {
+        writeln!(
+            out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="gray"/>"#,
+            x, y, width, height
+        )?;
+        writeln!(out, r#"<text x="{}" y="{}">{}</text>"#, x, y - 8.0, title)?;
+        writeln!(
+            out,
+            r#"<text x="{}" y="{}" text-anchor="end">{:.3}</text>"#,
+            x - 4.0,
+            y + 12.0,
+            max
+        )?;
+        writeln!(
+            out,
+            r#"<text x="{}" y="{}" text-anchor="end">{:.3}</text>"#,
+            x - 4.0,
+            y + height,
+            min
+        )?;
+    }
+    for (values, color) in [(a, "steelblue"), (b, "darkorange")] {
+        writeln!(
+            out,
+            r#"<polyline fill="none" stroke="{}" points="{}"/>"#,
+            color,
+            polyline(values, times, overlay)
+        )?;
+    }
+    writeln!(
+        out,
+        r#"<polyline fill="none" stroke="gray" stroke-dasharray="4" points="{}"/>"#,
+        polyline(&[0.0, 0.0], differences, bottom)
+    )?;
+    writeln!(
+        out,
+        r#"<polyline fill="none" stroke="black" points="{}"/>"#,
+        polyline(&delta, differences, bottom)
+    )?;
+    writeln!(
+        out,
+        r#"<text x="600" y="20" fill="steelblue">a</text><text x="620" y="20" fill="darkorange">b</text>"#
+    )?;
+    writeln!(out, "</svg>")?;
+    Ok(out)
+}
@@ -0,0 +1,163 @@
+use crate::common::Report;
+use std::{collections::BTreeMap, process};
+
+/// Throughput used to compare reports: medium if available, average otherwise.
+pub fn gflops(report: &Report) -> f64 {
+    benchmark_core::statistics::gflops(
+        report.flops(),
+        report.statistics.medium.map(|x| x.as_nanos()),
+        report.statistics.average,
+    )
+}
+
+/// Values available to hook commands as `{name}`.
+pub fn placeholders(report: &Report, saved_as: Option<&str>) -> BTreeMap<String, String> {
+    let ops = report.flops();
+    let statistics = &report.statistics;
+    let mut values = BTreeMap::new();
+    let mut insert = |key: &str, value: String| {
+        values.insert(key.to_string(), value);
+    };
+    insert("name", report.name.clone());
+    insert("m", report.dimensions.0.to_string());
+    insert("n", report.dimensions.1.to_string());
+    insert("k", report.dimensions.2.to_string());
+    insert("repeats", report.repeats.to_string());
+    insert("report", saved_as.unwrap_or_default().to_string());
+    for (key, duration) in [
+        ("medium", statistics.medium),
+        ("best", Some(statistics.minimum)),
+        ("worst", Some(statistics.maximum)),
+    ] {
+        let (ms, gflops) = match duration {
+            Some(x) => (
+                format!("{:.6}", x.as_millis()),
+                format!("{:.3}", ops / x.as_nanos()),
+            ),
+            None => ("n/a".to_string(), "n/a".to_string()),
+        };
+        insert(&["ms_", key].concat(), ms);
+        insert(&["gflops_", key].concat(), gflops);
+    }
+    insert("ms_average", format!("{:.6}", statistics.average));
+    insert(
+        "gflops_average",
+        format!("{:.3}", ops / statistics.average / 1000.0 / 1000.0),
+    );
+    insert("deviation", format!("{:.6}", statistics.deviation));
+    insert(
+        "expectations",
+        if report.expectations.iter().all(|x| x.passed) {
+            "passed"
+        } else {
+            "failed"
+        }
+        .to_string(),
+    );
+    values
+}
+
+/// Placeholders every report fills.
+pub const PLACEHOLDERS: [&str; 16] = [
+    "name",
+    "m",
+    "n",
+    "k",
+    "repeats",
+    "report",
+    "ms_medium",
+    "gflops_medium",
+    "ms_best",
+    "gflops_best",
+    "ms_worst",
+    "gflops_worst",
+    "ms_average",
+    "gflops_average",
+    "deviation",
+    "expectations",
+];
+
+/// Placeholders only a run with a baseline fills.
+pub const BASELINE_PLACEHOLDERS: [&str; 2] = ["baseline_gflops", "change"];
+
+/// Replace the `{name}` placeholders of `template` by what `value` gives for them.
+/// Braces that do not enclose a lowercase identifier, e.g. shell `${HOME}`, are kept verbatim.
+fn substitute(
+    template: &str,
+    mut value: impl FnMut(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let key = rest[1..].find('}').map(|end| &rest[1..end + 1]);
+        match key {
+            Some(key)
+                if !key.is_empty()
+                    && key
+                        .bytes()
+                        .all(|x| x.is_ascii_lowercase() || x.is_ascii_digit() || x == b'_') =>
+            {
+                out.push_str(&value(key)?);
+                rest = &rest[key.len() + 2..];
+            }
+            _ => {
+                out.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// `value` as one word of the platform shell, whatever it holds.
+fn quote(value: &str) -> String {
+    #[cfg(windows)]
+    return ["\"", &value.replace('"', "\"\""), "\""].concat();
+    #[cfg(not(windows))]
+    return ["'", &value.replace('\'', "'\\''"), "'"].concat();
+}
+
+/// Fill `{name}` placeholders of `template`, each value quoted as one word of the shell, so a
+/// name or tag cannot break or inject into the command.
+/// Braces that do not enclose a lowercase identifier, e.g. shell `${HOME}`, are kept verbatim.
+pub fn render(template: &str, values: &BTreeMap<String, String>) -> Result<String, String> {
+    substitute(template, |key| {
+        values
+            .get(key)
+            .map(|x| quote(x))
+            .ok_or_else(|| ["unknown placeholder '{", key, "}'"].concat())
+    })
+}
+
+/// Check that every placeholder of `template` is one a report fills, or with a `baseline` one it
+/// fills as well, before the run rather than after it.
+pub fn check(template: &str, baseline: bool) -> Result<(), String> {
+    substitute(template, |key| {
+        if PLACEHOLDERS.contains(&key) || baseline && BASELINE_PLACEHOLDERS.contains(&key) {
+            Ok(String::new())
+        } else if BASELINE_PLACEHOLDERS.contains(&key) {
+            Err(["placeholder '{", key, "}' requires --baseline"].concat())
+        } else {
+            Err(["unknown placeholder '{", key, "}'"].concat())
+        }
+    })
+    .map(|_| ())
+}
+
+/// Run `command` through the platform shell.
+pub fn run(command: &str) -> Result<(), String> {
+    #[cfg(windows)]
+    let status = process::Command::new("cmd").args(["/C", command]).status();
+    #[cfg(not(windows))]
+    let status = process::Command::new("sh").args(["-c", command]).status();
+
+    let status = status.map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("hook exited with {}", status))
+    }
+}
@@ -0,0 +1,68 @@
+use std::{fs, io, path};
+
+const KERNEL: &str = include_str!("../templates/kernel.c");
+const SUITE: &str = include_str!("../templates/suite.toml");
+const GITIGNORE: &str = include_str!("../templates/gitignore");
+
+pub const ARCHITECTURES: &[&str] = &["aarch64", "x86_64"];
+
+pub fn parse_arch(value: &str) -> Result<String, String> {
+    match value {
+        "aarch64" | "x86_64" => Ok(value.to_string()),
+        "x86" => Ok("x86_64".to_string()),
+        x => Err([
+            "expected one of [",
+            ARCHITECTURES.join(", ").as_str(),
+            "], but got ",
+            x,
+        ]
+        .concat()),
+    }
+}
+
+pub fn default_arch() -> String {
+    #[cfg(target_arch = "aarch64")]
+    return String::from("aarch64");
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    return String::from("x86_64");
+}
+
+/// Scaffold a kernel project in `dir`. Existing files are never overwritten.
+pub fn scaffold(dir: &path::Path, arch: &str) -> io::Result<Vec<path::PathBuf>> {
+    let (include, compiler) = match arch {
+        "aarch64" if cfg!(target_os = "macos") => ("#include <arm_neon.h>\n", "clang"),
+        "aarch64" => ("#include <arm_neon.h>\n", "armclang"),
+        _ => ("#include <immintrin.h>\n", "icc"),
+    };
+
+    let files = [
+        (
+            "kernel.c",
+            KERNEL
+                .replace("{{ARCH}}", arch)
+                .replace("{{INCLUDE}}", include),
+        ),
+        ("suite.toml", SUITE.replace("{{COMPILER}}", compiler)),
+        (".gitignore", GITIGNORE.to_string()),
+    ];
+
+    if let Some(file) = files
+        .iter()
+        .map(|(name, _)| dir.join(name))
+        .find(|x| x.exists())
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", file.display()),
+        ));
+    }
+
+    fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for (name, content) in files {
+        let file = dir.join(name);
+        fs::write(&file, content)?;
+        written.push(file);
+    }
+    Ok(written)
+}
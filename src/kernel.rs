@@ -0,0 +1,919 @@
+use crate::{abi, batch, common::Duration, conv, loader, openmp};
+use library::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+use std::{
+    ffi::{c_double, c_int, c_uint, c_void},
+    marker::PhantomData,
+    ptr, time,
+};
+
+pub type CallDgemmV1 = unsafe extern "C" fn(
+    layout: CBLAS_LAYOUT,
+    trans_a: CBLAS_TRANSPOSE,
+    trans_b: CBLAS_TRANSPOSE,
+    m: usize,
+    n: usize,
+    k: usize,
+    alpha: c_double,
+    a: *const c_double,
+    lda: usize,
+    b: *const c_double,
+    ldb: usize,
+    beta: c_double,
+    c: *mut c_double,
+    ldc: usize,
+);
+
+pub type CallDgemmV2 = unsafe extern "C" fn(
+    context: *const abi::Context,
+    layout: CBLAS_LAYOUT,
+    trans_a: CBLAS_TRANSPOSE,
+    trans_b: CBLAS_TRANSPOSE,
+    m: usize,
+    n: usize,
+    k: usize,
+    alpha: c_double,
+    a: *const c_double,
+    lda: usize,
+    b: *const c_double,
+    ldb: usize,
+    beta: c_double,
+    c: *mut c_double,
+    ldc: usize,
+);
+
+type CallDgemmBatchV1 = unsafe extern "C" fn(
+    layout: CBLAS_LAYOUT,
+    trans_a: *const CBLAS_TRANSPOSE,
+    trans_b: *const CBLAS_TRANSPOSE,
+    m: *const usize,
+    n: *const usize,
+    k: *const usize,
+    alpha: *const c_double,
+    a_array: *const *const c_double,
+    lda: *const usize,
+    b_array: *const *const c_double,
+    ldb: *const usize,
+    beta: *const c_double,
+    c_array: *const *mut c_double,
+    ldc: *const usize,
+    group_count: usize,
+    group_size: *const usize,
+);
+
+type CallDgemmBatchV2 = unsafe extern "C" fn(
+    context: *const abi::Context,
+    layout: CBLAS_LAYOUT,
+    trans_a: *const CBLAS_TRANSPOSE,
+    trans_b: *const CBLAS_TRANSPOSE,
+    m: *const usize,
+    n: *const usize,
+    k: *const usize,
+    alpha: *const c_double,
+    a_array: *const *const c_double,
+    lda: *const usize,
+    b_array: *const *const c_double,
+    ldb: *const usize,
+    beta: *const c_double,
+    c_array: *const *mut c_double,
+    ldc: *const usize,
+    group_count: usize,
+    group_size: *const usize,
+);
+
+type CallDgemmStridedBatchedV1 = unsafe extern "C" fn(
+    layout: CBLAS_LAYOUT,
+    trans_a: CBLAS_TRANSPOSE,
+    trans_b: CBLAS_TRANSPOSE,
+    m: usize,
+    n: usize,
+    k: usize,
+    alpha: c_double,
+    a: *const c_double,
+    lda: usize,
+    stride_a: usize,
+    b: *const c_double,
+    ldb: usize,
+    stride_b: usize,
+    beta: c_double,
+    c: *mut c_double,
+    ldc: usize,
+    stride_c: usize,
+    batch_count: usize,
+);
+
+type CallDgemmStridedBatchedV2 = unsafe extern "C" fn(
+    context: *const abi::Context,
+    layout: CBLAS_LAYOUT,
+    trans_a: CBLAS_TRANSPOSE,
+    trans_b: CBLAS_TRANSPOSE,
+    m: usize,
+    n: usize,
+    k: usize,
+    alpha: c_double,
+    a: *const c_double,
+    lda: usize,
+    stride_a: usize,
+    b: *const c_double,
+    ldb: usize,
+    stride_b: usize,
+    beta: c_double,
+    c: *mut c_double,
+    ldc: usize,
+    stride_c: usize,
+    batch_count: usize,
+);
+
+type PackV1 = unsafe extern "C" fn(
+    layout: CBLAS_LAYOUT,
+    trans: CBLAS_TRANSPOSE,
+    m: usize,
+    n: usize,
+    k: usize,
+    alpha: c_double,
+    src: *const c_double,
+    ld: usize,
+) -> *mut c_void;
+
+type PackV2 = unsafe extern "C" fn(
+    context: *const abi::Context,
+    layout: CBLAS_LAYOUT,
+    trans: CBLAS_TRANSPOSE,
+    m: usize,
+    n: usize,
+    k: usize,
+    alpha: c_double,
+    src: *const c_double,
+    ld: usize,
+) -> *mut c_void;
+
+type ComputePackedV1 = unsafe extern "C" fn(
+    layout: CBLAS_LAYOUT,
+    m: usize,
+    n: usize,
+    k: usize,
+    a: *const c_void,
+    b: *const c_void,
+    beta: c_double,
+    c: *mut c_double,
+    ldc: usize,
+);
+
+type ComputePackedV2 = unsafe extern "C" fn(
+    context: *const abi::Context,
+    layout: CBLAS_LAYOUT,
+    m: usize,
+    n: usize,
+    k: usize,
+    a: *const c_void,
+    b: *const c_void,
+    beta: c_double,
+    c: *mut c_double,
+    ldc: usize,
+);
+
+type FreePacked = unsafe extern "C" fn(packed: *mut c_void);
+
+type Im2colV1 = unsafe extern "C" fn(
+    layout: CBLAS_LAYOUT,
+    trans: CBLAS_TRANSPOSE,
+    n: usize,
+    c: usize,
+    h: usize,
+    w: usize,
+    r: usize,
+    s: usize,
+    stride_h: usize,
+    stride_w: usize,
+    pad_h: usize,
+    pad_w: usize,
+    input: *const c_double,
+    columns: *mut c_double,
+    ld: usize,
+);
+
+type Im2colV2 = unsafe extern "C" fn(
+    context: *const abi::Context,
+    layout: CBLAS_LAYOUT,
+    trans: CBLAS_TRANSPOSE,
+    n: usize,
+    c: usize,
+    h: usize,
+    w: usize,
+    r: usize,
+    s: usize,
+    stride_h: usize,
+    stride_w: usize,
+    pad_h: usize,
+    pad_w: usize,
+    input: *const c_double,
+    columns: *mut c_double,
+    ld: usize,
+);
+
+/// A loaded kernel, which owns the library and hands out its entry points. Kernels borrow the
+/// library and packed buffers borrow the kernel that packed them, so none of them can be used
+/// after the library is closed, and `close` takes the library by value.
+///
+/// ```no_run
+/// use benchmark::{abi, kernel::Library, loader};
+/// use library::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+///
+/// let library = Library::open("./kernel.so", loader::DEFAULT_FLAGS, false).unwrap();
+/// let kernel = library.kernel(None).unwrap();
+/// let (a, b, mut c) = ([1.0; 4], [1.0; 4], [0.0; 4]);
+/// let duration = kernel.run(
+///     abi::Context::new(abi::Phase::Measurement, 0, 0),
+///     CBLAS_LAYOUT::CblasRowMajor,
+///     CBLAS_TRANSPOSE::CblasNoTrans,
+///     CBLAS_TRANSPOSE::CblasNoTrans,
+///     (2, 2, 2),
+///     &a,
+///     2,
+///     &b,
+///     2,
+///     &mut c,
+///     2,
+///     1.0,
+///     0.0,
+/// );
+/// println!("{:.6}ms", duration.as_millis());
+/// library.close().unwrap();
+/// ```
+///
+/// A kernel cannot be used once its library is closed:
+///
+/// ```compile_fail
+/// use benchmark::{kernel::Library, loader};
+///
+/// let library = Library::open("./kernel.so", loader::DEFAULT_FLAGS, false).unwrap();
+/// let kernel = library.kernel(None).unwrap();
+/// library.close().unwrap();
+/// drop(kernel);
+/// ```
+///
+/// nor outlive it:
+///
+/// ```compile_fail
+/// use benchmark::{kernel::{Kernel, Library}, loader};
+///
+/// fn load(path: &str) -> Kernel<'static> {
+///     let library = Library::open(path, loader::DEFAULT_FLAGS, false).unwrap();
+///     library.kernel(None).unwrap()
+/// }
+/// ```
+///
+/// and packed buffers, which `free_packed` of the library frees when they are dropped, are
+/// dropped before it is closed:
+///
+/// ```compile_fail
+/// use benchmark::{abi, kernel::Library, loader};
+/// use library::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+///
+/// let library = Library::open("./kernel.so", loader::DEFAULT_FLAGS, false).unwrap();
+/// let kernel = library.packed_kernel(None).unwrap();
+/// let a = [1.0; 4];
+/// let (packed, _) = kernel.pack(
+///     abi::Context::new(abi::Phase::Measurement, 0, 0),
+///     CBLAS_LAYOUT::CblasRowMajor,
+///     CBLAS_TRANSPOSE::CblasNoTrans,
+///     (2, 2, 2),
+///     1.0,
+///     (&a, 2),
+///     false,
+/// );
+/// library.close().unwrap();
+/// ```
+pub struct Library {
+    library: libloading::Library,
+}
+
+impl Library {
+    /// Load the object at `path` with `loader::load`.
+    pub fn open(path: &str, flags: c_int, isolate: bool) -> Result<Library, String> {
+        loader::load(path, flags, isolate).map(|library| Library { library })
+    }
+
+    /// ABI version the kernel implements, checked against `required` if any.
+    pub fn version(&self, required: Option<c_uint>) -> Result<c_uint, String> {
+        let version = abi::version(&self.library)?;
+        if let Some(required) = required.filter(|&x| x != version) {
+            return Err(format!(
+                "kernel implements ABI version {}, but version {} is required",
+                version, required
+            ));
+        }
+        Ok(version)
+    }
+
+    pub fn kernel(&self, required: Option<c_uint>) -> Result<Kernel<'_>, String> {
+        let version = self.version(required)?;
+        let missing = |_| "compiled object does not contain symbol call_dgemm".to_string();
+        Ok(Kernel(match version {
+            abi::V2 => Call::V2(unsafe { self.library.get(b"call_dgemm") }.map_err(missing)?),
+            _ => Call::V1(unsafe { self.library.get(b"call_dgemm") }.map_err(missing)?),
+        }))
+    }
+
+    pub fn batch_kernel(
+        &self,
+        required: Option<c_uint>,
+        strided: bool,
+    ) -> Result<BatchKernel<'_>, String> {
+        let version = self.version(required)?;
+        let name = if strided {
+            "call_dgemm_strided_batched"
+        } else {
+            "call_dgemm_batch"
+        };
+        let missing = |_| format!("compiled object does not contain symbol {}", name);
+        let symbol = name.as_bytes();
+        Ok(BatchKernel(match (version, strided) {
+            (abi::V2, false) => Batch::V2(unsafe { self.library.get(symbol) }.map_err(missing)?),
+            (_, false) => Batch::V1(unsafe { self.library.get(symbol) }.map_err(missing)?),
+            (abi::V2, true) => {
+                Batch::StridedV2(unsafe { self.library.get(symbol) }.map_err(missing)?)
+            }
+            (_, true) => Batch::StridedV1(unsafe { self.library.get(symbol) }.map_err(missing)?),
+        }))
+    }
+
+    pub fn packed_kernel(&self, required: Option<c_uint>) -> Result<PackedKernel<'_>, String> {
+        let version = self.version(required)?;
+        fn get<'lib, T>(
+            library: &'lib libloading::Library,
+            name: &str,
+        ) -> Result<libloading::Symbol<'lib, T>, String> {
+            unsafe { library.get(name.as_bytes()) }
+                .map_err(|_| format!("compiled object does not contain symbol {}", name))
+        }
+        Ok(PackedKernel(match version {
+            abi::V2 => Packing::V2 {
+                pack_a: get(&self.library, "pack_a")?,
+                pack_b: get(&self.library, "pack_b")?,
+                compute: get(&self.library, "compute_packed")?,
+                free: get(&self.library, "free_packed")?,
+            },
+            _ => Packing::V1 {
+                pack_a: get(&self.library, "pack_a")?,
+                pack_b: get(&self.library, "pack_b")?,
+                compute: get(&self.library, "compute_packed")?,
+                free: get(&self.library, "free_packed")?,
+            },
+        }))
+    }
+
+    /// The kernel's `im2col`, None if it has none.
+    pub fn im2col(&self, required: Option<c_uint>) -> Result<Option<Im2col<'_>>, String> {
+        let version = self.version(required)?;
+        Ok(match version {
+            abi::V2 => unsafe { self.library.get(b"im2col") }
+                .ok()
+                .map(|x| Im2col(Lowering::V2(x))),
+            _ => unsafe { self.library.get(b"im2col") }
+                .ok()
+                .map(|x| Im2col(Lowering::V1(x))),
+        })
+    }
+
+    /// OpenMP runtime the kernel was linked with, if any.
+    pub fn openmp(&self) -> Option<openmp::OpenMP> {
+        openmp::query(&self.library)
+    }
+
+    /// Call the kernel's `prewarm_threads`, false if it has none.
+    pub fn prewarm_threads(&self) -> bool {
+        match unsafe {
+            self.library
+                .get::<unsafe extern "C" fn()>(b"prewarm_threads")
+        } {
+            Ok(prewarm) => {
+                unsafe { prewarm() };
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Close the library, which no kernel or packed buffer can outlive.
+    pub fn close(self) -> Result<(), String> {
+        self.library.close().map_err(|e| e.to_string())
+    }
+}
+
+/// Elements op(X) of `rows` × `cols` spans with `layout`, `trans` and leading dimension `ld`.
+/// Panics if `ld` is less than a stored row of a row-major matrix or column of a column-major
+/// one, as the kernel would read past it.
+fn extent(
+    layout: CBLAS_LAYOUT,
+    trans: CBLAS_TRANSPOSE,
+    (rows, cols): (usize, usize),
+    ld: usize,
+) -> usize {
+    let (rows, cols) = if trans == CBLAS_TRANSPOSE::CblasNoTrans {
+        (rows, cols)
+    } else {
+        (cols, rows)
+    };
+    let (lines, length) = if layout == CBLAS_LAYOUT::CblasRowMajor {
+        (rows, cols)
+    } else {
+        (cols, rows)
+    };
+    assert!(
+        ld >= length.max(1),
+        "leading dimension {} is less than {}",
+        ld,
+        length.max(1)
+    );
+    if lines == 0 || length == 0 {
+        0
+    } else {
+        (lines - 1) * ld + length
+    }
+}
+
+/// Panics unless `matrix`, named `name`, has at least `extent` elements.
+fn check(name: &str, matrix: &[f64], extent: usize) {
+    assert!(
+        matrix.len() >= extent,
+        "{} has {} elements, but the call reaches {}",
+        name,
+        matrix.len(),
+        extent
+    );
+}
+
+/// `call_dgemm` with the call convention selected by the kernel's ABI version.
+pub struct Kernel<'lib>(Call<'lib>);
+
+enum Call<'lib> {
+    V1(libloading::Symbol<'lib, CallDgemmV1>),
+    V2(libloading::Symbol<'lib, CallDgemmV2>),
+}
+
+impl Kernel<'_> {
+    /// Run the kernel on op(A) of m × k, op(B) of k × n and C of m × n; panics if the
+    /// dimensions and leading dimensions reach past the slices.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run(
+        &self,
+        context: abi::Context,
+        layout: CBLAS_LAYOUT,
+        trans_a: CBLAS_TRANSPOSE,
+        trans_b: CBLAS_TRANSPOSE,
+        (m, n, k): (usize, usize, usize),
+        a: &[f64],
+        lda: usize,
+        b: &[f64],
+        ldb: usize,
+        c: &mut [f64],
+        ldc: usize,
+        alpha: f64,
+        beta: f64,
+    ) -> Duration {
+        check("A", a, extent(layout, trans_a, (m, k), lda));
+        check("B", b, extent(layout, trans_b, (k, n), ldb));
+        check(
+            "C",
+            c,
+            extent(layout, CBLAS_TRANSPOSE::CblasNoTrans, (m, n), ldc),
+        );
+        let a = a.as_ptr();
+        let b = b.as_ptr();
+        let c = c.as_mut_ptr();
+
+        let start_time = time::Instant::now();
+        unsafe {
+            match &self.0 {
+                Call::V1(call) => call(
+                    layout, trans_a, trans_b, m, n, k, alpha, a, lda, b, ldb, beta, c, ldc,
+                ),
+                Call::V2(call) => call(
+                    &context, layout, trans_a, trans_b, m, n, k, alpha, a, lda, b, ldb, beta, c,
+                    ldc,
+                ),
+            }
+        }
+        let end_time = time::Instant::now();
+        Duration::from(end_time - start_time)
+    }
+}
+
+/// `im2col` of a kernel benchmarked on a convolution, which lowers the input tensor to B.
+pub struct Im2col<'lib>(Lowering<'lib>);
+
+enum Lowering<'lib> {
+    V1(libloading::Symbol<'lib, Im2colV1>),
+    V2(libloading::Symbol<'lib, Im2colV2>),
+}
+
+impl Im2col<'_> {
+    /// Lower `input`, an NCHW tensor of `shape`, into `columns`, laid out like B; panics if
+    /// either is too small for `shape`.
+    pub fn run(
+        &self,
+        context: abi::Context,
+        layout: CBLAS_LAYOUT,
+        trans: CBLAS_TRANSPOSE,
+        shape: &conv::Shape,
+        input: &[f64],
+        (columns, ld): (&mut [f64], usize),
+    ) -> Duration {
+        let (n, c, h, w) = (shape.batch, shape.channels, shape.height, shape.width);
+        let (r, s) = (shape.filter_height, shape.filter_width);
+        let ((stride_h, stride_w), (pad_h, pad_w)) = (shape.stride, shape.padding);
+        let (_, columns_n, columns_k) = shape.gemm();
+        check("the input", input, shape.input_size());
+        check(
+            "the columns",
+            columns,
+            extent(layout, trans, (columns_k, columns_n), ld),
+        );
+        let input = input.as_ptr();
+        let columns = columns.as_mut_ptr();
+
+        let start_time = time::Instant::now();
+        unsafe {
+            match &self.0 {
+                Lowering::V1(call) => call(
+                    layout, trans, n, c, h, w, r, s, stride_h, stride_w, pad_h, pad_w, input,
+                    columns, ld,
+                ),
+                Lowering::V2(call) => call(
+                    &context, layout, trans, n, c, h, w, r, s, stride_h, stride_w, pad_h, pad_w,
+                    input, columns, ld,
+                ),
+            }
+        }
+        let end_time = time::Instant::now();
+        Duration::from(end_time - start_time)
+    }
+}
+
+/// `call_dgemm_batch`, which takes the arguments of `cblas_dgemm_batch`, or
+/// `call_dgemm_strided_batched`, which takes those of `cblas_dgemm_batch_strided`.
+pub struct BatchKernel<'lib>(Batch<'lib>);
+
+enum Batch<'lib> {
+    V1(libloading::Symbol<'lib, CallDgemmBatchV1>),
+    V2(libloading::Symbol<'lib, CallDgemmBatchV2>),
+    StridedV1(libloading::Symbol<'lib, CallDgemmStridedBatchedV1>),
+    StridedV2(libloading::Symbol<'lib, CallDgemmStridedBatchedV2>),
+}
+
+/// Panics unless `x` has the arrays of one or more groups and a pointer per problem, and every
+/// problem, or every one the strides reach if `strided`, lies within the buffer of its group.
+fn check_batch(layout: CBLAS_LAYOUT, x: &batch::Operands, strided: bool) {
+    let groups = x.group_size.len();
+    assert!(groups > 0, "the batch has no groups");
+    for (name, length) in [
+        ("trans_a", x.trans_a.len()),
+        ("trans_b", x.trans_b.len()),
+        ("m", x.m.len()),
+        ("n", x.n.len()),
+        ("k", x.k.len()),
+        ("alpha", x.alpha.len()),
+        ("lda", x.lda.len()),
+        ("ldb", x.ldb.len()),
+        ("beta", x.beta.len()),
+        ("ldc", x.ldc.len()),
+        ("the buffers of A", x.a.len()),
+        ("the buffers of B", x.b.len()),
+        ("the buffers of C", x.c.len()),
+    ] {
+        assert!(
+            length == groups,
+            "{} has {} entries for {} groups",
+            name,
+            length,
+            groups
+        );
+    }
+    let problems = x.group_size.iter().sum::<usize>();
+    for (name, length) in [
+        ("a_array", x.a_array.len()),
+        ("b_array", x.b_array.len()),
+        ("c_array", x.c_array.len()),
+    ] {
+        assert!(
+            length == problems,
+            "{} has {} pointers for {} problems",
+            name,
+            length,
+            problems
+        );
+    }
+    let strides = x.strides.filter(|_| strided).map(|x| [x.a, x.b, x.c]);
+    if strided {
+        assert!(
+            groups == 1 && strides.is_some() && x.group_size[0] > 0,
+            "a strided batch has one group of strided operands"
+        );
+    }
+
+    let first = [x.a_array[0], x.b_array[0], x.c_array[0] as *const f64];
+    let mut problem = 0;
+    for group in 0..groups {
+        let (m, n, k) = (x.m[group], x.n[group], x.k[group]);
+        let matrices: [(&str, &[f64], usize); 3] = [
+            (
+                "A",
+                &x.a[group],
+                extent(layout, x.trans_a[group], (m, k), x.lda[group]),
+            ),
+            (
+                "B",
+                &x.b[group],
+                extent(layout, x.trans_b[group], (k, n), x.ldb[group]),
+            ),
+            (
+                "C",
+                &x.c[group],
+                extent(layout, CBLAS_TRANSPOSE::CblasNoTrans, (m, n), x.ldc[group]),
+            ),
+        ];
+        for index in 0..x.group_size[group] {
+            let pointers = [
+                x.a_array[problem],
+                x.b_array[problem],
+                x.c_array[problem] as *const f64,
+            ];
+            for (matrix, (name, buffer, extent)) in matrices.into_iter().enumerate() {
+                // the strided variants reach every problem from the first pointer.
+                let offset = match strides {
+                    Some(strides) => offset(first[matrix], buffer)
+                        .and_then(|x| strides[matrix].checked_mul(index)?.checked_add(x)),
+                    None => offset(pointers[matrix], buffer),
+                };
+                let name = format!("{} of problem {}", name, problem);
+                match offset.filter(|&x| x <= buffer.len()) {
+                    Some(offset) => check(&name, &buffer[offset..], extent),
+                    None => panic!("{} points outside its buffer", name),
+                }
+            }
+            problem += 1;
+        }
+    }
+}
+
+/// Elements from the start of `buffer` to `pointer`, if it does not point before it.
+fn offset(pointer: *const f64, buffer: &[f64]) -> Option<usize> {
+    (pointer as usize)
+        .checked_sub(buffer.as_ptr() as usize)
+        .map(|x| x / size_of::<f64>())
+}
+
+impl BatchKernel<'_> {
+    /// Run the kernel on every problem of `x`; panics if a problem reaches past its buffer.
+    pub fn run(
+        &self,
+        context: abi::Context,
+        layout: CBLAS_LAYOUT,
+        x: &batch::Operands,
+    ) -> Duration {
+        let strided = matches!(self.0, Batch::StridedV1(_) | Batch::StridedV2(_));
+        check_batch(layout, x, strided);
+        // only strided operands are run by the strided variants.
+        let strides = x.strides.unwrap_or(batch::Strides { a: 0, b: 0, c: 0 });
+        let start_time = time::Instant::now();
+        unsafe {
+            match &self.0 {
+                Batch::StridedV1(call) => call(
+                    layout,
+                    x.trans_a[0],
+                    x.trans_b[0],
+                    x.m[0],
+                    x.n[0],
+                    x.k[0],
+                    x.alpha[0],
+                    x.a_array[0],
+                    x.lda[0],
+                    strides.a,
+                    x.b_array[0],
+                    x.ldb[0],
+                    strides.b,
+                    x.beta[0],
+                    x.c_array[0],
+                    x.ldc[0],
+                    strides.c,
+                    x.group_size[0],
+                ),
+                Batch::StridedV2(call) => call(
+                    &context,
+                    layout,
+                    x.trans_a[0],
+                    x.trans_b[0],
+                    x.m[0],
+                    x.n[0],
+                    x.k[0],
+                    x.alpha[0],
+                    x.a_array[0],
+                    x.lda[0],
+                    strides.a,
+                    x.b_array[0],
+                    x.ldb[0],
+                    strides.b,
+                    x.beta[0],
+                    x.c_array[0],
+                    x.ldc[0],
+                    strides.c,
+                    x.group_size[0],
+                ),
+                Batch::V1(call) => call(
+                    layout,
+                    x.trans_a.as_ptr(),
+                    x.trans_b.as_ptr(),
+                    x.m.as_ptr(),
+                    x.n.as_ptr(),
+                    x.k.as_ptr(),
+                    x.alpha.as_ptr(),
+                    x.a_array.as_ptr(),
+                    x.lda.as_ptr(),
+                    x.b_array.as_ptr(),
+                    x.ldb.as_ptr(),
+                    x.beta.as_ptr(),
+                    x.c_array.as_ptr(),
+                    x.ldc.as_ptr(),
+                    x.group_size.len(),
+                    x.group_size.as_ptr(),
+                ),
+                Batch::V2(call) => call(
+                    &context,
+                    layout,
+                    x.trans_a.as_ptr(),
+                    x.trans_b.as_ptr(),
+                    x.m.as_ptr(),
+                    x.n.as_ptr(),
+                    x.k.as_ptr(),
+                    x.alpha.as_ptr(),
+                    x.a_array.as_ptr(),
+                    x.lda.as_ptr(),
+                    x.b_array.as_ptr(),
+                    x.ldb.as_ptr(),
+                    x.beta.as_ptr(),
+                    x.c_array.as_ptr(),
+                    x.ldc.as_ptr(),
+                    x.group_size.len(),
+                    x.group_size.as_ptr(),
+                ),
+            }
+        }
+        let end_time = time::Instant::now();
+        Duration::from(end_time - start_time)
+    }
+}
+
+/// `pack_a`, `pack_b`, `compute_packed` and `free_packed` of a kernel that packs A and B into
+/// its own buffers once and computes from them many times.
+pub struct PackedKernel<'lib>(Packing<'lib>);
+
+enum Packing<'lib> {
+    V1 {
+        pack_a: libloading::Symbol<'lib, PackV1>,
+        pack_b: libloading::Symbol<'lib, PackV1>,
+        compute: libloading::Symbol<'lib, ComputePackedV1>,
+        free: libloading::Symbol<'lib, FreePacked>,
+    },
+    V2 {
+        pack_a: libloading::Symbol<'lib, PackV2>,
+        pack_b: libloading::Symbol<'lib, PackV2>,
+        compute: libloading::Symbol<'lib, ComputePackedV2>,
+        free: libloading::Symbol<'lib, FreePacked>,
+    },
+}
+
+/// Buffer returned by `pack_a` or `pack_b`, freed by `free_packed` when it is dropped.
+pub struct Packed<'kernel> {
+    pointer: *mut c_void,
+    free: FreePacked,
+    /// kernel, operand and dimensions it was packed for, which `compute` has to be called with.
+    kernel: *const c_void,
+    b: bool,
+    dimensions: (usize, usize, usize),
+    lifetime: PhantomData<&'kernel ()>,
+}
+
+impl Packed<'_> {
+    pub fn is_null(&self) -> bool {
+        self.pointer.is_null()
+    }
+}
+
+impl Drop for Packed<'_> {
+    fn drop(&mut self) {
+        if !self.pointer.is_null() {
+            unsafe { (self.free)(self.pointer) }
+        }
+    }
+}
+
+impl PackedKernel<'_> {
+    /// Pack A, or B if `b`; alpha is folded into A. Panics if the dimensions and leading
+    /// dimension reach past `src`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn pack<'kernel>(
+        &'kernel self,
+        context: abi::Context,
+        layout: CBLAS_LAYOUT,
+        trans: CBLAS_TRANSPOSE,
+        (m, n, k): (usize, usize, usize),
+        alpha: f64,
+        (src, ld): (&[f64], usize),
+        b: bool,
+    ) -> (Packed<'kernel>, Duration) {
+        let (name, dimensions) = if b { ("B", (k, n)) } else { ("A", (m, k)) };
+        check(name, src, extent(layout, trans, dimensions, ld));
+        let start_time = time::Instant::now();
+        let packed = unsafe {
+            match (&self.0, b) {
+                (Packing::V1 { pack_a, .. }, false) => {
+                    pack_a(layout, trans, m, n, k, alpha, src.as_ptr(), ld)
+                }
+                (Packing::V1 { pack_b, .. }, true) => {
+                    pack_b(layout, trans, m, n, k, alpha, src.as_ptr(), ld)
+                }
+                (Packing::V2 { pack_a, .. }, false) => {
+                    pack_a(&context, layout, trans, m, n, k, alpha, src.as_ptr(), ld)
+                }
+                (Packing::V2 { pack_b, .. }, true) => {
+                    pack_b(&context, layout, trans, m, n, k, alpha, src.as_ptr(), ld)
+                }
+            }
+        };
+        let end_time = time::Instant::now();
+        let free = match &self.0 {
+            Packing::V1 { free, .. } | Packing::V2 { free, .. } => **free,
+        };
+        let packed = Packed {
+            pointer: packed,
+            free,
+            kernel: self as *const Self as *const c_void,
+            b,
+            dimensions: (m, n, k),
+            lifetime: PhantomData,
+        };
+        (packed, Duration::from(end_time - start_time))
+    }
+
+    /// Compute C from A and B packed by this kernel for the same dimensions; panics if they
+    /// were not, or if C is too small.
+    #[allow(clippy::too_many_arguments)]
+    pub fn compute<'kernel>(
+        &'kernel self,
+        context: abi::Context,
+        layout: CBLAS_LAYOUT,
+        (m, n, k): (usize, usize, usize),
+        (a, b): (&Packed<'kernel>, &Packed<'kernel>),
+        beta: f64,
+        c: &mut [f64],
+        ldc: usize,
+    ) -> Duration {
+        for (packed, is_b) in [(a, false), (b, true)] {
+            assert!(
+                ptr::eq(packed.kernel, self as *const Self as *const c_void)
+                    && packed.b == is_b
+                    && packed.dimensions == (m, n, k),
+                "{} was not packed by this kernel for {}x{}x{}",
+                if is_b { "B" } else { "A" },
+                m,
+                n,
+                k
+            );
+        }
+        check(
+            "C",
+            c,
+            extent(layout, CBLAS_TRANSPOSE::CblasNoTrans, (m, n), ldc),
+        );
+        let start_time = time::Instant::now();
+        unsafe {
+            match &self.0 {
+                Packing::V1 { compute, .. } => compute(
+                    layout,
+                    m,
+                    n,
+                    k,
+                    a.pointer,
+                    b.pointer,
+                    beta,
+                    c.as_mut_ptr(),
+                    ldc,
+                ),
+                Packing::V2 { compute, .. } => compute(
+                    &context,
+                    layout,
+                    m,
+                    n,
+                    k,
+                    a.pointer,
+                    b.pointer,
+                    beta,
+                    c.as_mut_ptr(),
+                    ldc,
+                ),
+            }
+        }
+        let end_time = time::Instant::now();
+        Duration::from(end_time - start_time)
+    }
+}
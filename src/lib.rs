@@ -1,2 +1,62 @@
+pub mod abi;
+pub mod affinity;
+pub mod autotune;
+pub mod batch;
+pub mod bayesian;
+pub mod bundle;
+pub mod cache;
+pub mod campaign;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod checkpoint;
 pub mod common;
+pub mod compile_commands;
+pub mod compiler;
+pub mod config;
+pub mod conformance;
+pub mod constraint;
+pub mod conv;
+pub mod counter;
+pub mod diagnostics;
+pub mod disassembly;
+pub mod distributed;
+pub mod examples;
+pub mod expect;
+pub mod footprint;
+pub mod hints;
+pub mod history;
+pub mod hook;
+pub mod init;
+pub mod kernel;
+pub mod loader;
+pub mod lock;
+pub mod machine;
+pub mod methodology;
+pub mod model;
+pub mod noise;
+pub mod openmp;
+pub mod paired;
+pub mod placement;
+pub mod pool;
+pub mod preset;
+pub mod protect;
+pub mod prune;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod redact;
+pub mod reproducer;
+pub mod session;
+pub mod sink;
+pub mod status;
+pub mod suite;
+pub mod sweep;
+pub mod table;
+pub mod threads;
+pub mod units;
 pub mod utils;
+pub mod validate;
+pub mod verification;
+pub mod verifier;
+pub mod workspace;
+
+pub use benchmark_core::number;
@@ -0,0 +1,119 @@
+use std::ffi::c_int;
+#[cfg(target_os = "linux")]
+use std::ffi::{CStr, CString};
+
+/// Flags used by `libloading::Library::new`.
+#[cfg(unix)]
+pub const DEFAULT_FLAGS: c_int = libc::RTLD_LAZY | libc::RTLD_LOCAL;
+#[cfg(windows)]
+pub const DEFAULT_FLAGS: c_int = 0;
+
+/// Parse comma-separated dlopen flags, e.g. "now,local,deepbind".
+#[cfg(unix)]
+pub fn parse_flags(value: &str) -> Result<c_int, String> {
+    let mut flags = 0;
+    for flag in value.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+        flags |= match flag.to_uppercase().as_str() {
+            "LAZY" => libc::RTLD_LAZY,
+            "NOW" => libc::RTLD_NOW,
+            "LOCAL" => libc::RTLD_LOCAL,
+            "GLOBAL" => libc::RTLD_GLOBAL,
+            #[cfg(target_os = "linux")]
+            "DEEPBIND" => libc::RTLD_DEEPBIND,
+            "NODELETE" => libc::RTLD_NODELETE,
+            x => {
+                return Err([
+                    "expected one of [LAZY, NOW, LOCAL, GLOBAL, DEEPBIND, NODELETE], but got ",
+                    x,
+                ]
+                .concat());
+            }
+        };
+    }
+    if flags & (libc::RTLD_LAZY | libc::RTLD_NOW) == 0 {
+        flags |= libc::RTLD_LAZY;
+    }
+    Ok(flags)
+}
+
+/// Inverse of `parse_flags`.
+#[cfg(unix)]
+pub fn format_flags(flags: c_int) -> String {
+    let mut names = vec![
+        if flags & libc::RTLD_NOW != 0 {
+            "now"
+        } else {
+            "lazy"
+        },
+        if flags & libc::RTLD_GLOBAL != 0 {
+            "global"
+        } else {
+            "local"
+        },
+    ];
+    #[cfg(target_os = "linux")]
+    if flags & libc::RTLD_DEEPBIND != 0 {
+        names.push("deepbind");
+    }
+    if flags & libc::RTLD_NODELETE != 0 {
+        names.push("nodelete");
+    }
+    names.join(",")
+}
+
+/// Load a shared object with `flags`.
+/// If `isolate` is set, the object is loaded into a new link-map namespace with dlmopen (Linux only),
+/// so neither it nor its dependencies resolve against symbols already loaded by the harness.
+#[cfg(unix)]
+pub fn load(path: &str, flags: c_int, isolate: bool) -> Result<libloading::Library, String> {
+    if !isolate {
+        return unsafe { libloading::os::unix::Library::open(Some(path), flags) }
+            .map(libloading::Library::from)
+            .map_err(|e| e.to_string());
+    }
+    load_isolated(path, flags)
+}
+
+#[cfg(target_os = "linux")]
+fn load_isolated(path: &str, flags: c_int) -> Result<libloading::Library, String> {
+    let filename = CString::new(path).map_err(|e| e.to_string())?;
+    let handle = unsafe { libc::dlmopen(libc::LM_ID_NEWLM, filename.as_ptr(), flags) };
+    if handle.is_null() {
+        let error = unsafe { libc::dlerror() };
+        return Err(if error.is_null() {
+            "dlmopen failed".to_string()
+        } else {
+            unsafe { CStr::from_ptr(error) }
+                .to_string_lossy()
+                .to_string()
+        });
+    }
+    Ok(unsafe { libloading::os::unix::Library::from_raw(handle) }.into())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn load_isolated(_: &str, _: c_int) -> Result<libloading::Library, String> {
+    Err("isolated namespaces are not supported on this platform".to_string())
+}
+
+#[cfg(windows)]
+pub fn parse_flags(value: &str) -> Result<c_int, String> {
+    if value.trim().is_empty() {
+        Ok(DEFAULT_FLAGS)
+    } else {
+        Err("dlopen flags are not supported on this platform".to_string())
+    }
+}
+
+#[cfg(windows)]
+pub fn format_flags(_: c_int) -> String {
+    String::new()
+}
+
+#[cfg(windows)]
+pub fn load(path: &str, _: c_int, isolate: bool) -> Result<libloading::Library, String> {
+    if isolate {
+        return Err("isolated namespaces are not supported on this platform".to_string());
+    }
+    unsafe { libloading::Library::new(path) }.map_err(|e| e.to_string())
+}
@@ -0,0 +1,120 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{env, fs, io, io::Write, process, time};
+
+/// Lock file every run on a machine takes unless given another one.
+#[cfg(unix)]
+pub const DEFAULT_PATH: &str = "/tmp/benchmark-dgemm.lock";
+#[cfg(not(unix))]
+pub const DEFAULT_PATH: &str = "benchmark-dgemm.lock";
+
+/// Environment variable through which a run holding the lock passes it on to the runs it
+/// starts, e.g. the entries of a suite, which would otherwise wait for it forever.
+pub const HELD: &str = "BENCHMARK_DGEMM_LOCK";
+
+/// How a run got the machine-wide lock.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Locking {
+    /// lock file
+    pub path: String,
+    /// whether another run held the lock, so this one waited for it
+    pub contended: bool,
+    /// seconds spent waiting for it
+    pub waited: f64,
+}
+
+/// Advisory lock that makes benchmark runs on one machine take turns, held until dropped.
+pub struct Lock {
+    /// None if a parent run holds the lock
+    _file: Option<fs::File>,
+    pub locking: Locking,
+}
+
+/// Open the lock file at `path`, creating it for everyone if no run has yet.
+fn open(path: &str) -> io::Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.read(true).write(true);
+    // a symlink planted in /tmp must not redirect the lock to a file of whoever runs next.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.custom_flags(libc::O_NOFOLLOW);
+    }
+    // O_CREAT on a file of another user in a sticky directory such as /tmp fails under
+    // fs.protected_regular even when the file exists, so only a missing file is created.
+    match options.open(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        opened => return opened,
+    }
+    match options.clone().create_new(true).open(path) {
+        Ok(file) => {
+            // runs of other users take the same lock; only its owner can change the mode, on
+            // the file that was opened rather than the path.
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                drop(file.set_permissions(fs::Permissions::from_mode(0o666)));
+            }
+            Ok(file)
+        }
+        // another run created it in between.
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => options.open(path),
+        Err(e) => Err(e),
+    }
+}
+
+/// Take the lock at `path`, waiting for the run that holds it; a run started by one holding
+/// the lock shares it instead.
+pub fn acquire(path: &str) -> Result<Lock, String> {
+    if let Some(locking) = env::var(HELD)
+        .ok()
+        .and_then(|x| serde_json::from_str::<Locking>(&x).ok())
+    {
+        return Ok(Lock {
+            _file: None,
+            locking,
+        });
+    }
+
+    let mut file = open(path).map_err(|e| format!("{}: {}", path, e))?;
+
+    let start = time::Instant::now();
+    let contended = match file.try_lock() {
+        Ok(()) => false,
+        Err(fs::TryLockError::WouldBlock) => {
+            let holder = fs::read_to_string(path).unwrap_or_default();
+            eprintln!(
+                "Waiting for {}, held by {}",
+                path,
+                holder.lines().next().unwrap_or("another run")
+            );
+            file.lock().map_err(|e| format!("{}: {}", path, e))?;
+            true
+        }
+        Err(fs::TryLockError::Error(e)) => return Err(format!("{}: {}", path, e)),
+    };
+    let locking = Locking {
+        path: path.to_string(),
+        contended,
+        waited: if contended {
+            start.elapsed().as_secs_f64()
+        } else {
+            0.0
+        },
+    };
+
+    // tell waiting runs who they wait for; only the pid, as every user can read the file and
+    // the arguments may hold secrets such as --post-token.
+    drop(
+        file.set_len(0)
+            .and_then(|_| writeln!(file, "pid {}", process::id())),
+    );
+    env::set_var(
+        HELD,
+        serde_json::to_string(&locking).expect("Error: failed to serialize"),
+    );
+    Ok(Lock {
+        _file: Some(file),
+        locking,
+    })
+}
@@ -0,0 +1,506 @@
+use crate::cache::Cache;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path, process};
+
+/// Hardware and system software a report was measured on.
+#[derive(Default, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Machine {
+    pub hostname: String,
+    pub cpu_model: String,
+    /// CPUs available to the benchmark
+    pub logical_cpus: usize,
+    /// cpufreq scaling governor of CPU 0
+    pub governor: Option<String>,
+    /// shared libraries mapped into the benchmark when the report was made, e.g. the reference BLAS
+    pub libraries: Vec<String>,
+    /// details of aarch64 machines, whose CPU model alone tells little
+    #[serde(default)]
+    pub arm: Option<Arm>,
+    /// details of x86-64 machines, from cpuid
+    #[serde(default)]
+    pub x86: Option<X86>,
+}
+
+/// What tells x86-64 machines with similar CPU models apart.
+#[derive(Default, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct X86 {
+    /// AVX-512 subsets the CPU reports, e.g. avx512f, avx512vl; empty without AVX-512
+    pub avx512: Vec<String>,
+    /// whether the operating system saves the AVX-512 registers, without which they fault
+    pub avx512_enabled: bool,
+    /// AMX subsets the CPU reports, e.g. amx-tile, amx-bf16
+    pub amx: Vec<String>,
+    /// how the model lowers its clock while running AVX-512, if known
+    pub frequency_hint: Option<String>,
+    /// data and unified caches from the cache leaves of cpuid, which sysfs may hide in VMs
+    pub caches: Vec<Cache>,
+}
+
+/// A CPU the benchmark may run on, as big.LITTLE systems mix core types.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Core {
+    pub cpu: usize,
+    /// "CPU part" of /proc/cpuinfo, e.g. 0xd40 for a Neoverse V1
+    pub part: String,
+    /// relative performance the scheduler assumes, 1024 for the biggest cores
+    pub capacity: Option<u32>,
+    /// highest cpufreq frequency in kHz
+    pub max_frequency: Option<u64>,
+}
+
+/// What tells otherwise identical aarch64 machines apart.
+#[derive(Default, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Arm {
+    /// SVE vector length in bytes from prctl(PR_SVE_GET_VL); None without SVE
+    pub sve_vector_length: Option<usize>,
+    /// CPU the compiler resolves -mcpu=native to, e.g. neoverse-v1
+    pub mcpu: Option<String>,
+    /// current frequency in Hz of every devfreq device, which includes the interconnect (CMN,
+    /// CCI) and L3 or DSU where the platform exposes them
+    pub uncore_frequencies: BTreeMap<String, u64>,
+    /// core type of every CPU the benchmark may run on
+    pub cores: Vec<Core>,
+}
+
+#[cfg(target_os = "linux")]
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .map(|x| x.trim().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn hostname() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_default()
+}
+
+/// "model name" on x86, "CPU part" on ARM, which has no model name.
+#[cfg(target_os = "linux")]
+fn cpu_model() -> String {
+    let Ok(cpuinfo) = std::fs::read_to_string("/proc/cpuinfo") else {
+        return String::new();
+    };
+    let field = |name: &str| {
+        cpuinfo.lines().find_map(|x| {
+            let (key, value) = x.split_once(':')?;
+            (key.trim() == name).then(|| value.trim().to_string())
+        })
+    };
+    field("model name")
+        .or_else(|| {
+            let implementer = field("CPU implementer")?;
+            Some(format!("{} part {}", implementer, field("CPU part")?))
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpu_model() -> String {
+    String::new()
+}
+
+#[cfg(target_os = "linux")]
+fn governor() -> Option<String> {
+    std::fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor")
+        .ok()
+        .map(|x| x.trim().to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn governor() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn libraries() -> Vec<String> {
+    let Ok(maps) = std::fs::read_to_string("/proc/self/maps") else {
+        return Vec::new();
+    };
+    let mut libraries = maps
+        .lines()
+        .filter_map(|x| x.split_whitespace().nth(5))
+        .filter(|x| x.starts_with('/') && x.contains(".so"))
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>();
+    libraries.sort();
+    libraries.dedup();
+    libraries
+}
+
+#[cfg(not(target_os = "linux"))]
+fn libraries() -> Vec<String> {
+    Vec::new()
+}
+
+/// SVE vector length of this thread, as the kernel will see it.
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+fn sve_vector_length() -> Option<usize> {
+    const PR_SVE_GET_VL: libc::c_int = 51;
+    const PR_SVE_VL_LEN_MASK: libc::c_int = 0xffff;
+    let value = unsafe { libc::prctl(PR_SVE_GET_VL) };
+    (value >= 0).then_some((value & PR_SVE_VL_LEN_MASK) as usize)
+}
+
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+fn uncore_frequencies() -> BTreeMap<String, u64> {
+    let Ok(devices) = std::fs::read_dir("/sys/class/devfreq") else {
+        return BTreeMap::new();
+    };
+    devices
+        .filter_map(Result::ok)
+        .filter_map(|x| {
+            let frequency = std::fs::read_to_string(x.path().join("cur_freq")).ok()?;
+            let name = std::fs::read_to_string(x.path().join("name"))
+                .map(|x| x.trim().to_string())
+                .unwrap_or_else(|_| x.file_name().to_string_lossy().to_string());
+            Some((name, frequency.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Core type of every CPU in `cpus`, from its block of /proc/cpuinfo and its sysfs directory.
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+fn cores(cpus: &[usize]) -> Vec<Core> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+    let mut parts = BTreeMap::new();
+    let mut processor = None;
+    for line in cpuinfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key.trim() {
+            "processor" => processor = value.trim().parse::<usize>().ok(),
+            "CPU part" => {
+                if let Some(processor) = processor {
+                    parts.insert(processor, value.trim().to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    let read = |cpu: usize, file: &str| {
+        std::fs::read_to_string(format!("/sys/devices/system/cpu/cpu{}/{}", cpu, file))
+            .ok()
+            .and_then(|x| x.trim().parse().ok())
+    };
+    cpus.iter()
+        .map(|&cpu| Core {
+            cpu,
+            part: parts.get(&cpu).cloned().unwrap_or_default(),
+            capacity: read(cpu, "cpu_capacity").map(|x: u64| x as u32),
+            max_frequency: read(cpu, "cpufreq/cpuinfo_max_freq"),
+        })
+        .collect()
+}
+
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+fn arm() -> Option<Arm> {
+    Some(Arm {
+        sve_vector_length: sve_vector_length(),
+        mcpu: None,
+        uncore_frequencies: uncore_frequencies(),
+        cores: cores(&crate::affinity::available()),
+    })
+}
+
+#[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+fn arm() -> Option<Arm> {
+    None
+}
+
+// __cpuid_count became safe in Rust 1.87.
+#[cfg(target_arch = "x86_64")]
+#[allow(unused_unsafe)]
+fn cpuid(leaf: u32, subleaf: u32) -> [u32; 4] {
+    let x = unsafe { std::arch::x86_64::__cpuid_count(leaf, subleaf) };
+    [x.eax, x.ebx, x.ecx, x.edx]
+}
+
+/// Names of the set bits of `register` listed in `bits`.
+#[cfg(target_arch = "x86_64")]
+fn features(register: u32, bits: &[(u32, &str)]) -> Vec<String> {
+    bits.iter()
+        .filter(|(bit, _)| register >> bit & 1 == 1)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+/// Frequency behaviour of AVX-512 code on the CPU of `vendor`, `family` and `model`. Intel
+/// servers up to Cascade Lake drop to a lower license for heavy 512-bit instructions, later
+/// ones much less, and AMD does not drop at all.
+#[cfg(target_arch = "x86_64")]
+fn frequency_hint(vendor: &str, family: u32, model: u32) -> Option<&'static str> {
+    match (vendor, family, model) {
+        ("GenuineIntel", 6, 0x55) => Some(
+            "heavy AVX-512 runs at the AVX-512 license frequency, often 20-30% below the AVX2 \
+             turbo; compare with ymm-only builds",
+        ),
+        ("GenuineIntel", 6, 0x6a | 0x6c | 0x7d | 0x7e) => Some(
+            "AVX-512 lowers the frequency by a few percent; license transitions stall for \
+             tens of microseconds",
+        ),
+        ("GenuineIntel", 6, 0x8f | 0xcf | 0xad | 0xae) => {
+            Some("AVX-512 and AMX lower the frequency slightly; AMX needs arch_prctl permission")
+        }
+        ("AuthenticAMD", 0x19 | 0x1a, _) => {
+            Some("no AVX-512 license frequency; Zen 4 executes 512-bit operations in two halves")
+        }
+        _ => None,
+    }
+}
+
+/// Data and unified caches from cpuid leaf 4 on Intel and 0x8000001d on AMD, which describe
+/// every level the same way.
+#[cfg(target_arch = "x86_64")]
+fn cpuid_caches(vendor: &str) -> Vec<Cache> {
+    let leaf = if vendor == "AuthenticAMD" {
+        0x8000_001d
+    } else {
+        4
+    };
+    let highest = cpuid(leaf & 0x8000_0000, 0)[0];
+    if highest < leaf {
+        return Vec::new();
+    }
+    let mut caches = Vec::new();
+    for subleaf in 0..16 {
+        let [eax, ebx, ecx, _] = cpuid(leaf, subleaf);
+        // 1 data, 2 instruction, 3 unified and 0 for no further caches.
+        match eax & 0x1f {
+            0 => break,
+            2 => continue,
+            _ => {}
+        }
+        let ways = (ebx >> 22) as usize + 1;
+        let partitions = (ebx >> 12 & 0x3ff) as usize + 1;
+        let line = (ebx & 0xfff) as usize + 1;
+        caches.push(Cache {
+            level: (eax >> 5 & 0x7) as u8,
+            size: ways * partitions * line * (ecx as usize + 1),
+        });
+    }
+    caches.sort_by_key(|x| x.level);
+    caches
+}
+
+#[cfg(target_arch = "x86_64")]
+fn x86() -> Option<X86> {
+    let [_, ebx, ecx, edx] = cpuid(0, 0);
+    let vendor = [ebx, edx, ecx]
+        .iter()
+        .flat_map(|x| x.to_le_bytes())
+        .map(char::from)
+        .collect::<String>();
+    let signature = cpuid(1, 0)[0];
+    let base_family = signature >> 8 & 0xf;
+    let family = if base_family == 0xf {
+        base_family + (signature >> 20 & 0xff)
+    } else {
+        base_family
+    };
+    let model = if base_family == 6 || base_family == 0xf {
+        (signature >> 12 & 0xf0) | (signature >> 4 & 0xf)
+    } else {
+        signature >> 4 & 0xf
+    };
+
+    let [_, ebx, ecx, edx] = cpuid(7, 0);
+    let mut avx512 = features(
+        ebx,
+        &[
+            (16, "avx512f"),
+            (17, "avx512dq"),
+            (21, "avx512ifma"),
+            (28, "avx512cd"),
+            (30, "avx512bw"),
+            (31, "avx512vl"),
+        ],
+    );
+    avx512.extend(features(ecx, &[(1, "avx512vbmi"), (11, "avx512vnni")]));
+    avx512.extend(features(edx, &[(23, "avx512fp16")]));
+    avx512.extend(features(cpuid(7, 1)[0], &[(5, "avx512bf16")]));
+    Some(X86 {
+        avx512_enabled: std::arch::is_x86_feature_detected!("avx512f"),
+        avx512,
+        amx: features(edx, &[(22, "amx-bf16"), (24, "amx-tile"), (25, "amx-int8")]),
+        frequency_hint: frequency_hint(&vendor, family, model).map(str::to_string),
+        caches: cpuid_caches(&vendor),
+    })
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn x86() -> Option<X86> {
+    None
+}
+
+/// CPU `compiler` resolves -mcpu=native to, from what GCC prints of its target options or the
+/// -target-cpu clang passes to its frontend.
+pub fn native_cpu(compiler: &str) -> Option<String> {
+    let output = process::Command::new(compiler)
+        .args(["-mcpu=native", "-Q", "--help=target"])
+        .output()
+        .ok()
+        .filter(|x| x.status.success());
+    if let Some(output) = output {
+        let text = String::from_utf8_lossy(&output.stdout);
+        let mcpu = text.lines().find_map(|x| {
+            let mut words = x.split_whitespace();
+            (words.next()? == "-mcpu=").then(|| words.next())?
+        });
+        if let Some(mcpu) = mcpu {
+            return Some(mcpu.to_string());
+        }
+    }
+    let output = process::Command::new(compiler)
+        .args(["-mcpu=native", "-###", "-x", "c", "-c", "-"])
+        .stdin(process::Stdio::null())
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stderr);
+    let mut words = text.split_whitespace();
+    words.find(|x| *x == "\"-target-cpu\"")?;
+    Some(words.next()?.trim_matches('"').to_string())
+}
+
+pub fn detect() -> Machine {
+    Machine {
+        hostname: hostname(),
+        cpu_model: cpu_model(),
+        logical_cpus: std::thread::available_parallelism().map_or(0, |x| x.get()),
+        governor: governor(),
+        libraries: libraries(),
+        arm: arm(),
+        x86: x86(),
+    }
+}
+
+/// `detect`, with the -mcpu=native of `compiler` on aarch64.
+pub fn detect_with(compiler: &str) -> Machine {
+    let mut machine = detect();
+    if let Some(arm) = &mut machine.arm {
+        arm.mcpu = native_cpu(compiler);
+    }
+    machine
+}
+
+fn file_name(library: &str) -> &str {
+    path::Path::new(library)
+        .file_name()
+        .and_then(|x| x.to_str())
+        .unwrap_or(library)
+}
+
+impl Machine {
+    /// Differences from `other` that make their results incomparable, one line each.
+    /// Hostnames are not compared, so replacing a node with identical hardware keeps its baseline.
+    pub fn differences(&self, other: &Machine) -> Vec<String> {
+        let mut differences = Vec::new();
+        if self.cpu_model != other.cpu_model {
+            differences.push(format!(
+                "CPU model '{}' differs from '{}'",
+                self.cpu_model, other.cpu_model
+            ));
+        }
+        if self.logical_cpus != other.logical_cpus {
+            differences.push(format!(
+                "{} CPUs differ from {}",
+                self.logical_cpus, other.logical_cpus
+            ));
+        }
+        if self.governor != other.governor {
+            differences.push(format!(
+                "governor {} differs from {}",
+                self.governor.as_deref().unwrap_or("unknown"),
+                other.governor.as_deref().unwrap_or("unknown")
+            ));
+        }
+        if let (Some(ours), Some(theirs)) = (&self.arm, &other.arm) {
+            if ours.sve_vector_length != theirs.sve_vector_length {
+                differences.push(format!(
+                    "SVE vector length {} differs from {}",
+                    ours.sve_vector_length
+                        .map_or("none".to_string(), |x| format!("{} bytes", x)),
+                    theirs
+                        .sve_vector_length
+                        .map_or("none".to_string(), |x| format!("{} bytes", x))
+                ));
+            }
+            let parts = |x: &Arm| x.cores.iter().map(|x| x.part.clone()).collect::<Vec<_>>();
+            if parts(ours) != parts(theirs) {
+                differences.push(format!(
+                    "core types {} differ from {}",
+                    parts(ours).join(","),
+                    parts(theirs).join(",")
+                ));
+            }
+        }
+        if let (Some(ours), Some(theirs)) = (&self.x86, &other.x86) {
+            let avx512 = |x: &X86| {
+                if x.avx512.is_empty() {
+                    "none".to_string()
+                } else if x.avx512_enabled {
+                    x.avx512.join(",")
+                } else {
+                    "disabled by the OS".to_string()
+                }
+            };
+            if avx512(ours) != avx512(theirs) {
+                differences.push(format!(
+                    "AVX-512 {} differs from {}",
+                    avx512(ours),
+                    avx512(theirs)
+                ));
+            }
+            if ours.amx != theirs.amx {
+                differences.push(format!(
+                    "AMX {} differs from {}",
+                    ours.amx.join(","),
+                    theirs.amx.join(",")
+                ));
+            }
+        }
+        // libraries are compared by file name, which carries the version, e.g. libmkl_rt.so.2.
+        fn names(machine: &Machine) -> Vec<&str> {
+            machine.libraries.iter().map(|x| file_name(x)).collect()
+        }
+        let (ours, theirs) = (names(self), names(other));
+        for library in ours.iter().filter(|x| !theirs.contains(x)) {
+            differences.push(format!("{} is not loaded in the baseline", library));
+        }
+        for library in theirs.iter().filter(|x| !ours.contains(x)) {
+            differences.push(format!("{} is only loaded in the baseline", library));
+        }
+        differences
+    }
+}
+
+/// What to do when the baseline was measured on a different machine.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mismatch {
+    Error,
+    Warn,
+}
+
+impl<'a> TryFrom<&'a str> for Mismatch {
+    type Error = String;
+
+    fn try_from(value: &'a str) -> Result<Self, String> {
+        match value.to_uppercase().as_str() {
+            "ERROR" => Ok(Mismatch::Error),
+            "WARN" => Ok(Mismatch::Warn),
+            x => Err(["expected one of [ERROR, WARN], but got ", x].concat()),
+        }
+    }
+}
+
+impl Mismatch {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mismatch::Error => "error",
+            Mismatch::Warn => "warn",
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{common::Duration, utils};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time;
+
+/// Random pause before every measured iteration but the first, chosen with `--jitter`, so
+/// periodic system activity such as timer ticks does not line up with the measurements.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Jitter {
+    pub min: Duration,
+    pub max: Duration,
+}
+
+/// Parse `MIN-MAX`, e.g. `0-50ms`, or `MAX` alone; a bare minimum takes the unit of the maximum.
+pub fn parse_jitter(value: &str) -> Result<Jitter, String> {
+    let (min, max) = value.split_once('-').unwrap_or(("0", value));
+    let (min, max) = (min.trim(), max.trim());
+    let number = |c: char| c.is_ascii_digit() || c == '.';
+    let min = if min.chars().all(number) {
+        [min, max.trim_start_matches(number)].concat()
+    } else {
+        min.to_string()
+    };
+    let (min, max) = (utils::parse_duration(&min)?, utils::parse_duration(max)?);
+    if min > max {
+        return Err(["expected MIN no longer than MAX, but got ", value].concat());
+    }
+    Ok(Jitter {
+        min: min.into(),
+        max: max.into(),
+    })
+}
+
+impl Jitter {
+    /// Pause before `iteration` of `session`, uniform in [`min`, `max`]; it only depends on
+    /// them, so a rerun pauses the same way.
+    pub fn pause(&self, session: usize, iteration: usize) -> time::Duration {
+        let x = utils::philox(iteration as u64, session as u64);
+        let fraction = (x[0] as u64 | (x[1] as u64) << 32) as f64 / u64::MAX as f64;
+        let nanos = self.min.as_nanos() + (self.max.as_nanos() - self.min.as_nanos()) * fraction;
+        time::Duration::from_nanos(nanos as u64)
+    }
+}
+
+/// What happens to the caches between iterations.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Flush {
+    /// nothing; every iteration finds what the previous one left in the caches
+    #[default]
+    None,
+}
+
+/// How C of the verification call was checked.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Verification {
+    Skipped,
+    /// every entry against the reference BLAS
+    Reference,
+    /// a `fraction` of the rows and columns against a compensated reference
+    Sampled {
+        fraction: f64,
+    },
+    /// every entry against a double-double reference
+    Extended,
+    /// every entry against a file of the expected C
+    File,
+}
+
+impl Verification {
+    /// Description as in the full report.
+    pub fn describe(&self) -> String {
+        match self {
+            Verification::Skipped => "skipped".to_string(),
+            Verification::Reference => "every entry against the reference BLAS".to_string(),
+            Verification::Sampled { fraction } => format!(
+                "{}% of the rows and columns against a compensated reference",
+                fraction * 100.0
+            ),
+            Verification::Extended => "every entry against a double-double reference".to_string(),
+            Verification::File => "every entry against a file of the expected C".to_string(),
+        }
+    }
+}
+
+/// When the input matrices are generated.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Regeneration {
+    /// A and B anew for every session, from the seed plus 1000 per session, and C zeroed
+    PerSession,
+    /// A and B from the pool of a library session, once per shape and seed
+    Pooled,
+}
+
+/// Clock the iterations are timed with, `std::time::Instant` on this platform.
+pub fn timer() -> &'static str {
+    if cfg!(windows) {
+        "QueryPerformanceCounter"
+    } else if cfg!(target_vendor = "apple") {
+        "clock_gettime(CLOCK_UPTIME_RAW)"
+    } else {
+        "clock_gettime(CLOCK_MONOTONIC)"
+    }
+}
+
+/// How the numbers of a report were obtained. C is never reset between iterations, so with a
+/// nonzero beta every iteration accumulates into the result of the previous one.
+#[derive(Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Methodology {
+    /// Unmeasured calls before the measured ones, in every session.
+    pub warm_up: usize,
+    /// Pause between sessions, to let the machine cool down.
+    pub session_gap: Duration,
+    /// Pause before measured iterations, if any.
+    #[serde(default)]
+    pub jitter: Option<Jitter>,
+    pub flush: Flush,
+    /// Whether the benchmark was pinned to the CPUs of `environment.affinity`.
+    pub pinned: bool,
+    pub timer: String,
+    pub verification: Verification,
+    /// Normalized residual above which verification fails; None for a difference above the
+    /// tolerance calibrated from the norms of A and B.
+    #[serde(default, with = "crate::number::option")]
+    #[schemars(schema_with = "crate::number::option::schema")]
+    pub max_residual: Option<f64>,
+    pub regeneration: Regeneration,
+}
@@ -0,0 +1,184 @@
+use crate::{common::Report, hook};
+
+/// Robust standard deviations of the relative residuals beyond which a point is anomalous.
+const ANOMALY: f64 = 3.0;
+/// Relative residual below which a point is never anomalous, so a near-perfect fit flags nothing.
+const TOLERANCE: f64 = 0.01;
+
+/// One report a model was fitted to.
+pub struct Point {
+    pub dimensions: (usize, usize, usize),
+    /// medium time of one call in nanoseconds
+    pub measured: f64,
+    pub predicted: f64,
+    /// whether the residual is far larger than those of the other points
+    pub anomalous: bool,
+}
+
+impl Point {
+    /// Residual relative to the prediction.
+    pub fn residual(&self) -> f64 {
+        self.measured / self.predicted - 1.0
+    }
+}
+
+/// Latency-throughput model t = a·mnk + b·(mk + kn + mn) + c of the time of one call in
+/// nanoseconds: `a` is the cost of a multiply-add, `b` that of an element of the matrices and
+/// `c` the fixed overhead of a call.
+pub struct Model {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    /// coefficient of determination of the measured times
+    pub r_squared: f64,
+    pub points: Vec<Point>,
+}
+
+fn terms((m, n, k): (usize, usize, usize)) -> [f64; 3] {
+    let (m, n, k) = (m as f64, n as f64, k as f64);
+    [m * n * k, m * k + k * n + m * n, 1.0]
+}
+
+/// Solve the 3×3 system `matrix` x = `b` by Gaussian elimination, None if it is singular.
+fn solve(mut matrix: [[f64; 3]; 3], mut b: [f64; 3]) -> Option<[f64; 3]> {
+    for column in 0..3 {
+        let pivot = (column..3)
+            .max_by(|&x, &y| matrix[x][column].abs().total_cmp(&matrix[y][column].abs()))?;
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        b.swap(column, pivot);
+        for row in column + 1..3 {
+            let (above, below) = matrix.split_at_mut(row);
+            let factor = below[0][column] / above[column][column];
+            for (x, y) in below[0][column..].iter_mut().zip(&above[column][column..]) {
+                *x -= factor * y;
+            }
+            b[row] -= factor * b[column];
+        }
+    }
+    let mut x = [0.0; 3];
+    for row in (0..3).rev() {
+        x[row] =
+            (b[row] - (row + 1..3).map(|y| matrix[row][y] * x[y]).sum::<f64>()) / matrix[row][row];
+    }
+    Some(x)
+}
+
+impl Model {
+    /// Fit the model to the medium times of `reports` by least squares of the relative error,
+    /// so small sizes weigh as much as large ones.
+    pub fn fit(reports: &[Report]) -> Result<Model, String> {
+        if reports
+            .iter()
+            .any(|x| !x.batch.is_empty() || x.reuse_b.is_some())
+        {
+            return Err("only reports of single products can be fitted".to_string());
+        }
+        let samples = reports
+            .iter()
+            .map(|x| (x.dimensions, x.flops() / hook::gflops(x)))
+            .collect::<Vec<((usize, usize, usize), f64)>>();
+        if samples.len() < 4 {
+            return Err(format!(
+                "fitting 3 coefficients needs at least 4 reports, got {}",
+                samples.len()
+            ));
+        }
+
+        // columns are scaled to at most 1, as mnk is orders of magnitude larger than 1.
+        let mut scale = [0.0f64; 3];
+        for (dimensions, time) in &samples {
+            for (scale, term) in scale.iter_mut().zip(terms(*dimensions)) {
+                *scale = scale.max(term / time);
+            }
+        }
+        let (mut normal, mut right) = ([[0.0; 3]; 3], [0.0; 3]);
+        for (dimensions, time) in &samples {
+            let row = terms(*dimensions);
+            let row = [0, 1, 2].map(|x| row[x] / time / scale[x]);
+            for x in 0..3 {
+                for y in 0..3 {
+                    normal[x][y] += row[x] * row[y];
+                }
+                right[x] += row[x];
+            }
+        }
+        let coefficients = solve(normal, right)
+            .ok_or("the sizes do not vary enough to tell the coefficients apart")?;
+        let [a, b, c] = [0, 1, 2].map(|x| coefficients[x] / scale[x]);
+
+        let mut points = samples
+            .iter()
+            .map(|&(dimensions, measured)| {
+                let [x, y, z] = terms(dimensions);
+                Point {
+                    dimensions,
+                    measured,
+                    predicted: a * x + b * y + c * z,
+                    anomalous: false,
+                }
+            })
+            .collect::<Vec<Point>>();
+        let mean = samples.iter().map(|x| x.1).sum::<f64>() / samples.len() as f64;
+        let total = samples.iter().map(|x| (x.1 - mean).powi(2)).sum::<f64>();
+        let error = points
+            .iter()
+            .map(|x| (x.measured - x.predicted).powi(2))
+            .sum::<f64>();
+
+        // median absolute deviation, which a few anomalous points do not inflate.
+        let mut deviations = points
+            .iter()
+            .map(|x| x.residual().abs())
+            .collect::<Vec<f64>>();
+        deviations.sort_by(f64::total_cmp);
+        let sigma = 1.4826 * deviations[deviations.len() / 2];
+        for point in &mut points {
+            let residual = point.residual().abs();
+            point.anomalous = residual > TOLERANCE && residual > ANOMALY * sigma;
+        }
+
+        Ok(Model {
+            a,
+            b,
+            c,
+            r_squared: if total > 0.0 {
+                1.0 - error / total
+            } else {
+                1.0
+            },
+            points,
+        })
+    }
+
+    /// Predicted time of one call in nanoseconds.
+    pub fn predict(&self, dimensions: (usize, usize, usize)) -> f64 {
+        let [x, y, z] = terms(dimensions);
+        self.a * x + self.b * y + self.c * z
+    }
+
+    /// GFLOPS the multiply-adds alone reach, as sizes grow.
+    pub fn peak_gflops(&self) -> f64 {
+        2.0 / self.a
+    }
+
+    /// GB/s at which the elements of double-precision matrices are moved.
+    pub fn bandwidth(&self) -> f64 {
+        8.0 / self.b
+    }
+}
+
+/// Parse "MxNxK", e.g. "1024x512x256".
+pub fn parse_dimensions(value: &str) -> Result<(usize, usize, usize), String> {
+    let sizes = value
+        .split('x')
+        .map(|x| x.trim().parse::<usize>())
+        .collect::<Result<Vec<usize>, _>>()
+        .map_err(|e| format!("invalid dimensions '{}': {}", value, e))?;
+    match sizes[..] {
+        [m, n, k] => Ok((m, n, k)),
+        _ => Err(format!("expected MxNxK, but got '{}'", value)),
+    }
+}
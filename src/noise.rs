@@ -0,0 +1,74 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{hint, time};
+
+/// Iterations of the idle loop, tens of microseconds on current CPUs.
+const SPINS: u64 = 20_000;
+
+/// Ambient jitter of the node, timed before the repeats while no kernel runs: back-to-back clock
+/// reads, and a loop that does the same work every time.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Noise {
+    /// clock reads and loops timed
+    pub samples: usize,
+    /// median time between two clock reads in nanoseconds, the resolution of a duration
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub clock_read: f64,
+    /// median time of the loop in nanoseconds
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub loop_time: f64,
+    /// standard deviation of the loop times relative to their average, the noise floor the
+    /// relative deviation of the kernel is judged against
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub floor: f64,
+    /// slowest loop relative to the median, the longest interruption seen
+    #[serde(with = "crate::number")]
+    #[schemars(schema_with = "crate::number::schema")]
+    pub worst: f64,
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    values[values.len() / 2]
+}
+
+/// Time `samples` clock reads and idle loops; None for 0.
+pub fn measure(samples: usize) -> Option<Noise> {
+    if samples == 0 {
+        return None;
+    }
+    let clock_read = median(
+        (0..samples)
+            .map(|_| {
+                let start = time::Instant::now();
+                let end = time::Instant::now();
+                (end - start).as_nanos() as f64
+            })
+            .collect(),
+    );
+    let loops = (0..samples)
+        .map(|_| {
+            let start = time::Instant::now();
+            let mut x = 0u64;
+            for i in 0..SPINS {
+                x = hint::black_box(x.wrapping_add(i));
+            }
+            hint::black_box(x);
+            start.elapsed().as_nanos() as f64
+        })
+        .collect::<Vec<f64>>();
+    let average = loops.iter().sum::<f64>() / samples as f64;
+    let variance = loops.iter().map(|x| (x - average).powi(2)).sum::<f64>() / samples as f64;
+    let slowest = loops.iter().copied().fold(0.0, f64::max);
+    let loop_time = median(loops);
+    Some(Noise {
+        samples,
+        clock_read,
+        loop_time,
+        floor: variance.sqrt() / average,
+        worst: slowest / loop_time,
+    })
+}
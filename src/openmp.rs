@@ -0,0 +1,54 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::ffi::c_int;
+
+/// Effective threading configuration of the OpenMP runtime the kernel is linked against.
+#[derive(PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OpenMP {
+    pub max_threads: i32,
+    pub dynamic: bool,
+    pub proc_bind: String,
+    /// Number of processors in each place.
+    pub places: Vec<i32>,
+}
+
+fn proc_bind(value: c_int) -> String {
+    match value {
+        0 => "false",
+        1 => "true",
+        2 => "primary",
+        3 => "close",
+        4 => "spread",
+        _ => "unknown",
+    }
+    .to_string()
+}
+
+/// Query the runtime through the symbols visible from `library`.
+/// Returns `None` when the library does not use OpenMP.
+pub fn query(library: &libloading::Library) -> Option<OpenMP> {
+    unsafe {
+        let max_threads = library
+            .get::<unsafe extern "C" fn() -> c_int>(b"omp_get_max_threads")
+            .ok()?;
+        let dynamic = library.get::<unsafe extern "C" fn() -> c_int>(b"omp_get_dynamic");
+        let get_proc_bind = library.get::<unsafe extern "C" fn() -> c_int>(b"omp_get_proc_bind");
+        let num_places = library.get::<unsafe extern "C" fn() -> c_int>(b"omp_get_num_places");
+        let place_num_procs =
+            library.get::<unsafe extern "C" fn(c_int) -> c_int>(b"omp_get_place_num_procs");
+
+        let places = match (num_places, place_num_procs) {
+            (Ok(num_places), Ok(place_num_procs)) => {
+                (0..num_places()).map(|i| place_num_procs(i)).collect()
+            }
+            _ => Vec::new(),
+        };
+
+        Some(OpenMP {
+            max_threads: max_threads(),
+            dynamic: dynamic.is_ok_and(|x| x() != 0),
+            proc_bind: get_proc_bind.map_or_else(|_| "unknown".to_string(), |x| proc_bind(x())),
+            places,
+        })
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{
+    common::{Against, Duration, Report},
+    suite,
+};
+pub use benchmark_core::statistics::{
+    erfc, geomean_change, spearman, verdict, wilcoxon, Correlation, Wilcoxon,
+};
+
+/// Two reports of the same configuration, e.g. from two runs of a suite.
+pub struct Pair<'a> {
+    pub configuration: &'a str,
+    pub before: &'a Report,
+    pub after: &'a Report,
+}
+
+/// Pair reports of `before` and `after` by the configuration hash suites store in them.
+/// Reports without one, or without a counterpart, are left out.
+pub fn pair<'a>(before: &'a [Report], after: &'a [Report]) -> Vec<Pair<'a>> {
+    before
+        .iter()
+        .filter_map(|x| {
+            let configuration = x.meta.get(suite::META_CONFIGURATION)?;
+            let y = after
+                .iter()
+                .find(|y| y.meta.get(suite::META_CONFIGURATION) == Some(configuration))?;
+            Some(Pair {
+                configuration,
+                before: x,
+                after: y,
+            })
+        })
+        .collect()
+}
+
+impl Pair<'_> {
+    /// Test on the durations of the two runs paired by iteration, which cancels drift both
+    /// runs share, e.g. from turbo budgets; None if a report has no samples.
+    pub fn test(&self) -> Option<Wilcoxon> {
+        let differences = self
+            .before
+            .samples
+            .iter()
+            .zip(&self.after.samples)
+            .map(|(x, y)| y.as_millis() - x.as_millis())
+            .collect::<Vec<f64>>();
+        wilcoxon(&differences)
+    }
+}
+
+/// Compare durations of a kernel with those of `kernel` run interleaved with it, paired by
+/// iteration.
+pub fn against(
+    kernel: &str,
+    samples: &[Duration],
+    other: &[Duration],
+    significance: f64,
+) -> Against {
+    let pairs = samples.iter().zip(other);
+    let differences = pairs
+        .clone()
+        .map(|(x, y)| y.as_millis() - x.as_millis())
+        .collect::<Vec<f64>>();
+    let ratios = pairs
+        .map(|(x, y)| (y.as_millis() / x.as_millis()).ln())
+        .collect::<Vec<f64>>();
+    let test = wilcoxon(&differences);
+    let change = geomean_change(&ratios);
+    Against {
+        kernel: kernel.to_string(),
+        samples: other.to_vec(),
+        change,
+        p: test.as_ref().map(|x| x.p),
+        verdict: verdict(test.as_ref(), change, significance).to_string(),
+    }
+}
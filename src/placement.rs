@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, path};
+
+/// Pages of a buffer whose NUMA node is sampled, evenly spaced from the first to the last.
+const PAGE_SAMPLES: usize = 64;
+
+/// Where a matrix the kernel ran on was in memory.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Buffer {
+    pub session: usize,
+    /// A, B or C
+    pub name: String,
+    pub address: usize,
+    pub bytes: usize,
+    /// largest power of two the address is a multiple of, up to the page size
+    pub alignment: usize,
+    /// leading dimension in elements
+    pub ld: usize,
+    pub page_size: usize,
+    /// NUMA node of every sampled page, None if it is not resident or the node is unknown
+    pub nodes: Vec<Option<usize>>,
+    /// bytes of transparent huge pages in the mappings that hold the buffer
+    pub huge_page_bytes: Option<usize>,
+}
+
+/// Placement of every buffer of a run, written by `--placement-manifest`.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub buffers: Vec<Buffer>,
+}
+
+fn page_size() -> usize {
+    #[cfg(unix)]
+    {
+        unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+    }
+    #[cfg(not(unix))]
+    {
+        4096
+    }
+}
+
+/// NUMA nodes of the pages at `pages`, via move_pages without moving any.
+#[cfg(target_os = "linux")]
+fn page_nodes(pages: &[usize]) -> Vec<Option<usize>> {
+    let addresses = pages
+        .iter()
+        .map(|&x| x as *mut libc::c_void)
+        .collect::<Vec<*mut libc::c_void>>();
+    let mut status = vec![0 as libc::c_int; pages.len()];
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_move_pages,
+            0,
+            addresses.len() as libc::c_ulong,
+            addresses.as_ptr(),
+            std::ptr::null::<libc::c_int>(),
+            status.as_mut_ptr(),
+            0,
+        )
+    };
+    if result != 0 {
+        return vec![None; pages.len()];
+    }
+    // a negative status is an errno, e.g. ENOENT for a page that is not resident.
+    status.iter().map(|&x| usize::try_from(x).ok()).collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn page_nodes(pages: &[usize]) -> Vec<Option<usize>> {
+    vec![None; pages.len()]
+}
+
+/// Bytes of AnonHugePages of the mappings of /proc/self/smaps that overlap [start, end).
+fn huge_page_bytes(start: usize, end: usize) -> Option<usize> {
+    let smaps = fs::read_to_string("/proc/self/smaps").ok()?;
+    let mut overlaps = false;
+    let mut bytes = 0;
+    for line in smaps.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(first) = fields.next() else {
+            continue;
+        };
+        if let Some((low, high)) = first.split_once('-').filter(|_| !first.ends_with(':')) {
+            let range = usize::from_str_radix(low, 16)
+                .ok()
+                .zip(usize::from_str_radix(high, 16).ok());
+            overlaps = range.is_some_and(|(low, high)| low < end && start < high);
+        } else if overlaps && first == "AnonHugePages:" {
+            bytes += fields.next()?.parse::<usize>().ok()? * 1024;
+        }
+    }
+    Some(bytes)
+}
+
+impl Buffer {
+    /// Describe `data`, a matrix of leading dimension `ld`, as it is placed now.
+    pub fn describe(session: usize, name: &str, data: &[f64], ld: usize) -> Buffer {
+        let address = data.as_ptr() as usize;
+        let bytes = size_of_val(data);
+        let page_size = page_size();
+        let first = address / page_size * page_size;
+        let pages = (address + bytes).div_ceil(page_size) - first / page_size;
+        let samples = pages.min(PAGE_SAMPLES);
+        let sampled = (0..samples)
+            .map(|x| first + x * (pages - 1) / (samples - 1).max(1) * page_size)
+            .collect::<Vec<usize>>();
+        Buffer {
+            session,
+            name: name.to_string(),
+            address,
+            bytes,
+            alignment: 1 << address.trailing_zeros().min(page_size.trailing_zeros()),
+            ld,
+            page_size,
+            nodes: if bytes == 0 {
+                Vec::new()
+            } else {
+                page_nodes(&sampled)
+            },
+            huge_page_bytes: huge_page_bytes(address, address + bytes),
+        }
+    }
+
+    /// Share of the sampled pages, of those known in both, on another node than in `other`.
+    fn moved(&self, other: &Buffer) -> Option<f64> {
+        let known = self
+            .nodes
+            .iter()
+            .zip(&other.nodes)
+            .filter_map(|(x, y)| x.zip(*y))
+            .collect::<Vec<(usize, usize)>>();
+        if known.is_empty() {
+            return None;
+        }
+        Some(known.iter().filter(|(x, y)| x != y).count() as f64 / known.len() as f64)
+    }
+}
+
+impl Manifest {
+    pub fn load(file: &path::Path) -> Result<Manifest, String> {
+        let data = fs::read_to_string(file).map_err(|e| e.to_string())?;
+        serde_json::from_str(&data).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, file: &path::Path) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(file, data).map_err(|e| e.to_string())
+    }
+
+    /// How the buffers differ from those of the same session and name in `before`, one line per
+    /// difference.
+    pub fn compare(&self, before: &Manifest) -> Vec<String> {
+        let mut differences = Vec::new();
+        for buffer in &self.buffers {
+            let Some(old) = before
+                .buffers
+                .iter()
+                .find(|x| x.session == buffer.session && x.name == buffer.name)
+            else {
+                continue;
+            };
+            let label = format!("{} of session {}", buffer.name, buffer.session);
+            if buffer.alignment != old.alignment {
+                differences.push(format!(
+                    "{}: aligned to {} bytes, {} before",
+                    label, buffer.alignment, old.alignment
+                ));
+            }
+            if let Some(moved) = buffer.moved(old).filter(|&x| x > 0.0) {
+                differences.push(format!(
+                    "{}: {:.0}% of the sampled pages are on other NUMA nodes",
+                    label,
+                    moved * 100.0
+                ));
+            }
+            if buffer.huge_page_bytes != old.huge_page_bytes {
+                let bytes = |x: Option<usize>| x.map_or("unknown".to_string(), |x| x.to_string());
+                differences.push(format!(
+                    "{}: {} bytes of huge pages, {} before",
+                    label,
+                    bytes(buffer.huge_page_bytes),
+                    bytes(old.huge_page_bytes)
+                ));
+            }
+        }
+        differences
+    }
+}
@@ -0,0 +1,122 @@
+use crate::utils;
+
+/// Bytes of generated matrices a pool keeps by default.
+pub const DEFAULT_CEILING: usize = 4 << 30;
+
+/// Arguments of `utils::fill_rand` a buffer was generated with; buffers of equal keys are equal.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Key {
+    pub size: usize,
+    pub seed: u64,
+    /// bounds of the uniform distribution
+    pub range: (f64, f64),
+}
+
+impl Key {
+    fn bytes(&self) -> usize {
+        self.size * size_of::<f64>()
+    }
+}
+
+struct Entry {
+    key: Key,
+    matrix: Box<[f64]>,
+    /// `Pool::clock` when the entry was last requested
+    used: u64,
+}
+
+/// Generated input matrices, reused by every request for the same key until they are evicted,
+/// least recently used first, to stay below a ceiling in bytes.
+pub struct Pool {
+    ceiling: usize,
+    entries: Vec<Entry>,
+    clock: u64,
+    hits: usize,
+    misses: usize,
+}
+
+impl Pool {
+    pub fn new(ceiling: usize) -> Self {
+        Pool {
+            ceiling,
+            entries: Vec::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Matrices of `keys`, generating those the pool does not hold. Matrices of `keys` are never
+    /// evicted by the same call, so the pool may exceed the ceiling while they alone do.
+    pub fn get<const N: usize>(&mut self, keys: [Key; N]) -> [&[f64]; N] {
+        self.clock += 1;
+        let mut missing = Vec::new();
+        for key in keys {
+            match self.entries.iter_mut().find(|x| x.key == key) {
+                Some(entry) => {
+                    entry.used = self.clock;
+                    self.hits += 1;
+                }
+                None if missing.contains(&key) => self.hits += 1,
+                None => missing.push(key),
+            }
+        }
+        for key in missing {
+            self.misses += 1;
+            self.evict(key.bytes());
+            // the evicted matrices are freed before the next one is allocated.
+            self.entries.push(Entry {
+                key,
+                matrix: utils::fill_rand(key.size, key.seed, key.range.0, key.range.1),
+                used: self.clock,
+            });
+        }
+        keys.map(|key| &*self.entries.iter().find(|x| x.key == key).unwrap().matrix)
+    }
+
+    /// Evict least recently used matrices, other than those of the current request, until
+    /// `reserve` more bytes fit below the ceiling.
+    fn evict(&mut self, reserve: usize) {
+        while self.bytes() + reserve > self.ceiling {
+            let Some(index) = (0..self.entries.len())
+                .filter(|&x| self.entries[x].used != self.clock)
+                .min_by_key(|&x| self.entries[x].used)
+            else {
+                break;
+            };
+            self.entries.swap_remove(index);
+        }
+    }
+
+    pub fn ceiling(&self) -> usize {
+        self.ceiling
+    }
+
+    /// Change the ceiling, evicting matrices down to it.
+    pub fn set_ceiling(&mut self, ceiling: usize) {
+        self.ceiling = ceiling;
+        // no request is in progress, so any matrix can be evicted.
+        self.clock += 1;
+        self.evict(0);
+    }
+
+    /// Bytes of the matrices the pool holds.
+    pub fn bytes(&self) -> usize {
+        self.entries.iter().map(|x| x.key.bytes()).sum()
+    }
+
+    /// Requests served by a matrix generated before.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Requests that generated a matrix.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Free every matrix.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,115 @@
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// (m, n, k) of every entry
+    pub shapes: &'static [(usize, usize, usize)],
+}
+
+/// Curated shape lists, runnable as `--preset <name>`, so results of different kernels and
+/// teams are measured on the same shapes.
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "skinny",
+        description: "one small dimension against large ones",
+        shapes: &[
+            (1, 4096, 4096),
+            (2, 4096, 4096),
+            (4, 4096, 4096),
+            (8, 4096, 4096),
+            (16, 4096, 4096),
+            (32, 4096, 4096),
+            (64, 4096, 4096),
+            (4096, 1, 4096),
+            (4096, 8, 4096),
+            (4096, 64, 4096),
+            (4096, 4096, 8),
+            (4096, 4096, 64),
+        ],
+    },
+    Preset {
+        name: "square-large",
+        description: "large square products",
+        shapes: &[
+            (1024, 1024, 1024),
+            (2048, 2048, 2048),
+            (4096, 4096, 4096),
+            (8192, 8192, 8192),
+            (10240, 10240, 10240),
+        ],
+    },
+    Preset {
+        name: "llm-prefill",
+        description: "attention and MLP projections of a 7B model over a 512 and 2048-token prompt",
+        shapes: &[
+            (512, 4096, 4096),
+            (512, 11008, 4096),
+            (512, 4096, 11008),
+            (2048, 4096, 4096),
+            (2048, 11008, 4096),
+            (2048, 4096, 11008),
+        ],
+    },
+    Preset {
+        name: "llm-decode",
+        description: "attention and MLP projections of a 7B model for 1 to 16 tokens per step",
+        shapes: &[
+            (1, 4096, 4096),
+            (1, 11008, 4096),
+            (1, 4096, 11008),
+            (4, 4096, 4096),
+            (4, 11008, 4096),
+            (4, 4096, 11008),
+            (16, 4096, 4096),
+            (16, 11008, 4096),
+            (16, 4096, 11008),
+        ],
+    },
+    Preset {
+        name: "hpl-panel",
+        description: "HPL trailing-matrix updates and panels with block sizes 192 and 256",
+        shapes: &[
+            (4096, 4096, 192),
+            (8192, 8192, 192),
+            (16384, 16384, 192),
+            (8192, 8192, 256),
+            (16384, 16384, 256),
+            (16384, 192, 192),
+            (16384, 256, 256),
+        ],
+    },
+    Preset {
+        name: "resnet50-conv",
+        description: "im2col GEMMs of the stem and bottleneck convolutions of ResNet-50",
+        shapes: &[
+            (64, 12544, 147),
+            (64, 3136, 64),
+            (64, 3136, 576),
+            (256, 3136, 64),
+            (64, 3136, 256),
+            (128, 784, 1152),
+            (512, 784, 128),
+            (128, 784, 512),
+            (256, 196, 2304),
+            (1024, 196, 256),
+            (256, 196, 1024),
+            (512, 49, 4608),
+            (2048, 49, 512),
+            (512, 49, 2048),
+        ],
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|x| x.name == name)
+}
+
+pub fn parse_preset(value: &str) -> Result<&'static Preset, String> {
+    find(value).ok_or_else(|| {
+        let names = PRESETS
+            .iter()
+            .map(|x| x.name)
+            .collect::<Vec<_>>()
+            .join(", ");
+        ["expected one of ", &names, ", but got '", value, "'"].concat()
+    })
+}
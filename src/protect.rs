@@ -0,0 +1,246 @@
+use std::slice;
+#[cfg(unix)]
+use std::{
+    ffi::c_void,
+    ptr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Once, OnceLock,
+    },
+};
+
+/// Writes to a read-only matrix, found after the kernel returned.
+pub struct Violation {
+    /// address of the first faulting write
+    pub address: usize,
+    /// element of the matrix at `address`
+    pub element: usize,
+    /// pages written to
+    pub pages: usize,
+}
+
+#[cfg(unix)]
+/// Address range watched by the fault handler, and what it caught.
+struct Watch {
+    /// whether a `ReadOnly` holds the slot, so two sessions cannot overwrite each other's range
+    claimed: AtomicBool,
+    start: AtomicUsize,
+    end: AtomicUsize,
+    first: AtomicUsize,
+    pages: AtomicUsize,
+}
+
+#[cfg(unix)]
+#[allow(clippy::declare_interior_mutable_const)]
+const UNWATCHED: Watch = Watch {
+    claimed: AtomicBool::new(false),
+    start: AtomicUsize::new(0),
+    end: AtomicUsize::new(0),
+    first: AtomicUsize::new(0),
+    pages: AtomicUsize::new(0),
+};
+
+#[cfg(unix)]
+static WATCHES: [Watch; 2] = [UNWATCHED; 2];
+#[cfg(unix)]
+static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
+#[cfg(unix)]
+static INSTALL: Once = Once::new();
+/// Signals the handler takes over; macOS reports writes to read-only pages as SIGBUS.
+#[cfg(unix)]
+const SIGNALS: [libc::c_int; 2] = [libc::SIGSEGV, libc::SIGBUS];
+/// Actions for `SIGNALS` before the handler was installed, which get every other fault.
+#[cfg(unix)]
+static PREVIOUS: OnceLock<[libc::sigaction; 2]> = OnceLock::new();
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+unsafe fn fault_address(info: *mut libc::siginfo_t) -> usize {
+    (*info).si_addr() as usize
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+unsafe fn fault_address(info: *mut libc::siginfo_t) -> usize {
+    (*info).si_addr as usize
+}
+
+#[cfg(unix)]
+/// Make the faulting page writable and record the write, so the kernel can go on and every
+/// page it writes to is counted once. Faults elsewhere go to the handler installed before, e.g.
+/// the stack overflow report of the Rust runtime, or crash as usual.
+extern "C" fn handle(signal: libc::c_int, info: *mut libc::siginfo_t, context: *mut c_void) {
+    let address = unsafe { fault_address(info) };
+    let page_size = PAGE_SIZE.load(Ordering::Relaxed);
+    for watch in &WATCHES {
+        let start = watch.start.load(Ordering::Acquire);
+        if start != 0 && address >= start && address < watch.end.load(Ordering::Acquire) {
+            let page = address & !(page_size - 1);
+            unsafe {
+                libc::mprotect(
+                    page as *mut c_void,
+                    page_size,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                );
+            }
+            let _ = watch
+                .first
+                .compare_exchange(0, address, Ordering::AcqRel, Ordering::Relaxed);
+            watch.pages.fetch_add(1, Ordering::AcqRel);
+            return;
+        }
+    }
+    let previous = PREVIOUS
+        .get()
+        .and_then(|x| SIGNALS.iter().position(|&y| y == signal).map(|i| x[i]))
+        .filter(|x| x.sa_sigaction != libc::SIG_DFL && x.sa_sigaction != libc::SIG_IGN);
+    match previous {
+        Some(previous) if previous.sa_flags & libc::SA_SIGINFO != 0 => unsafe {
+            std::mem::transmute::<
+                libc::sighandler_t,
+                extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut c_void),
+            >(previous.sa_sigaction)(signal, info, context)
+        },
+        Some(previous) => unsafe {
+            std::mem::transmute::<libc::sighandler_t, extern "C" fn(libc::c_int)>(
+                previous.sa_sigaction,
+            )(signal)
+        },
+        // the access is retried with the default action, which terminates the process; an
+        // ignored fault would only be retried forever.
+        None => unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+        },
+    }
+}
+
+#[cfg(unix)]
+fn install() {
+    INSTALL.call_once(|| unsafe {
+        PAGE_SIZE.store(
+            libc::sysconf(libc::_SC_PAGESIZE) as usize,
+            Ordering::Relaxed,
+        );
+        let mut action = std::mem::zeroed::<libc::sigaction>();
+        action.sa_sigaction =
+            handle as extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut c_void) as usize;
+        action.sa_flags = libc::SA_SIGINFO;
+        libc::sigemptyset(&mut action.sa_mask);
+        let mut previous = [std::mem::zeroed::<libc::sigaction>(); 2];
+        for (signal, previous) in SIGNALS.into_iter().zip(&mut previous) {
+            libc::sigaction(signal, ptr::null(), previous);
+        }
+        let _ = PREVIOUS.set(previous);
+        for signal in SIGNALS {
+            libc::sigaction(signal, &action, ptr::null_mut());
+        }
+    });
+}
+
+/// A page-aligned copy of a matrix that can be made read-only.
+pub struct ReadOnly {
+    pointer: *mut f64,
+    len: usize,
+    size: usize,
+    slot: usize,
+}
+
+impl ReadOnly {
+    /// `slot` is 0 for A and 1 for B; only one matrix per slot can be watched at a time, so
+    /// this fails while another copy holds it.
+    #[cfg(unix)]
+    pub fn new(data: &[f64], slot: usize) -> Result<Self, String> {
+        if WATCHES[slot].claimed.swap(true, Ordering::AcqRel) {
+            return Err(format!(
+                "slot {} is already watched for another session",
+                slot
+            ));
+        }
+        install();
+        let page_size = PAGE_SIZE.load(Ordering::Relaxed);
+        let size = (size_of_val(data).max(1)).div_ceil(page_size) * page_size;
+        let pointer = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if pointer == libc::MAP_FAILED {
+            WATCHES[slot].claimed.store(false, Ordering::Release);
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        let pointer = pointer as *mut f64;
+        unsafe { ptr::copy_nonoverlapping(data.as_ptr(), pointer, data.len()) };
+        Ok(ReadOnly {
+            pointer,
+            len: data.len(),
+            size,
+            slot,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn new(_: &[f64], _: usize) -> Result<Self, String> {
+        Err("protecting inputs is not supported on this platform".to_string())
+    }
+
+    pub fn as_slice(&self) -> &[f64] {
+        unsafe { slice::from_raw_parts(self.pointer, self.len) }
+    }
+
+    /// Make the copy read-only and start watching it.
+    #[cfg(unix)]
+    pub fn arm(&self) -> Result<(), String> {
+        let watch = &WATCHES[self.slot];
+        watch.first.store(0, Ordering::Release);
+        watch.pages.store(0, Ordering::Release);
+        watch
+            .end
+            .store(self.pointer as usize + self.size, Ordering::Release);
+        watch.start.store(self.pointer as usize, Ordering::Release);
+        if unsafe { libc::mprotect(self.pointer as *mut c_void, self.size, libc::PROT_READ) } != 0 {
+            watch.start.store(0, Ordering::Release);
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+
+    /// Writes caught since `arm`, which also re-protects the pages they unlocked.
+    #[cfg(unix)]
+    pub fn check(&self) -> Option<Violation> {
+        let watch = &WATCHES[self.slot];
+        let pages = watch.pages.swap(0, Ordering::AcqRel);
+        let address = watch.first.swap(0, Ordering::AcqRel);
+        if pages == 0 {
+            return None;
+        }
+        unsafe { libc::mprotect(self.pointer as *mut c_void, self.size, libc::PROT_READ) };
+        Some(Violation {
+            address,
+            element: (address - self.pointer as usize) / size_of::<f64>(),
+            pages,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn arm(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn check(&self) -> Option<Violation> {
+        None
+    }
+}
+
+#[cfg(unix)]
+impl Drop for ReadOnly {
+    fn drop(&mut self) {
+        let watch = &WATCHES[self.slot];
+        watch.start.store(0, Ordering::Release);
+        unsafe { libc::munmap(self.pointer as *mut c_void, self.size) };
+        watch.claimed.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{
+    common::{Duration, Report},
+    hook,
+};
+
+/// Abandons a run whose running median is far below the best configuration of a sweep so far.
+pub struct Pruner {
+    /// floating-point operations of one iteration
+    pub flops: f64,
+    /// GFLOPS below which the run is abandoned
+    pub below: f64,
+    /// iterations measured before the first check
+    pub after: usize,
+}
+
+impl Pruner {
+    /// GFLOPS of the running median of `records`, if the run should be abandoned.
+    pub fn check(&self, records: &[Duration]) -> Option<f64> {
+        if records.len() < self.after.max(1) {
+            return None;
+        }
+        let mut sorted = records.to_vec();
+        sorted.sort();
+        let gflops = self.flops / sorted[sorted.len() / 2].as_nanos();
+        (gflops < self.below).then_some(gflops)
+    }
+}
+
+/// Best GFLOPS of the completed entries of a sweep, which sets the threshold of the next ones.
+#[derive(Default)]
+pub struct Tracker {
+    pub best: f64,
+    /// entries that were abandoned
+    pub pruned: usize,
+}
+
+impl Tracker {
+    /// Record a completed entry. Returns the new threshold, `fraction` below it, if the entry
+    /// is the best so far.
+    pub fn complete(&mut self, report: &Report, fraction: f64) -> Option<f64> {
+        if report.pruned {
+            self.pruned += 1;
+            return None;
+        }
+        let gflops = hook::gflops(report);
+        (gflops > self.best).then(|| {
+            self.best = gflops;
+            gflops * (1.0 - fraction)
+        })
+    }
+}
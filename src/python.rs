@@ -0,0 +1,172 @@
+// the pyo3 macros convert the PyResult of every function into itself.
+#![allow(clippy::useless_conversion)]
+
+use crate::{common, compiler, hook, session};
+use pyo3::{exceptions::PyRuntimeError, prelude::*};
+use std::{env, fs, path};
+
+/// Result of a run, as `benchmark_dgemm.Report`.
+#[pyclass(name = "Report", module = "benchmark_dgemm")]
+#[derive(Clone)]
+pub struct Report(pub common::Report);
+
+#[pymethods]
+impl Report {
+    #[getter]
+    fn name(&self) -> String {
+        self.0.name.clone()
+    }
+
+    #[getter]
+    fn dimensions(&self) -> (usize, usize, usize) {
+        self.0.dimensions
+    }
+
+    #[getter]
+    fn repeats(&self) -> usize {
+        self.0.repeats
+    }
+
+    #[getter]
+    fn tags(&self) -> Vec<String> {
+        self.0.tags.clone()
+    }
+
+    /// medium GFLOPS, or those of the average time without a medium
+    #[getter]
+    fn gflops(&self) -> f64 {
+        hook::gflops(&self.0)
+    }
+
+    #[getter]
+    fn medium_ns(&self) -> Option<f64> {
+        self.0.statistics.medium.map(|x| x.as_nanos())
+    }
+
+    #[getter]
+    fn minimum_ns(&self) -> f64 {
+        self.0.statistics.minimum.as_nanos()
+    }
+
+    #[getter]
+    fn maximum_ns(&self) -> f64 {
+        self.0.statistics.maximum.as_nanos()
+    }
+
+    #[getter]
+    fn average_ms(&self) -> f64 {
+        self.0.statistics.average
+    }
+
+    #[getter]
+    fn deviation_ms(&self) -> f64 {
+        self.0.statistics.deviation
+    }
+
+    /// measured durations in order
+    #[getter]
+    fn samples_ns(&self) -> Vec<f64> {
+        self.0.samples.iter().map(|x| x.as_nanos()).collect()
+    }
+
+    /// the report as JSON, as `--save-as` writes it
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.0).map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    fn save(&self, file: &str) -> PyResult<()> {
+        fs::write(file, self.to_json()?).map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    fn summary(&self) -> PyResult<String> {
+        self.0
+            .summary()
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        let (m, n, k) = self.0.dimensions;
+        format!(
+            "Report(name={:?}, dimensions=({}, {}, {}), repeats={}, gflops={:.3})",
+            self.0.name,
+            m,
+            n,
+            k,
+            self.0.repeats,
+            hook::gflops(&self.0)
+        )
+    }
+}
+
+/// Benchmark a kernel source, compiled with `compiler`, or an object built beforehand.
+#[pyfunction]
+#[pyo3(signature = (
+    kernel_path, m, n, k, *, compiler=None, repeats=10, warm_up=0, alpha=1.0, beta=1.0, seed=0,
+    name=None
+))]
+#[allow(clippy::too_many_arguments)]
+fn run(
+    py: Python<'_>,
+    kernel_path: &str,
+    m: usize,
+    n: usize,
+    k: usize,
+    compiler: Option<String>,
+    repeats: usize,
+    warm_up: usize,
+    alpha: f64,
+    beta: f64,
+    seed: u64,
+    name: Option<String>,
+) -> PyResult<Report> {
+    let object = path::Path::new(kernel_path)
+        .extension()
+        .is_some_and(|x| x == env::consts::DLL_EXTENSION);
+    let compiler = compiler.unwrap_or_else(compiler::default_compiler);
+    // the kernel does not touch Python objects, so other threads may run meanwhile.
+    py.allow_threads(|| -> Result<common::Report, String> {
+        let mut session = if object {
+            session::Session::open(kernel_path)?
+        } else {
+            session::Session::compile(kernel_path, &compiler)?
+        };
+        let mut run = session::Run::new((m, n, k));
+        run.name = name;
+        run.repeats = repeats;
+        run.warm_up = warm_up;
+        run.alpha = alpha;
+        run.beta = beta;
+        run.seed = seed;
+        session.run(&run)?;
+        Ok(session.take_reports().remove(0))
+    })
+    .map(Report)
+    .map_err(PyRuntimeError::new_err)
+}
+
+/// Reports of a report or suite report file.
+#[pyfunction]
+fn load(file: &str) -> PyResult<Vec<Report>> {
+    let json = fs::read_to_string(file).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    common::parse_reports(&json)
+        .map(|x| x.into_iter().map(Report).collect())
+        .map_err(PyRuntimeError::new_err)
+}
+
+/// One report of all iterations of `reports`, as `benchmark-viewer` merges them.
+#[pyfunction]
+fn merge(reports: Vec<Report>) -> PyResult<Report> {
+    let reports = reports.into_iter().map(|x| x.0).collect::<Vec<_>>();
+    common::Report::merge(&reports)
+        .map(Report)
+        .map_err(PyRuntimeError::new_err)
+}
+
+#[pymodule]
+fn benchmark_dgemm(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Report>()?;
+    module.add_function(wrap_pyfunction!(run, module)?)?;
+    module.add_function(wrap_pyfunction!(load, module)?)?;
+    module.add_function(wrap_pyfunction!(merge, module)?)?;
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use crate::common::Report;
+use serde_json::Value;
+
+const REDACTED: &str = "<redacted>";
+
+/// Kind of sensitive information stripped from a report before it is shared.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Hostnames,
+    /// absolute paths, which keep only their file name
+    Paths,
+    /// user names in home directories
+    Usernames,
+}
+
+impl<'a> TryFrom<&'a str> for Field {
+    type Error = String;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_uppercase().as_str() {
+            "HOSTNAMES" => Ok(Field::Hostnames),
+            "PATHS" => Ok(Field::Paths),
+            "USERNAMES" => Ok(Field::Usernames),
+            x => Err(["expected one of [HOSTNAMES, PATHS, USERNAMES], but got ", x].concat()),
+        }
+    }
+}
+
+/// Comma-separated fields, e.g. "hostnames,paths".
+pub fn parse_fields(value: &str) -> Result<Vec<Field>, String> {
+    value.split(',').map(Field::try_from).collect()
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ':' | ';' | ',' | '=' | '"' | '\'')
+}
+
+/// Start of an absolute path at `rest`: "/..." or "C:\...".
+fn is_path(rest: &str) -> bool {
+    let bytes = rest.as_bytes();
+    bytes.first() == Some(&b'/')
+        || (bytes.len() > 2
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && matches!(bytes[2], b'\\' | b'/'))
+}
+
+/// Replace every absolute path in `value` by its file name.
+fn strip_paths(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while !rest.is_empty() {
+        let at_start = out.is_empty() || out.ends_with(is_separator);
+        if at_start && is_path(rest) {
+            // the drive letter's colon is part of the path, not a separator.
+            let skip = if rest.starts_with('/') { 0 } else { 2 };
+            let end = rest[skip..]
+                .find(is_separator)
+                .map_or(rest.len(), |x| x + skip);
+            let path = &rest[..end];
+            out.push_str(path.rsplit(['/', '\\']).next().unwrap_or_default());
+            rest = &rest[end..];
+        } else {
+            let c = rest.chars().next().unwrap();
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+/// Replace the user name of home directories, e.g. "/home/alice/lib", in `value`.
+fn strip_usernames(value: &str) -> String {
+    let mut out = value.to_string();
+    for home in ["/home/", "/Users/", "\\Users\\", "/u/"] {
+        let mut start = 0;
+        while let Some(found) = out[start..].find(home) {
+            let name = start + found + home.len();
+            let end = out[name..]
+                .find(|c: char| c == '/' || c == '\\' || is_separator(c))
+                .map_or(out.len(), |x| name + x);
+            if end > name && &out[name..end] != REDACTED {
+                out.replace_range(name..end, REDACTED);
+            }
+            start = name;
+        }
+    }
+    out
+}
+
+/// Replace `word` in `value` where it is not part of a longer word.
+fn replace_word(value: &str, word: &str) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(found) = rest.find(word) {
+        let (before, after) = (&rest[..found], &rest[found + word.len()..]);
+        out.push_str(before);
+        let bounded = !before.ends_with(is_word) && !after.starts_with(is_word);
+        out.push_str(if bounded { REDACTED } else { word });
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Apply `f` to every string of `value`, and to object keys if `keys`, such as the names of
+/// kernel environment variables and defines.
+fn walk(value: &mut Value, f: &dyn Fn(&str) -> String, keys: bool) {
+    match value {
+        Value::String(x) => *x = f(x),
+        Value::Array(x) => x.iter_mut().for_each(|x| walk(x, f, keys)),
+        Value::Object(x) => {
+            *x = std::mem::take(x)
+                .into_iter()
+                .map(|(key, mut value)| {
+                    walk(&mut value, f, keys);
+                    (if keys { f(&key) } else { key }, value)
+                })
+                .collect();
+        }
+        _ => {}
+    }
+}
+
+/// Strip `fields` from every string of `report`, including metadata, tags and environment.
+pub fn redact(report: &Report, fields: &[Field]) -> Result<Report, String> {
+    let mut value = serde_json::to_value(report).map_err(|e| e.to_string())?;
+    if fields.contains(&Field::Usernames) {
+        walk(&mut value, &strip_usernames, true);
+    }
+    if fields.contains(&Field::Paths) {
+        walk(&mut value, &strip_paths, true);
+    }
+    if fields.contains(&Field::Hostnames) {
+        let hostname = report
+            .environment
+            .machine
+            .as_ref()
+            .map(|x| x.hostname.clone())
+            .filter(|x| !x.is_empty());
+        if let Some(hostname) = hostname {
+            // short names, e.g. "node12" of "node12.cluster.example.org", are matched as well.
+            let short = hostname.split('.').next().unwrap_or_default().to_string();
+            // field names of the report itself could equal a hostname, so keys are left alone.
+            walk(
+                &mut value,
+                &|x: &str| replace_word(&replace_word(x, &hostname), &short),
+                false,
+            );
+        }
+    }
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
@@ -0,0 +1,117 @@
+use crate::abi;
+use library::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+
+const TEMPLATE: &str = include_str!("../templates/reproducer.c");
+/// Elements of A and B up to which they are written out bit for bit; larger inputs are
+/// regenerated from the seed.
+const EXACT_LIMIT: usize = 16384;
+
+/// A failed verification of `call_dgemm` on inputs as the harness generates them: A from
+/// `seed + 100` and B from `seed + 200`, C zeroed, or NaN for a beta of 0.
+pub struct Failure<'a> {
+    /// kernel source or object the test is built with
+    pub kernel: &'a str,
+    pub abi_version: u32,
+    pub session: usize,
+    pub seed: u64,
+    pub layout: CBLAS_LAYOUT,
+    pub transpose: (CBLAS_TRANSPOSE, CBLAS_TRANSPOSE),
+    pub dimensions: (usize, usize, usize),
+    pub leading_dimensions: (usize, usize, usize),
+    pub alpha: f64,
+    pub beta: f64,
+    pub a: &'a [f64],
+    pub b: &'a [f64],
+    /// largest ||C - Ĉ|| that passes
+    pub limit: f64,
+    /// what verification found
+    pub message: String,
+}
+
+/// `values` as a C array of their bits, four to a line.
+fn bits(name: &str, values: &[f64]) -> String {
+    let lines = values
+        .chunks(4)
+        .map(|x| {
+            x.iter()
+                .map(|x| format!("0x{:016x}ULL", x.to_bits()))
+                .collect::<Vec<String>>()
+                .join(", ")
+        })
+        .collect::<Vec<String>>()
+        .join(",\n    ");
+    format!(
+        "static const uint64_t {}[{}] = {{\n    {}\n}};\n",
+        name,
+        values.len(),
+        lines
+    )
+}
+
+/// CBLAS value of `trans`, whose type differs between the BLAS libraries.
+fn transpose(trans: CBLAS_TRANSPOSE) -> u32 {
+    if trans == CBLAS_TRANSPOSE::CblasNoTrans {
+        111
+    } else if trans == CBLAS_TRANSPOSE::CblasTrans {
+        112
+    } else {
+        113
+    }
+}
+
+impl Failure<'_> {
+    /// A standalone C test that calls the kernel once on the same inputs and exits with 1 as
+    /// long as it fails, for the kernel's own test suite.
+    pub fn c_test(&self) -> String {
+        let (m, n, k) = self.dimensions;
+        let (lda, ldb, ldc) = self.leading_dimensions;
+        let exact = self.a.len().max(self.b.len()) <= EXACT_LIMIT;
+        let data = if exact {
+            [bits("A_BITS", self.a), bits("B_BITS", self.b)].join("\n")
+        } else {
+            String::new()
+        };
+        let kernel = std::path::Path::new(self.kernel)
+            .file_name()
+            .map_or(self.kernel.to_string(), |x| x.to_string_lossy().to_string());
+        let abi_version = if self.abi_version == abi::V2 { 2 } else { 1 };
+        [
+            ("{{KERNEL}}", kernel),
+            ("{{FAILURE}}", self.message.replace("*/", "* /")),
+            ("{{ABI}}", abi_version.to_string()),
+            ("{{EXACT}}", (exact as u8).to_string()),
+            ("{{SEED}}", self.seed.to_string()),
+            ("{{SESSION}}", self.session.to_string()),
+            (
+                "{{LAYOUT}}",
+                if self.layout == CBLAS_LAYOUT::CblasRowMajor {
+                    "101".to_string()
+                } else {
+                    "102".to_string()
+                },
+            ),
+            ("{{TRANS_A}}", transpose(self.transpose.0).to_string()),
+            ("{{TRANS_B}}", transpose(self.transpose.1).to_string()),
+            ("{{M}}", m.to_string()),
+            ("{{N}}", n.to_string()),
+            ("{{K}}", k.to_string()),
+            ("{{LDA}}", lda.to_string()),
+            ("{{LDB}}", ldb.to_string()),
+            ("{{LDC}}", ldc.to_string()),
+            ("{{SIZE_A}}", self.a.len().to_string()),
+            ("{{SIZE_B}}", self.b.len().to_string()),
+            ("{{SIZE_C}}", (m * n).to_string()),
+            ("{{DATA}}", data),
+            ("{{ALPHA}}", format!("{:#018x}", self.alpha.to_bits())),
+            ("{{ALPHA_VALUE}}", format!("{:e}", self.alpha)),
+            ("{{BETA}}", format!("{:#018x}", self.beta.to_bits())),
+            ("{{BETA_VALUE}}", format!("{:e}", self.beta)),
+            ("{{LIMIT}}", format!("{:#018x}", self.limit.to_bits())),
+            ("{{LIMIT_VALUE}}", format!("{:e}", self.limit)),
+        ]
+        .iter()
+        .fold(TEMPLATE.to_string(), |out, (key, value)| {
+            out.replace(key, value)
+        })
+    }
+}
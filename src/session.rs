@@ -0,0 +1,272 @@
+use crate::{
+    abi, cache, common, compiler, conformance, diagnostics, hints, kernel::Library, loader,
+    machine, methodology, pool, units, utils,
+};
+use library::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+use std::{
+    env, fs, path, process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Sessions of this process so far, so every compiled object gets a name of its own.
+static SESSIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Parameters of one `Session::run`; `Run::new` has the defaults of the command line.
+#[derive(Clone)]
+pub struct Run {
+    pub name: Option<String>,
+    pub dimensions: (usize, usize, usize),
+    pub layout: CBLAS_LAYOUT,
+    pub transpose: (CBLAS_TRANSPOSE, CBLAS_TRANSPOSE),
+    pub alpha: f64,
+    pub beta: f64,
+    pub warm_up: usize,
+    pub repeats: usize,
+    /// A is generated from `seed + 100` and B from `seed + 200`
+    pub seed: u64,
+    /// bounds of the uniform distribution of the elements of A and B
+    pub range: (f64, f64),
+}
+
+impl Run {
+    pub fn new(dimensions: (usize, usize, usize)) -> Self {
+        Run {
+            name: None,
+            dimensions,
+            layout: CBLAS_LAYOUT::CblasRowMajor,
+            transpose: (CBLAS_TRANSPOSE::CblasNoTrans, CBLAS_TRANSPOSE::CblasNoTrans),
+            alpha: 1.0,
+            beta: 1.0,
+            warm_up: 0,
+            repeats: 10,
+            seed: 0,
+            range: (0.0, 2.0),
+        }
+    }
+}
+
+/// A kernel benchmarked from a host program, e.g. a tuning framework, any number of times.
+/// The session owns the compiled object, the library loaded from it, a pool of the generated
+/// matrices and the reports of all runs. Kernels of the library are looked up for every run and
+/// borrow it, and dropping the session closes the library before it removes the object.
+/// Sessions are `Send` and `Sync`, so several threads can share one behind a `Mutex`.
+pub struct Session {
+    name: String,
+    library: Option<Library>,
+    /// object compiled by the session, which it removes
+    object: Option<path::PathBuf>,
+    /// A and B of previous runs, which runs of the same shapes and seeds reuse
+    pool: pool::Pool,
+    c: Box<[f64]>,
+    reports: Vec<common::Report>,
+}
+
+impl Session {
+    /// Compile `kernel` with `compiler` and its default arguments, and load it.
+    pub fn compile(kernel: &str, compiler: &str) -> Result<Session, String> {
+        let index = SESSIONS.fetch_add(1, Ordering::Relaxed);
+        let object = env::temp_dir()
+            .join(format!(
+                "benchmark-dgemm-session-{}-{}",
+                process::id(),
+                index
+            ))
+            .with_extension(env::consts::DLL_EXTENSION);
+        let out = object.to_string_lossy().to_string();
+        let compiler = compiler.to_string();
+        let status = compiler::build_command(
+            &compiler,
+            compiler::Profile::infer(&compiler),
+            &None,
+            false,
+            &[],
+            None,
+            &kernel.to_string(),
+            &out,
+        )
+        .status()
+        .map_err(|e| format!("failed to run compiler: {}", e))?;
+        if !status.success() {
+            drop(fs::remove_file(&object));
+            return Err("compilation failed".to_string());
+        }
+        let mut session = Session::open(&out)?;
+        session.name = path::Path::new(kernel)
+            .file_name()
+            .map_or(out, |x| x.to_string_lossy().to_string());
+        session.object = Some(object);
+        Ok(session)
+    }
+
+    /// Load an object compiled beforehand, which the session leaves in place.
+    pub fn open(object: &str) -> Result<Session, String> {
+        let library = Library::open(object, loader::DEFAULT_FLAGS, false)?;
+        // fail now rather than on the first run.
+        library.kernel(None)?;
+        Ok(Session {
+            name: object.to_string(),
+            library: Some(library),
+            object: None,
+            pool: pool::Pool::new(pool::DEFAULT_CEILING),
+            c: Box::default(),
+            reports: Vec::new(),
+        })
+    }
+
+    /// Benchmark the kernel once and keep the report. A and B are taken from the pool, so only
+    /// the first run of every shape and seed generates them.
+    pub fn run(&mut self, run: &Run) -> Result<&common::Report, String> {
+        if run.repeats == 0 {
+            return Err("repeats should not be 0".to_string());
+        }
+        let library = self.library.as_ref().ok_or("library is closed")?;
+        let kernel = library.kernel(None)?;
+        let key = |size, seed| pool::Key {
+            size,
+            seed,
+            range: run.range,
+        };
+        let [size_a, size_b, size_c] = utils::element_counts(run.dimensions)?;
+        let [a, b] = self
+            .pool
+            .get([key(size_a, run.seed + 100), key(size_b, run.seed + 200)]);
+        if self.c.len() != size_c {
+            // the previous C is freed before the next is allocated.
+            self.c = Box::default();
+            self.c = vec![0.0; size_c].into_boxed_slice();
+        }
+        let c = &mut self.c;
+        let (trans_a, trans_b) = run.transpose;
+        let (lda, ldb, ldc) =
+            utils::leading_dimensions(run.layout, trans_a, trans_b, run.dimensions);
+        let mut call = |phase: abi::Phase, iteration: usize| {
+            kernel.run(
+                abi::Context::new(phase, 0, iteration),
+                run.layout,
+                trans_a,
+                trans_b,
+                run.dimensions,
+                a,
+                lda,
+                b,
+                ldb,
+                c,
+                ldc,
+                run.alpha,
+                run.beta,
+            )
+        };
+        for iteration in 0..run.warm_up {
+            call(abi::Phase::WarmUp, iteration);
+        }
+        let records = (0..run.repeats)
+            .map(|x| call(abi::Phase::Measurement, x))
+            .collect::<Vec<common::Duration>>();
+
+        let mut report = common::Report {
+            name: run.name.clone().unwrap_or_else(|| self.name.clone()),
+            dimensions: run.dimensions,
+            repeats: records.len(),
+            alpha: run.alpha,
+            beta: run.beta,
+            layout: run.layout,
+            transpose: run.transpose,
+            statistics: common::Statistics::from(&records),
+            anova: None,
+            tags: Vec::new(),
+            meta: Default::default(),
+            defines: Default::default(),
+            prewarmed: false,
+            environment: common::Environment {
+                openmp: library.openmp(),
+                machine: Some(machine::detect()),
+                ..Default::default()
+            },
+            working_set: Some(cache::WorkingSet::new(run.dimensions, cache::detect())),
+            footprint: None,
+            extensions: None,
+            instruction_mix: None,
+            residual: None,
+            tolerance_used: None,
+            workspace_bytes: None,
+            active_threads: None,
+            batch: Vec::new(),
+            batch_strides: None,
+            packing: None,
+            convolution: None,
+            against: None,
+            noise: None,
+            lock: None,
+            phases: None,
+            reuse_b: None,
+            counters: None,
+            expectations: Vec::new(),
+            samples: records,
+            pruned: false,
+            diagnostics: None,
+            hints: Vec::new(),
+            units: units::Metadata::default(),
+            build: None,
+            seed: Some(run.seed),
+            methodology: Some(methodology::Methodology {
+                warm_up: run.warm_up,
+                session_gap: common::Duration::ZERO,
+                jitter: None,
+                flush: methodology::Flush::None,
+                pinned: false,
+                timer: methodology::timer().to_string(),
+                verification: methodology::Verification::Skipped,
+                max_residual: None,
+                regeneration: methodology::Regeneration::Pooled,
+            }),
+        };
+        report.diagnostics = Some(diagnostics::analyze(&report.samples, 5.0));
+        report.hints = hints::collect(&report, None);
+        self.reports.push(report);
+        Ok(self.reports.last().unwrap())
+    }
+
+    /// Check the kernel against the contract of the harness, see `conformance::run`.
+    pub fn conformance(&self) -> Result<conformance::Scorecard, String> {
+        let library = self.library.as_ref().ok_or("library is closed")?;
+        let kernel = library.kernel(None)?;
+        Ok(conformance::run(
+            &self.name,
+            &kernel,
+            library.version(None)?,
+        ))
+    }
+
+    /// Pool of the generated matrices, e.g. to read its hits and misses.
+    pub fn pool(&self) -> &pool::Pool {
+        &self.pool
+    }
+
+    /// Limit the bytes of generated matrices the session keeps between runs; the default is
+    /// `pool::DEFAULT_CEILING`.
+    pub fn set_memory_ceiling(&mut self, bytes: usize) {
+        self.pool.set_ceiling(bytes);
+    }
+
+    /// Reports of every run so far.
+    pub fn reports(&self) -> &[common::Report] {
+        &self.reports
+    }
+
+    /// Take the reports of every run so far, leaving none.
+    pub fn take_reports(&mut self) -> Vec<common::Report> {
+        std::mem::take(&mut self.reports)
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // no kernel borrows the library outside `run`, so it can be closed first.
+        if let Some(library) = self.library.take() {
+            drop(library.close());
+        }
+        if let Some(object) = self.object.take() {
+            drop(fs::remove_file(object));
+        }
+    }
+}
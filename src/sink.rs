@@ -0,0 +1,104 @@
+use flate2::{write::GzEncoder, Compression};
+use std::{
+    fs,
+    io::Write,
+    path, thread,
+    time::{self, SystemTime},
+};
+
+pub enum Submission {
+    Posted,
+    Spooled(path::PathBuf),
+}
+
+/// Posts serialized reports to a collector over HTTP.
+pub struct HttpSink {
+    pub url: String,
+    pub token: Option<String>,
+    pub gzip: bool,
+    pub retries: usize,
+    /// Reports that could not be posted are written here and re-sent on the next run.
+    pub spool: Option<path::PathBuf>,
+}
+
+impl HttpSink {
+    pub fn submit(&self, body: &[u8]) -> Result<Submission, String> {
+        match self.post_with_retry(body) {
+            Ok(()) => Ok(Submission::Posted),
+            Err(e) => match &self.spool {
+                Some(spool) => Self::spool(spool, body).map(Submission::Spooled),
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Re-send spooled reports. Returns the number of reports that were delivered.
+    pub fn flush_spool(&self) -> Result<usize, String> {
+        let Some(spool) = &self.spool else {
+            return Ok(0);
+        };
+        if !spool.exists() {
+            return Ok(0);
+        }
+
+        let mut entries = fs::read_dir(spool)
+            .map_err(|e| e.to_string())?
+            .filter_map(|x| x.ok().map(|x| x.path()))
+            .filter(|x| x.extension().is_some_and(|x| x == "json"))
+            .collect::<Vec<path::PathBuf>>();
+        entries.sort();
+
+        let mut delivered = 0;
+        for entry in entries {
+            let body = fs::read(&entry).map_err(|e| e.to_string())?;
+            // the collector is still unreachable; keep the rest for later.
+            self.post(&body)?;
+            drop(fs::remove_file(&entry));
+            delivered += 1;
+        }
+        Ok(delivered)
+    }
+
+    fn post_with_retry(&self, body: &[u8]) -> Result<(), String> {
+        let mut attempt = 0;
+        loop {
+            match self.post(body) {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt >= self.retries => return Err(e),
+                Err(_) => {
+                    thread::sleep(time::Duration::from_secs(1 << attempt.min(6)));
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    fn post(&self, body: &[u8]) -> Result<(), String> {
+        let mut request = ureq::post(&self.url).set("Content-Type", "application/json");
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+
+        let response = if self.gzip {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(body).map_err(|e| e.to_string())?;
+            let body = encoder.finish().map_err(|e| e.to_string())?;
+            request.set("Content-Encoding", "gzip").send_bytes(&body)
+        } else {
+            request.send_bytes(body)
+        };
+        response.map(drop).map_err(|e| e.to_string())
+    }
+
+    fn spool(spool: &path::Path, body: &[u8]) -> Result<path::PathBuf, String> {
+        fs::create_dir_all(spool).map_err(|e| e.to_string())?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|e| e.to_string())?
+            .as_nanos();
+        let file = spool.join(format!("{}.json", timestamp));
+        fs::write(&file, body).map_err(|e| e.to_string())?;
+        Ok(file)
+    }
+}
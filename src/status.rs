@@ -0,0 +1,98 @@
+use serde::Serialize;
+use std::{io::Write, sync::Mutex};
+
+/// Progress of a run, emitted as one JSON object per line.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    EntryStarted {
+        index: usize,
+        total: usize,
+        arguments: &'a [String],
+    },
+    EntryFinished {
+        index: usize,
+        total: usize,
+        success: bool,
+    },
+    Compiled {
+        kernel: &'a str,
+    },
+    SessionStarted {
+        session: usize,
+        sessions: usize,
+    },
+    VerificationPassed {
+        session: usize,
+        #[serde(with = "crate::number")]
+        difference: f64,
+        /// ||C - Ĉ|| / (||A|| ||B|| k ε)
+        #[serde(with = "crate::number")]
+        residual: f64,
+        /// fraction of the calibrated tolerance ||C - Ĉ|| used
+        #[serde(with = "crate::number")]
+        tolerance_used: f64,
+    },
+    IterationCompleted {
+        session: usize,
+        iteration: usize,
+        repeats: usize,
+        /// nanoseconds
+        duration: u128,
+    },
+    Completed {
+        name: &'a str,
+        #[serde(with = "crate::number")]
+        gflops: f64,
+    },
+}
+
+static SINK: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+
+fn install(sink: Box<dyn Write + Send>) {
+    *SINK.lock().unwrap() = Some(sink);
+}
+
+/// Emit events to an inherited file descriptor, e.g. `--status-fd 3` with `3>status.jsonl`.
+#[cfg(unix)]
+pub fn open_fd(fd: i32) -> Result<(), String> {
+    use std::os::fd::FromRawFd;
+
+    if unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+        return Err(format!("file descriptor {} is not open", fd));
+    }
+    install(Box::new(unsafe { std::fs::File::from_raw_fd(fd) }));
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn open_fd(_: i32) -> Result<(), String> {
+    Err("status file descriptors are not supported on this platform".to_string())
+}
+
+/// Emit events to a Unix-domain socket the consumer is listening on.
+#[cfg(unix)]
+pub fn connect(path: &str) -> Result<(), String> {
+    let stream = std::os::unix::net::UnixStream::connect(path).map_err(|e| e.to_string())?;
+    install(Box::new(stream));
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn connect(_: &str) -> Result<(), String> {
+    Err("status sockets are not supported on this platform".to_string())
+}
+
+/// Write `event` to the status sink, if any.
+/// A consumer that went away must not abort the benchmark, so the sink is dropped on the first failure.
+pub fn emit(event: Event) {
+    let mut sink = SINK.lock().unwrap();
+    let Some(out) = sink.as_mut() else {
+        return;
+    };
+    let mut line = serde_json::to_vec(&event).expect("Error: failed to serialize");
+    line.push(b'\n');
+    if out.write_all(&line).and_then(|_| out.flush()).is_err() {
+        *sink = None;
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{common::Report, config, utils};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, path};
+
+/// A list of benchmark configurations for one kernel.
+///
+/// Every key of an entry is passed to the benchmark as `--key value`; `true` becomes a switch,
+/// `false` is dropped and arrays repeat the option.
+#[derive(Deserialize)]
+pub struct Suite {
+    pub kernel: String,
+    /// Template parameters substituted for `{name}` in option values; an array instantiates
+    /// every entry once per value. Entries may add their own in a `params` table.
+    #[serde(default)]
+    pub params: toml::Table,
+    /// Options shared by all entries; entries override them.
+    #[serde(default)]
+    pub defaults: toml::Table,
+    #[serde(default, rename = "entry")]
+    pub entries: Vec<toml::Table>,
+}
+
+pub use benchmark_core::summary::META_CONFIGURATION;
+
+/// Meta key of the position, counting from 0, at which an entry ran within its suite.
+pub const META_POSITION: &str = "position";
+
+/// Position at which `report` ran within its suite, if it was run by one.
+pub fn position(report: &Report) -> Option<usize> {
+    report.meta.get(META_POSITION)?.parse().ok()
+}
+
+/// Put `items` in a random order that only depends on `seed` (Fisher-Yates).
+pub fn shuffle<T>(items: &mut [T], seed: u64) {
+    for i in (1..items.len()).rev() {
+        let x = utils::philox(i as u64, seed);
+        let j = ((x[0] as u64 | (x[1] as u64) << 32) % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// Stable hash of an entry, covering its arguments and the kernel source (FNV-1a).
+/// Arguments must not contain output paths, or the same configuration would hash differently per run.
+pub fn configuration(arguments: &[String]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        // separator, so ["ab", "c"] and ["a", "bc"] differ.
+        hash ^= 0xff;
+        hash = hash.wrapping_mul(0x100000001b3);
+    };
+    if let Some(kernel) = arguments.first() {
+        feed(&fs::read(kernel).unwrap_or_default());
+    }
+    for argument in arguments {
+        feed(argument.as_bytes());
+    }
+    format!("{:016x}", hash)
+}
+
+fn param_string(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(x) => x.clone(),
+        x => x.to_string(),
+    }
+}
+
+/// Cartesian product of parameter values.
+pub fn instances(params: &toml::Table) -> Vec<BTreeMap<String, String>> {
+    let mut instances = vec![BTreeMap::new()];
+    for (name, value) in params {
+        let values = match value {
+            toml::Value::Array(values) => values.iter().map(param_string).collect(),
+            x => vec![param_string(x)],
+        };
+        instances = instances
+            .into_iter()
+            .flat_map(|instance| {
+                values.iter().map(move |value| {
+                    let mut instance = instance.clone();
+                    instance.insert(name.clone(), value.clone());
+                    instance
+                })
+            })
+            .collect();
+    }
+    instances
+}
+
+/// Replace `{name}` of every parameter in string values; other braces are kept,
+/// as hook commands have placeholders of their own.
+pub fn substitute(value: &toml::Value, params: &BTreeMap<String, String>) -> toml::Value {
+    match value {
+        toml::Value::String(x) => {
+            let mut x = x.clone();
+            for (name, value) in params {
+                x = x.replace(&["{", name, "}"].concat(), value);
+            }
+            toml::Value::String(x)
+        }
+        toml::Value::Array(values) => {
+            toml::Value::Array(values.iter().map(|x| substitute(x, params)).collect())
+        }
+        x => x.clone(),
+    }
+}
+
+impl Suite {
+    pub fn load(file: &path::Path) -> Result<Suite, String> {
+        let mut suite: Suite =
+            toml::from_str(&fs::read_to_string(file).map_err(|e| e.to_string())?)
+                .map_err(|e| e.to_string())?;
+
+        // kernel path is relative to the suite file.
+        if let Some(dir) = file.parent() {
+            suite.kernel = dir.join(&suite.kernel).to_string_lossy().to_string();
+        }
+        Ok(suite)
+    }
+
+    /// Benchmark arguments of every entry, kernel path first.
+    /// Entries are instantiated once per combination of parameter values.
+    pub fn arguments(&self) -> Vec<Vec<String>> {
+        let mut out = Vec::new();
+        for entry in &self.entries {
+            let mut table = self.defaults.clone();
+            table.extend(entry.clone());
+            let mut params = self.params.clone();
+            if let Some(toml::Value::Table(x)) = table.remove("params") {
+                params.extend(x);
+            }
+
+            for instance in instances(&params) {
+                let mut arguments = vec![self.kernel.clone()];
+                for (key, value) in &table {
+                    config::push_value(
+                        &mut arguments,
+                        &["--", key.replace('_', "-").as_str()].concat(),
+                        &substitute(value, &instance),
+                    );
+                }
+                out.push(arguments);
+            }
+        }
+        out
+    }
+}
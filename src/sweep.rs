@@ -0,0 +1,97 @@
+use crate::utils;
+use std::path;
+
+/// Parse an inclusive range "START:END:STEP", or a list "A,B,C", into its values.
+/// STEP is "*FACTOR" for a geometric progression or "+INCREMENT" (or a bare number) for an arithmetic one.
+pub fn parse_range(value: &str) -> Result<Vec<usize>, String> {
+    let error = |reason: &str| ["invalid range '", value, "': ", reason].concat();
+
+    if !value.contains(':') {
+        return value.split(',').map(utils::parse_dimension).collect();
+    }
+
+    let parts = value.split(':').collect::<Vec<&str>>();
+    let [start, end, step] = parts.as_slice() else {
+        return Err(error("expected START:END:STEP"));
+    };
+    let start = utils::parse_dimension(start)?;
+    let end = utils::parse_dimension(end)?;
+    if start > end {
+        return Err(error("START is greater than END"));
+    }
+
+    let mut values = Vec::new();
+    if let Some(factor) = step.strip_prefix('*') {
+        let factor = utils::parse_dimension(factor)?;
+        if factor < 2 || start == 0 {
+            return Err(error("geometric progression never reaches END"));
+        }
+        let mut value = start;
+        while value <= end {
+            values.push(value);
+            let Some(next) = value.checked_mul(factor) else {
+                break;
+            };
+            value = next;
+        }
+    } else {
+        let increment = utils::parse_dimension(step.strip_prefix('+').unwrap_or(step))?;
+        if increment == 0 {
+            return Err(error("arithmetic progression never reaches END"));
+        }
+        values.extend((start..=end).step_by(increment));
+    }
+    Ok(values)
+}
+
+/// Parse a list of scalars "A,B,C", e.g. the values of alpha.
+pub fn parse_scalars(value: &str) -> Result<Vec<f64>, String> {
+    value
+        .split(',')
+        .map(|x| match x.trim().parse::<f64>() {
+            Ok(x) if x.is_finite() => Ok(x),
+            _ => Err(["invalid scalar '", x, "' in '", value, "'"].concat()),
+        })
+        .collect()
+}
+
+/// Cartesian product of the dimensions.
+pub fn points(m: &[usize], n: &[usize], k: &[usize]) -> Vec<(usize, usize, usize)> {
+    let mut points = Vec::with_capacity(m.len() * n.len() * k.len());
+    for &m in m {
+        for &n in n {
+            for &k in k {
+                points.push((m, n, k));
+            }
+        }
+    }
+    points
+}
+
+/// Remove `options` with their values, and `switches`, from command-line arguments.
+pub fn strip_options(args: &[String], options: &[&str], switches: &[&str]) -> Vec<String> {
+    let mut out = Vec::with_capacity(args.len());
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if options.contains(&arg.as_str()) {
+            args.next();
+        } else if !switches.contains(&arg.as_str()) {
+            out.push(arg.clone());
+        }
+    }
+    out
+}
+
+/// Insert `suffix` before the extension, e.g. "report.json" into "report-256x256x256.json".
+pub fn suffixed(file: &str, suffix: &str) -> String {
+    let file = path::Path::new(file);
+    let stem = file
+        .file_stem()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = match file.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, suffix, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, suffix),
+    };
+    file.with_file_name(name).to_string_lossy().to_string()
+}
@@ -0,0 +1,143 @@
+use serde_json::{Map, Value};
+use std::{fs, path};
+
+/// Field of a CSV row, quoted if it contains a separator, quote or newline.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        ["\"", &value.replace('"', "\"\""), "\""].concat()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Text with the characters HTML reserves escaped.
+pub fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Format of an exported table.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+/// Parse the file of `--export`, whose extension chooses the format.
+pub fn parse_export(value: &str) -> Result<(String, Format), String> {
+    match path::Path::new(value).extension().and_then(|x| x.to_str()) {
+        Some("csv") => Ok((value.to_string(), Format::Csv)),
+        Some("json") => Ok((value.to_string(), Format::Json)),
+        _ => Err(["expected a .csv or .json file, but got ", value].concat()),
+    }
+}
+
+/// A table as the viewer prints it. Cells keep the printed text, so an export holds exactly the
+/// numbers on the terminal.
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(columns: &[&str]) -> Self {
+        Table {
+            columns: columns.iter().map(|x| x.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// Print the header and the rows, separated by tabs.
+    pub fn print(&self) {
+        println!("{}", self.columns.join("\t "));
+        for row in &self.rows {
+            println!("{}", row.join("\t "));
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        for row in [&self.columns].into_iter().chain(&self.rows) {
+            let fields = row.iter().map(|x| csv_field(x)).collect::<Vec<String>>();
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// A Markdown table with pipes in cells escaped.
+    pub fn to_markdown(&self) -> String {
+        let line = |row: &[String]| {
+            let cells = row
+                .iter()
+                .map(|x| x.replace('|', "\\|"))
+                .collect::<Vec<_>>();
+            ["| ", &cells.join(" | "), " |\n"].concat()
+        };
+        let mut out = line(&self.columns);
+        out.push_str(&line(&vec!["---".to_string(); self.columns.len()]));
+        for row in &self.rows {
+            out.push_str(&line(row));
+        }
+        out
+    }
+
+    /// An HTML table, with cells escaped.
+    pub fn to_html(&self) -> String {
+        let line = |row: &[String], tag: &str| {
+            let cells = row
+                .iter()
+                .map(|x| format!("<{}>{}</{}>", tag, html_escape(x), tag))
+                .collect::<String>();
+            ["<tr>", &cells, "</tr>\n"].concat()
+        };
+        let mut out = String::from("<table>\n");
+        out.push_str(&line(&self.columns, "th"));
+        for row in &self.rows {
+            out.push_str(&line(row, "td"));
+        }
+        out.push_str("</table>\n");
+        out
+    }
+
+    /// One object per row, keyed by column. Cells that are numbers become JSON numbers and
+    /// cells printed as `-` become null.
+    pub fn to_json(&self) -> Value {
+        let cell = |x: &String| match x.parse::<f64>() {
+            Ok(number) if number.is_finite() => Value::from(number),
+            _ if x == "-" => Value::Null,
+            _ => Value::from(x.as_str()),
+        };
+        Value::Array(
+            self.rows
+                .iter()
+                .map(|row| {
+                    Value::Object(
+                        self.columns
+                            .iter()
+                            .cloned()
+                            .zip(row.iter().map(cell))
+                            .collect::<Map<String, Value>>(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    pub fn export(&self, (file, format): &(String, Format)) -> Result<(), String> {
+        let out = match format {
+            Format::Csv => self.to_csv(),
+            Format::Json => {
+                serde_json::to_string_pretty(&self.to_json()).map_err(|e| e.to_string())?
+            }
+        };
+        fs::write(file, out).map_err(|e| e.to_string())
+    }
+}
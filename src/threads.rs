@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+
+/// Time each thread of this process has spent on a CPU, in nanoseconds, by thread id.
+#[cfg(target_os = "linux")]
+fn runtimes() -> Option<BTreeMap<u32, u64>> {
+    let mut runtimes = BTreeMap::new();
+    for entry in std::fs::read_dir("/proc/self/task").ok()?.flatten() {
+        let Some(id) = entry
+            .file_name()
+            .to_str()
+            .and_then(|x| x.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        // the thread may have exited in the meantime.
+        let Ok(schedstat) = std::fs::read_to_string(entry.path().join("schedstat")) else {
+            continue;
+        };
+        runtimes.insert(
+            id,
+            schedstat.split_whitespace().next()?.parse::<u64>().ok()?,
+        );
+    }
+    Some(runtimes)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn runtimes() -> Option<BTreeMap<u32, u64>> {
+    None
+}
+
+/// Threads that ran on a CPU during a kernel call, which catches kernels that ignore
+/// OMP_NUM_THREADS or oversubscribe. Threads spinning in a barrier count as active.
+pub struct Probe {
+    before: Option<BTreeMap<u32, u64>>,
+}
+
+impl Probe {
+    pub fn start() -> Self {
+        Probe { before: runtimes() }
+    }
+
+    /// Number of threads, including the calling one, whose CPU time grew since `start`;
+    /// threads created in between count if they ran at all.
+    pub fn finish(self) -> Option<usize> {
+        let before = self.before?;
+        let after = runtimes()?;
+        Some(
+            after
+                .iter()
+                .filter(|(id, runtime)| before.get(id).is_none_or(|x| *runtime > x))
+                .count(),
+        )
+    }
+}
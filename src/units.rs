@@ -0,0 +1,145 @@
+use crate::common::Duration;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Unit durations are printed in, chosen with `--units`.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum TimeUnit {
+    Us,
+    #[default]
+    Ms,
+    S,
+}
+
+impl<'a> TryFrom<&'a str> for TimeUnit {
+    type Error = String;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "us" => Ok(TimeUnit::Us),
+            "ms" => Ok(TimeUnit::Ms),
+            "s" => Ok(TimeUnit::S),
+            x => Err(["expected one of [us, ms, s], but got ", x].concat()),
+        }
+    }
+}
+
+impl TimeUnit {
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            TimeUnit::Us => "us",
+            TimeUnit::Ms => "ms",
+            TimeUnit::S => "s",
+        }
+    }
+
+    /// Value of `duration` in this unit.
+    pub fn of(&self, duration: Duration) -> f64 {
+        match self {
+            TimeUnit::Us => duration.as_micros(),
+            TimeUnit::Ms => duration.as_millis(),
+            TimeUnit::S => duration.as_secs(),
+        }
+    }
+
+    /// Value of `millis`, a time in milliseconds, in this unit.
+    pub fn of_millis(&self, millis: f64) -> f64 {
+        match self {
+            TimeUnit::Us => millis * 1e3,
+            TimeUnit::Ms => millis,
+            TimeUnit::S => millis / 1e3,
+        }
+    }
+}
+
+/// Unit floating-point throughput is printed in, chosen with `--flops-units`.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum FlopsUnit {
+    #[default]
+    Gflops,
+    Tflops,
+}
+
+impl<'a> TryFrom<&'a str> for FlopsUnit {
+    type Error = String;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "gflops" => Ok(FlopsUnit::Gflops),
+            "tflops" => Ok(FlopsUnit::Tflops),
+            x => Err(["expected one of [gflops, tflops], but got ", x].concat()),
+        }
+    }
+}
+
+impl FlopsUnit {
+    /// Name in lowercase, as in CSV headers.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FlopsUnit::Gflops => "gflops",
+            FlopsUnit::Tflops => "tflops",
+        }
+    }
+
+    /// Name as in table headers.
+    pub fn label(&self) -> &'static str {
+        match self {
+            FlopsUnit::Gflops => "GFLOPS",
+            FlopsUnit::Tflops => "TFLOPS",
+        }
+    }
+
+    /// Value of `gflops` in this unit.
+    pub fn of(&self, gflops: f64) -> f64 {
+        match self {
+            FlopsUnit::Gflops => gflops,
+            FlopsUnit::Tflops => gflops / 1e3,
+        }
+    }
+}
+
+/// Units of printed and exported numbers; reports themselves always keep nanoseconds and
+/// milliseconds, as their `units` block says.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct Units {
+    pub time: TimeUnit,
+    pub flops: FlopsUnit,
+}
+
+impl Units {
+    /// `duration` formatted with six decimals and the unit suffix, e.g. `1.500000ms`.
+    pub fn time(&self, duration: Duration) -> String {
+        format!("{:.6}{}", self.time.of(duration), self.time.suffix())
+    }
+
+    /// Like `time`, for a time in milliseconds.
+    pub fn time_millis(&self, millis: f64) -> String {
+        format!("{:.6}{}", self.time.of_millis(millis), self.time.suffix())
+    }
+
+    /// Throughput of `ops` floating-point operations in `duration`.
+    pub fn flops(&self, ops: f64, duration: Duration) -> f64 {
+        self.flops.of(ops / duration.as_nanos())
+    }
+}
+
+/// Units of the numbers of a report, so that readers need not guess them.
+#[derive(Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Metadata {
+    /// of every `Duration`, e.g. the samples, minimum, maximum and medium
+    pub durations: String,
+    /// of `statistics.average` and `statistics.deviation`
+    pub statistics: String,
+    /// of the ANOVA variance components
+    pub variance: String,
+}
+
+impl Default for Metadata {
+    fn default() -> Self {
+        Metadata {
+            durations: "ns".to_string(),
+            statistics: "ms".to_string(),
+            variance: "ms^2".to_string(),
+        }
+    }
+}
@@ -1,37 +1,356 @@
+use crate::affinity;
+use library::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
 use rayon::{
     iter::{IndexedParallelIterator, ParallelIterator},
     slice::ParallelSliceMut,
 };
+use std::{
+    ffi::c_int,
+    sync::atomic::{AtomicU8, Ordering},
+    thread, time,
+};
 
 #[inline(always)]
 pub unsafe fn malloc<T>(size: usize) -> Box<[T]> {
     Box::<[T]>::new_uninit_slice(size).assume_init()
 }
 
-/// ???
-const CHUNK_SIZE: usize = 2048;
+/// Verbosity set by `-v`, which library code reads to log details of the setup.
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
 
-/// Originally written by Enoch Jung in C.
-pub fn fill_rand(size: usize, seed: u64, min: f64, max: f64) -> Box<[f64]> {
-    let mul = 192499u64;
-    let add = 6837199u64;
+pub fn set_verbosity(level: u8) {
+    VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> u8 {
+    VERBOSITY.load(Ordering::Relaxed)
+}
 
-    let scaling_factor = (max - min) / (u64::MAX as f64);
+/// Chunks per thread of `fill_rand`, so threads that finish early can take over some work.
+const CHUNKS_PER_THREAD: usize = 4;
+
+/// Philox4x32-10, the counter-based generator of Salmon et al., "Parallel Random Numbers: As Easy
+/// as 1, 2, 3": the `counter`-th block of 128 random bits of the stream of `key`.
+pub fn philox(counter: u64, key: u64) -> [u32; 4] {
+    const M: [u64; 2] = [0xD2511F53, 0xCD9E8D57];
+    const W: [u32; 2] = [0x9E3779B9, 0xBB67AE85];
+
+    let mut x = [counter as u32, (counter >> 32) as u32, 0, 0];
+    let mut k = [key as u32, (key >> 32) as u32];
+    for _ in 0..10 {
+        let p0 = M[0] * x[0] as u64;
+        let p1 = M[1] * x[2] as u64;
+        x = [
+            (p1 >> 32) as u32 ^ x[1] ^ k[0],
+            p1 as u32,
+            (p0 >> 32) as u32 ^ x[3] ^ k[1],
+            p0 as u32,
+        ];
+        k = [k[0].wrapping_add(W[0]), k[1].wrapping_add(W[1])];
+    }
+    x
+}
+
+/// Fill `chunk`, which starts at element `start` of the matrix, with the values `fill_rand` gives
+/// those elements.
+fn fill_values(chunk: &mut [f64], start: usize, seed: u64, (min, max): (f64, f64)) {
+    // 53 random bits are the most a double holds.
+    let scaling_factor = (max - min) / (1u64 << 53) as f64;
+    // every block of 128 bits gives two values.
+    let value = |x: [u32; 4], lane: usize| {
+        let bits = x[2 * lane] as u64 | (x[2 * lane + 1] as u64) << 32;
+        (bits >> 11) as f64 * scaling_factor + min
+    };
+    let (head, rest) = chunk.split_at_mut((start % 2).min(chunk.len()));
+    if let Some(cell) = head.first_mut() {
+        *cell = value(philox((start / 2) as u64, seed), 1);
+    }
+    let first = start.div_ceil(2) as u64;
+    for (block, pair) in rest.chunks_mut(2).enumerate() {
+        let x = philox(first + block as u64, seed);
+        for (lane, cell) in pair.iter_mut().enumerate() {
+            *cell = value(x, lane);
+        }
+    }
+}
+
+fn log_generation(size: usize, threads: usize, start_time: time::Instant) {
+    if verbosity() >= 2 {
+        let seconds = time::Instant::now()
+            .duration_since(start_time)
+            .as_secs_f64();
+        eprintln!(
+            "Generated {} elements in {:.3}ms on {} threads, {:.3} GB/s",
+            size,
+            seconds * 1e3,
+            threads,
+            (size * size_of::<f64>()) as f64 / seconds / 1e9
+        );
+    }
+}
+
+/// `size` values uniformly distributed in [`min`, `max`). Element i only depends on `seed` and
+/// i, so the values do not change with the number of threads.
+pub fn fill_rand(size: usize, seed: u64, min: f64, max: f64) -> Box<[f64]> {
+    let start_time = time::Instant::now();
     let mut matrix = unsafe { malloc::<f64>(size) };
+    let threads = rayon::current_num_threads();
+    let chunk_size = size.div_ceil(threads * CHUNKS_PER_THREAD).max(1);
     matrix
-        .par_chunks_mut(CHUNK_SIZE)
+        .par_chunks_mut(chunk_size)
         .enumerate()
-        .for_each(|(tid, chunk)| {
-            let mut value = (tid as u64 * 1034871 + 10581) * seed;
-
-            for _ in 0..(50 + tid as u64) {
-                value = value.wrapping_mul(mul).wrapping_add(add);
-            }
-
-            for cell in chunk.iter_mut() {
-                value = value.wrapping_mul(mul).wrapping_add(add);
-                *cell = (value as f64) * scaling_factor + min;
-            }
-        });
+        .for_each(|(index, chunk)| fill_values(chunk, index * chunk_size, seed, (min, max)));
+    log_generation(size, threads, start_time);
     matrix
 }
+
+/// First-touch placement of matrices: blocks of `rows` rows are dealt round robin to threads
+/// pinned to `cpus`, which write them first, so under the kernel's static schedule every page
+/// lands on the NUMA node of the thread that will use it.
+pub struct Placement {
+    pub cpus: Vec<usize>,
+    pub rows: usize,
+}
+
+impl Placement {
+    /// Run `f` on every row block of a matrix of leading dimension `ld`, with its offset, on the
+    /// thread of its CPU.
+    fn touch(
+        &self,
+        matrix: &mut [f64],
+        ld: usize,
+        f: impl Fn(&mut [f64], usize) + Sync,
+    ) -> Result<(), String> {
+        let block = (self.rows * ld).max(1);
+        let mut shares = self.cpus.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+        for (index, chunk) in matrix.chunks_mut(block).enumerate() {
+            shares[index % self.cpus.len()].push((index * block, chunk));
+        }
+        let f = &f;
+        thread::scope(|scope| {
+            let threads = self
+                .cpus
+                .iter()
+                .zip(shares)
+                .map(|(&cpu, share)| {
+                    scope.spawn(move || {
+                        // only this thread is pinned.
+                        affinity::pin(&[cpu])?;
+                        for (offset, chunk) in share {
+                            f(chunk, offset);
+                        }
+                        Ok(())
+                    })
+                })
+                .collect::<Vec<_>>();
+            threads.into_iter().try_for_each(|x| x.join().unwrap())
+        })
+    }
+
+    /// `fill_rand` of a matrix of leading dimension `ld`, placed by first touch.
+    pub fn fill_rand(
+        &self,
+        (size, ld): (usize, usize),
+        seed: u64,
+        min: f64,
+        max: f64,
+    ) -> Result<Box<[f64]>, String> {
+        let start_time = time::Instant::now();
+        let mut matrix = unsafe { malloc::<f64>(size) };
+        self.touch(&mut matrix, ld, |chunk, start| {
+            fill_values(chunk, start, seed, (min, max))
+        })?;
+        log_generation(size, self.cpus.len(), start_time);
+        Ok(matrix)
+    }
+
+    /// Zeroed matrix of leading dimension `ld`, placed by first touch.
+    pub fn zeroed(&self, (size, ld): (usize, usize)) -> Result<Box<[f64]>, String> {
+        let mut matrix = unsafe { malloc::<f64>(size) };
+        self.touch(&mut matrix, ld, |chunk, _| chunk.fill(0.0))?;
+        Ok(matrix)
+    }
+}
+
+/// Leading dimensions of tightly packed A, B and C.
+pub fn leading_dimensions(
+    layout: CBLAS_LAYOUT,
+    trans_a: CBLAS_TRANSPOSE,
+    trans_b: CBLAS_TRANSPOSE,
+    (m, n, k): (usize, usize, usize),
+) -> (usize, usize, usize) {
+    let lda =
+        if (trans_a != CBLAS_TRANSPOSE::CblasNoTrans) != (layout == CBLAS_LAYOUT::CblasRowMajor) {
+            k
+        } else {
+            m
+        };
+    let ldb =
+        if (trans_b != CBLAS_TRANSPOSE::CblasNoTrans) != (layout == CBLAS_LAYOUT::CblasRowMajor) {
+            n
+        } else {
+            k
+        };
+    let ldc = if layout == CBLAS_LAYOUT::CblasRowMajor {
+        n
+    } else {
+        m
+    };
+    (lda, ldb, ldc)
+}
+
+/// Format a duration as e.g. "1h 2m 3s".
+pub fn format_duration(duration: time::Duration) -> String {
+    let seconds = duration.as_secs();
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, m, s) => format!("{}h {}m {}s", h, m, s),
+    }
+}
+
+/// Parse a duration such as "500ms", "60s" or "2m". A bare number is seconds.
+pub fn parse_duration(value: &str) -> Result<time::Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number = number
+        .parse::<f64>()
+        .map_err(|_| ["expected a duration, but got '", value, "'"].concat())?;
+    let seconds = match unit {
+        "ns" => number / 1e9,
+        "us" => number / 1e6,
+        "ms" => number / 1e3,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        x => {
+            return Err(["expected one of [ns, us, ms, s, m, h], but got '", x, "'"].concat());
+        }
+    };
+    Ok(time::Duration::from_secs_f64(seconds))
+}
+
+fn parse_factor(value: &str, factor: &str) -> Result<i128, String> {
+    let error = |token: &str| ["invalid dimension '", value, "' at '", token, "'"].concat();
+    let factor = factor.trim();
+    let split = factor
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(factor.len());
+    let (number, suffix) = factor.split_at(split);
+    let number = number.parse::<i128>().map_err(|_| error(factor))?;
+    let scale = match suffix {
+        "" => 1,
+        "k" | "K" => 1 << 10,
+        "m" | "M" => 1 << 20,
+        "g" | "G" => 1 << 30,
+        x => return Err(error(x)),
+    };
+    Ok(number * scale)
+}
+
+/// Parse a matrix dimension such as "4096", "16k" or "2x4096".
+/// Suffixes k, m and g are binary; terms can be combined with x, *, + and -.
+pub fn parse_dimension(value: &str) -> Result<usize, String> {
+    let overflow = || ["dimension '", value, "' is out of range"].concat();
+
+    let mut total: i128 = 0;
+    let mut sign = 1;
+    let mut rest = value.trim();
+    loop {
+        let end = rest.find(['+', '-']).unwrap_or(rest.len());
+        let mut product: i128 = 1;
+        for factor in rest[..end].split(['x', 'X', '*']) {
+            product = product
+                .checked_mul(parse_factor(value, factor)?)
+                .ok_or_else(overflow)?;
+        }
+        total = total.checked_add(sign * product).ok_or_else(overflow)?;
+
+        if end == rest.len() {
+            break;
+        }
+        sign = if rest[end..].starts_with('-') { -1 } else { 1 };
+        rest = &rest[end + 1..];
+    }
+    usize::try_from(total).map_err(|_| overflow())
+}
+
+/// Largest count the 32-bit integers of the reference BLAS (LP64 MKL, Arm PL, Accelerate) hold.
+pub const BLAS_INT_MAX: usize = c_int::MAX as usize;
+
+/// Elements of A, B and C of an `m`×`n`×`k` problem, `[m*k, k*n, m*n]`, if the three of them fit
+/// in the address space together; the products would wrap silently on 32-bit targets otherwise.
+/// A dimension of 0 is rejected as well, as it leaves no valid leading dimension.
+///
+/// ```
+/// use benchmark::utils::element_counts;
+///
+/// assert_eq!(element_counts((2, 3, 4)), Ok([8, 12, 6]));
+/// assert!(element_counts((2, 0, 4)).is_err());
+/// // m*k + k*n + m*n = 2m + 1 elements of 8 bytes with n = k = 1.
+/// let largest = (isize::MAX as usize / 8 - 1) / 2;
+/// assert!(element_counts((largest, 1, 1)).is_ok());
+/// assert!(element_counts((largest + 1, 1, 1)).is_err());
+/// assert!(element_counts((usize::MAX, 2, 1)).is_err());
+/// let half = 1 << (usize::BITS / 2);
+/// assert!(element_counts((half, half, 1)).is_err());
+/// ```
+pub fn element_counts((m, n, k): (usize, usize, usize)) -> Result<[usize; 3], String> {
+    if m == 0 || n == 0 || k == 0 {
+        return Err(format!(
+            "m, n and k should not be 0, but got {}x{}x{}",
+            m, n, k
+        ));
+    }
+    let product = |name: &str, x: usize, y: usize| {
+        x.checked_mul(y)
+            .ok_or_else(|| format!("{} of {}x{}x{} overflows", name, m, n, k))
+    };
+    let counts = [
+        product("m*k", m, k)?,
+        product("k*n", k, n)?,
+        product("m*n", m, n)?,
+    ];
+    counts
+        .iter()
+        .try_fold(0usize, |total, x| total.checked_add(*x))
+        .and_then(|x| x.checked_mul(size_of::<f64>()))
+        .filter(|x| *x <= isize::MAX as usize)
+        .ok_or_else(|| format!("A, B and C of {}x{}x{} do not fit in memory", m, n, k))?;
+    Ok(counts)
+}
+
+/// Why the reference BLAS cannot index the matrices of an `m`×`n`×`k` problem with its 32-bit
+/// integers, if it cannot: a dimension or element count above `BLAS_INT_MAX` is truncated.
+///
+/// ```
+/// use benchmark::utils::{blas_int_overflow, BLAS_INT_MAX};
+///
+/// assert!(blas_int_overflow((BLAS_INT_MAX, 1, 1)).is_none());
+/// assert!(blas_int_overflow((BLAS_INT_MAX + 1, 1, 1)).is_some());
+/// // 46340² is the largest square below 2³¹.
+/// assert!(blas_int_overflow((46340, 46340, 1)).is_none());
+/// assert!(blas_int_overflow((46341, 46341, 1)).is_some());
+/// assert!(blas_int_overflow((1, 46341, 46341)).is_some());
+/// ```
+pub fn blas_int_overflow((m, n, k): (usize, usize, usize)) -> Option<String> {
+    [
+        ("m", m, 1),
+        ("n", n, 1),
+        ("k", k, 1),
+        ("m*k", m, k),
+        ("k*n", k, n),
+        ("m*n", m, n),
+    ]
+    .into_iter()
+    .find(|(_, x, y)| x.checked_mul(*y).is_none_or(|x| x > BLAS_INT_MAX))
+    .map(|(name, ..)| {
+        format!(
+            "{} of {}x{}x{} exceeds the {} the 32-bit integers of the reference BLAS hold",
+            name, m, n, k, BLAS_INT_MAX
+        )
+    })
+}
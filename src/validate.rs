@@ -0,0 +1,134 @@
+use crate::{
+    common::{Duration, Report, Statistics},
+    hook, units,
+};
+
+/// Relative difference below which a stored number matches the one recomputed from the report.
+const TOLERANCE: f64 = 1e-9;
+
+fn differs(stored: f64, recomputed: f64) -> bool {
+    let difference = (stored - recomputed).abs();
+    difference.is_nan() || difference > TOLERANCE * stored.abs().max(recomputed.abs())
+}
+
+/// Inconsistencies of `report`, which a report written by the benchmark never has: they point
+/// to a corrupted or hand-edited file.
+pub fn check(report: &Report) -> Vec<String> {
+    let mut problems = Vec::new();
+    let statistics = &report.statistics;
+    let (m, n, k) = report.dimensions;
+
+    if m == 0 || n == 0 || k == 0 {
+        problems.push(format!("dimensions {}x{}x{} are empty", m, n, k));
+    }
+    if report.repeats == 0 {
+        problems.push("repeats is 0".to_string());
+    }
+    if !report.samples.is_empty() && report.samples.len() != report.repeats {
+        problems.push(format!(
+            "repeats is {}, but there are {} samples",
+            report.repeats,
+            report.samples.len()
+        ));
+    }
+
+    if statistics.minimum > statistics.maximum {
+        problems.push("minimum is above maximum".to_string());
+    }
+    if let Some(medium) = statistics.medium {
+        if medium < statistics.minimum || medium > statistics.maximum {
+            problems.push("medium is outside of minimum and maximum".to_string());
+        }
+    }
+    // the average is kept in milliseconds, so it is compared with a little slack.
+    let (minimum, maximum) = (
+        statistics.minimum.as_millis() * (1.0 - TOLERANCE),
+        statistics.maximum.as_millis() * (1.0 + TOLERANCE),
+    );
+    if !(minimum..=maximum).contains(&statistics.average) {
+        problems.push("average is outside of minimum and maximum".to_string());
+    }
+    if !(statistics.deviation >= 0.0 && statistics.deviation <= maximum - minimum) {
+        problems.push(format!(
+            "deviation of {}ms is impossible for the spread of minimum and maximum",
+            statistics.deviation
+        ));
+    }
+
+    // merged reports have no medium, and their deviation is pooled from their parts.
+    if !report.samples.is_empty() {
+        let recomputed = Statistics::from(&report.samples);
+        let mut mismatch = |name: &str, stored: Duration, recomputed: Duration| {
+            if stored != recomputed {
+                problems.push(format!("{} does not match the samples", name));
+            }
+        };
+        mismatch("minimum", statistics.minimum, recomputed.minimum);
+        mismatch("maximum", statistics.maximum, recomputed.maximum);
+        if let (Some(stored), Some(recomputed)) = (statistics.medium, recomputed.medium) {
+            mismatch("medium", stored, recomputed);
+        }
+        if differs(statistics.average, recomputed.average) {
+            problems.push("average does not match the samples".to_string());
+        }
+    }
+
+    // without --max-residual, a report is only written if the difference stayed in the tolerance.
+    let calibrated = report
+        .methodology
+        .as_ref()
+        .is_some_and(|x| x.max_residual.is_none());
+    if let Some(used) = report.tolerance_used.filter(|_| calibrated) {
+        if !(0.0..=1.0).contains(&used) {
+            problems.push(format!(
+                "verification passed, but used {}% of its tolerance",
+                used * 100.0
+            ));
+        }
+    }
+
+    if let Some(anova) = &report.anova {
+        if anova.sessions < 2 {
+            problems.push(format!("ANOVA of {} session(s)", anova.sessions));
+        }
+        if anova.between < 0.0 || anova.within < 0.0 {
+            problems.push("ANOVA has a negative variance component".to_string());
+        }
+    }
+
+    let gflops = hook::gflops(report);
+    if !(gflops.is_finite() && gflops > 0.0) {
+        problems.push(format!("throughput of {} GFLOPS", gflops));
+    }
+    for expectation in &report.expectations {
+        if expectation.name == "expect-gflops-min" && differs(expectation.actual, gflops) {
+            problems.push(format!(
+                "expect-gflops-min measured {} GFLOPS, but the dimensions and times give {}",
+                expectation.actual, gflops
+            ));
+        }
+        let passed = match expectation.name.as_str() {
+            "expect-gflops-min" => Some(expectation.actual >= expectation.limit),
+            "expect-max-deviation" => Some(expectation.actual <= expectation.limit),
+            _ => None,
+        };
+        if passed.is_some_and(|x| x != expectation.passed) {
+            problems.push(format!(
+                "{} is marked {}, which contradicts {} against a limit of {}",
+                expectation.name,
+                if expectation.passed {
+                    "passed"
+                } else {
+                    "failed"
+                },
+                expectation.actual,
+                expectation.limit
+            ));
+        }
+    }
+
+    if report.units != units::Metadata::default() {
+        problems.push("units are not those the benchmark writes".to_string());
+    }
+    problems
+}
@@ -0,0 +1,283 @@
+use library::{CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+/// Parse a sampling fraction such as "1%" or "0.01".
+pub fn parse_fraction(value: &str) -> Result<f64, String> {
+    let error = || {
+        [
+            "expected a fraction like '1%' or '0.01', but got '",
+            value,
+            "'",
+        ]
+        .concat()
+    };
+    let fraction = match value.trim().strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map_err(|_| error())? / 100.0,
+        None => value.trim().parse::<f64>().map_err(|_| error())?,
+    };
+    if !(fraction > 0.0 && fraction <= 1.0) {
+        return Err(error());
+    }
+    Ok(fraction)
+}
+
+/// Offset of element (`row`, `column`) of op(X) in X.
+pub fn index(
+    layout: CBLAS_LAYOUT,
+    trans: CBLAS_TRANSPOSE,
+    ld: usize,
+    row: usize,
+    column: usize,
+) -> usize {
+    let (row, column) = if trans == CBLAS_TRANSPOSE::CblasNoTrans {
+        (row, column)
+    } else {
+        (column, row)
+    };
+    if layout == CBLAS_LAYOUT::CblasRowMajor {
+        row * ld + column
+    } else {
+        row + column * ld
+    }
+}
+
+/// `count` distinct indices below `total`, in order.
+fn choose(total: usize, count: usize, seed: u64) -> Vec<usize> {
+    let mut indices = (0..total).collect::<Vec<usize>>();
+    let mut value = seed
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+    // partial Fisher-Yates shuffle.
+    for i in 0..count {
+        value = value
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let j = i + ((value >> 33) as usize) % (total - i);
+        indices.swap(i, j);
+    }
+    indices.truncate(count);
+    indices.sort_unstable();
+    indices
+}
+
+/// Unevaluated sum of two doubles, which carries about 106 bits of mantissa.
+#[derive(Clone, Copy)]
+struct DoubleDouble {
+    hi: f64,
+    lo: f64,
+}
+
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+fn quick_two_sum(a: f64, b: f64) -> DoubleDouble {
+    let s = a + b;
+    DoubleDouble {
+        hi: s,
+        lo: b - (s - a),
+    }
+}
+
+impl DoubleDouble {
+    const ZERO: DoubleDouble = DoubleDouble { hi: 0.0, lo: 0.0 };
+
+    /// Exact product of two doubles.
+    fn product(a: f64, b: f64) -> DoubleDouble {
+        let hi = a * b;
+        DoubleDouble {
+            hi,
+            lo: a.mul_add(b, -hi),
+        }
+    }
+
+    fn add(self, other: DoubleDouble) -> DoubleDouble {
+        let (s, e) = two_sum(self.hi, other.hi);
+        quick_two_sum(s, e + self.lo + other.lo)
+    }
+
+    fn scale(self, x: f64) -> DoubleDouble {
+        let product = DoubleDouble::product(self.hi, x);
+        quick_two_sum(product.hi, product.lo + self.lo * x)
+    }
+}
+
+/// Entries of C checked against a double-double reference: every entry of some rows,
+/// and the remaining entries of some columns.
+pub struct Sample {
+    n: usize,
+    rows: Vec<usize>,
+    /// rows not in `rows`
+    others: Vec<usize>,
+    columns: Vec<usize>,
+    /// entries of C before the kernel ran, for beta
+    before: Vec<f64>,
+}
+
+impl Sample {
+    /// Every entry of `fraction` of the rows and `fraction` of the columns of an `m`x`n` C.
+    pub fn new((m, n): (usize, usize), fraction: f64, seed: u64) -> Self {
+        let count = |total: usize| ((total as f64 * fraction).ceil() as usize).clamp(1, total);
+        let rows = choose(m, count(m), seed);
+        let columns = choose(n, count(n), seed + 1);
+        let others = (0..m).filter(|x| rows.binary_search(x).is_err()).collect();
+        Sample {
+            n,
+            rows,
+            others,
+            columns,
+            before: Vec::new(),
+        }
+    }
+
+    /// Every entry of an `m`x`n` C.
+    pub fn all((m, n): (usize, usize)) -> Self {
+        Sample {
+            n,
+            rows: (0..m).collect(),
+            others: Vec::new(),
+            columns: Vec::new(),
+            before: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len() * self.n + self.others.len() * self.columns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// (row, column) of the `index`-th checked entry.
+    fn position(&self, index: usize) -> (usize, usize) {
+        let full = self.rows.len() * self.n;
+        if index < full {
+            (self.rows[index / self.n], index % self.n)
+        } else {
+            let index = index - full;
+            let columns = self.columns.len();
+            (self.others[index / columns], self.columns[index % columns])
+        }
+    }
+
+    /// Remember the checked entries of C before the kernel overwrites them.
+    pub fn snapshot(&mut self, layout: CBLAS_LAYOUT, c: &[f64], ldc: usize) {
+        self.before = (0..self.len())
+            .into_par_iter()
+            .map(|x| {
+                let (i, j) = self.position(x);
+                c[index(layout, CBLAS_TRANSPOSE::CblasNoTrans, ldc, i, j)]
+            })
+            .collect();
+    }
+
+    /// Frobenius norm of the difference between the checked entries of C and
+    /// alpha * op(A) * op(B) + beta * C, computed in double-double.
+    #[allow(clippy::too_many_arguments)]
+    pub fn difference(
+        &self,
+        layout: CBLAS_LAYOUT,
+        trans_a: CBLAS_TRANSPOSE,
+        trans_b: CBLAS_TRANSPOSE,
+        k: usize,
+        (a, lda): (&[f64], usize),
+        (b, ldb): (&[f64], usize),
+        (c, ldc): (&[f64], usize),
+        alpha: f64,
+        beta: f64,
+    ) -> f64 {
+        (0..self.len())
+            .into_par_iter()
+            .map(|x| {
+                let (i, j) = self.position(x);
+                let mut sum = DoubleDouble::ZERO;
+                for l in 0..k {
+                    sum = sum.add(DoubleDouble::product(
+                        a[index(layout, trans_a, lda, i, l)],
+                        b[index(layout, trans_b, ldb, l, j)],
+                    ));
+                }
+                let mut expected = sum.scale(alpha);
+                if beta != 0.0 {
+                    expected = expected.add(DoubleDouble::product(
+                        beta,
+                        self.before.get(x).copied().unwrap_or(0.0),
+                    ));
+                }
+                let actual = c[index(layout, CBLAS_TRANSPOSE::CblasNoTrans, ldc, i, j)];
+                let residual = expected.scale(-1.0).add(DoubleDouble {
+                    hi: actual,
+                    lo: 0.0,
+                });
+                residual.hi * residual.hi
+            })
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+/// Residual normalized by the rounding error a dot product of length `k` may accumulate,
+/// ||C - Ĉ|| / (||A|| ||B|| k ε), with Frobenius norms.
+pub fn normalized_residual(difference: f64, a: &[f64], b: &[f64], k: usize) -> f64 {
+    let norm = |x: &[f64]| x.par_iter().map(|x| x * x).sum::<f64>().sqrt();
+    let scale = norm(a) * norm(b) * k as f64 * f64::EPSILON;
+    if scale == 0.0 {
+        if difference == 0.0 {
+            0.0
+        } else {
+            f64::INFINITY
+        }
+    } else {
+        difference / scale
+    }
+}
+
+/// Infinity norm of a `rows`x`columns` op(X), its largest sum of absolute values in a row.
+pub fn infinity_norm(
+    layout: CBLAS_LAYOUT,
+    trans: CBLAS_TRANSPOSE,
+    ld: usize,
+    (rows, columns): (usize, usize),
+    x: &[f64],
+) -> f64 {
+    (0..rows)
+        .into_par_iter()
+        .map(|row| {
+            (0..columns)
+                .map(|column| x[index(layout, trans, ld, row, column)].abs())
+                .sum::<f64>()
+        })
+        .reduce(|| 0.0, f64::max)
+}
+
+/// Largest difference a correct product of inner dimension `k` may show over `entries` of C:
+/// every entry of alpha·op(A)·op(B) may be off by k·ε·|alpha|·‖A‖∞·‖B‖∞, in the kernel and
+/// in the reference alike. C is zero when verified, or unread with a beta of 0, so beta adds
+/// nothing.
+pub fn tolerance(entries: usize, k: usize, alpha: f64, (norm_a, norm_b): (f64, f64)) -> f64 {
+    2.0 * (entries as f64).sqrt() * k as f64 * f64::EPSILON * alpha.abs() * norm_a * norm_b
+}
+
+/// Fraction of `tolerance` that `difference` used; verification passes up to 1.
+pub fn used(difference: f64, tolerance: f64) -> f64 {
+    if difference == 0.0 {
+        0.0
+    } else {
+        difference / tolerance
+    }
+}
+
+/// Fill C with NaN before a verification call with a beta of 0, which must not read C: a kernel
+/// that does carries the NaN into its result.
+pub fn poison(c: &mut [f64]) {
+    c.fill(f64::NAN);
+}
+
+/// Whether C still holds a NaN of `poison` after the call, as the kernel read C or left entries
+/// unwritten; the inputs never have any.
+pub fn poisoned(c: &[f64]) -> bool {
+    c.par_iter().any(|x| x.is_nan())
+}
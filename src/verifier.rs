@@ -0,0 +1,257 @@
+use crate::{methodology::Verification, verification};
+use library::{cblas_daxpy, cblas_dgemm, cblas_dnrm2, CBLAS_LAYOUT, CBLAS_TRANSPOSE};
+use std::fs;
+
+/// A dgemm call whose C a `Verifier` checks: C as the kernel found it in `prepare`, and as it
+/// left it in `check`.
+pub struct Problem<'a> {
+    pub layout: CBLAS_LAYOUT,
+    pub transpose: (CBLAS_TRANSPOSE, CBLAS_TRANSPOSE),
+    pub dimensions: (usize, usize, usize),
+    pub alpha: f64,
+    pub beta: f64,
+    pub a: (&'a [f64], usize),
+    pub b: (&'a [f64], usize),
+    pub c: (&'a [f64], usize),
+}
+
+impl Problem<'_> {
+    /// Infinity norms of op(A) and op(B), which the tolerance is calibrated from.
+    pub fn norms(&self) -> (f64, f64) {
+        let (m, n, k) = self.dimensions;
+        let (trans_a, trans_b) = self.transpose;
+        (
+            verification::infinity_norm(self.layout, trans_a, self.a.1, (m, k), self.a.0),
+            verification::infinity_norm(self.layout, trans_b, self.b.1, (k, n), self.b.0),
+        )
+    }
+}
+
+/// Checks C of a kernel call against the expected product. Every precision or operation
+/// implements verification once, selected with `--verify-with`; programs embedding the
+/// library can bring their own.
+pub trait Verifier {
+    /// How the methodology of a report describes the check.
+    fn methodology(&self) -> Verification;
+
+    /// Look at C before the kernel runs, e.g. to keep the entries beta scales.
+    fn prepare(&mut self, _problem: &Problem) {}
+
+    /// Frobenius norm of the difference between C and the expected result, with the number of
+    /// entries it covers, which the tolerance grows with.
+    fn check(&mut self, problem: &Problem) -> Result<(f64, usize), String>;
+}
+
+/// Every entry against the product of the reference BLAS.
+#[derive(Default)]
+pub struct VendorBlas {
+    before: Vec<f64>,
+}
+
+impl Verifier for VendorBlas {
+    fn methodology(&self) -> Verification {
+        Verification::Reference
+    }
+
+    fn prepare(&mut self, problem: &Problem) {
+        self.before = problem.c.0.to_vec();
+    }
+
+    fn check(&mut self, problem: &Problem) -> Result<(f64, usize), String> {
+        let (m, n, k) = problem.dimensions;
+        let (c, ldc) = problem.c;
+        if self.before.len() != c.len() {
+            return Err("C was not prepared".to_string());
+        }
+        // the padding between lines of C is the same in both, so it adds nothing.
+        let mut d = std::mem::take(&mut self.before);
+        let difference = unsafe {
+            cblas_dgemm(
+                problem.layout,
+                problem.transpose.0,
+                problem.transpose.1,
+                m as _,
+                n as _,
+                k as _,
+                problem.alpha,
+                problem.a.0.as_ptr(),
+                problem.a.1 as _,
+                problem.b.0.as_ptr(),
+                problem.b.1 as _,
+                problem.beta,
+                d.as_mut_ptr(),
+                ldc as _,
+            );
+            cblas_daxpy(d.len() as _, -1.0, c.as_ptr(), 1, d.as_mut_ptr(), 1);
+            cblas_dnrm2(d.len() as _, d.as_ptr(), 1)
+        };
+        Ok((difference, m * n))
+    }
+}
+
+/// Entries of a `verification::Sample` against dot products computed in double-double.
+pub struct Compensated {
+    /// fraction of the rows and columns, or None for every entry
+    fraction: Option<f64>,
+    seed: u64,
+    sample: Option<verification::Sample>,
+}
+
+impl Compensated {
+    /// Every entry of C, for `pure-rust`.
+    pub fn all() -> Self {
+        Compensated {
+            fraction: None,
+            seed: 0,
+            sample: None,
+        }
+    }
+
+    /// A `fraction` of the rows and columns, chosen from `seed`, for `sampled`.
+    pub fn sampled(fraction: f64, seed: u64) -> Self {
+        Compensated {
+            fraction: Some(fraction),
+            seed,
+            sample: None,
+        }
+    }
+}
+
+impl Verifier for Compensated {
+    fn methodology(&self) -> Verification {
+        match self.fraction {
+            Some(fraction) => Verification::Sampled { fraction },
+            None => Verification::Extended,
+        }
+    }
+
+    fn prepare(&mut self, problem: &Problem) {
+        let (m, n, _) = problem.dimensions;
+        let mut sample = match self.fraction {
+            Some(fraction) => verification::Sample::new((m, n), fraction, self.seed),
+            None => verification::Sample::all((m, n)),
+        };
+        sample.snapshot(problem.layout, problem.c.0, problem.c.1);
+        self.sample = Some(sample);
+    }
+
+    fn check(&mut self, problem: &Problem) -> Result<(f64, usize), String> {
+        let sample = self.sample.take().ok_or("C was not prepared")?;
+        let difference = sample.difference(
+            problem.layout,
+            problem.transpose.0,
+            problem.transpose.1,
+            problem.dimensions.2,
+            problem.a,
+            problem.b,
+            problem.c,
+            problem.alpha,
+            problem.beta,
+        );
+        Ok((difference, sample.len()))
+    }
+}
+
+/// Every entry against a file of the expected C: M·N little-endian doubles in the layout of
+/// the run, without padding.
+pub struct FileCompare {
+    pub file: String,
+}
+
+impl Verifier for FileCompare {
+    fn methodology(&self) -> Verification {
+        Verification::File
+    }
+
+    fn check(&mut self, problem: &Problem) -> Result<(f64, usize), String> {
+        let (m, n, _) = problem.dimensions;
+        let bytes = fs::read(&self.file).map_err(|e| format!("{}: {}", self.file, e))?;
+        if bytes.len() != m * n * 8 {
+            return Err(format!(
+                "{} holds {} bytes, but C of {}x{} needs {}",
+                self.file,
+                bytes.len(),
+                m,
+                n,
+                m * n * 8
+            ));
+        }
+        let expected = bytes
+            .chunks_exact(8)
+            .map(|x| f64::from_le_bytes(x.try_into().unwrap()))
+            .collect::<Vec<f64>>();
+        let ld = if problem.layout == CBLAS_LAYOUT::CblasRowMajor {
+            n
+        } else {
+            m
+        };
+        let (c, ldc) = problem.c;
+        let mut sum = 0.0;
+        for i in 0..m {
+            for j in 0..n {
+                let entry = |ld| {
+                    verification::index(problem.layout, CBLAS_TRANSPOSE::CblasNoTrans, ld, i, j)
+                };
+                let difference = c[entry(ldc)] - expected[entry(ld)];
+                sum += difference * difference;
+            }
+        }
+        Ok((sum.sqrt(), m * n))
+    }
+}
+
+/// Verifier of `--verify-with`.
+#[derive(Clone, PartialEq)]
+pub enum Kind {
+    VendorBlas,
+    PureRust,
+    Sampled(f64),
+    /// file of the expected C, where `{session}` stands for the number of the session
+    FileCompare(String),
+    None,
+}
+
+impl TryFrom<&str> for Kind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (name, parameter) = match value.split_once('=') {
+            Some((name, parameter)) => (name, Some(parameter)),
+            None => (value, None),
+        };
+        match (name, parameter) {
+            ("vendor-blas", None) => Ok(Kind::VendorBlas),
+            ("pure-rust", None) => Ok(Kind::PureRust),
+            ("sampled", None) => Ok(Kind::Sampled(0.01)),
+            ("sampled", Some(fraction)) => verification::parse_fraction(fraction).map(Kind::Sampled),
+            ("file-compare", Some(file)) if !file.is_empty() => Ok(Kind::FileCompare(file.to_string())),
+            ("file-compare", _) => Err("expected file-compare=FILE".to_string()),
+            ("none", None) => Ok(Kind::None),
+            _ => Err([
+                "expected vendor-blas, pure-rust, sampled[=FRACTION], file-compare=FILE or none, but got ",
+                value,
+            ]
+            .concat()),
+        }
+    }
+}
+
+impl Kind {
+    /// Verifier of `session`, which samples from `seed`; None for `none`.
+    pub fn build(&self, seed: u64, session: usize) -> Option<Box<dyn Verifier>> {
+        match self {
+            Kind::VendorBlas => Some(Box::new(VendorBlas::default())),
+            Kind::PureRust => Some(Box::new(Compensated::all())),
+            Kind::Sampled(fraction) => Some(Box::new(Compensated::sampled(*fraction, seed))),
+            Kind::FileCompare(file) => Some(Box::new(FileCompare {
+                file: file.replace("{session}", &session.to_string()),
+            })),
+            Kind::None => None,
+        }
+    }
+
+    pub fn methodology(&self) -> Verification {
+        self.build(0, 0)
+            .map_or(Verification::Skipped, |x| x.methodology())
+    }
+}
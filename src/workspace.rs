@@ -0,0 +1,47 @@
+/// Value of a "VmRSS:    1234 kB" line of /proc/self/status, in bytes.
+#[cfg(target_os = "linux")]
+fn status(field: &str) -> Option<usize> {
+    std::fs::read_to_string("/proc/self/status")
+        .ok()?
+        .lines()
+        .find_map(|x| x.strip_prefix(field)?.strip_prefix(':'))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .map(|x| x * 1024)
+}
+
+/// Peak resident memory of a kernel call above what was resident before it, which counts
+/// packing buffers and other memory the kernel allocates and touches internally.
+pub struct Probe {
+    baseline: Option<usize>,
+}
+
+impl Probe {
+    /// Reset the peak resident set size of this process (Linux 4.0+).
+    #[cfg(target_os = "linux")]
+    pub fn start() -> Self {
+        let reset = std::fs::write("/proc/self/clear_refs", "5").is_ok();
+        Probe {
+            baseline: status("VmRSS").filter(|_| reset),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn start() -> Self {
+        Probe { baseline: None }
+    }
+
+    /// Bytes the peak grew by since `start`.
+    #[cfg(target_os = "linux")]
+    pub fn finish(self) -> Option<usize> {
+        Some(status("VmHWM")?.saturating_sub(self.baseline?))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn finish(self) -> Option<usize> {
+        None
+    }
+}
@@ -0,0 +1,167 @@
+/*
+ * Reproducer of a failed verification of {{KERNEL}}, written by the benchmark harness.
+ *
+ * {{FAILURE}}
+ *
+ * It calls call_dgemm once on the inputs the harness generated and checks C against a
+ * reference computed in long double. It exits with 1 as long as the kernel fails:
+ *
+ *   cc -O2 -o reproducer reproducer.c {{KERNEL}} -lm && ./reproducer
+ */
+#include <math.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define ABI_VERSION {{ABI}}
+/* 1 if A and B are written out below, 0 if they are regenerated from SEED */
+#define EXACT {{EXACT}}
+#define SEED {{SEED}}ULL
+#define SESSION {{SESSION}}
+
+#define LAYOUT {{LAYOUT}}
+#define TRANS_A {{TRANS_A}}
+#define TRANS_B {{TRANS_B}}
+#define ROW_MAJOR 101
+#define NO_TRANS 111
+
+static const size_t M = {{M}}, N = {{N}}, K = {{K}};
+static const size_t LDA = {{LDA}}, LDB = {{LDB}}, LDC = {{LDC}};
+static const size_t SIZE_A = {{SIZE_A}}, SIZE_B = {{SIZE_B}}, SIZE_C = {{SIZE_C}};
+
+#if ABI_VERSION == 2
+struct context { unsigned int abi_version, phase, session, iteration; };
+void call_dgemm(const struct context *context, unsigned int layout, unsigned int trans_a,
+                unsigned int trans_b, size_t m, size_t n, size_t k, double alpha,
+                const double *A, size_t lda, const double *B, size_t ldb,
+                double beta, double *C, size_t ldc);
+#else
+void call_dgemm(unsigned int layout, unsigned int trans_a, unsigned int trans_b,
+                size_t m, size_t n, size_t k, double alpha,
+                const double *A, size_t lda, const double *B, size_t ldb,
+                double beta, double *C, size_t ldc);
+#endif
+
+static double from_bits(uint64_t bits) {
+    double x;
+    memcpy(&x, &bits, sizeof x);
+    return x;
+}
+
+#if EXACT
+{{DATA}}
+static void fill(double *x, size_t size, const uint64_t *bits) {
+    for (size_t i = 0; i < size; i++) {
+        x[i] = from_bits(bits[i]);
+    }
+}
+
+#define FILL_A(x) fill(x, SIZE_A, A_BITS)
+#define FILL_B(x) fill(x, SIZE_B, B_BITS)
+#else
+/* Philox4x32-10, as the harness generates matrices with it */
+static void philox(uint64_t counter, uint64_t key, uint32_t x[4]) {
+    uint32_t k0 = (uint32_t)key, k1 = (uint32_t)(key >> 32);
+    x[0] = (uint32_t)counter;
+    x[1] = (uint32_t)(counter >> 32);
+    x[2] = 0;
+    x[3] = 0;
+    for (int round = 0; round < 10; round++) {
+        uint64_t p0 = 0xD2511F53ULL * x[0];
+        uint64_t p1 = 0xCD9E8D57ULL * x[2];
+        uint32_t y[4] = {
+            (uint32_t)(p1 >> 32) ^ x[1] ^ k0,
+            (uint32_t)p1,
+            (uint32_t)(p0 >> 32) ^ x[3] ^ k1,
+            (uint32_t)p0,
+        };
+        memcpy(x, y, sizeof y);
+        k0 += 0x9E3779B9U;
+        k1 += 0xBB67AE85U;
+    }
+}
+
+/* values in [0, 2), two from every block of 128 random bits */
+static void fill(double *x, size_t size, uint64_t seed) {
+    for (size_t i = 0; i < size; i++) {
+        uint32_t block[4];
+        philox(i / 2, seed, block);
+        uint64_t bits = block[2 * (i % 2)] | (uint64_t)block[2 * (i % 2) + 1] << 32;
+        x[i] = (double)(bits >> 11) * (2.0 / 9007199254740992.0);
+    }
+}
+
+#define FILL_A(x) fill(x, SIZE_A, SEED + 100)
+#define FILL_B(x) fill(x, SIZE_B, SEED + 200)
+#endif
+
+/* index of op(X)(i, j) */
+static size_t at(int row_major, int trans, size_t i, size_t j, size_t ld) {
+    return (row_major != trans) ? i * ld + j : i + j * ld;
+}
+
+int main(void) {
+    const double alpha = from_bits({{ALPHA}}ULL); /* {{ALPHA_VALUE}} */
+    const double beta = from_bits({{BETA}}ULL);   /* {{BETA_VALUE}} */
+    /* largest ||C - C_expected|| the harness accepted */
+    const double limit = from_bits({{LIMIT}}ULL); /* {{LIMIT_VALUE}} */
+
+    double *A = malloc(SIZE_A * sizeof(double));
+    double *B = malloc(SIZE_B * sizeof(double));
+    double *C = malloc(SIZE_C * sizeof(double));
+    if (!A || !B || !C) {
+        fprintf(stderr, "out of memory\n");
+        return 2;
+    }
+    FILL_A(A);
+    FILL_B(B);
+    /* C starts zeroed, or as NaN a kernel must not read when beta is 0 */
+    for (size_t i = 0; i < SIZE_C; i++) {
+        C[i] = beta == 0.0 ? NAN : 0.0;
+    }
+
+#if ABI_VERSION == 2
+    struct context context = {2, 1, SESSION, 0};
+    call_dgemm(&context, LAYOUT, TRANS_A, TRANS_B, M, N, K, alpha, A, LDA, B, LDB, beta, C, LDC);
+#else
+    call_dgemm(LAYOUT, TRANS_A, TRANS_B, M, N, K, alpha, A, LDA, B, LDB, beta, C, LDC);
+#endif
+
+    int row_major = LAYOUT == ROW_MAJOR;
+    int ta = TRANS_A != NO_TRANS, tb = TRANS_B != NO_TRANS;
+    size_t nan = 0;
+    long double sum = 0.0L;
+    for (size_t i = 0; i < M; i++) {
+        for (size_t j = 0; j < N; j++) {
+            /* beta scales zeros, so only the product is left */
+            long double expected = 0.0L;
+            for (size_t p = 0; p < K; p++) {
+                expected += (long double)A[at(row_major, ta, i, p, LDA)] * B[at(row_major, tb, p, j, LDB)];
+            }
+            double actual = C[at(row_major, 0, i, j, LDC)];
+            if (isnan(actual)) {
+                nan++;
+            }
+            long double difference = actual - alpha * expected;
+            sum += difference * difference;
+        }
+    }
+    double difference = (double)sqrtl(sum);
+
+    printf("||C - C_expected|| = %.3e, limit %.3e, %zu NaN entries\n", difference, limit, nan);
+    free(A);
+    free(B);
+    free(C);
+    if (nan > 0) {
+        printf("FAIL: C holds NaN%s\n",
+               beta == 0.0 ? ", so the kernel read C although beta is 0 or left entries unwritten" : "");
+        return 1;
+    }
+    if (!(difference <= limit)) {
+        printf("FAIL: C is wrong\n");
+        return 1;
+    }
+    printf("PASS\n");
+    return 0;
+}